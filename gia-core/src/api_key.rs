@@ -1,33 +1,85 @@
-use crate::constants::{API_KEY_LENGTH, API_KEY_PREFIX, GEMINI_API_KEY_URL, GEMINI_DOCS_URL};
+use crate::config_file::load_settings;
+use crate::constants::{
+    API_KEY_LENGTH, API_KEY_PREFIX, GEMINI_API_BASE_URL, GEMINI_API_KEY_URL, GEMINI_DOCS_URL,
+};
 use crate::logging::{log_info, log_warn};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::env;
+use std::time::Duration;
 
 pub fn get_api_keys() -> Result<Vec<String>> {
-    // First try environment variable - now supports pipe-separated keys
-    if let Ok(keys_string) = env::var("GEMINI_API_KEY")
-        && !keys_string.trim().is_empty()
-    {
-        // Split by pipe character and filter out empty strings
-        let keys: Vec<String> = keys_string
-            .split('|')
-            .map(|k| k.trim().to_string())
-            .filter(|k| !k.is_empty())
-            .collect();
-
-        if !keys.is_empty() {
-            log_info(&format!(
-                "Found {} API key(s) in GEMINI_API_KEY environment variable",
-                keys.len()
-            ));
-            return Ok(keys);
-        }
+    if let Some(keys) = get_configured_api_keys() {
+        return Ok(keys);
     }
 
     // If we reach here, no valid keys were found
     handle_api_key_error()
 }
 
+/// Keys from GEMINI_API_KEY (pipe-separated), without asking the user when none are set
+pub fn get_api_keys_from_env() -> Option<Vec<String>> {
+    let keys_string = env::var("GEMINI_API_KEY").ok()?;
+
+    // Split by pipe character and filter out empty strings
+    let keys: Vec<String> = keys_string
+        .split('|')
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+
+    if keys.is_empty() {
+        return None;
+    }
+
+    log_info(&format!(
+        "Found {} API key(s) in GEMINI_API_KEY environment variable",
+        keys.len()
+    ));
+    Some(keys)
+}
+
+/// Keys from GEMINI_API_KEY, else from `api_keys` in ~/.gia/config
+pub fn get_configured_api_keys() -> Option<Vec<String>> {
+    get_api_keys_from_env().or_else(|| {
+        let keys = load_settings().api_keys;
+        if keys.is_empty() {
+            return None;
+        }
+        log_info(&format!("Found {} API key(s) in settings file", keys.len()));
+        Some(keys)
+    })
+}
+
+/// Check that Gemini accepts a key by listing the models with it
+pub fn check_api_key(api_key: &str) -> Result<()> {
+    check_api_key_at(GEMINI_API_BASE_URL, api_key)
+}
+
+fn check_api_key_at(base_url: &str, api_key: &str) -> Result<()> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(10)))
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let mut response = agent
+        .get(format!("{}/v1beta/models", base_url.trim_end_matches('/')))
+        .header("x-goog-api-key", api_key)
+        .call()
+        .context("Failed to reach the Gemini API")?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let body = response.body_mut().read_to_string().unwrap_or_default();
+    let message = serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|error| error["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or(body);
+    Err(anyhow::anyhow!("API key rejected ({status}): {message}"))
+}
+
 fn handle_api_key_error() -> Result<Vec<String>> {
     eprintln!();
     eprintln!("🔑 API Keys Required");
@@ -148,6 +200,52 @@ mod tests {
         assert!(!validate_api_key_format(wrong_prefix));
     }
 
+    /// Fake Gemini API answering one request with the given status and body
+    fn fake_gemini(
+        status: &'static str,
+        body: &'static str,
+    ) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 2048];
+            let size = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            String::from_utf8_lossy(&request[..size]).to_lowercase()
+        });
+        (base_url, server)
+    }
+
+    #[test]
+    fn test_check_api_key() {
+        let (base_url, server) = fake_gemini("200 OK", r#"{"models":[]}"#);
+        check_api_key_at(&base_url, "good-key").unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("get /v1beta/models "));
+        assert!(request.contains("x-goog-api-key: good-key"));
+
+        let (base_url, server) = fake_gemini(
+            "400 Bad Request",
+            r#"{"error":{"message":"API key not valid. Please pass a valid API key."}}"#,
+        );
+        let err = check_api_key_at(&base_url, "bad-key").unwrap_err();
+        server.join().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "API key rejected (400 Bad Request): API key not valid. Please pass a valid API key."
+        );
+
+        assert!(check_api_key_at("http://127.0.0.1:9", "key").is_err());
+    }
+
     #[test]
     fn test_invalid_characters() {
         let invalid_chars = "AIzaSyDummy@Key#ForTesting1234567890123";
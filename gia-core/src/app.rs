@@ -1,15 +1,16 @@
 use anyhow::{Context, Result};
 
 use crate::cli::{Config, ContentSource};
-use crate::constants::get_context_window_limit;
+use crate::constants::{MAX_TOOL_ROUNDS, get_context_window_limit};
 use crate::content_part_wrapper::{ChatMessageWrapper, ContentPartWrapper, MessageContentWrapper};
 use crate::conversation::TokenUsage;
 use crate::conversation::{Conversation, ConversationManager, ResourceInfo, ResourceType};
 use crate::input::get_input_text;
 use crate::logging::{log_error, log_info, setup_conversation_file_logging};
 use crate::output::output_text_with_usage;
-use crate::provider::{ProviderConfig, ProviderFactory};
-use crate::spinner::SpinnerProcess;
+use crate::progress::{self, ProgressOverlay, Stage};
+use crate::provider::{ProviderConfig, ProviderFactory, TextCallback};
+use crate::tools::ToolRegistry;
 
 pub async fn run_app(mut config: Config) -> Result<()> {
     // Handle list audio devices command
@@ -17,6 +18,16 @@ pub async fn run_app(mut config: Config) -> Result<()> {
         return crate::audio::list_audio_devices();
     }
 
+    // Handle MCP server mode (gia mcp-serve)
+    if config.mcp_serve {
+        return crate::mcp_server::run_mcp_server().await;
+    }
+
+    // Handle HTTP API mode (gia serve)
+    if let Some(options) = config.serve.take() {
+        return crate::server::run_server(options).await;
+    }
+
     // Initialize conversation manager
     let conversation_manager =
         ConversationManager::new().context("Failed to initialize conversation manager")?;
@@ -31,53 +42,139 @@ pub async fn run_app(mut config: Config) -> Result<()> {
         return handle_show_conversation(&conversation_manager, conversation_id, &config);
     }
 
-    // Get API keys - only required for non-Ollama providers
+    // The overlay shows the progress from the recording to the response
+    let overlay = config.spinner.then(ProgressOverlay::start);
+
+    let completed = async {
+        let (mut conversation, api_keys) = prepare_request(&mut config, &conversation_manager)?;
+
+        if config.ordered_content.is_empty() {
+            log_error("No input content provided");
+            eprintln!(
+                "Error: No input content provided. Provide prompt as command line arguments or use -c/-f/-i for additional input."
+            );
+            std::process::exit(1);
+        }
+
+        let (response, usage) = match &overlay {
+            Some(overlay) => {
+                complete_with_overlay(
+                    &config,
+                    &conversation_manager,
+                    &mut conversation,
+                    &api_keys,
+                    overlay,
+                )
+                .await?
+            }
+            None => {
+                complete_request(
+                    &config,
+                    &conversation_manager,
+                    &mut conversation,
+                    &api_keys,
+                    None,
+                )
+                .await?
+            }
+        };
+        anyhow::Ok((conversation, response, usage))
+    }
+    .await;
+
+    // Close the overlay before the output (browser, TTS) starts
+    if let Some(overlay) = overlay {
+        overlay.finish(completed.as_ref().err());
+    }
+    let (conversation, response, usage) = completed?;
+
+    // Output response
+    output_text_with_usage(&response, &config, Some(usage), &conversation.id)
+        .context("Failed to output response")?;
+
+    log_info("Successfully completed request");
+    Ok(())
+}
+
+/// Complete the request streaming, so the overlay shows the response coming in.
+/// Cancelling in the overlay drops the request.
+async fn complete_with_overlay(
+    config: &Config,
+    conversation_manager: &ConversationManager,
+    conversation: &mut Conversation,
+    api_keys: &[String],
+    overlay: &ProgressOverlay,
+) -> Result<(String, TokenUsage)> {
+    let mut chars = 0;
+    let mut on_text = |text: &str| {
+        chars += text.chars().count();
+        progress::report(Stage::Streaming { chars });
+    };
+    tokio::select! {
+        result = complete_request(config, conversation_manager, conversation, api_keys, Some(&mut on_text)) => result,
+        () = overlay.cancelled() => Err(anyhow::anyhow!("Request cancelled by user")),
+    }
+}
+
+/// Resolve the conversation for a request and gather its input into
+/// `config.ordered_content`. Returns the conversation and the API keys to use.
+pub fn prepare_request(
+    config: &mut Config,
+    conversation_manager: &ConversationManager,
+) -> Result<(Conversation, Vec<String>)> {
+    // Get API keys - only required for non-Ollama providers. Server modes can't ask
+    // the user, so they only take keys from the environment or the settings file.
     let api_keys = if config.model.to_lowercase().starts_with("ollama::") {
         Vec::new()
+    } else if !config.read_stdin {
+        crate::api_key::get_configured_api_keys().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No API keys found. Set GEMINI_API_KEY (pipe-separated for fallback) or api_keys in ~/.gia/config."
+            )
+        })?
     } else {
         crate::api_key::get_api_keys().context("Failed to get API keys")?
     };
 
     // Determine conversation mode and adjust prompt if needed
-    let (mut conversation, final_prompt) =
-        resolve_conversation(&config, &conversation_manager, &config.model, &api_keys)?;
+    let (conversation, final_prompt) =
+        resolve_conversation(config, conversation_manager, &config.model, &api_keys)?;
 
     // Setup file logging for this conversation if GIA_LOG_TO_FILE is set
     setup_conversation_file_logging(&conversation.id)
         .context("Failed to setup conversation file logging")?;
 
     // Get input content (this may modify config to add clipboard images)
-    // Note: Audio recording happens here, so spinner must start AFTER this
-    get_input_text(&mut config, Some(&final_prompt)).context("Failed to get input text")?;
-
-    if config.ordered_content.is_empty() {
-        log_error("No input content provided");
-        eprintln!(
-            "Error: No input content provided. Provide prompt as command line arguments or use -c/-f/-i for additional input."
-        );
-        std::process::exit(1);
-    }
+    get_input_text(config, Some(&final_prompt)).context("Failed to get input text")?;
 
+    Ok((conversation, api_keys))
+}
+
+/// Send the gathered input with the conversation history to the model (running
+/// requested tools), add the exchange to the conversation and save it unless
+/// `no_save` is set. With `on_text` the response text is streamed to it.
+/// Returns the response text and the accumulated token usage.
+pub async fn complete_request(
+    config: &Config,
+    conversation_manager: &ConversationManager,
+    conversation: &mut Conversation,
+    api_keys: &[String],
+    mut on_text: Option<TextCallback<'_>>,
+) -> Result<(String, TokenUsage)> {
     log_info(&format!(
         "Processing prompt with {} content source(s)",
         config.ordered_content.len()
     ));
 
-    // Start spinner now (after audio recording completes, before AI operations)
-    let _spinner = if config.spinner {
-        Some(SpinnerProcess::start())
-    } else {
-        None
-    };
-
     // Truncate conversation if it's getting too long
     conversation.truncate_if_needed(get_context_window_limit());
 
     // Initialize AI provider with preferred API key index from conversation (for caching)
     let provider_config = ProviderConfig {
         model: config.model.clone(),
-        api_keys: api_keys.clone(),
+        api_keys: api_keys.to_vec(),
         preferred_api_key_index: conversation.metadata.api_key_index,
+        temperature: config.temperature,
     };
 
     let mut provider = ProviderFactory::create_provider(provider_config)
@@ -116,18 +213,107 @@ pub async fn run_app(mut config: Config) -> Result<()> {
         provider.model_name()
     ));
 
-    // Spinner already started earlier if requested
-
-    let ai_response = provider
-        .generate_content_with_chat_messages(all_genai_messages)
-        .await
+    // Tools offered to the model (only with --tools)
+    let tool_registry = if config.tools {
+        Some(ToolRegistry::shared(&config.allowed_commands, &config.mcp_servers).await?)
+    } else {
+        None
+    };
+    let tools = tool_registry
+        .as_ref()
+        .map(|registry| registry.definitions())
+        .unwrap_or_default();
+
+    // Tool loop: run requested tools and send their results back until the model answers
+    let mut tool_messages: Vec<ChatMessageWrapper> = Vec::new();
+    let mut mcp_invocations: Vec<ResourceInfo> = Vec::new();
+    let mut usage = TokenUsage::default();
+    let mut tool_rounds = 0;
+    // The media of the new message is sent with the first round
+    let upload_bytes = media_bytes(&new_user_message_wrapper);
+    let response = loop {
+        progress::report(if upload_bytes > 0 && tool_rounds == 0 {
+            Stage::Uploading {
+                bytes: upload_bytes,
+            }
+        } else {
+            Stage::Waiting {
+                model: provider.model_name().to_string(),
+            }
+        });
+        let ai_response = match on_text.as_mut() {
+            Some(on_text) => {
+                provider
+                    .generate_content_streaming(all_genai_messages.clone(), &tools, *on_text)
+                    .await
+            }
+            None => {
+                provider
+                    .generate_content_with_chat_messages(all_genai_messages.clone(), &tools)
+                    .await
+            }
+        }
         .context("Failed to generate content")?;
+        usage.accumulate(ai_response.usage);
 
-    // Spinner is automatically killed when dropped here
-    drop(_spinner);
+        let Some(registry) = tool_registry
+            .as_ref()
+            .filter(|_| !ai_response.tool_calls.is_empty())
+        else {
+            break ai_response.content;
+        };
 
-    let response = ai_response.content;
-    let usage = ai_response.usage;
+        tool_rounds += 1;
+        if tool_rounds > MAX_TOOL_ROUNDS {
+            return Err(anyhow::anyhow!(
+                "The model kept requesting tools after {MAX_TOOL_ROUNDS} rounds"
+            ));
+        }
+        log_info(&format!(
+            "Tool round {tool_rounds}: model requested {} tool call(s)",
+            ai_response.tool_calls.len()
+        ));
+
+        let call_message = ChatMessageWrapper {
+            role: "Assistant".to_string(),
+            content: MessageContentWrapper::Parts {
+                parts: ai_response
+                    .tool_calls
+                    .iter()
+                    .map(|call| ContentPartWrapper::ToolCall {
+                        call_id: call.call_id.clone(),
+                        name: call.fn_name.clone(),
+                        arguments: call.fn_arguments.clone(),
+                    })
+                    .collect(),
+            },
+        };
+        all_genai_messages.push(call_message.to_genai_chat_message()?);
+        tool_messages.push(call_message);
+
+        for call in &ai_response.tool_calls {
+            if let Some(mcp_tool) = registry.mcp_tool(&call.fn_name) {
+                mcp_invocations.push(ResourceInfo {
+                    resource_type: ResourceType::McpTool,
+                    path: Some(mcp_tool),
+                    selection: Some(call.fn_arguments.to_string()),
+                });
+            }
+            let result = registry.execute(call).await;
+            let result_message = ChatMessageWrapper {
+                role: "Tool".to_string(),
+                content: MessageContentWrapper::Parts {
+                    parts: vec![ContentPartWrapper::ToolResponse {
+                        call_id: call.call_id.clone(),
+                        name: call.fn_name.clone(),
+                        content: result,
+                    }],
+                },
+            };
+            all_genai_messages.push(result_message.to_genai_chat_message()?);
+            tool_messages.push(result_message);
+        }
+    };
 
     // Build resources from ordered content
     let mut resources = Vec::new();
@@ -136,26 +322,32 @@ pub async fn run_app(mut config: Config) -> Result<()> {
             ContentSource::ImageFile(path) => Some(ResourceInfo {
                 resource_type: ResourceType::Image,
                 path: Some(path.clone()),
+                selection: None,
             }),
             ContentSource::AudioRecording(path) => Some(ResourceInfo {
                 resource_type: ResourceType::Audio,
                 path: Some(path.clone()),
+                selection: None,
             }),
-            ContentSource::TextFile(path, _) => Some(ResourceInfo {
+            ContentSource::TextFile(path, _, selection) => Some(ResourceInfo {
                 resource_type: ResourceType::TextFile,
                 path: Some(path.clone()),
+                selection: selection.clone(),
             }),
             ContentSource::ClipboardText(_) => Some(ResourceInfo {
                 resource_type: ResourceType::ClipboardText,
                 path: None,
+                selection: None,
             }),
             ContentSource::ClipboardImage => Some(ResourceInfo {
                 resource_type: ResourceType::ClipboardImage,
                 path: None,
+                selection: None,
             }),
             ContentSource::StdinText(_) => Some(ResourceInfo {
                 resource_type: ResourceType::Stdin,
                 path: None,
+                selection: None,
             }),
             ContentSource::RoleDefinition(name, _, is_task) => Some(ResourceInfo {
                 resource_type: if *is_task {
@@ -164,6 +356,27 @@ pub async fn run_app(mut config: Config) -> Result<()> {
                     ResourceType::Role
                 },
                 path: Some(name.clone()),
+                selection: None,
+            }),
+            ContentSource::GitDiff(description, _) => Some(ResourceInfo {
+                resource_type: ResourceType::GitDiff,
+                path: Some(description.clone()),
+                selection: None,
+            }),
+            ContentSource::GitLog(range, _) => Some(ResourceInfo {
+                resource_type: ResourceType::GitLog,
+                path: Some(range.clone()),
+                selection: None,
+            }),
+            ContentSource::WebPage(url, _) => Some(ResourceInfo {
+                resource_type: ResourceType::WebPage,
+                path: Some(url.clone()),
+                selection: None,
+            }),
+            ContentSource::EmbeddedImage(source, _, _) => Some(ResourceInfo {
+                resource_type: ResourceType::Image,
+                path: Some(source.clone()),
+                selection: None,
             }),
             _ => None, // Skip CommandLinePrompt
         };
@@ -172,6 +385,7 @@ pub async fn run_app(mut config: Config) -> Result<()> {
             resources.push(res);
         }
     }
+    resources.extend(mcp_invocations);
 
     // 4. Create assistant message wrapper
     let assistant_message_wrapper = ChatMessageWrapper {
@@ -183,26 +397,40 @@ pub async fn run_app(mut config: Config) -> Result<()> {
 
     // 5. Add messages to conversation with token usage
     conversation.add_message_with_usage(new_user_message_wrapper, resources, TokenUsage::default());
+    for tool_message in tool_messages {
+        conversation.add_message_with_usage(tool_message, Vec::new(), TokenUsage::default());
+    }
     conversation.add_message_with_usage(assistant_message_wrapper, Vec::new(), usage);
 
     // Save conversation (only if no_save flag is not set)
     if !config.no_save {
         conversation_manager
-            .save_conversation(&conversation)
+            .save_conversation(conversation)
             .context("Failed to save conversation")?;
 
         // Save markdown
         conversation_manager
-            .save_markdown(&conversation)
+            .save_markdown(conversation)
             .context("Failed to save markdown")?;
     }
 
-    // Output response
-    output_text_with_usage(&response, &config, Some(usage), &conversation.id)
-        .context("Failed to output response")?;
+    Ok((response, usage))
+}
 
-    log_info("Successfully completed request");
-    Ok(())
+/// Approximate size of the images and audio of a message
+fn media_bytes(message: &ChatMessageWrapper) -> usize {
+    let MessageContentWrapper::Parts { parts } = &message.content else {
+        return 0;
+    };
+    parts
+        .iter()
+        .map(|part| match part {
+            ContentPartWrapper::Image { data, .. } | ContentPartWrapper::Audio { data, .. } => {
+                data.len() / 4 * 3 // base64
+            }
+            _ => 0,
+        })
+        .sum()
 }
 
 fn build_content_part_wrappers(
@@ -240,7 +468,7 @@ fn build_content_part_wrappers(
                     is_task: *is_task,
                 });
             }
-            ContentSource::TextFile(path, content) => {
+            ContentSource::TextFile(path, content, selection) => {
                 log_info(&format!(
                     "[{}] Text file: {} ({} characters)",
                     index + 1,
@@ -250,6 +478,7 @@ fn build_content_part_wrappers(
                 wrappers.push(ContentPartWrapper::TextFile {
                     path: path.clone(),
                     content: content.clone(),
+                    selection: selection.clone(),
                 });
             }
             ContentSource::ClipboardText(text) => {
@@ -268,6 +497,56 @@ fn build_content_part_wrappers(
                 ));
                 wrappers.push(ContentPartWrapper::StdinText(text.clone()));
             }
+            ContentSource::GitDiff(description, content) => {
+                log_info(&format!(
+                    "[{}] Git diff ({}): {} characters",
+                    index + 1,
+                    description,
+                    content.len()
+                ));
+                wrappers.push(ContentPartWrapper::GitDiff {
+                    description: description.clone(),
+                    content: content.clone(),
+                });
+            }
+            ContentSource::GitLog(range, content) => {
+                log_info(&format!(
+                    "[{}] Git log ({}): {} characters",
+                    index + 1,
+                    range,
+                    content.len()
+                ));
+                wrappers.push(ContentPartWrapper::GitLog {
+                    range: range.clone(),
+                    content: content.clone(),
+                });
+            }
+            ContentSource::WebPage(url, content) => {
+                log_info(&format!(
+                    "[{}] Web page ({}): {} characters",
+                    index + 1,
+                    url,
+                    content.len()
+                ));
+                wrappers.push(ContentPartWrapper::WebPage {
+                    url: url.clone(),
+                    content: content.clone(),
+                });
+            }
+            ContentSource::EmbeddedImage(source, mime_type, data) => {
+                log_info(&format!(
+                    "[{}] Embedded image: {} (type: {}, {} base64 chars)",
+                    index + 1,
+                    source,
+                    mime_type,
+                    data.len()
+                ));
+                wrappers.push(ContentPartWrapper::Image {
+                    path: Some(source.clone()),
+                    mime_type: mime_type.clone(),
+                    data: data.clone(),
+                });
+            }
             ContentSource::ImageFile(path) => {
                 let mime_type = crate::image::get_mime_type(std::path::Path::new(path))?;
                 let data = crate::image::read_media_as_base64(path)?;
@@ -0,0 +1,508 @@
+//! Archive support for file inputs.
+//!
+//! Members of `.zip`, `.tar`, `.tar.gz` and `.tgz` archives are addressed with
+//! virtual paths such as `bundle.zip!/logs/app.log`, so they can flow through
+//! the same classification and reading code as regular files.
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::SystemTime;
+
+use crate::constants::{MAX_ARCHIVE_MEMBERS, MAX_ARCHIVE_TOTAL_SIZE, get_max_input_file_size};
+use crate::logging::{log_debug, log_info};
+
+/// Separator between the archive path and the member path in virtual paths
+pub const ARCHIVE_SEPARATOR: &str = "!/";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn archive_kind(path: &str) -> Option<ArchiveKind> {
+    let lower = path.to_lowercase();
+    if lower.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if lower.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// Check if a path points to a supported archive, based on its extension
+pub fn is_archive_file(path: &Path) -> bool {
+    path.to_str().and_then(archive_kind).is_some()
+}
+
+/// Split a virtual path like `bundle.zip!/logs/app.log` into archive path and member name
+pub fn split_virtual_path(path: &str) -> Option<(&str, &str)> {
+    let (archive, member) = path.split_once(ARCHIVE_SEPARATOR)?;
+    archive_kind(archive)?;
+    Some((archive, member))
+}
+
+/// A file member of an expanded archive
+struct Member {
+    size: u64,
+    bytes: Option<Vec<u8>>, // None when the member exceeds the input size limit
+}
+
+/// How much of an archive is read into memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ArchiveLimits {
+    member_size: u64, // bytes per member (GIA_MAX_FILE_SIZE)
+    total_size: u64,  // bytes of all members together
+    members: usize,   // number of file members
+}
+
+impl ArchiveLimits {
+    fn current() -> Self {
+        Self {
+            member_size: get_max_input_file_size(),
+            total_size: MAX_ARCHIVE_TOTAL_SIZE,
+            members: MAX_ARCHIVE_MEMBERS,
+        }
+    }
+}
+
+/// An archive read into memory, with what it was read from and with which limits
+struct ExpandedArchive {
+    path: String,
+    modified: Option<SystemTime>,
+    len: u64,
+    limits: ArchiveLimits,
+    members: BTreeMap<String, Member>,
+    complete: bool, // false = only a selected member was read
+}
+
+/// The most recently expanded archive. Its members are classified and read one by
+/// one right after listing them, and keeping the archive in memory for that avoids
+/// decompressing it again (from the start, for tar.gz) for every member. Cleared
+/// with `forget_expanded_archives` once the input of a request is gathered.
+static LAST_ARCHIVE: Mutex<Option<Arc<ExpandedArchive>>> = Mutex::new(None);
+
+/// Drop the archive kept in memory by the last expansion
+pub fn forget_expanded_archives() {
+    *LAST_ARCHIVE.lock().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+/// Members read so far while expanding an archive, kept within the limits
+struct Expansion<'a> {
+    archive_path: &'a str,
+    limits: ArchiveLimits,
+    members: BTreeMap<String, Member>,
+    total_size: u64,
+}
+
+impl Expansion<'_> {
+    fn add(&mut self, name: String, reader: impl Read, declared_size: u64) -> Result<()> {
+        if self.members.len() >= self.limits.members {
+            return Err(anyhow::anyhow!(
+                "Archive has more than {} files (limit of files per archive): {}",
+                self.limits.members,
+                self.archive_path
+            ));
+        }
+        let member = read_member(reader, declared_size, self.limits.member_size)
+            .with_context(|| format!("Failed to read archive member: {name}"))?;
+        self.total_size += member.bytes.as_ref().map_or(0, |bytes| bytes.len() as u64);
+        if self.total_size > self.limits.total_size {
+            return Err(anyhow::anyhow!(
+                "Archive content exceeds the limit of {} bytes for all files of an archive: {}",
+                self.limits.total_size,
+                self.archive_path
+            ));
+        }
+        self.members.insert(name, member);
+        Ok(())
+    }
+}
+
+/// Read the file members of an archive (`only` = just this member), or return them
+/// from the last expansion if the archive file hasn't changed since
+fn expand_archive(
+    archive_path: &str,
+    only: Option<&str>,
+    limits: ArchiveLimits,
+) -> Result<Arc<ExpandedArchive>> {
+    let kind = archive_kind(archive_path)
+        .with_context(|| format!("Unsupported archive type: {archive_path}"))?;
+    let metadata = fs::metadata(archive_path)
+        .with_context(|| format!("Failed to open archive: {archive_path}"))?;
+    let modified = metadata.modified().ok();
+
+    let mut last = LAST_ARCHIVE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(archive) = last.as_ref()
+        && archive.path == archive_path
+        && archive.modified == modified
+        && archive.len == metadata.len()
+        && archive.limits == limits
+        && (archive.complete || only.is_some_and(|member| archive.members.contains_key(member)))
+    {
+        return Ok(archive.clone());
+    }
+
+    log_debug(&format!("Expanding archive: {archive_path}"));
+    let mut expansion = Expansion {
+        archive_path,
+        limits,
+        members: BTreeMap::new(),
+        total_size: 0,
+    };
+    match kind {
+        ArchiveKind::Zip => {
+            let file = fs::File::open(archive_path)
+                .with_context(|| format!("Failed to open archive: {archive_path}"))?;
+            let mut archive = zip::ZipArchive::new(file)
+                .with_context(|| format!("Failed to read zip archive: {archive_path}"))?;
+            if let Some(member) = only {
+                if let Ok(entry) = archive.by_name(member)
+                    && entry.is_file()
+                {
+                    let declared_size = entry.size();
+                    expansion.add(member.to_string(), entry, declared_size)?;
+                }
+            } else {
+                for index in 0..archive.len() {
+                    let entry = archive.by_index(index)?;
+                    if entry.is_file() {
+                        let name = entry.name().to_string();
+                        let declared_size = entry.size();
+                        expansion.add(name, entry, declared_size)?;
+                    }
+                }
+            }
+        }
+        ArchiveKind::Tar | ArchiveKind::TarGz => {
+            let mut archive = open_tar(archive_path, kind)?;
+            for entry in archive
+                .entries()
+                .with_context(|| format!("Failed to read tar archive: {archive_path}"))?
+            {
+                let entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                if only.is_some_and(|member| member != name) {
+                    continue;
+                }
+                let declared_size = entry.header().size()?;
+                expansion.add(name, entry, declared_size)?;
+                if only.is_some() {
+                    break;
+                }
+            }
+        }
+    }
+
+    if only.is_none() {
+        log_info(&format!(
+            "Found {} file(s) in archive: {archive_path}",
+            expansion.members.len()
+        ));
+    }
+
+    let archive = Arc::new(ExpandedArchive {
+        path: archive_path.to_string(),
+        modified,
+        len: metadata.len(),
+        limits,
+        members: expansion.members,
+        complete: only.is_none(),
+    });
+    *last = Some(archive.clone());
+    Ok(archive)
+}
+
+/// Read a member, keeping its bytes only when they are within the size limit. At
+/// most one byte over the limit is read, so a forged size in the archive header
+/// can't make this allocate without bound.
+fn read_member(reader: impl Read, declared_size: u64, max_size: u64) -> Result<Member> {
+    let mut bytes = Vec::new();
+    reader.take(max_size + 1).read_to_end(&mut bytes)?;
+    let size = bytes.len() as u64;
+    if size > max_size {
+        return Ok(Member {
+            size: size.max(declared_size),
+            bytes: None,
+        });
+    }
+    Ok(Member {
+        size,
+        bytes: Some(bytes),
+    })
+}
+
+/// Look up a member of an archive by its virtual path
+fn with_member<T>(
+    archive_path: &str,
+    member: &str,
+    path: &str,
+    f: impl FnOnce(&Member) -> Result<T>,
+) -> Result<T> {
+    let archive = expand_archive(archive_path, Some(member), ArchiveLimits::current())?;
+    let member = archive
+        .members
+        .get(member)
+        .with_context(|| format!("Member not found in archive: {path}"))?;
+    f(member)
+}
+
+/// List all file members of an archive as virtual paths, sorted for consistent ordering
+pub fn list_archive_members(archive_path: &str) -> Result<Vec<String>> {
+    let archive = expand_archive(archive_path, None, ArchiveLimits::current())?;
+    Ok(archive
+        .members
+        .keys()
+        .map(|member| format!("{archive_path}{ARCHIVE_SEPARATOR}{member}"))
+        .collect())
+}
+
+fn open_tar(archive_path: &str, kind: ArchiveKind) -> Result<tar::Archive<Box<dyn Read>>> {
+    let file = fs::File::open(archive_path)
+        .with_context(|| format!("Failed to open archive: {archive_path}"))?;
+    let reader: Box<dyn Read> = if kind == ArchiveKind::TarGz {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(tar::Archive::new(reader))
+}
+
+/// Size in bytes of a regular file or archive member
+pub fn path_size(path: &str) -> Result<u64> {
+    let Some((archive_path, member)) = split_virtual_path(path) else {
+        return Ok(fs::metadata(path)
+            .with_context(|| format!("Failed to read metadata: {path}"))?
+            .len());
+    };
+
+    with_member(archive_path, member, path, |member| Ok(member.size))
+}
+
+/// Read the bytes of a regular file or archive member, enforcing the input size limit
+pub fn read_path_bytes(path: &str) -> Result<Vec<u8>> {
+    let too_large = |size: u64, max_size: u64| {
+        anyhow::anyhow!("File too large ({size} bytes, limit {max_size} bytes): {path}")
+    };
+
+    let Some((archive_path, member)) = split_virtual_path(path) else {
+        let size = path_size(path)?;
+        let max_size = get_max_input_file_size();
+        if size > max_size {
+            return Err(too_large(size, max_size));
+        }
+        return fs::read(path).with_context(|| format!("Failed to read file: {path}"));
+    };
+
+    log_debug(&format!("Reading archive member: {path}"));
+    with_member(archive_path, member, path, |member| {
+        member
+            .bytes
+            .clone()
+            .ok_or_else(|| too_large(member.size, get_max_input_file_size()))
+    })
+}
+
+/// Read up to `limit` bytes from the start of a regular file or archive member
+pub fn read_path_sample(path: &str, limit: usize) -> Result<Vec<u8>> {
+    if split_virtual_path(path).is_none() {
+        let file = fs::File::open(path).with_context(|| format!("Failed to open: {path}"))?;
+        let mut buffer = Vec::with_capacity(limit);
+        file.take(limit as u64).read_to_end(&mut buffer)?;
+        return Ok(buffer);
+    }
+
+    let mut bytes = read_path_bytes(path)?;
+    bytes.truncate(limit);
+    Ok(bytes)
+}
+
+/// Wrap the bytes of a file or archive member in a seekable reader
+pub fn open_path_reader(path: &str) -> Result<Cursor<Vec<u8>>> {
+    Ok(Cursor::new(read_path_bytes(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn create_zip(dir: &Path) -> String {
+        let path = dir.join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("logs/", options).unwrap();
+        zip.start_file("logs/app.log", options).unwrap();
+        zip.write_all(b"ERROR something failed").unwrap();
+        zip.start_file("README.md", options).unwrap();
+        zip.write_all(b"# Bundle").unwrap();
+        zip.finish().unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn create_tar_gz(dir: &Path) -> String {
+        let path = dir.join("snapshot.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        let content = b"fn main() {}";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "src/main.rs", &content[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_is_archive_file() {
+        assert!(is_archive_file(Path::new("bundle.zip")));
+        assert!(is_archive_file(Path::new("snapshot.tar")));
+        assert!(is_archive_file(Path::new("snapshot.tar.gz")));
+        assert!(is_archive_file(Path::new("snapshot.TGZ")));
+        assert!(!is_archive_file(Path::new("notes.gz")));
+        assert!(!is_archive_file(Path::new("main.rs")));
+    }
+
+    #[test]
+    fn test_split_virtual_path() {
+        assert_eq!(
+            split_virtual_path("bundle.zip!/logs/app.log"),
+            Some(("bundle.zip", "logs/app.log"))
+        );
+        assert_eq!(split_virtual_path("logs/app.log"), None);
+        assert_eq!(split_virtual_path("weird!/name.txt"), None);
+    }
+
+    #[test]
+    fn test_zip_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_zip(temp_dir.path());
+
+        let members = list_archive_members(&archive).unwrap();
+        assert_eq!(
+            members,
+            vec![
+                format!("{archive}!/README.md"),
+                format!("{archive}!/logs/app.log"),
+            ]
+        );
+
+        let bytes = read_path_bytes(&members[1]).unwrap();
+        assert_eq!(bytes, b"ERROR something failed");
+        assert_eq!(path_size(&members[1]).unwrap(), 22);
+    }
+
+    #[test]
+    fn test_tar_gz_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_tar_gz(temp_dir.path());
+
+        let members = list_archive_members(&archive).unwrap();
+        assert_eq!(members, vec![format!("{archive}!/src/main.rs")]);
+        assert_eq!(read_path_bytes(&members[0]).unwrap(), b"fn main() {}");
+        assert_eq!(read_path_sample(&members[0], 2).unwrap(), b"fn");
+    }
+
+    #[test]
+    fn test_changed_archive_is_read_again() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_zip(temp_dir.path());
+        let member = format!("{archive}!/README.md");
+        assert_eq!(read_path_bytes(&member).unwrap(), b"# Bundle");
+
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        zip.start_file("README.md", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"# Updated bundle").unwrap();
+        zip.finish().unwrap();
+
+        assert_eq!(read_path_bytes(&member).unwrap(), b"# Updated bundle");
+        assert!(read_path_bytes(&format!("{archive}!/logs/app.log")).is_err());
+    }
+
+    #[test]
+    fn test_missing_member() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_zip(temp_dir.path());
+
+        assert!(read_path_bytes(&format!("{archive}!/missing.txt")).is_err());
+    }
+
+    fn limits(member_size: u64, total_size: u64, members: usize) -> ArchiveLimits {
+        ArchiveLimits {
+            member_size,
+            total_size,
+            members,
+        }
+    }
+
+    #[test]
+    fn test_member_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_zip(temp_dir.path());
+
+        let expanded = expand_archive(&archive, None, limits(10, 1024, 10)).unwrap();
+        let member = &expanded.members["logs/app.log"];
+        assert!(member.bytes.is_none());
+        assert_eq!(member.size, 22);
+        assert_eq!(
+            expanded.members["README.md"].bytes.as_deref(),
+            Some(&b"# Bundle"[..])
+        );
+    }
+
+    #[test]
+    fn test_total_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_zip(temp_dir.path());
+
+        let error = expand_archive(&archive, None, limits(1024, 25, 10))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("limit of 25 bytes"), "{error}");
+    }
+
+    #[test]
+    fn test_member_count_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_zip(temp_dir.path());
+
+        let error = expand_archive(&archive, None, limits(1024, 1024, 1))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("more than 1 files"), "{error}");
+    }
+
+    #[test]
+    fn test_selected_member_is_read_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip = create_zip(temp_dir.path());
+        let tar = create_tar_gz(temp_dir.path());
+
+        let expanded = expand_archive(&zip, Some("README.md"), limits(1024, 1024, 1)).unwrap();
+        assert_eq!(expanded.members.keys().collect::<Vec<_>>(), ["README.md"]);
+        let expanded = expand_archive(&tar, Some("src/main.rs"), limits(1024, 1024, 1)).unwrap();
+        assert_eq!(expanded.members.keys().collect::<Vec<_>>(), ["src/main.rs"]);
+    }
+}
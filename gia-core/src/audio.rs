@@ -0,0 +1,639 @@
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use native_dialog::MessageDialog;
+use rubato::{
+    Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config_file::load_settings;
+use crate::logging::{log_debug, log_info};
+use crate::progress::{self, Stage};
+
+/// Resample audio data to a target sample rate
+/// Returns resampled data and the target sample rate
+fn resample_audio(audio_data: Vec<i16>, from_rate: u32, channels: u16) -> Result<(Vec<i16>, u32)> {
+    // Supported rates by ogg-opus in order of preference
+    const SUPPORTED_RATES: [u32; 5] = [48000, 24000, 16000, 12000, 8000];
+
+    // If already supported, return as-is
+    if SUPPORTED_RATES.contains(&from_rate) {
+        return Ok((audio_data, from_rate));
+    }
+
+    // Choose target rate (prefer 48000 Hz for quality)
+    let to_rate = 48000u32;
+
+    log_info(&format!(
+        "Resampling audio from {} Hz to {} Hz ({} channels)",
+        from_rate, to_rate, channels
+    ));
+    eprintln!(
+        "🔄 Resampling audio from {} Hz to {} Hz...",
+        from_rate, to_rate
+    );
+
+    // Convert i16 samples to f32 for resampling
+    let mut samples_f32: Vec<Vec<f32>> = vec![Vec::new(); channels as usize];
+
+    for (i, &sample) in audio_data.iter().enumerate() {
+        let channel = i % channels as usize;
+        samples_f32[channel].push(sample as f32 / 32768.0);
+    }
+
+    // Create resampler with high-quality settings
+    let params = SincInterpolationParameters {
+        sinc_len: 256,
+        f_cutoff: 0.95,
+        interpolation: SincInterpolationType::Linear,
+        oversampling_factor: 256,
+        window: WindowFunction::BlackmanHarris2,
+    };
+
+    let mut resampler = SincFixedIn::<f32>::new(
+        to_rate as f64 / from_rate as f64,
+        2.0,
+        params,
+        samples_f32[0].len(),
+        channels as usize,
+    )
+    .context("Failed to create resampler")?;
+
+    // Resample each channel
+    let resampled = resampler
+        .process(&samples_f32, None)
+        .context("Failed to resample audio")?;
+
+    // Interleave channels back to i16
+    let mut output = Vec::new();
+    let sample_count = resampled[0].len();
+
+    for i in 0..sample_count {
+        for (_channel, channel_data) in resampled.iter().enumerate().take(channels as usize) {
+            let sample = (channel_data[i] * 32768.0).clamp(-32768.0, 32767.0) as i16;
+            output.push(sample);
+        }
+    }
+
+    log_info(&format!(
+        "Resampling complete: {} samples -> {} samples",
+        audio_data.len() / channels as usize,
+        output.len() / channels as usize
+    ));
+
+    Ok((output, to_rate))
+}
+
+/// Names of the audio input devices, the default device first
+pub fn audio_input_devices() -> Result<Vec<String>> {
+    let host = cpal::default_host();
+    let default_device_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let devices = host
+        .input_devices()
+        .context("Failed to enumerate audio input devices")?;
+
+    let mut names: Vec<String> = default_device_name.into_iter().collect();
+    for device in devices {
+        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        if !names.contains(&device_name) {
+            names.push(device_name);
+        }
+    }
+    Ok(names)
+}
+
+/// List all available audio input devices
+pub fn list_audio_devices() -> Result<()> {
+    let has_default = cpal::default_host().default_input_device().is_some();
+    let names = audio_input_devices()?;
+
+    println!("Available audio input devices:");
+    println!();
+
+    for (index, name) in names.iter().enumerate() {
+        if index == 0 && has_default {
+            println!("  [DEFAULT] {}", name);
+        } else {
+            println!("  {}", name);
+        }
+    }
+
+    if names.is_empty() {
+        println!("  No audio input devices found");
+    }
+
+    println!();
+    println!("Usage:");
+    println!("  gia --audio-device \"Device Name\" --record-audio \"your prompt\"");
+    println!("  GIA_AUDIO_DEVICE=\"Device Name\" gia --record-audio \"your prompt\"");
+
+    Ok(())
+}
+
+/// Helper function to find a device by name
+fn find_device_by_name(host: &cpal::Host, target_name: &str) -> Result<Option<cpal::Device>> {
+    let devices = host
+        .input_devices()
+        .context("Failed to enumerate audio input devices")?;
+
+    for device in devices {
+        if let Ok(dev_name) = device.name()
+            && dev_name == target_name
+        {
+            return Ok(Some(device));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Get the audio device to use based on priority: CLI param > env var > settings > default
+fn get_audio_device(device_name: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+
+    // Priority 1: CLI parameter
+    if let Some(name) = device_name {
+        log_debug(&format!(
+            "Looking for audio device from CLI parameter: {}",
+            name
+        ));
+
+        if let Some(device) = find_device_by_name(&host, name)? {
+            log_info(&format!("Using audio device from CLI parameter: {}", name));
+            return Ok(device);
+        }
+
+        return Err(anyhow::anyhow!(
+            "Audio device '{}' not found. Use --list-audio-devices to see available devices.",
+            name
+        ));
+    }
+
+    // Priority 2: Environment variable
+    if let Ok(env_device) = std::env::var("GIA_AUDIO_DEVICE") {
+        log_debug(&format!(
+            "Looking for audio device from GIA_AUDIO_DEVICE: {}",
+            env_device
+        ));
+
+        if let Some(device) = find_device_by_name(&host, &env_device)? {
+            log_info(&format!(
+                "Using audio device from GIA_AUDIO_DEVICE: {}",
+                env_device
+            ));
+            return Ok(device);
+        }
+
+        return Err(anyhow::anyhow!(
+            "Audio device '{}' (from GIA_AUDIO_DEVICE) not found. Use --list-audio-devices to see available devices.",
+            env_device
+        ));
+    }
+
+    // Priority 3: Settings file
+    if let Some(settings_device) = load_settings().audio_device {
+        log_debug(&format!(
+            "Looking for audio device from settings: {}",
+            settings_device
+        ));
+
+        if let Some(device) = find_device_by_name(&host, &settings_device)? {
+            log_info(&format!(
+                "Using audio device from settings: {}",
+                settings_device
+            ));
+            return Ok(device);
+        }
+
+        return Err(anyhow::anyhow!(
+            "Audio device '{}' (from ~/.gia/config) not found. Use --list-audio-devices to see available devices.",
+            settings_device
+        ));
+    }
+
+    // Priority 4: Default device
+    log_debug("Using default audio input device");
+    host.default_input_device()
+        .ok_or_else(|| anyhow::anyhow!("No default input device available"))
+}
+
+/// Length of one waveform bucket of the level meter
+const WAVEFORM_BUCKET: Duration = Duration::from_millis(50);
+
+/// How long to wait for the first audio packet of a new recording
+const FIRST_PACKET_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Peak level and waveform of a running recording, levels are 0.0..=1.0
+#[derive(Debug, Default)]
+pub struct LevelMeter {
+    bucket_size: usize, // samples per waveform bucket (all channels)
+    bucket_len: usize,
+    bucket_peak: f32,
+    level: f32,
+    waveform: Vec<f32>,
+}
+
+impl LevelMeter {
+    fn new(sample_rate: u32, channels: u16) -> Self {
+        let samples_per_second = sample_rate as f32 * channels as f32;
+        Self {
+            bucket_size: ((samples_per_second * WAVEFORM_BUCKET.as_secs_f32()) as usize).max(1),
+            ..Default::default()
+        }
+    }
+
+    /// Add a packet of samples; the level is the peak of the latest packet
+    fn add(&mut self, samples: &[i16]) {
+        let mut packet_peak: f32 = 0.0;
+        for &sample in samples {
+            let value = (sample as f32 / 32768.0).abs();
+            packet_peak = packet_peak.max(value);
+            self.bucket_peak = self.bucket_peak.max(value);
+            self.bucket_len += 1;
+            if self.bucket_len == self.bucket_size {
+                self.waveform.push(self.bucket_peak);
+                self.bucket_len = 0;
+                self.bucket_peak = 0.0;
+            }
+        }
+        self.level = packet_peak;
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    /// Peak of every 50 ms of the recording so far
+    pub fn waveform(&self) -> &[f32] {
+        &self.waveform
+    }
+}
+
+type SharedWavWriter = Arc<Mutex<Option<hound::WavWriter<std::io::BufWriter<fs::File>>>>>;
+
+/// A running recording to a WAV file. Unlike `record_audio` it shows no dialog;
+/// the caller decides when to `stop` or `cancel` it.
+pub struct Recorder {
+    stream: cpal::Stream,
+    writer: SharedWavWriter,
+    recording: Arc<Mutex<bool>>,
+    meter: Arc<Mutex<LevelMeter>>,
+    device_name: String,
+    wav_path: PathBuf,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Start recording from the device (priority: parameter > env var > settings > default).
+    /// Returns once audio arrives.
+    pub fn start(device_name: Option<&str>) -> Result<Self> {
+        log_debug("Starting native audio recording with cpal");
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let wav_path = std::env::temp_dir().join(format!("{timestamp}-prompt.wav"));
+        log_debug(&format!("Recording to: {}", wav_path.display()));
+
+        let device = get_audio_device(device_name)?;
+        let device_name = device.name().unwrap_or_else(|_| "Unknown".to_string());
+        log_info(&format!("Using audio device: {device_name}"));
+
+        let config = device
+            .default_input_config()
+            .context("Failed to get default input config")?;
+
+        log_debug(&format!(
+            "Audio config - Sample rate: {}, Channels: {}, Format: {:?}",
+            config.sample_rate().0,
+            config.channels(),
+            config.sample_format()
+        ));
+
+        // Create WAV writer
+        let spec = hound::WavSpec {
+            channels: config.channels(),
+            sample_rate: config.sample_rate().0,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let writer: SharedWavWriter = Arc::new(Mutex::new(Some(
+            hound::WavWriter::create(&wav_path, spec).context("Failed to create WAV writer")?,
+        )));
+        log_debug("WAV writer created successfully");
+
+        let input = InputState {
+            writer: Arc::clone(&writer),
+            recording: Arc::new(Mutex::new(true)),
+            meter: Arc::new(Mutex::new(LevelMeter::new(
+                config.sample_rate().0,
+                config.channels(),
+            ))),
+            packet_count: Arc::new(AtomicUsize::new(0)),
+        };
+        let recording = Arc::clone(&input.recording);
+        let meter = Arc::clone(&input.meter);
+        let packet_count = Arc::clone(&input.packet_count);
+
+        // Build input stream based on sample format - write directly to WAV
+        let stream_config: cpal::StreamConfig = config.clone().into();
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => {
+                input.build_stream(&device, &stream_config, |s: f32| (s * 32767.0) as i16)
+            }
+            cpal::SampleFormat::I16 => input.build_stream(&device, &stream_config, |s: i16| s),
+            cpal::SampleFormat::U16 => {
+                input.build_stream(&device, &stream_config, |s: u16| (s as i32 - 32768) as i16)
+            }
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported sample format: {:?}",
+                    config.sample_format()
+                ));
+            }
+        }
+        .context("Failed to build input stream")?;
+
+        stream.play().context("Failed to start audio stream")?;
+        log_debug("Audio stream started");
+
+        // Wait for the first audio packet, so that the caller only says "speak now"
+        // when audio is actually flowing
+        let waiting = Instant::now();
+        while packet_count.load(Ordering::Relaxed) == 0 {
+            if waiting.elapsed() > FIRST_PACKET_TIMEOUT {
+                return Err(anyhow::anyhow!(
+                    "No audio arrives from device '{device_name}'"
+                ));
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        Ok(Self {
+            stream,
+            writer,
+            recording,
+            meter,
+            device_name,
+            wav_path,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Peak level of the latest packet
+    pub fn level(&self) -> f32 {
+        self.meter.lock().unwrap().level()
+    }
+
+    /// Peak of every 50 ms recorded so far
+    pub fn waveform(&self) -> Vec<f32> {
+        self.meter.lock().unwrap().waveform().to_vec()
+    }
+
+    /// Stop recording and return the finished WAV file (see `wav_to_opus`)
+    pub fn stop(self) -> Result<PathBuf> {
+        *self.recording.lock().unwrap() = false;
+        drop(self.stream);
+
+        // Extract the writer from the Option without unwrapping the Arc (cpal pattern)
+        self.writer
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("WAV writer was already taken"))?
+            .finalize()
+            .context("Failed to finalize WAV file")?;
+
+        log_debug("WAV file finalized");
+
+        let wav_size = fs::metadata(&self.wav_path)
+            .context("Failed to get WAV file metadata")?
+            .len();
+
+        if wav_size == 0 {
+            return Err(anyhow::anyhow!("WAV file is empty - no audio recorded"));
+        }
+
+        log_info(&format!("✅ Recorded WAV file: {wav_size} bytes"));
+        Ok(self.wav_path)
+    }
+
+    /// Stop recording and throw the audio away
+    pub fn cancel(self) {
+        *self.recording.lock().unwrap() = false;
+        drop(self.stream);
+        drop(self.writer.lock().unwrap().take());
+        let _ = fs::remove_file(&self.wav_path);
+        log_debug("Recording cancelled");
+    }
+}
+
+/// What the input stream callback writes to
+struct InputState {
+    writer: SharedWavWriter,
+    recording: Arc<Mutex<bool>>,
+    meter: Arc<Mutex<LevelMeter>>,
+    packet_count: Arc<AtomicUsize>,
+}
+
+impl InputState {
+    fn build_stream<T: cpal::SizedSample + 'static>(
+        self,
+        device: &cpal::Device,
+        config: &cpal::StreamConfig,
+        to_i16: fn(T) -> i16,
+    ) -> std::result::Result<cpal::Stream, cpal::BuildStreamError> {
+        device.build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                if *self.recording.lock().unwrap()
+                    && let Some(ref mut writer) = *self.writer.lock().unwrap()
+                {
+                    let samples: Vec<i16> = data.iter().map(|&sample| to_i16(sample)).collect();
+                    for &sample in &samples {
+                        let _ = writer.write_sample(sample);
+                    }
+                    self.meter.lock().unwrap().add(&samples);
+                    self.packet_count.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+            |err| log_debug(&format!("Stream error: {err}")),
+            None,
+        )
+    }
+}
+
+/// Convert a recorded WAV file to Opus (next to it, `.opus`) and remove the WAV file.
+/// Returns the path to the Opus file.
+pub fn wav_to_opus(wav_path: &Path) -> Result<String> {
+    log_debug("Converting WAV to Opus with ogg-opus...");
+    let opus_path = wav_path
+        .with_extension("opus")
+        .to_string_lossy()
+        .to_string();
+
+    // Read WAV file back using hound
+    let mut reader =
+        hound::WavReader::open(wav_path).context("Failed to open WAV file for conversion")?;
+    let wav_spec = reader.spec();
+
+    // Read all samples as i16
+    let audio_data: Vec<i16> = reader
+        .samples::<i16>()
+        .collect::<Result<Vec<i16>, _>>()
+        .context("Failed to read audio samples from WAV file")?;
+
+    // Resample if necessary (handles any sample rate -> supported rate)
+    let (resampled_data, target_rate) =
+        resample_audio(audio_data, wav_spec.sample_rate, wav_spec.channels)?;
+
+    // Encode to Opus based on sample rate and channel count
+    let opus_data = match (target_rate, wav_spec.channels) {
+        (16000, 1) => ogg_opus::encode::<16000, 1>(&resampled_data),
+        (16000, 2) => ogg_opus::encode::<16000, 2>(&resampled_data),
+        (8000, 1) => ogg_opus::encode::<8000, 1>(&resampled_data),
+        (8000, 2) => ogg_opus::encode::<8000, 2>(&resampled_data),
+        (12000, 1) => ogg_opus::encode::<12000, 1>(&resampled_data),
+        (12000, 2) => ogg_opus::encode::<12000, 2>(&resampled_data),
+        (24000, 1) => ogg_opus::encode::<24000, 1>(&resampled_data),
+        (24000, 2) => ogg_opus::encode::<24000, 2>(&resampled_data),
+        (48000, 1) => ogg_opus::encode::<48000, 1>(&resampled_data),
+        (48000, 2) => ogg_opus::encode::<48000, 2>(&resampled_data),
+        _ => return Err(anyhow::anyhow!(
+            "Unsupported WAV format after resampling: {} Hz, {} channels (supported: 8k/12k/16k/24k/48k Hz, 1-2 channels)",
+            target_rate,
+            wav_spec.channels
+        )),
+    }.context("Failed to encode audio to Opus format")?;
+
+    // Write Opus file
+    fs::write(&opus_path, &opus_data).context("Failed to write Opus file")?;
+
+    // Clean up WAV file
+    let _ = fs::remove_file(wav_path);
+
+    // Verify Opus file
+    let opus_size = opus_data.len() as u64;
+    if opus_size == 0 {
+        return Err(anyhow::anyhow!(
+            "Opus conversion failed - output file is empty"
+        ));
+    }
+
+    log_info(&format!("✅ Converted to Opus: {opus_size} bytes"));
+    Ok(opus_path)
+}
+
+/// Record audio natively using cpal (fast recording to WAV, then quick ogg-opus conversion)
+/// Returns the path to the recorded Opus file
+pub fn record_audio_native(device_name: Option<&str>) -> Result<String> {
+    let recorder = Recorder::start(device_name)?;
+    eprintln!(
+        "🎙️  Recording audio from device: {}",
+        recorder.device_name()
+    );
+
+    // Immediate visual feedback - recording is now active (after first packet received)
+    eprintln!("🎤 SPEAK NOW!");
+    progress::report(Stage::Recording {
+        device: recorder.device_name().to_string(),
+    });
+
+    // Show message dialog to stop recording (no MessageType to avoid Windows notification sound)
+    log_debug("Showing message dialog to stop recording");
+    let dialog_text = format!(
+        "🎙️  Recording in progress from device:\n{}\n\nClick Yes to take over the recording, No to cancel",
+        recorder.device_name()
+    );
+    let user_confirmed = MessageDialog::new()
+        .set_title("Recording...")
+        .set_text(&dialog_text)
+        .show_confirm()
+        .context("Failed to show recording dialog");
+
+    match user_confirmed {
+        Ok(true) => log_debug("User clicked OK, stopping recording"),
+        Ok(false) => {
+            log_debug("User pressed Cancel, aborting");
+            recorder.cancel();
+            return Err(anyhow::anyhow!("Recording cancelled by user"));
+        }
+        Err(e) => {
+            recorder.cancel();
+            return Err(e);
+        }
+    }
+
+    let wav_path = recorder.stop()?;
+
+    // Convert WAV to Opus (fast since WAV is already recorded)
+    eprintln!("🔄 Converting to Opus format...");
+    progress::report(Stage::Converting);
+    let opus_path = wav_to_opus(&wav_path)?;
+    eprintln!("✅ Audio recording complete!");
+
+    Ok(opus_path)
+}
+
+/// Record audio using native Rust implementation (cpal + ogg-opus)
+/// No external dependencies required
+///
+/// Priority for device selection:
+/// 1. device_name parameter (from CLI --audio-device)
+/// 2. GIA_AUDIO_DEVICE environment variable
+/// 3. audio_device in the settings of ~/.gia/config
+/// 4. Default system audio input device
+pub fn record_audio(device_name: Option<&str>) -> Result<String> {
+    log_debug("Starting native audio recording...");
+    record_audio_native(device_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_meter() {
+        // 20 samples per second, one channel: one bucket per sample
+        let mut meter = LevelMeter::new(20, 1);
+        assert_eq!(meter.bucket_size, 1);
+
+        // 400 Hz, two channels: 40 samples per bucket
+        let mut stereo = LevelMeter::new(400, 2);
+        stereo.add(&[16384; 30]);
+        assert!(stereo.waveform().is_empty());
+        stereo.add(&[-32768; 10]);
+        assert_eq!(stereo.waveform(), &[1.0]);
+        assert_eq!(stereo.level(), 1.0);
+        stereo.add(&[0; 40]);
+        assert_eq!(stereo.waveform(), &[1.0, 0.0]);
+        assert_eq!(stereo.level(), 0.0);
+
+        meter.add(&[8192, -16384]);
+        assert_eq!(meter.waveform(), &[0.25, 0.5]);
+        assert_eq!(meter.level(), 0.5);
+    }
+
+    #[test]
+    fn test_wav_to_opus_missing_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let err = wav_to_opus(&dir.path().join("missing.wav")).unwrap_err();
+        assert_eq!(err.to_string(), "Failed to open WAV file for conversion");
+        assert!(!dir.path().join("missing.opus").exists());
+    }
+}
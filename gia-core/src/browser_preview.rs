@@ -133,12 +133,16 @@ hr {
 
 use crate::conversation::TokenUsage;
 
+/// Details about a response shown below it: model, tokens and inputs
+#[derive(Debug, Clone)]
 pub struct FooterMetadata {
     pub model_name: String,
     pub provider_name: String,
     pub timestamp: DateTime<Utc>,
     pub image_files: Vec<String>,
     pub text_files: Vec<String>,
+    pub git_sources: Vec<String>,
+    pub web_pages: Vec<String>,
     pub has_clipboard: bool,
     pub has_audio: bool,
     pub has_stdin: bool,
@@ -148,6 +152,41 @@ pub struct FooterMetadata {
     pub token_usage: Option<TokenUsage>,
 }
 
+impl FooterMetadata {
+    /// The inputs of the footer as plain text lines (the HTML footer links web pages)
+    pub fn input_items(&self) -> Vec<String> {
+        let mut items = Vec::new();
+        if !self.roles.is_empty() {
+            items.push(format!("Roles: {}", self.roles.join(", ")));
+        }
+        if !self.tasks.is_empty() {
+            items.push(format!("Tasks: {}", self.tasks.join(", ")));
+        }
+        if !self.image_files.is_empty() {
+            items.push(format!("Images: {}", self.image_files.join(", ")));
+        }
+        if !self.text_files.is_empty() {
+            items.push(format!("Text files: {}", self.text_files.join(", ")));
+        }
+        if !self.git_sources.is_empty() {
+            items.push(format!("Git: {}", self.git_sources.join(", ")));
+        }
+        if !self.web_pages.is_empty() {
+            items.push(format!("Web pages: {}", self.web_pages.join(", ")));
+        }
+        if self.has_clipboard {
+            items.push("Clipboard content".to_string());
+        }
+        if self.has_audio {
+            items.push("Audio recording".to_string());
+        }
+        if self.has_stdin {
+            items.push("Stdin content".to_string());
+        }
+        items
+    }
+}
+
 pub fn open_markdown_preview(
     markdown_content: &str,
     md_file_path: &Path,
@@ -199,6 +238,8 @@ fn build_footer_html(metadata: &FooterMetadata) -> String {
     // Inputs section
     if !metadata.image_files.is_empty()
         || !metadata.text_files.is_empty()
+        || !metadata.git_sources.is_empty()
+        || !metadata.web_pages.is_empty()
         || metadata.has_clipboard
         || metadata.has_audio
         || metadata.has_stdin
@@ -229,6 +270,28 @@ fn build_footer_html(metadata: &FooterMetadata) -> String {
             ));
         }
 
+        if !metadata.git_sources.is_empty() {
+            footer.push_str(&format!(
+                "<li>Git: {}</li>",
+                html_escape::encode_text(&metadata.git_sources.join(", "))
+            ));
+        }
+
+        if !metadata.web_pages.is_empty() {
+            let links: Vec<String> = metadata
+                .web_pages
+                .iter()
+                .map(|url| {
+                    format!(
+                        r#"<a href="{}" target="_blank">{}</a>"#,
+                        html_escape::encode_double_quoted_attribute(url),
+                        html_escape::encode_text(url)
+                    )
+                })
+                .collect();
+            footer.push_str(&format!("<li>Web pages: {}</li>", links.join(", ")));
+        }
+
         if metadata.has_clipboard {
             footer.push_str("<li>Clipboard content</li>");
         }
@@ -307,6 +370,8 @@ mod tests {
             timestamp: Utc::now(),
             image_files: vec![],
             text_files: vec![],
+            git_sources: vec![],
+            web_pages: vec![],
             has_clipboard: false,
             has_audio: false,
             has_stdin: false,
@@ -330,6 +395,8 @@ mod tests {
             timestamp: Utc::now(),
             image_files: vec!["image1.jpg".to_string(), "image2.png".to_string()],
             text_files: vec!["file.txt".to_string()],
+            git_sources: vec![],
+            web_pages: vec!["https://example.com/a?b=1&c=2".to_string()],
             has_clipboard: true,
             has_audio: true,
             has_stdin: true,
@@ -344,11 +411,44 @@ mod tests {
         assert!(footer.contains("image1.jpg"));
         assert!(footer.contains("image2.png"));
         assert!(footer.contains("file.txt"));
+        assert!(footer.contains(
+            r#"Web pages: <a href="https://example.com/a?b=1&amp;c=2" target="_blank">"#
+        ));
         assert!(footer.contains("Clipboard"));
         assert!(footer.contains("Audio"));
         assert!(footer.contains("Stdin"));
     }
 
+    #[test]
+    fn test_footer_input_items() {
+        let metadata = FooterMetadata {
+            model_name: "model".to_string(),
+            provider_name: "provider".to_string(),
+            timestamp: Utc::now(),
+            image_files: vec![],
+            text_files: vec!["a.rs".to_string(), "b.rs".to_string()],
+            git_sources: vec!["diff HEAD~1".to_string()],
+            web_pages: vec![],
+            has_clipboard: true,
+            has_audio: false,
+            has_stdin: false,
+            roles: vec!["rust-dev".to_string()],
+            tasks: vec![],
+            prompt: String::new(),
+            token_usage: None,
+        };
+
+        assert_eq!(
+            metadata.input_items(),
+            vec![
+                "Roles: rust-dev",
+                "Text files: a.rs, b.rs",
+                "Git: diff HEAD~1",
+                "Clipboard content"
+            ]
+        );
+    }
+
     #[test]
     fn test_build_prompt_header_with_prompt() {
         let metadata = FooterMetadata {
@@ -357,6 +457,8 @@ mod tests {
             timestamp: Utc::now(),
             image_files: vec![],
             text_files: vec![],
+            git_sources: vec![],
+            web_pages: vec![],
             has_clipboard: false,
             has_audio: false,
             has_stdin: false,
@@ -380,6 +482,8 @@ mod tests {
             timestamp: Utc::now(),
             image_files: vec![],
             text_files: vec![],
+            git_sources: vec![],
+            web_pages: vec![],
             has_clipboard: false,
             has_audio: false,
             has_stdin: false,
@@ -402,6 +506,8 @@ mod tests {
             timestamp: Utc::now(),
             image_files: vec![],
             text_files: vec![],
+            git_sources: vec![],
+            web_pages: vec![],
             has_clipboard: false,
             has_audio: false,
             has_stdin: false,
@@ -437,6 +543,8 @@ mod tests {
             timestamp: Utc::now(),
             image_files: vec![],
             text_files: vec![],
+            git_sources: vec![],
+            web_pages: vec![],
             has_clipboard: false,
             has_audio: false,
             has_stdin: false,
@@ -0,0 +1,988 @@
+use crate::constants::{DEFAULT_SERVE_PORT, get_default_model, get_default_tts_language};
+use crate::role::{
+    COMMIT_MESSAGE_TASK, RoleOutput, RoleSettings, list_definitions, load_role_settings,
+};
+use clap::builder::PossibleValuesParser;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgMatches, Command};
+use clap_complete::{generate, shells};
+use clap_complete_nushell::Nushell;
+
+#[derive(Debug, Clone)]
+pub enum OutputMode {
+    Stdout,
+    Clipboard,
+    TempFileWithPreview,
+    Tts(String),   // language code (e.g., "de-DE", "en-US")
+    CommitMessage, // write to .git/COMMIT_EDITMSG
+}
+
+#[derive(Debug, Clone)]
+pub enum ContentSource {
+    CommandLinePrompt(String),
+    AudioRecording(String), // file path
+    ClipboardText(String),
+    StdinText(String),
+    TextFile(String, String, Option<String>), // (file_path, content, selection label)
+    ImageFile(String),                        // file path
+    ClipboardImage,
+    RoleDefinition(String, String, bool), // (name, content, is_task)
+    GitDiff(String, String),              // (description, diff with stat header)
+    GitLog(String, String),               // (range, log with stats and patches)
+    WebPage(String, String),              // (url, page content as markdown)
+    EmbeddedImage(String, String, String), // (source, mime_type, base64 data)
+}
+
+/// Address and access token of the HTTP API (`gia serve`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServeOptions {
+    pub host: String,
+    pub port: u16,
+    pub token: Option<String>, // None = no Authorization header required
+    pub save_proxied: bool,    // save /v1/chat/completions exchanges as conversations
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub prompt: String,
+    pub use_clipboard_input: bool,
+
+    pub text_files: Vec<String>,
+    pub output_mode: OutputMode,
+    pub resume_conversation: Option<String>, // None = new, Some("") = latest, Some(id) = specific
+    pub resume_last: bool,                   // true = resume latest conversation
+    pub list_conversations: Option<usize>, // None = don't list, Some(n) = list top n, Some(0) = list all
+    pub show_conversation: Option<String>, // Some(id) = show specific conversation
+    pub model: String,
+    pub record_audio: bool,                  // true = record audio input
+    pub audio_device: Option<String>,        // None = default/env, Some(name) = specific device
+    pub list_audio_devices: bool,            // true = list audio devices and exit
+    pub roles: Vec<String>,                  // role names to load from ~/.gia/<role>.md
+    pub ordered_content: Vec<ContentSource>, // ordered content for multimodal requests
+    pub spinner: bool,                       // true = show progress overlay during AI request
+    pub no_save: bool, // true = don't save to conversation history (transcribe-only mode)
+    pub native_documents: bool, // true = upload PDFs natively when the provider supports it
+    pub git_diff: Option<String>, // None = no diff, Some("") = working tree, Some(range) = range
+    pub git_staged: bool, // true = add staged changes
+    pub git_log: Option<String>, // Some(range) = add commit log for range
+    pub urls: Vec<String>, // web pages (http, https, file) to add as content
+    pub vars: Vec<(String, String)>, // template variables from --var key=value
+    pub temperature: Option<f64>, // None = provider default
+    pub tools: bool,   // true = let the model call local tools
+    pub allowed_commands: Vec<String>, // programs the run_command tool may run
+    pub mcp_servers: Vec<String>, // MCP servers from ~/.gia/config to connect for this request
+    pub mcp_serve: bool, // true = serve gia as MCP tools over stdio (`gia mcp-serve`)
+    pub read_stdin: bool, // false = never read stdin (it carries requests in server modes)
+    pub serve: Option<ServeOptions>, // Some = run the HTTP API (`gia serve`)
+    pub remote_request: bool, // true = request from the HTTP API or MCP: no template functions, public URLs only
+}
+
+impl Config {
+    pub fn from_args() -> Self {
+        let matches = Self::build_cli().get_matches();
+
+        // Handle completions generation immediately
+        if let Some(shell) = matches.get_one::<String>("completions") {
+            Self::handle_completions(shell);
+            std::process::exit(0);
+        }
+
+        // Handle verbose help immediately
+        if matches.get_flag("verbose-help") {
+            Self::handle_verbose_help();
+            std::process::exit(0);
+        }
+
+        // Handle role/task management immediately
+        if let Some(("roles", roles_matches)) = matches.subcommand() {
+            if let Err(e) = crate::roles_command::run_roles_command(roles_matches) {
+                eprintln!("Error: {e:#}");
+                std::process::exit(1);
+            }
+            std::process::exit(0);
+        }
+
+        match Self::from_matches(&matches) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Error: {e:#}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /// Config for a request given as gia command line arguments (without the program
+    /// name), e.g. from the MCP server. Role/task front-matter is applied as usual.
+    pub fn from_arg_list(args: &[String]) -> anyhow::Result<Self> {
+        let matches = Self::build_cli()
+            .try_get_matches_from(std::iter::once("gia".to_string()).chain(args.iter().cloned()))?;
+        Self::from_matches(&matches)
+    }
+
+    /// The `--help` text of the gia command line
+    pub fn help_text() -> String {
+        Self::build_cli().render_help().to_string()
+    }
+
+    fn serve_options(matches: &ArgMatches) -> Option<ServeOptions> {
+        let (_, serve) = matches.subcommand().filter(|(name, _)| *name == "serve")?;
+        Some(ServeOptions {
+            host: serve.get_one::<String>("host").unwrap().clone(),
+            port: *serve.get_one::<u16>("port").unwrap(),
+            token: serve.get_one::<String>("token").cloned(),
+            save_proxied: serve.get_flag("save-proxied"),
+        })
+    }
+
+    fn from_matches(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let prompt_parts: Vec<String> = matches
+            .get_many::<String>("prompt")
+            .unwrap_or_default()
+            .cloned()
+            .collect();
+
+        let resume_conversation = matches.get_one::<String>("resume").cloned();
+
+        let output_mode = if matches.get_flag("browser-output") {
+            OutputMode::TempFileWithPreview
+        } else if matches.get_flag("clipboard-output") {
+            OutputMode::Clipboard
+        } else if let Some(lang) = matches.get_one::<String>("tts-output").cloned() {
+            OutputMode::Tts(lang)
+        } else {
+            OutputMode::Stdout
+        };
+
+        let text_files: Vec<String> = matches
+            .get_many::<String>("file")
+            .unwrap_or_default()
+            .cloned()
+            .collect();
+
+        let roles: Vec<String> = matches
+            .get_many::<String>("role")
+            .unwrap_or_default()
+            .cloned()
+            .collect();
+
+        let mut config = Self {
+            prompt: prompt_parts.join(" "),
+            use_clipboard_input: matches.get_flag("clipboard-input"),
+
+            text_files,
+            output_mode,
+            resume_conversation,
+            resume_last: matches.get_flag("resume-last"),
+            list_conversations: matches
+                .get_one::<String>("list-conversations")
+                .map(|s| s.parse::<usize>().unwrap_or(0)),
+            show_conversation: matches.get_one::<String>("show-conversation").cloned(),
+            model: matches.get_one::<String>("model").unwrap().clone(),
+            record_audio: matches.get_flag("record-audio"),
+            audio_device: matches.get_one::<String>("audio-device").cloned(),
+            list_audio_devices: matches.get_flag("list-audio-devices"),
+            roles,
+            ordered_content: Vec::new(), // will be populated in input.rs
+            spinner: matches.get_flag("spinner"),
+            no_save: matches.get_flag("no-save"),
+            native_documents: matches.get_flag("native-docs"),
+            git_diff: matches.get_one::<String>("git-diff").cloned(),
+            git_staged: matches.get_flag("git-staged"),
+            git_log: matches.get_one::<String>("git-log").cloned(),
+            urls: matches
+                .get_many::<String>("url")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            vars: matches
+                .get_many::<(String, String)>("var")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            temperature: matches.get_one::<f64>("temperature").copied(),
+            tools: matches.get_flag("tools")
+                || matches.contains_id("allow-command")
+                || matches.contains_id("mcp"),
+            allowed_commands: matches
+                .get_many::<String>("allow-command")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            mcp_servers: matches
+                .get_many::<String>("mcp")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            mcp_serve: matches.subcommand_name() == Some("mcp-serve"),
+            read_stdin: matches.subcommand_name().is_none(),
+            serve: Self::serve_options(matches),
+            remote_request: false,
+        };
+
+        // Role/task front-matter fills in options not given on the command line
+        let settings = load_role_settings(&config.roles)?;
+        config.apply_role_settings(settings, matches);
+
+        config.apply_task_defaults();
+        Ok(config)
+    }
+
+    /// Apply settings from role/task front-matter. Options given on the command line
+    /// take precedence; default files are only used when no -f is given.
+    fn apply_role_settings(&mut self, settings: RoleSettings, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        if let Some(model) = settings.model
+            && !from_cli("model")
+        {
+            self.model = model;
+        }
+
+        if settings.temperature.is_some() && !from_cli("temperature") {
+            self.temperature = settings.temperature;
+        }
+
+        let output_from_cli = ["browser-output", "clipboard-output", "tts-output"]
+            .iter()
+            .any(|id| from_cli(id));
+        if let Some(output) = settings.output
+            && !output_from_cli
+        {
+            self.output_mode = match output {
+                RoleOutput::Stdout => OutputMode::Stdout,
+                RoleOutput::Clipboard => OutputMode::Clipboard,
+                RoleOutput::Browser => OutputMode::TempFileWithPreview,
+                RoleOutput::Tts => OutputMode::Tts(
+                    settings
+                        .tts_language
+                        .clone()
+                        .unwrap_or_else(get_default_tts_language),
+                ),
+            };
+        }
+
+        if !from_cli("no-save")
+            && let Some(no_save) = settings.no_save
+        {
+            self.no_save = no_save;
+        }
+
+        if self.text_files.is_empty() {
+            self.text_files = settings.files;
+        }
+    }
+
+    /// Built-in tasks that imply inputs or outputs.
+    /// `-t commit-message` reads staged changes (unless another git source is given)
+    /// and writes to .git/COMMIT_EDITMSG (unless another output is chosen).
+    fn apply_task_defaults(&mut self) {
+        if !self.roles.iter().any(|r| r == COMMIT_MESSAGE_TASK) {
+            return;
+        }
+
+        if self.git_diff.is_none() && self.git_log.is_none() {
+            self.git_staged = true;
+        }
+
+        if matches!(self.output_mode, OutputMode::Stdout) {
+            self.output_mode = OutputMode::CommitMessage;
+        }
+    }
+
+    fn build_cli() -> Command {
+        Command::new("gia")
+            .version(env!("GIA_VERSION"))
+            .about("AI CLI tool using Google Gemini API (stdout default)")
+            .next_help_heading("Input Options")
+            .arg(
+                Arg::new("prompt")
+                    .help("Prompt text for the AI")
+                    .num_args(0..)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("clipboard-input")
+                    .short('c')
+                    .long("clipboard-input")
+                    .help("Add clipboard content to prompt")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("file")
+                    .short('f')
+                    .long("file")
+                    .help("Add file content to prompt (can be used multiple times). Automatically detects media files (jpg, png, mp4, etc.), documents (pdf, docx, odt, pptx, xlsx, csv) and text files. Supports files, directories and archives (zip, tar, tar.gz; processes all files recursively). Use 'path:120-180' or 'path#symbol' to send only part of a file")
+                    .value_name("FILE_OR_DIR")
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("url")
+                    .short('u')
+                    .long("url")
+                    .help("Add readable content of a web page (http, https or file:// HTML) to prompt (can be used multiple times). Navigation and other boilerplate is removed and small images are attached")
+                    .value_name("URL")
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("var")
+                    .long("var")
+                    .help("Set a template variable used as {{key}} in the prompt and role/task files (can be used multiple times). Built-in: {{date}}, {{time}}, {{lang}}, {{clipboard}}, {{file:path}}, {{env:VAR}}")
+                    .value_name("KEY=VALUE")
+                    .value_parser(crate::template::parse_var)
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("native-docs")
+                    .long("native-docs")
+                    .help("Upload PDFs natively instead of extracting their text locally (only if the provider supports it, e.g. Gemini)")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("git-diff")
+                    .long("git-diff")
+                    .help("Add git diff with file summary (working tree, or a range like '--git-diff=HEAD~3' or '--git-diff=main..feature')")
+                    .value_name("RANGE")
+                    .num_args(0..=1)
+                    .require_equals(true)
+                    .default_missing_value("")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("git-staged")
+                    .long("git-staged")
+                    .help("Add staged changes (git diff --cached) with file summary")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("git-log")
+                    .long("git-log")
+                    .help("Add commit messages and changes for a range (e.g., 'main..feature', '-3'). Size is limited by GIA_GIT_BUDGET")
+                    .value_name("RANGE")
+                    .allow_hyphen_values(true)
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("record-audio")
+                    .short('a')
+                    .long("record-audio")
+                    .help("Record audio input natively (no external dependencies required)")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("audio-device")
+                    .long("audio-device")
+                    .help("Specify audio input device name for recording. Overrides GIA_AUDIO_DEVICE environment variable. Use --list-audio-devices to see available devices.")
+                    .value_name("DEVICE")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("list-audio-devices")
+                    .long("list-audio-devices")
+                    .help("List all available audio input devices and exit")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("role")
+                    .short('t')
+                    .long("role")
+                    .help("Load role/task from roles/<name>.md or tasks/<name>.md in the project's .gia/, GIA_ROLE_DIRS or ~/.gia (can be used multiple times). Built-in task 'commit-message' writes a commit message for staged changes to .git/COMMIT_EDITMSG")
+                    .value_name("NAME")
+                    .action(clap::ArgAction::Append),
+            )
+            .next_help_heading("Output Options")
+            .arg(
+                Arg::new("clipboard-output")
+                    .short('o')
+                    .long("clipboard-output")
+                    .help("Write output to clipboard instead of stdout")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("browser-output")
+                    .short('b')
+                    .long("browser-output")
+                    .help("Write output to temp file, copy file path to clipboard, and open browser preview")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("tts-output")
+                    .short('T')
+                    .long("tts")
+                    .help("Use text-to-speech for output with optional language (e.g., 'de-DE', 'en-US'). Default: de-DE or tts_language from ~/.gia/config")
+                    .value_name("LANG")
+                    .num_args(0..=1)
+                    .default_missing_value(get_default_tts_language())
+                    .action(clap::ArgAction::Set),
+            )
+            .next_help_heading("Conversation Management")
+            .arg(
+                Arg::new("resume")
+                    .short('r')
+                    .long("resume")
+                    .help("Resume last conversation or specify conversation ID")
+                    .value_name("ID")
+                    .num_args(0..=1)
+                    .default_missing_value("")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("resume-last")
+                    .short('R')
+                    .help("Resume the very last conversation")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("list-conversations")
+                    .short('l')
+                    .long("list-conversations")
+                    .help("List saved conversations (optionally specify number to limit, empty for all)")
+                    .value_name("NUMBER")
+                    .num_args(0..=1)
+                    .default_missing_value("0")
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("show-conversation")
+                    .short('s')
+                    .long("show-conversation")
+                    .help("Show a specific conversation in chat mode (or latest if no ID provided)")
+                    .value_name("ID")
+                    .num_args(0..=1)
+                    .default_missing_value("")
+                    .action(clap::ArgAction::Set),
+            )
+            .next_help_heading("Other Options")
+            .arg(
+                Arg::new("model")
+                    .short('m')
+                    .long("model")
+                    .help("Specify the model to use. Format: 'provider::model' or just 'model' for Gemini (e.g., 'ollama::llama3.2', 'gemini-2.5-flash-lite'). Can be set via GIA_DEFAULT_MODEL environment variable.")
+                    .value_name("MODEL")
+                    .default_value(get_default_model())
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("temperature")
+                    .long("temperature")
+                    .help("Sampling temperature for the model (e.g., 0.2 for focused, 1.0 for creative answers). Overrides the role/task front-matter")
+                    .value_name("TEMPERATURE")
+                    .value_parser(clap::value_parser!(f64))
+                    .action(clap::ArgAction::Set),
+            )
+            .arg(
+                Arg::new("tools")
+                    .long("tools")
+                    .help("Let the model call local tools: read_file, list_dir and grep (current directory only), get_clipboard and run_command (see --allow-command)")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("allow-command")
+                    .long("allow-command")
+                    .help("Program the run_command tool may run after confirmation (can be used multiple times, implies --tools)")
+                    .value_name("PROGRAM")
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("mcp")
+                    .long("mcp")
+                    .help("Offer the tools of an MCP server configured in ~/.gia/config (can be used multiple times, implies --tools)")
+                    .value_name("SERVER")
+                    .action(clap::ArgAction::Append),
+            )
+            .arg(
+                Arg::new("verbose-help")
+                    .long("verbose-help")
+                    .help("Open browser with detailed documentation on GitHub")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("spinner")
+                    .long("spinner")
+                    .help("Show progress overlay with cancel button during AI request (requires giagui)")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("no-save")
+                    .long("no-save")
+                    .help("Don't save to conversation history (transcribe-only mode)")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("completions")
+                    .long("completions")
+                    .help("Generate shell completion script")
+                    .value_name("SHELL")
+                    .value_parser(["bash", "zsh", "fish", "powershell", "nushell"])
+                    .action(clap::ArgAction::Set),
+            )
+            .subcommand(Self::build_roles_command())
+            .subcommand(
+                Command::new("mcp-serve").about(
+                    "Serve ask, conversations and transcription as MCP tools over stdio (for other agents and editors)",
+                ),
+            )
+            .subcommand(
+                Command::new("serve")
+                    .about("Serve an HTTP/JSON API (gia and OpenAI-compatible) for chat requests and saved conversations")
+                    .arg(
+                        Arg::new("port")
+                            .short('p')
+                            .long("port")
+                            .help("Port to listen on")
+                            .value_name("PORT")
+                            .value_parser(clap::value_parser!(u16))
+                            .default_value(DEFAULT_SERVE_PORT.to_string()),
+                    )
+                    .arg(
+                        Arg::new("host")
+                            .long("host")
+                            .help("Address to bind (a token is required for non-loopback addresses)")
+                            .value_name("HOST")
+                            .default_value("127.0.0.1"),
+                    )
+                    .arg(
+                        Arg::new("token")
+                            .long("token")
+                            .help("Require 'Authorization: Bearer TOKEN' on every request")
+                            .value_name("TOKEN")
+                            .env("GIA_SERVE_TOKEN"),
+                    )
+                    .arg(
+                        Arg::new("save-proxied")
+                            .long("save-proxied")
+                            .help("Save exchanges of the OpenAI-compatible endpoint as gia conversations")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+    }
+
+    fn build_roles_command() -> Command {
+        let name_arg = || {
+            Arg::new("name")
+                .help("Role/task name")
+                .value_name("NAME")
+                .required(true)
+        };
+
+        Command::new("roles")
+            .about("Manage role/task definitions (project .gia/, GIA_ROLE_DIRS and ~/.gia)")
+            .subcommand(
+                Command::new("list")
+                    .about("List roles and tasks with type, description and path")
+                    .arg(
+                        Arg::new("names")
+                            .long("names")
+                            .help("Print only the names (one per line)")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new("show")
+                    .about("Print a role/task definition")
+                    .arg(name_arg()),
+            )
+            .subcommand(
+                Command::new("new")
+                    .about("Create a role (or task) from a template")
+                    .arg(name_arg())
+                    .arg(
+                        Arg::new("task")
+                            .long("task")
+                            .help("Create a task in ~/.gia/tasks instead of a role")
+                            .action(clap::ArgAction::SetTrue),
+                    )
+                    .arg(
+                        Arg::new("local")
+                            .long("local")
+                            .help("Create it in the project's .gia directory (./.gia if none is found)")
+                            .action(clap::ArgAction::SetTrue),
+                    )
+                    .arg(
+                        Arg::new("edit")
+                            .short('e')
+                            .long("edit")
+                            .help("Open the new file in $EDITOR")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new("edit")
+                    .about("Open a role/task definition in $EDITOR")
+                    .arg(name_arg()),
+            )
+            .subcommand(
+                Command::new("rm")
+                    .about("Delete a role/task definition")
+                    .arg(name_arg())
+                    .arg(
+                        Arg::new("yes")
+                            .short('y')
+                            .long("yes")
+                            .help("Delete without asking for confirmation")
+                            .action(clap::ArgAction::SetTrue),
+                    ),
+            )
+            .subcommand(
+                Command::new("validate")
+                    .about("Check front-matter, default files and models of roles/tasks")
+                    .arg(
+                        Arg::new("name")
+                            .help("Role/task names to check (default: all)")
+                            .value_name("NAME")
+                            .num_args(0..),
+                    ),
+            )
+    }
+
+    fn handle_completions(shell: &str) {
+        let mut cmd = Self::build_cli();
+
+        // Complete role/task names for -t and the roles subcommands
+        let mut names: Vec<String> = list_definitions()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|definition| definition.name)
+            .collect();
+        names.sort();
+        names.dedup();
+        if !names.is_empty() {
+            let parser = PossibleValuesParser::new(names);
+            cmd = cmd.mut_arg("role", |arg| arg.value_parser(parser.clone()));
+            for sub in ["show", "edit", "rm", "validate"] {
+                cmd = cmd.mut_subcommand("roles", |roles| {
+                    roles.mut_subcommand(sub, |command| {
+                        command.mut_arg("name", |arg| arg.value_parser(parser.clone()))
+                    })
+                });
+            }
+        }
+        let bin_name = "gia";
+
+        match shell {
+            "bash" => generate(shells::Bash, &mut cmd, bin_name, &mut std::io::stdout()),
+            "zsh" => generate(shells::Zsh, &mut cmd, bin_name, &mut std::io::stdout()),
+            "fish" => generate(shells::Fish, &mut cmd, bin_name, &mut std::io::stdout()),
+            "powershell" => generate(
+                shells::PowerShell,
+                &mut cmd,
+                bin_name,
+                &mut std::io::stdout(),
+            ),
+            "nushell" => generate(Nushell, &mut cmd, bin_name, &mut std::io::stdout()),
+            _ => eprintln!("Unsupported shell: {}", shell),
+        }
+    }
+
+    fn handle_verbose_help() {
+        const GITHUB_README_URL: &str = "https://github.com/panjamo/gia/blob/master/README.md";
+
+        println!("Opening detailed documentation in your browser...");
+
+        if let Err(e) = webbrowser::open(GITHUB_README_URL) {
+            eprintln!("Failed to open browser: {e}");
+            eprintln!("Please visit: {GITHUB_README_URL}");
+        } else {
+            println!("Documentation URL: {GITHUB_README_URL}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::env;
+
+    #[test]
+    #[serial]
+    fn test_default_model_from_env_var() {
+        // Clean up any existing environment variable first
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+
+        // Test without environment variable - should use hardcoded default
+        let config = Config::from_args_with_test(&[]);
+        assert_eq!(config.model, "gemini-2.5-flash-lite");
+
+        // Test with environment variable
+        unsafe { env::set_var("GIA_DEFAULT_MODEL", "gemini-2.5-pro") };
+        let config = Config::from_args_with_test(&[]);
+        assert_eq!(config.model, "gemini-2.5-pro");
+
+        // Test with Ollama format
+        unsafe { env::set_var("GIA_DEFAULT_MODEL", "ollama::llama3.2") };
+        let config = Config::from_args_with_test(&[]);
+        assert_eq!(config.model, "ollama::llama3.2");
+
+        // Test that explicit command line argument overrides environment variable
+        unsafe { env::set_var("GIA_DEFAULT_MODEL", "gemini-2.5-pro") };
+        let config = Config::from_args_with_test(&["--model", "Gemini 2.5 Flash-Lite"]);
+        assert_eq!(config.model, "Gemini 2.5 Flash-Lite");
+
+        // Clean up
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+    }
+
+    #[test]
+    fn test_git_source_args() {
+        let config = Config::from_args_with_test(&["--git-diff"]);
+        assert_eq!(config.git_diff.as_deref(), Some(""));
+        assert!(!config.git_staged);
+
+        let config = Config::from_args_with_test(&["--git-diff=main..feature", "--git-log", "-3"]);
+        assert_eq!(config.git_diff.as_deref(), Some("main..feature"));
+        assert_eq!(config.git_log.as_deref(), Some("-3"));
+
+        // Without '=' the next argument is the prompt, not a range
+        let config = Config::from_args_with_test(&["--git-diff", "summarize these changes"]);
+        assert_eq!(config.git_diff.as_deref(), Some(""));
+        assert_eq!(config.prompt, "summarize these changes");
+    }
+
+    #[test]
+    fn test_url_args() {
+        let config = Config::from_args_with_test(&[
+            "-u",
+            "https://example.com/a",
+            "--url",
+            "file:///tmp/page.html",
+        ]);
+        assert_eq!(
+            config.urls,
+            vec!["https://example.com/a", "file:///tmp/page.html"]
+        );
+    }
+
+    #[test]
+    fn test_tool_args() {
+        let config = Config::from_args_with_test(&["--tools"]);
+        assert!(config.tools);
+        assert!(config.allowed_commands.is_empty());
+
+        let config =
+            Config::from_args_with_test(&["--allow-command", "git", "--allow-command", "cargo"]);
+        assert!(config.tools);
+        assert_eq!(config.allowed_commands, vec!["git", "cargo"]);
+
+        let config = Config::from_args_with_test(&["--mcp", "jira"]);
+        assert!(config.tools);
+        assert_eq!(config.mcp_servers, vec!["jira"]);
+
+        assert!(!Config::from_args_with_test(&[]).tools);
+    }
+
+    #[test]
+    fn test_serve_args() {
+        let config = Config::from_args_with_test(&["serve", "--port", "9000", "--token", "s3"]);
+        assert_eq!(
+            config.serve,
+            Some(ServeOptions {
+                host: "127.0.0.1".to_string(),
+                port: 9000,
+                token: Some("s3".to_string()),
+                save_proxied: false,
+            })
+        );
+        assert!(!config.read_stdin);
+
+        let config = Config::from_args_with_test(&["serve", "--save-proxied"]);
+        let options = config.serve.unwrap();
+        assert_eq!(options.port, DEFAULT_SERVE_PORT);
+        assert!(options.save_proxied);
+
+        let config = Config::from_args_with_test(&["hello"]);
+        assert!(config.serve.is_none());
+        assert!(config.read_stdin);
+    }
+
+    #[test]
+    fn test_var_args() {
+        let config =
+            Config::from_args_with_test(&["--var", "lang=de-DE", "--var", "audience=team"]);
+        assert_eq!(
+            config.vars,
+            vec![
+                ("lang".to_string(), "de-DE".to_string()),
+                ("audience".to_string(), "team".to_string())
+            ]
+        );
+
+        let result = Config::build_cli().try_get_matches_from(["gia", "--var", "novalue"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_roles_subcommand_args() {
+        let matches = Config::build_cli()
+            .try_get_matches_from(["gia", "roles", "new", "reviewer", "--task"])
+            .unwrap();
+        let Some(("roles", roles)) = matches.subcommand() else {
+            panic!("expected roles subcommand");
+        };
+        let Some(("new", new)) = roles.subcommand() else {
+            panic!("expected new subcommand");
+        };
+        assert_eq!(new.get_one::<String>("name").unwrap(), "reviewer");
+        assert!(new.get_flag("task"));
+
+        // Plain prompts are not taken for subcommands
+        let config = Config::from_args_with_test(&["explain", "roles"]);
+        assert_eq!(config.prompt, "explain roles");
+        assert!(
+            Config::build_cli()
+                .try_get_matches_from(["gia", "roles", "rm"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_role_settings_apply_unless_given_on_cli() {
+        let settings = RoleSettings {
+            model: Some("ollama::llama3.2".to_string()),
+            temperature: Some(0.2),
+            output: Some(RoleOutput::Tts),
+            tts_language: Some("en-US".to_string()),
+            no_save: Some(true),
+            files: vec!["/notes/glossary.md".to_string()],
+            ..Default::default()
+        };
+
+        let apply = |args: &[&str]| {
+            let matches = Config::build_cli()
+                .try_get_matches_from(std::iter::once("gia").chain(args.iter().copied()))
+                .unwrap();
+            let mut config = Config::from_args_with_test(args);
+            config.apply_role_settings(settings.clone(), &matches);
+            config
+        };
+
+        let config = apply(&["-t", "transkribiere"]);
+        assert_eq!(config.model, "ollama::llama3.2");
+        assert_eq!(config.temperature, Some(0.2));
+        assert!(matches!(config.output_mode, OutputMode::Tts(ref lang) if lang == "en-US"));
+        assert!(config.no_save);
+        assert_eq!(config.text_files, vec!["/notes/glossary.md"]);
+
+        let config = apply(&[
+            "-t",
+            "transkribiere",
+            "-m",
+            "gemini-2.5-pro",
+            "--temperature",
+            "0.9",
+            "-o",
+            "-f",
+            "input.txt",
+        ]);
+        assert_eq!(config.model, "gemini-2.5-pro");
+        assert_eq!(config.temperature, Some(0.9));
+        assert!(matches!(config.output_mode, OutputMode::Clipboard));
+        assert_eq!(config.text_files, vec!["input.txt"]);
+    }
+
+    #[test]
+    fn test_commit_message_task_defaults() {
+        let config = Config::from_args_with_test(&["-t", "commit-message"]);
+        assert!(config.git_staged);
+        assert!(matches!(config.output_mode, OutputMode::CommitMessage));
+
+        // Explicit sources and outputs are kept
+        let config =
+            Config::from_args_with_test(&["-t", "commit-message", "--git-diff=HEAD~1", "-o"]);
+        assert!(!config.git_staged);
+        assert!(matches!(config.output_mode, OutputMode::Clipboard));
+    }
+
+    impl Config {
+        fn from_args_with_test(args: &[&str]) -> Self {
+            let matches = Self::build_cli()
+                .try_get_matches_from(std::iter::once("gia").chain(args.iter().copied()))
+                .unwrap();
+
+            let prompt_parts: Vec<String> = matches
+                .get_many::<String>("prompt")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+
+            let resume_conversation = matches.get_one::<String>("resume").cloned();
+
+            let output_mode = if matches.get_flag("browser-output") {
+                OutputMode::TempFileWithPreview
+            } else if matches.get_flag("clipboard-output") {
+                OutputMode::Clipboard
+            } else if let Some(lang) = matches.get_one::<String>("tts-output").cloned() {
+                OutputMode::Tts(lang)
+            } else {
+                OutputMode::Stdout
+            };
+
+            let text_files: Vec<String> = matches
+                .get_many::<String>("file")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+
+            let roles: Vec<String> = matches
+                .get_many::<String>("role")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+
+            let mut config = Self {
+                prompt: prompt_parts.join(" "),
+                use_clipboard_input: matches.get_flag("clipboard-input"),
+                text_files,
+                output_mode,
+                resume_conversation,
+                resume_last: matches.get_flag("resume-last"),
+                list_conversations: matches
+                    .get_one::<String>("list-conversations")
+                    .map(|s| s.parse::<usize>().unwrap_or(0)),
+                show_conversation: matches.get_one::<String>("show-conversation").cloned(),
+                model: matches.get_one::<String>("model").unwrap().clone(),
+                record_audio: matches.get_flag("record-audio"),
+                audio_device: matches.get_one::<String>("audio-device").cloned(),
+                list_audio_devices: matches.get_flag("list-audio-devices"),
+                roles,
+                ordered_content: Vec::new(),
+                spinner: matches.get_flag("spinner"),
+                no_save: matches.get_flag("no-save"),
+                native_documents: matches.get_flag("native-docs"),
+                git_diff: matches.get_one::<String>("git-diff").cloned(),
+                git_staged: matches.get_flag("git-staged"),
+                git_log: matches.get_one::<String>("git-log").cloned(),
+                urls: matches
+                    .get_many::<String>("url")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+                vars: matches
+                    .get_many::<(String, String)>("var")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+                temperature: matches.get_one::<f64>("temperature").copied(),
+                tools: matches.get_flag("tools")
+                    || matches.contains_id("allow-command")
+                    || matches.contains_id("mcp"),
+                allowed_commands: matches
+                    .get_many::<String>("allow-command")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+                mcp_servers: matches
+                    .get_many::<String>("mcp")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+                mcp_serve: matches.subcommand_name() == Some("mcp-serve"),
+                read_stdin: matches.subcommand_name().is_none(),
+                serve: Self::serve_options(&matches),
+                remote_request: false,
+            };
+
+            config.apply_task_defaults();
+            config
+        }
+    }
+}
@@ -0,0 +1,677 @@
+//! Optional settings file `~/.gia/config` (YAML).
+//!
+//! ```text
+//! mcp_servers:
+//!   jira:                      # stdio server, started for the request
+//!     command: jira-mcp
+//!     args: [--stdio]
+//!     env: { JIRA_URL: "http://localhost:9000" }
+//!     allow: [search_issues, get_issue]   # tools exposed to the model (default: all)
+//!   docs:                      # local streamable HTTP server
+//!     url: http://127.0.0.1:8808/mcp
+//!     default: true            # connect whenever --tools is used
+//! settings:                    # defaults, environment variables take precedence
+//!   default_model: gemini-2.5-pro          # GIA_DEFAULT_MODEL
+//!   api_keys: [AIza...]                    # GEMINI_API_KEY
+//!   audio_device: "USB Microphone"         # GIA_AUDIO_DEVICE
+//!   tts_language: en-US                    # --tts without a language
+//!   tts_voice: Samantha
+//!   context_limit: 16000                   # CONTEXT_WINDOW_LIMIT
+//!   conversations_dir: /data/gia/conversations
+//!   outputs_dir: /data/gia/outputs
+//!   theme: dark                            # giagui: dark, light or system
+//! hotkeys:                     # giagui --tray: named actions on global hotkeys
+//!   transcribe:                # record -> transcribe -> paste
+//!     keys: Alt+U
+//!     input: [audio]
+//!     task: transkribiere
+//!     output: paste            # paste (default), clipboard, notification or browser
+//!   translate:                 # clipboard -> translate -> clipboard
+//!     keys: Ctrl+Alt+T
+//!     input: [clipboard]
+//!     prompt: translate to english
+//!     output: clipboard
+//! ```
+//!
+//! giagui writes the `settings` section; the file is rewritten without comments.
+//! Settings are read on first use and again whenever the file's modification
+//! time changes, so long-running processes like `gia serve` pick up edits.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::logging::{log_debug, log_info, log_warn};
+
+/// Themes giagui can use
+pub const THEMES: &[&str] = &["dark", "light", "system"];
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub mcp_servers: BTreeMap<String, McpServerConfig>,
+    pub settings: Settings,
+    pub hotkeys: BTreeMap<String, HotkeyAction>,
+}
+
+/// Defaults edited in giagui's settings panel
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_device: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts_language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts_voice: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversations_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+}
+
+impl Settings {
+    /// Check values that would break requests
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .default_model
+            .as_deref()
+            .is_some_and(|m| m.trim().is_empty())
+        {
+            return Err(anyhow::anyhow!("Default model must not be empty"));
+        }
+        if self
+            .api_keys
+            .iter()
+            .any(|key| key.trim().is_empty() || key.contains('|'))
+        {
+            return Err(anyhow::anyhow!(
+                "API keys must not be empty or contain '|' (one key per entry)"
+            ));
+        }
+        if self.context_limit == Some(0) {
+            return Err(anyhow::anyhow!("Context limit must be greater than 0"));
+        }
+        for (name, dir) in [
+            ("Conversations", &self.conversations_dir),
+            ("Outputs", &self.outputs_dir),
+        ] {
+            if let Some(dir) = dir {
+                if !dir.is_absolute() {
+                    return Err(anyhow::anyhow!(
+                        "{name} directory must be an absolute path: {}",
+                        dir.display()
+                    ));
+                }
+                if dir.exists() && !dir.is_dir() {
+                    return Err(anyhow::anyhow!(
+                        "{name} directory is not a directory: {}",
+                        dir.display()
+                    ));
+                }
+            }
+        }
+        if let Some(language) = &self.tts_language
+            && !is_language_tag(language)
+        {
+            return Err(anyhow::anyhow!(
+                "TTS language '{language}' must be a language code like 'de' or 'de-DE'"
+            ));
+        }
+        if let Some(theme) = &self.theme
+            && !THEMES.contains(&theme.as_str())
+        {
+            return Err(anyhow::anyhow!(
+                "Unknown theme '{theme}' (use {})",
+                THEMES.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An MCP server reachable over stdio (`command`) or local HTTP (`url`)
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct McpServerConfig {
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub url: Option<String>,
+    pub allow: Option<Vec<String>>, // None = all tools of the server
+    pub default: bool,              // connect whenever tools are enabled
+}
+
+impl McpServerConfig {
+    /// Whether the model may use the given tool of this server
+    pub fn allows(&self, tool: &str) -> bool {
+        self.allow
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|name| name == tool))
+    }
+}
+
+/// A named action giagui's tray mode runs when its global hotkey is pressed
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HotkeyAction {
+    pub keys: String, // e.g. "Ctrl+Alt+T"
+    pub input: Vec<HotkeyInput>,
+    pub task: Option<String>,
+    pub role: Option<String>,
+    pub model: Option<String>,
+    pub prompt: Option<String>,
+    pub options: Vec<String>, // further gia options
+    pub output: HotkeyOutput,
+    pub hold: bool, // record only while the keys are held
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HotkeyInput {
+    Audio,
+    Clipboard,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HotkeyOutput {
+    #[default]
+    Paste,
+    Clipboard,
+    Notification,
+    Browser,
+}
+
+impl HotkeyAction {
+    pub fn records_audio(&self) -> bool {
+        self.input.contains(&HotkeyInput::Audio)
+    }
+
+    fn validate(&self, name: &str) -> Result<()> {
+        if self.keys.trim().is_empty() {
+            return Err(anyhow::anyhow!("Hotkey '{name}' needs 'keys'"));
+        }
+        if self.input.is_empty() && self.prompt.is_none() {
+            return Err(anyhow::anyhow!(
+                "Hotkey '{name}' needs an 'input' or a 'prompt'"
+            ));
+        }
+        if self.hold && !self.records_audio() {
+            return Err(anyhow::anyhow!(
+                "Hotkey '{name}': 'hold' only works with audio input"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A language code like `de` or a language with region like `de-DE`
+pub fn is_language_tag(tag: &str) -> bool {
+    let is_code = |code: &str| code.len() == 2 && code.bytes().all(|b| b.is_ascii_alphabetic());
+    match tag.split_once('-') {
+        Some((language, region)) => is_code(language) && is_code(region),
+        None => is_code(tag),
+    }
+}
+
+/// Path of the settings file
+pub fn config_file_path() -> Result<PathBuf> {
+    let home_dir =
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+    Ok(home_dir.join(".gia").join("config"))
+}
+
+/// Load `~/.gia/config`; a missing file gives the default settings
+pub fn load_config_file() -> Result<ConfigFile> {
+    load_config_file_from(&config_file_path()?)
+}
+
+/// Settings read from `~/.gia/config`, with the file's modification time then
+struct CachedSettings {
+    settings: Settings,
+    modified: Option<SystemTime>, // None = no file
+}
+
+/// Settings read from `~/.gia/config`, loaded on first use
+static SETTINGS: Mutex<Option<CachedSettings>> = Mutex::new(None);
+
+fn config_file_modified() -> Option<SystemTime> {
+    let path = config_file_path().ok()?;
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+fn cache_settings(settings: Settings) {
+    *SETTINGS.lock().unwrap() = Some(CachedSettings {
+        settings,
+        modified: config_file_modified(),
+    });
+}
+
+/// The `settings` section of `~/.gia/config`, read again when the file has
+/// changed. A broken file only gives a warning here, so that defaults never stop
+/// a request.
+pub fn load_settings() -> Settings {
+    let modified = config_file_modified();
+    let mut cached = SETTINGS.lock().unwrap();
+    match cached.as_ref() {
+        Some(cached) if cached.modified == modified => cached.settings.clone(),
+        _ => {
+            let settings = read_settings();
+            *cached = Some(CachedSettings {
+                settings: settings.clone(),
+                modified,
+            });
+            settings
+        }
+    }
+}
+
+/// Read the settings again, e.g. after the file was edited by hand
+pub fn reload_settings() -> Settings {
+    let settings = read_settings();
+    cache_settings(settings.clone());
+    settings
+}
+
+fn read_settings() -> Settings {
+    match load_config_file() {
+        Ok(config) => config.settings,
+        Err(e) => {
+            log_warn(&format!("Ignoring settings: {e:#}"));
+            Settings::default()
+        }
+    }
+}
+
+/// Use the given settings instead of reading `~/.gia/config`
+#[cfg(test)]
+pub(crate) fn set_cached_settings(settings: Settings) {
+    cache_settings(settings);
+}
+
+/// Replace the `settings` section of `~/.gia/config`, keeping the rest of the file
+pub fn save_settings(settings: &Settings) -> Result<()> {
+    save_settings_to(&config_file_path()?, settings)?;
+    cache_settings(settings.clone());
+    Ok(())
+}
+
+/// Whether `~/.gia/config` has comments, which saving the settings drops
+pub fn config_file_has_comments() -> bool {
+    config_file_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .is_some_and(|content| has_comments(&content))
+}
+
+/// Whether YAML text has comments: a `#` at the start of a line or after
+/// whitespace, outside of quoted strings
+fn has_comments(content: &str) -> bool {
+    content.lines().any(|line| {
+        let mut chars = line.chars().peekable();
+        let mut quote = None;
+        let mut previous = ' ';
+        while let Some(c) = chars.next() {
+            match quote {
+                // Escapes: \" in double quotes, '' in single quotes
+                Some('"') if c == '\\' => {
+                    chars.next();
+                }
+                Some('\'') if c == '\'' && chars.peek() == Some(&'\'') => {
+                    chars.next();
+                }
+                Some(open) if c == open => quote = None,
+                Some(_) => {}
+                // Quotes only start a string at the start of a value (not in "it's")
+                None if (c == '"' || c == '\'') && " \t:[{,".contains(previous) => {
+                    quote = Some(c);
+                }
+                None if c == '#' && previous.is_whitespace() => return true,
+                None => {}
+            }
+            previous = c;
+        }
+        false
+    })
+}
+
+fn save_settings_to(path: &Path, settings: &Settings) -> Result<()> {
+    settings.validate()?;
+
+    let mut document = if path.exists() {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        if has_comments(&content) {
+            log_warn(&format!(
+                "Saving settings drops the comments in {}",
+                path.display()
+            ));
+        }
+        serde_yaml::from_str::<Option<serde_yaml::Value>>(&content)
+            .with_context(|| format!("Invalid config file: {}", path.display()))?
+            .unwrap_or_else(|| serde_yaml::Value::Mapping(Default::default()))
+    } else {
+        serde_yaml::Value::Mapping(Default::default())
+    };
+    let mapping = document
+        .as_mapping_mut()
+        .ok_or_else(|| anyhow::anyhow!("Config file is not a mapping: {}", path.display()))?;
+
+    let settings_value = serde_yaml::to_value(settings).context("Failed to serialize settings")?;
+    if settings == &Settings::default() {
+        mapping.remove("settings");
+    } else {
+        mapping.insert("settings".into(), settings_value);
+    }
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+    let content = serde_yaml::to_string(&document).context("Failed to serialize config file")?;
+    write_private_file(path, &content)
+        .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+
+    log_info(&format!("Saved settings to {}", path.display()));
+    Ok(())
+}
+
+/// Write a file readable only by the user (it may hold API keys). The content
+/// goes to a temporary file first, which then replaces the file in one step.
+fn write_private_file(path: &Path, content: &str) -> Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Not a file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    // A leftover from an interrupted save may have other permissions
+    let _ = fs::remove_file(&temp_path);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let result = options
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+fn load_config_file_from(path: &Path) -> Result<ConfigFile> {
+    if !path.exists() {
+        log_debug(&format!("No config file at {}", path.display()));
+        return Ok(ConfigFile::default());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    if content.trim().is_empty() {
+        return Ok(ConfigFile::default());
+    }
+
+    let config: ConfigFile = serde_yaml::from_str(&content)
+        .with_context(|| format!("Invalid config file: {}", path.display()))?;
+
+    for (name, server) in &config.mcp_servers {
+        if server.command.is_some() == server.url.is_some() {
+            return Err(anyhow::anyhow!(
+                "MCP server '{name}' in {} needs either 'command' or 'url'",
+                path.display()
+            ));
+        }
+    }
+
+    for (name, action) in &config.hotkeys {
+        action
+            .validate(name)
+            .with_context(|| format!("Invalid config file: {}", path.display()))?;
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_config_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config");
+
+        assert_eq!(load_config_file_from(&path).unwrap(), ConfigFile::default());
+
+        fs::write(
+            &path,
+            "mcp_servers:\n  jira:\n    command: jira-mcp\n    args: [--stdio]\n    allow: [search]\n  docs:\n    url: http://127.0.0.1:8808/mcp\n    default: true\n",
+        )
+        .unwrap();
+        let config = load_config_file_from(&path).unwrap();
+        let jira = &config.mcp_servers["jira"];
+        assert_eq!(jira.command.as_deref(), Some("jira-mcp"));
+        assert_eq!(jira.args, vec!["--stdio"]);
+        assert!(jira.allows("search"));
+        assert!(!jira.allows("delete_issue"));
+        assert!(!jira.default);
+        assert!(config.mcp_servers["docs"].allows("anything"));
+        assert!(config.mcp_servers["docs"].default);
+
+        fs::write(&path, "mcp_servers:\n  broken:\n    args: [x]\n").unwrap();
+        let err = load_config_file_from(&path).unwrap_err();
+        assert!(err.to_string().contains("needs either 'command' or 'url'"));
+
+        fs::write(&path, "mcp_server: {}\n").unwrap();
+        assert!(load_config_file_from(&path).is_err());
+    }
+
+    #[test]
+    fn test_load_hotkeys() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config");
+
+        fs::write(
+            &path,
+            "hotkeys:\n  transcribe:\n    keys: Alt+U\n    input: [audio]\n    task: transkribiere\n    hold: true\n  translate:\n    keys: Ctrl+Alt+T\n    input: [clipboard]\n    prompt: translate to english\n    output: clipboard\n",
+        )
+        .unwrap();
+        let config = load_config_file_from(&path).unwrap();
+        let transcribe = &config.hotkeys["transcribe"];
+        assert!(transcribe.records_audio());
+        assert!(transcribe.hold);
+        assert_eq!(transcribe.output, HotkeyOutput::Paste);
+        assert_eq!(transcribe.task.as_deref(), Some("transkribiere"));
+        let translate = &config.hotkeys["translate"];
+        assert_eq!(translate.input, vec![HotkeyInput::Clipboard]);
+        assert_eq!(translate.output, HotkeyOutput::Clipboard);
+        assert!(!translate.records_audio());
+
+        for broken in [
+            "hotkeys:\n  x:\n    input: [audio]\n",
+            "hotkeys:\n  x:\n    keys: Alt+U\n",
+            "hotkeys:\n  x:\n    keys: Alt+U\n    input: [clipboard]\n    hold: true\n",
+            "hotkeys:\n  x:\n    keys: Alt+U\n    input: [camera]\n",
+        ] {
+            fs::write(&path, broken).unwrap();
+            assert!(load_config_file_from(&path).is_err(), "{broken}");
+        }
+    }
+
+    #[test]
+    fn test_save_settings_keeps_mcp_servers() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config");
+        fs::write(
+            &path,
+            "mcp_servers:\n  docs:\n    url: http://127.0.0.1:8808/mcp\n",
+        )
+        .unwrap();
+
+        let settings = Settings {
+            default_model: Some("gemini-2.5-pro".to_string()),
+            api_keys: vec!["key1".to_string(), "key2".to_string()],
+            context_limit: Some(16000),
+            theme: Some("light".to_string()),
+            ..Default::default()
+        };
+        save_settings_to(&path, &settings).unwrap();
+
+        let config = load_config_file_from(&path).unwrap();
+        assert_eq!(config.settings, settings);
+        assert!(config.mcp_servers.contains_key("docs"));
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("audio_device"));
+
+        save_settings_to(&path, &Settings::default()).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("settings"));
+        assert!(
+            load_config_file_from(&path)
+                .unwrap()
+                .mcp_servers
+                .contains_key("docs")
+        );
+    }
+
+    #[test]
+    fn test_save_settings_creates_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(".gia").join("config");
+        let settings = Settings {
+            tts_language: Some("en-US".to_string()),
+            ..Default::default()
+        };
+        save_settings_to(&path, &settings).unwrap();
+        assert_eq!(load_config_file_from(&path).unwrap().settings, settings);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_has_comments() {
+        assert!(has_comments("# settings\nsettings: {}\n"));
+        assert!(has_comments("settings:\n  theme: dark  # or light\n"));
+        assert!(has_comments("prompt: it's # a comment\n"));
+        assert!(!has_comments("prompt: \"use C #define\"\n"));
+        assert!(!has_comments("prompt: 'it''s #1'\n"));
+        assert!(!has_comments("url: http://host/page#anchor\n"));
+        assert!(!has_comments("keys: [\"a #b\", 'c #d']\n"));
+    }
+
+    #[test]
+    fn test_save_settings_replaces_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config");
+        fs::write(&path, "# my settings\nsettings:\n  theme: dark\n").unwrap();
+        assert!(has_comments(&fs::read_to_string(&path).unwrap()));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        let settings = Settings {
+            api_keys: vec!["key1".to_string()],
+            ..Default::default()
+        };
+        save_settings_to(&path, &settings).unwrap();
+        assert_eq!(load_config_file_from(&path).unwrap().settings, settings);
+        assert!(!has_comments(&fs::read_to_string(&path).unwrap()));
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(Settings::default().validate().is_ok());
+        for language in ["de", "en-US"] {
+            let settings = Settings {
+                tts_language: Some(language.to_string()),
+                ..Default::default()
+            };
+            assert!(settings.validate().is_ok(), "{language}");
+        }
+
+        let invalid = [
+            Settings {
+                context_limit: Some(0),
+                ..Default::default()
+            },
+            Settings {
+                api_keys: vec!["a|b".to_string()],
+                ..Default::default()
+            },
+            Settings {
+                outputs_dir: Some(PathBuf::from("relative/outputs")),
+                ..Default::default()
+            },
+            Settings {
+                theme: Some("pink".to_string()),
+                ..Default::default()
+            },
+            Settings {
+                default_model: Some(" ".to_string()),
+                ..Default::default()
+            },
+            Settings {
+                tts_language: Some("e".to_string()),
+                ..Default::default()
+            },
+            Settings {
+                tts_language: Some("german".to_string()),
+                ..Default::default()
+            },
+        ];
+        for settings in invalid {
+            assert!(settings.validate().is_err(), "{settings:?}");
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config");
+        assert!(
+            save_settings_to(
+                &path,
+                &Settings {
+                    context_limit: Some(0),
+                    ..Default::default()
+                }
+            )
+            .is_err()
+        );
+        assert!(!path.exists());
+    }
+}
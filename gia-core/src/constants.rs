@@ -0,0 +1,248 @@
+use crate::config_file::load_settings;
+
+/// API key validation constants
+pub const API_KEY_LENGTH: usize = 39;
+pub const API_KEY_PREFIX: &str = "AIza";
+
+/// Default model constants
+pub const DEFAULT_MODEL: &str = "gemini-2.5-flash-lite";
+
+/// Get default model from environment variable, settings file or default
+pub fn get_default_model() -> String {
+    std::env::var("GIA_DEFAULT_MODEL")
+        .ok()
+        .or_else(|| load_settings().default_model)
+        .unwrap_or_else(|| DEFAULT_MODEL.to_string())
+}
+
+/// Language of `--tts` without a value
+pub const DEFAULT_TTS_LANGUAGE: &str = "de-DE";
+
+/// Get TTS language from settings file or default
+pub fn get_default_tts_language() -> String {
+    load_settings()
+        .tts_language
+        .unwrap_or_else(|| DEFAULT_TTS_LANGUAGE.to_string())
+}
+
+/// Gemini models offered by `gia serve` at /v1/models
+pub const GEMINI_MODELS: &[&str] = &[
+    "gemini-2.5-pro",
+    "gemini-2.5-flash",
+    "gemini-2.5-flash-lite",
+];
+
+/// Ollama server used when OLLAMA_API_BASE is not set
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+/// Conversation management constants
+pub const DEFAULT_CONTEXT_WINDOW_LIMIT: usize = 8000;
+
+/// Get context window limit from environment variable, settings file or default
+pub fn get_context_window_limit() -> usize {
+    std::env::var("CONTEXT_WINDOW_LIMIT")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| load_settings().context_limit)
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW_LIMIT)
+}
+pub const CONVERSATION_TRUNCATION_KEEP_MESSAGES: usize = 20;
+
+/// Gemini API checked by the settings panel's key test
+pub const GEMINI_API_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+/// URLs for user guidance
+pub const GEMINI_API_KEY_URL: &str = "https://makersuite.google.com/app/apikey";
+pub const GEMINI_DOCS_URL: &str = "https://ai.google.dev/gemini-api/docs/api-key";
+
+/// Supported media file extensions
+pub const MEDIA_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "webp", "heic", "pdf", "ogg", "opus", "mp3", "m4a", "mp4",
+];
+
+/// Document extensions whose text is extracted locally (see document.rs)
+pub const DOCUMENT_EXTENSIONS: &[&str] =
+    &["pdf", "docx", "odt", "pptx", "xlsx", "xls", "ods", "csv"];
+
+/// The PDF helper process is killed when it takes longer than this many seconds
+pub const PDF_HELPER_TIMEOUT_SECS: u64 = 60;
+
+/// Input file size limit (applies to files and archive members)
+pub const DEFAULT_MAX_INPUT_FILE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Get input file size limit in bytes from environment variable or default
+pub fn get_max_input_file_size() -> u64 {
+    std::env::var("GIA_MAX_FILE_SIZE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_MAX_INPUT_FILE_SIZE)
+}
+
+/// Limit in bytes for all members of one archive read into memory together
+pub const MAX_ARCHIVE_TOTAL_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Limit of file members read from one archive
+pub const MAX_ARCHIVE_MEMBERS: usize = 10_000;
+
+/// Size budget in bytes for git diffs and logs
+pub const DEFAULT_GIT_CONTEXT_BUDGET: usize = 100_000;
+
+/// Get git content size budget from environment variable or default
+pub fn get_git_context_budget() -> usize {
+    std::env::var("GIA_GIT_BUDGET")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_GIT_CONTEXT_BUDGET)
+}
+
+/// Additional role/task directories (each with roles/ and tasks/ like ~/.gia),
+/// searched after the project's .gia and before ~/.gia
+pub fn get_extra_role_dirs() -> Vec<std::path::PathBuf> {
+    std::env::var_os("GIA_ROLE_DIRS")
+        .map(|dirs| {
+            std::env::split_paths(&dirs)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Projects (or directories holding projects) whose .gia definitions may read
+/// files outside the project and use `{{env:VAR}}`
+pub fn get_trusted_projects() -> Vec<std::path::PathBuf> {
+    std::env::var_os("GIA_TRUSTED_PROJECTS")
+        .map(|dirs| {
+            std::env::split_paths(&dirs)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// JSON, CSV and log files larger than this (in bytes) are summarised instead of sent verbatim
+pub const DEFAULT_SUMMARY_THRESHOLD: u64 = 50 * 1024;
+
+/// Get summary threshold in bytes from environment variable or default
+pub fn get_summary_threshold() -> u64 {
+    std::env::var("GIA_SUMMARY_THRESHOLD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_SUMMARY_THRESHOLD)
+}
+
+/// Notebook cell outputs longer than this (in characters) are truncated
+pub const NOTEBOOK_OUTPUT_LIMIT: usize = 2000;
+
+/// Maximum number of images attached from notebook cell outputs
+pub const MAX_NOTEBOOK_IMAGES: usize = 10;
+
+/// Maximum number of model turns that may request tools before giving up
+pub const MAX_TOOL_ROUNDS: usize = 10;
+
+/// Tool results longer than this (in bytes) are truncated before they are sent back
+pub const TOOL_OUTPUT_LIMIT: usize = 50_000;
+
+/// Maximum number of matching lines returned by the grep tool
+pub const MAX_GREP_MATCHES: usize = 200;
+
+/// Files larger than this (in bytes) are skipped by the `grep` tool
+pub const MAX_GREP_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Commands run by the run_command tool are killed after this many seconds
+pub const TOOL_COMMAND_TIMEOUT_SECS: u64 = 60;
+
+/// Images from web pages larger than this are not attached
+pub const MAX_WEB_IMAGE_SIZE: u64 = 1024 * 1024;
+
+/// Maximum number of images attached per web page
+pub const MAX_WEB_IMAGES: usize = 5;
+
+/// MCP protocol revision announced when connecting to MCP servers
+pub const MCP_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Requests to MCP servers (including tool calls) fail after this many seconds
+pub const MCP_REQUEST_TIMEOUT_SECS: u64 = 60;
+
+/// Port of the HTTP API (`gia serve`)
+pub const DEFAULT_SERVE_PORT: u16 = 8765;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_file::{Settings, set_cached_settings};
+    use serial_test::serial;
+    use std::env;
+
+    #[test]
+    #[serial]
+    fn test_default_model_without_env_var() {
+        // Clean up any existing environment variable first
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+
+        // Independent of the settings in the real ~/.gia/config
+        set_cached_settings(Settings::default());
+        let result = get_default_model();
+        assert_eq!(result, DEFAULT_MODEL);
+
+        set_cached_settings(Settings {
+            default_model: Some("gemini-2.5-pro".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(get_default_model(), "gemini-2.5-pro");
+
+        // Clean up
+        set_cached_settings(Settings::default());
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_model_with_env_var() {
+        // Clean up any existing environment variable first
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+
+        // Set a custom model via environment variable
+        let custom_model = "gemini-2.5-pro";
+        unsafe { env::set_var("GIA_DEFAULT_MODEL", custom_model) };
+
+        let result = get_default_model();
+        assert_eq!(result, custom_model);
+
+        // Clean up
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_model_with_ollama_format() {
+        // Clean up any existing environment variable first
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+
+        // Test with Ollama format
+        let ollama_model = "ollama::llama3.2";
+        unsafe { env::set_var("GIA_DEFAULT_MODEL", ollama_model) };
+
+        let result = get_default_model();
+        assert_eq!(result, ollama_model);
+
+        // Clean up
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_model_with_empty_env_var() {
+        // Clean up any existing environment variable first
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+
+        // Set empty environment variable
+        unsafe { env::set_var("GIA_DEFAULT_MODEL", "") };
+
+        let result = get_default_model();
+        // Empty string should still be returned (env var exists but is empty)
+        assert_eq!(result, "");
+
+        // Clean up
+        unsafe { env::remove_var("GIA_DEFAULT_MODEL") };
+    }
+}
@@ -2,7 +2,7 @@
 /// This allows us to save conversations with strongly-typed content parts
 /// instead of parsing text with === markers
 use anyhow::Result;
-use genai::chat::ContentPart;
+use genai::chat::{ContentPart, ToolCall, ToolResponse};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,8 +18,13 @@ pub enum ContentPartWrapper {
         is_task: bool,
     },
 
-    /// Text file content
-    TextFile { path: String, content: String },
+    /// Text file content, optionally restricted to a selection (e.g. "lines 120-180")
+    TextFile {
+        path: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selection: Option<String>,
+    },
 
     /// Clipboard text
     ClipboardText(String),
@@ -27,6 +32,18 @@ pub enum ContentPartWrapper {
     /// Stdin text
     StdinText(String),
 
+    /// Git diff (working tree, staged or range) with file summary
+    GitDiff {
+        description: String,
+        content: String,
+    },
+
+    /// Git commit log for a range
+    GitLog { range: String, content: String },
+
+    /// Readable web page content converted to markdown
+    WebPage { url: String, content: String },
+
     /// Image as base64
     Image {
         path: Option<String>, // Original file path if from file
@@ -41,6 +58,20 @@ pub enum ContentPartWrapper {
         data: String, // base64
     },
 
+    /// Tool call requested by the model
+    ToolCall {
+        call_id: String,
+        name: String,
+        arguments: serde_json::Value,
+    },
+
+    /// Result of a tool call
+    ToolResponse {
+        call_id: String,
+        name: String,
+        content: String,
+    },
+
     /// Plain text (for any other text content)
     Text(String),
 }
@@ -69,11 +100,19 @@ impl ContentPartWrapper {
                 };
                 ContentPart::Text(formatted)
             }
-            ContentPartWrapper::TextFile { path, content } => {
+            ContentPartWrapper::TextFile {
+                path,
+                content,
+                selection,
+            } => {
+                let header = match selection {
+                    Some(selection) => format!("### Content from: {} ({})", path, selection),
+                    None => format!("### Content from: {}", path),
+                };
                 let formatted = if content.ends_with('\n') {
-                    format!("### Content from: {}\n\n{}", path, content)
+                    format!("{}\n\n{}", header, content)
                 } else {
-                    format!("### Content from: {}\n\n{}\n", path, content)
+                    format!("{}\n\n{}\n", header, content)
                 };
                 ContentPart::Text(formatted)
             }
@@ -83,12 +122,40 @@ impl ContentPartWrapper {
             ContentPartWrapper::StdinText(text) => {
                 ContentPart::Text(format!("### Content from: stdin\n\n{}", text))
             }
+            ContentPartWrapper::GitDiff {
+                description,
+                content,
+            } => ContentPart::Text(format!(
+                "### Git diff: {}\n\n```diff\n{}\n```\n",
+                description,
+                content.trim_end()
+            )),
+            ContentPartWrapper::GitLog { range, content } => ContentPart::Text(format!(
+                "### Git log: {}\n\n```\n{}\n```\n",
+                range,
+                content.trim_end()
+            )),
+            ContentPartWrapper::WebPage { url, content } => {
+                ContentPart::Text(format!("### Web page: {}\n\n{}\n", url, content.trim_end()))
+            }
             ContentPartWrapper::Image {
                 mime_type, data, ..
             } => ContentPart::from_binary_base64(mime_type.clone(), data.clone(), None),
             ContentPartWrapper::Audio {
                 mime_type, data, ..
             } => ContentPart::from_binary_base64(mime_type.clone(), data.clone(), None),
+            ContentPartWrapper::ToolCall {
+                call_id,
+                name,
+                arguments,
+            } => ContentPart::ToolCall(ToolCall {
+                call_id: call_id.clone(),
+                fn_name: name.clone(),
+                fn_arguments: arguments.clone(),
+            }),
+            ContentPartWrapper::ToolResponse {
+                call_id, content, ..
+            } => ContentPart::ToolResponse(ToolResponse::new(call_id.clone(), content.clone())),
             ContentPartWrapper::Text(text) => ContentPart::Text(text.clone()),
         }
     }
@@ -101,8 +168,14 @@ impl ContentPartWrapper {
             ContentPartWrapper::TextFile { content, .. } => Some(content.clone()),
             ContentPartWrapper::ClipboardText(text) => Some(text.clone()),
             ContentPartWrapper::StdinText(text) => Some(text.clone()),
+            ContentPartWrapper::GitDiff { content, .. }
+            | ContentPartWrapper::GitLog { content, .. }
+            | ContentPartWrapper::WebPage { content, .. } => Some(content.clone()),
             ContentPartWrapper::Text(text) => Some(text.clone()),
-            ContentPartWrapper::Image { .. } | ContentPartWrapper::Audio { .. } => None,
+            ContentPartWrapper::ToolResponse { content, .. } => Some(content.clone()),
+            ContentPartWrapper::Image { .. }
+            | ContentPartWrapper::Audio { .. }
+            | ContentPartWrapper::ToolCall { .. } => None,
         }
     }
 
@@ -142,7 +215,7 @@ impl MessageContentWrapper {
 /// Custom ChatMessage wrapper for serialization
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessageWrapper {
-    pub role: String, // "User", "Assistant", "System"
+    pub role: String, // "User", "Assistant", "System", "Tool"
     pub content: MessageContentWrapper,
 }
 
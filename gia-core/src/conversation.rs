@@ -7,6 +7,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+use crate::config_file::load_settings;
 use crate::constants::CONVERSATION_TRUNCATION_KEEP_MESSAGES;
 use crate::content_part_wrapper::ChatMessageWrapper;
 use crate::logging::{log_debug, log_info, log_warn};
@@ -21,6 +22,10 @@ pub enum ResourceType {
     Stdin,
     Role,
     Task,
+    GitDiff,
+    GitLog,
+    WebPage,
+    McpTool,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
@@ -43,12 +48,26 @@ impl TokenUsage {
             _ => "N/A".to_string(),
         }
     }
+
+    /// Add the usage of another request (e.g. a tool round) to this one
+    pub fn accumulate(&mut self, other: TokenUsage) {
+        fn add(total: &mut Option<u32>, value: Option<u32>) {
+            if let Some(value) = value {
+                *total = Some(total.unwrap_or(0) + value);
+            }
+        }
+        add(&mut self.prompt_tokens, other.prompt_tokens);
+        add(&mut self.completion_tokens, other.completion_tokens);
+        add(&mut self.total_tokens, other.total_tokens);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceInfo {
     pub resource_type: ResourceType,
     pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<String>, // e.g. "lines 120-180" for partial file inputs
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +82,8 @@ pub struct ConversationMetadata {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
     pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>, // set by renaming, shown instead of the first prompt
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub messages: Vec<ChatMessageWrapper>,
@@ -123,6 +144,7 @@ impl Conversation {
 
         Self {
             id,
+            title: None,
             created_at: now,
             updated_at: now,
             messages: Vec::new(),
@@ -161,6 +183,10 @@ impl Conversation {
             .collect()
     }
 
+    /// Drop the oldest turns (a user message with the tool exchanges and the
+    /// answer that follow it) until the conversation fits, keeping at least
+    /// CONVERSATION_TRUNCATION_KEEP_MESSAGES messages. Whole turns are dropped, so
+    /// the history starts with a user message and no tool response loses its call.
     pub fn truncate_if_needed(&mut self, max_length: usize) {
         let current_length = self.estimate_token_length();
         if current_length <= max_length {
@@ -171,38 +197,34 @@ impl Conversation {
             "Conversation too long ({current_length} chars), truncating to fit context window"
         ));
 
-        // Keep removing oldest messages until we're under the limit
-        while self.estimate_token_length() > max_length
-            && self.messages.len() > CONVERSATION_TRUNCATION_KEEP_MESSAGES
-        {
-            self.messages.remove(0);
-            self.metadata.resources_per_message.remove(0);
-            self.metadata.token_usage_per_message.remove(0);
-            log_debug("Removed oldest message to fit context window");
-        }
-
-        // If still too long and we have messages, keep only the most recent pair
-        if self.estimate_token_length() > max_length
-            && self.messages.len() > CONVERSATION_TRUNCATION_KEEP_MESSAGES
-        {
-            let last_messages = self
+        let mut dropped = 0;
+        while self.estimate_token_length() > max_length {
+            // The oldest turn ends where the next user message starts
+            let Some(turn_length) = self
                 .messages
-                .split_off(self.messages.len() - CONVERSATION_TRUNCATION_KEEP_MESSAGES);
-            let last_resources = self
-                .metadata
-                .resources_per_message
-                .split_off(self.messages.len());
-            let last_token_usage = self
-                .metadata
-                .token_usage_per_message
-                .split_off(self.messages.len());
-
-            self.messages = last_messages;
-            self.metadata.resources_per_message = last_resources;
-            self.metadata.token_usage_per_message = last_token_usage;
+                .iter()
+                .skip(1)
+                .position(|message| message.role == "User")
+                .map(|position| position + 1)
+            else {
+                break;
+            };
+            if self.messages.len() - turn_length < CONVERSATION_TRUNCATION_KEEP_MESSAGES {
+                break;
+            }
 
+            self.messages.drain(..turn_length);
+            self.metadata.resources_per_message.drain(..turn_length);
+            self.metadata.token_usage_per_message.drain(..turn_length);
+            dropped += turn_length;
+            log_debug(&format!(
+                "Removed oldest turn ({turn_length} messages) to fit context window"
+            ));
+        }
+
+        if self.estimate_token_length() > max_length {
             log_warn(&format!(
-                "Had to truncate conversation to only the last {CONVERSATION_TRUNCATION_KEEP_MESSAGES} messages"
+                "Conversation still exceeds the context window after dropping {dropped} message(s)"
             ));
         }
     }
@@ -257,12 +279,53 @@ impl Conversation {
         }
     }
 
+    /// Tool calls of an assistant message, one line each
+    fn format_tool_calls(message: &ChatMessageWrapper) -> String {
+        use crate::content_part_wrapper::{ContentPartWrapper, MessageContentWrapper};
+
+        let MessageContentWrapper::Parts { parts } = &message.content else {
+            return String::new();
+        };
+        parts
+            .iter()
+            .filter_map(|part| match part {
+                ContentPartWrapper::ToolCall {
+                    name, arguments, ..
+                } => Some(format!("🔧 **Tool call:** `{name}({arguments})`\n")),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Tool results as collapsed blocks
+    fn format_tool_responses(message: &ChatMessageWrapper) -> String {
+        use crate::content_part_wrapper::{ContentPartWrapper, MessageContentWrapper};
+
+        let MessageContentWrapper::Parts { parts } = &message.content else {
+            return String::new();
+        };
+        parts
+            .iter()
+            .filter_map(|part| match part {
+                ContentPartWrapper::ToolResponse { name, content, .. } => Some(format!(
+                    "<details><summary>🔧 Tool result: {}</summary>\n<pre>{}</pre>\n</details>\n",
+                    html_escape::encode_text(name),
+                    html_escape::encode_text(content)
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn format_as_chat_markdown(&self) -> String {
         let username = whoami::username();
         let mut markdown = String::new();
 
         // Add conversation header
         write!(markdown, "### Conversation {}\n\n", self.id).unwrap();
+        if let Some(title) = &self.title {
+            writeln!(markdown, "**Title:** {title}").unwrap();
+        }
         writeln!(
             markdown,
             "**Created:** {}",
@@ -321,7 +384,12 @@ impl Conversation {
                                 }
                                 ResourceType::TextFile => {
                                     if let Some(path) = &resource.path {
-                                        format!("📄 File: {}", path)
+                                        match &resource.selection {
+                                            Some(selection) => {
+                                                format!("📄 File: {} ({})", path, selection)
+                                            }
+                                            None => format!("📄 File: {}", path),
+                                        }
                                     } else {
                                         "📄 File".to_string()
                                     }
@@ -343,6 +411,36 @@ impl Conversation {
                                         "✅ Task".to_string()
                                     }
                                 }
+                                ResourceType::GitDiff => {
+                                    if let Some(description) = &resource.path {
+                                        format!("🔀 Git diff: {}", description)
+                                    } else {
+                                        "🔀 Git diff".to_string()
+                                    }
+                                }
+                                ResourceType::GitLog => {
+                                    if let Some(range) = &resource.path {
+                                        format!("🔀 Git log: {}", range)
+                                    } else {
+                                        "🔀 Git log".to_string()
+                                    }
+                                }
+                                ResourceType::WebPage => {
+                                    if let Some(url) = &resource.path {
+                                        format!("🌐 Web page: {}", url)
+                                    } else {
+                                        "🌐 Web page".to_string()
+                                    }
+                                }
+                                ResourceType::McpTool => {
+                                    let tool = resource.path.as_deref().unwrap_or("unknown");
+                                    match &resource.selection {
+                                        Some(arguments) => {
+                                            format!("🔌 MCP tool: {}({})", tool, arguments)
+                                        }
+                                        None => format!("🔌 MCP tool: {}", tool),
+                                    }
+                                }
                             };
                             let escaped_resource = html_escape::encode_text(&resource_text);
                             resources_html.push_str(&format!("<li>{}</li>", escaped_resource));
@@ -363,8 +461,12 @@ impl Conversation {
                 }
                 "Assistant" => {
                     let text_content = Self::extract_text_content(message);
-                    markdown.push_str("**Assistant:** ");
-                    markdown.push_str(&text_content);
+                    let tool_calls = Self::format_tool_calls(message);
+                    if tool_calls.is_empty() || !text_content.trim().is_empty() {
+                        markdown.push_str("**Assistant:** ");
+                        markdown.push_str(&text_content);
+                    }
+                    markdown.push_str(&tool_calls);
 
                     // Add token usage information for assistant responses
                     if usage.prompt_tokens.is_some()
@@ -379,8 +481,9 @@ impl Conversation {
 
                     markdown.push('\n');
                 }
+                "Tool" => markdown.push_str(&Self::format_tool_responses(message)),
                 _ => {
-                    // Ignore System/Tool messages in markdown output
+                    // Ignore System messages in markdown output
                 }
             }
 
@@ -414,7 +517,16 @@ impl ConversationManager {
         Ok(Self { conversations_dir })
     }
 
-    fn get_conversations_dir() -> Result<PathBuf> {
+    /// Store conversations in an existing directory instead of ~/.gia/conversations
+    pub fn with_dir(conversations_dir: PathBuf) -> Self {
+        Self { conversations_dir }
+    }
+
+    /// conversations_dir from the settings of ~/.gia/config, else ~/.gia/conversations
+    pub fn get_conversations_dir() -> Result<PathBuf> {
+        if let Some(dir) = load_settings().conversations_dir {
+            return Ok(dir);
+        }
         let home_dir =
             dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
         Ok(home_dir.join(".gia").join("conversations"))
@@ -453,6 +565,12 @@ impl ConversationManager {
             return self.load_conversation_by_index(index);
         }
 
+        // IDs come from clients of `gia serve` and `gia mcp-serve` too, so they must
+        // not reach files outside the conversations directory
+        if id.contains(['/', '\\']) || id.contains("..") {
+            return Err(anyhow::anyhow!("Invalid conversation ID '{id}'"));
+        }
+
         // Try exact match first
         let filename = format!("{id}.json");
         let file_path = self.conversations_dir.join(&filename);
@@ -575,6 +693,69 @@ impl ConversationManager {
         Ok(summaries)
     }
 
+    /// Set the title of a conversation; an empty title removes it
+    pub fn rename_conversation(&self, id: &str, title: &str) -> Result<Conversation> {
+        let mut conversation = self.load_conversation(id)?;
+        let title = title.trim();
+        conversation.title = (!title.is_empty()).then(|| title.to_string());
+
+        self.save_conversation(&conversation)?;
+        self.save_markdown(&conversation)?;
+        log_info(&format!("Renamed conversation {}", conversation.id));
+        Ok(conversation)
+    }
+
+    /// Copy a conversation under a new ID so it can continue in another direction
+    pub fn fork_conversation(&self, id: &str) -> Result<Conversation> {
+        let original = self.load_conversation(id)?;
+        let first_prompt = original
+            .messages
+            .iter()
+            .find(|m| m.role == "User")
+            .map(Conversation::extract_prompt_section)
+            .unwrap_or_default();
+
+        let mut fork = Conversation::new_with_prompt(
+            original.metadata.model_used.clone(),
+            &first_prompt,
+            original.metadata.api_key_index,
+        );
+        fork.title = original.title.map(|title| format!("{title} (fork)"));
+        fork.messages = original.messages;
+        fork.metadata = original.metadata;
+
+        self.save_conversation(&fork)?;
+        self.save_markdown(&fork)?;
+        log_info(&format!(
+            "Forked conversation {} as {}",
+            original.id, fork.id
+        ));
+        Ok(fork)
+    }
+
+    /// Remove a conversation with its markdown and HTML preview files
+    pub fn delete_conversation(&self, id: &str) -> Result<()> {
+        let conversation = self.load_conversation(id)?;
+        fs::remove_file(
+            self.conversations_dir
+                .join(format!("{}.json", conversation.id)),
+        )
+        .context("Failed to delete conversation file")?;
+
+        for extension in ["md", "html"] {
+            let path = self
+                .conversations_dir
+                .join(format!("{}.{extension}", conversation.id));
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to delete {}", path.display()))?;
+            }
+        }
+
+        log_info(&format!("Deleted conversation {}", conversation.id));
+        Ok(())
+    }
+
     fn load_conversation_from_path(path: &Path) -> Result<Conversation> {
         let content = fs::read_to_string(path).context("Failed to read conversation file")?;
 
@@ -585,10 +766,10 @@ impl ConversationManager {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ConversationSummary {
     pub id: String,
-    #[allow(dead_code)]
+    pub title: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub message_count: usize,
@@ -614,6 +795,7 @@ impl ConversationSummary {
 
         Self {
             id: conversation.id.clone(),
+            title: conversation.title.clone(),
             created_at: conversation.created_at,
             updated_at: conversation.updated_at,
             message_count: conversation.messages.len(),
@@ -634,7 +816,11 @@ impl ConversationSummary {
         };
 
         let default_message = "(no messages)".to_string();
-        let preview = self.first_user_message.as_ref().unwrap_or(&default_message);
+        let preview = self
+            .title
+            .as_ref()
+            .or(self.first_user_message.as_ref())
+            .unwrap_or(&default_message);
 
         // Replace line feeds and tabs with spaces for table format
         let preview_clean = preview.replace(['\n', '\r', '\t'], " ");
@@ -679,6 +865,93 @@ mod tests {
         assert_eq!(conversation.metadata.token_usage_per_message.len(), 1);
     }
 
+    #[test]
+    fn test_tool_messages() {
+        use crate::content_part_wrapper::{
+            ChatMessageWrapper, ContentPartWrapper, MessageContentWrapper,
+        };
+        use genai::chat::ChatRole;
+
+        let mut conversation = Conversation::new("test-model".to_string());
+        let call = ChatMessageWrapper {
+            role: "Assistant".to_string(),
+            content: MessageContentWrapper::Parts {
+                parts: vec![ContentPartWrapper::ToolCall {
+                    call_id: "call_1".to_string(),
+                    name: "read_file".to_string(),
+                    arguments: serde_json::json!({"path": "Cargo.toml"}),
+                }],
+            },
+        };
+        let result = ChatMessageWrapper {
+            role: "Tool".to_string(),
+            content: MessageContentWrapper::Parts {
+                parts: vec![ContentPartWrapper::ToolResponse {
+                    call_id: "call_1".to_string(),
+                    name: "read_file".to_string(),
+                    content: "[package] <gia>".to_string(),
+                }],
+            },
+        };
+        conversation.add_message_with_usage(call, Vec::new(), TokenUsage::default());
+        conversation.add_message_with_usage(result, Vec::new(), TokenUsage::default());
+
+        let messages = conversation.to_genai_messages().unwrap();
+        assert!(matches!(messages[0].role, ChatRole::Assistant));
+        assert_eq!(messages[0].content.tool_calls()[0].fn_name, "read_file");
+        assert!(matches!(messages[1].role, ChatRole::Tool));
+        assert_eq!(
+            messages[1].content.tool_responses()[0].content,
+            "[package] <gia>"
+        );
+
+        let markdown = conversation.format_as_chat_markdown();
+        assert!(markdown.contains("🔧 **Tool call:** `read_file({\"path\":\"Cargo.toml\"})`"));
+        assert!(markdown.contains("Tool result: read_file"));
+        assert!(markdown.contains("[package] &lt;gia&gt;"));
+        assert!(!markdown.contains("**Assistant:**"));
+    }
+
+    #[test]
+    fn test_mcp_tool_resource() {
+        use crate::content_part_wrapper::MessageContentWrapper;
+
+        let mut conversation = Conversation::new("test-model".to_string());
+        conversation.add_message_with_usage(
+            ChatMessageWrapper {
+                role: "User".to_string(),
+                content: MessageContentWrapper::Text {
+                    text: "Find the login bug".to_string(),
+                },
+            },
+            vec![ResourceInfo {
+                resource_type: ResourceType::McpTool,
+                path: Some("jira/search".to_string()),
+                selection: Some("{\"query\":\"login\"}".to_string()),
+            }],
+            TokenUsage::default(),
+        );
+
+        let markdown = conversation.format_as_chat_markdown();
+        assert!(markdown.contains("🔌 MCP tool: jira/search({\"query\":\"login\"})"));
+    }
+
+    #[test]
+    fn test_token_usage_accumulate() {
+        let mut usage = TokenUsage::default();
+        usage.accumulate(TokenUsage {
+            prompt_tokens: Some(10),
+            completion_tokens: Some(2),
+            total_tokens: Some(12),
+        });
+        usage.accumulate(TokenUsage {
+            prompt_tokens: Some(20),
+            completion_tokens: Some(3),
+            total_tokens: Some(23),
+        });
+        assert_eq!(usage.format_short(), "30+5=35");
+    }
+
     #[test]
     fn test_extract_text_content() {
         use crate::content_part_wrapper::{ChatMessageWrapper, MessageContentWrapper};
@@ -736,6 +1009,78 @@ mod tests {
         // Should have fewer messages now, but at least CONVERSATION_TRUNCATION_KEEP_MESSAGES
         assert!(conversation.messages.len() < initial_count);
         assert!(conversation.messages.len() >= 20); // At least the minimum
+        assert_eq!(conversation.messages[0].role, "User");
+        assert_eq!(
+            conversation.metadata.resources_per_message.len(),
+            conversation.messages.len()
+        );
+    }
+
+    #[test]
+    fn test_truncate_keeps_tool_exchanges_whole() {
+        use crate::content_part_wrapper::{
+            ChatMessageWrapper, ContentPartWrapper, MessageContentWrapper,
+        };
+
+        let parts = |role: &str, parts: Vec<ContentPartWrapper>| ChatMessageWrapper {
+            role: role.to_string(),
+            content: MessageContentWrapper::Parts { parts },
+        };
+        let mut conversation = Conversation::new("test-model".to_string());
+        for turn in 0..10 {
+            let call_id = format!("call-{turn}");
+            let messages = [
+                parts(
+                    "User",
+                    vec![ContentPartWrapper::Text("question ".repeat(50))],
+                ),
+                parts(
+                    "Assistant",
+                    vec![ContentPartWrapper::ToolCall {
+                        call_id: call_id.clone(),
+                        name: "read_file".to_string(),
+                        arguments: serde_json::json!({"path": "src/main.rs"}),
+                    }],
+                ),
+                parts(
+                    "Tool",
+                    vec![ContentPartWrapper::ToolResponse {
+                        call_id,
+                        name: "read_file".to_string(),
+                        content: "fn main() {}".repeat(50),
+                    }],
+                ),
+                parts(
+                    "Assistant",
+                    vec![ContentPartWrapper::Text("answer".repeat(50))],
+                ),
+            ];
+            for message in messages {
+                conversation.add_message_with_usage(message, Vec::new(), TokenUsage::default());
+            }
+        }
+
+        // Whole turns of 4 messages are dropped down to the minimum of 20
+        conversation.truncate_if_needed(1000);
+        assert_eq!(conversation.messages.len(), 20);
+        assert_eq!(conversation.messages[0].role, "User");
+        assert_eq!(conversation.metadata.token_usage_per_message.len(), 20);
+
+        let mut call_ids = Vec::new();
+        for message in &conversation.messages {
+            let MessageContentWrapper::Parts { parts } = &message.content else {
+                continue;
+            };
+            for part in parts {
+                match part {
+                    ContentPartWrapper::ToolCall { call_id, .. } => call_ids.push(call_id),
+                    ContentPartWrapper::ToolResponse { call_id, .. } => {
+                        assert!(call_ids.contains(&call_id), "{call_id} without its call")
+                    }
+                    _ => {}
+                }
+            }
+        }
     }
 
     #[test]
@@ -869,4 +1214,92 @@ mod tests {
         assert_ne!(conv1.id, conv2.id);
         assert_ne!(conv2.id, conv3.id);
     }
+
+    fn saved_conversation(manager: &ConversationManager, prompt: &str) -> Conversation {
+        use crate::content_part_wrapper::{ChatMessageWrapper, MessageContentWrapper};
+
+        let mut conversation = Conversation::new_with_prompt("test-model".to_string(), prompt, 0);
+        for (role, text) in [("User", prompt), ("Assistant", "Answer")] {
+            let message = ChatMessageWrapper {
+                role: role.to_string(),
+                content: MessageContentWrapper::Text {
+                    text: text.to_string(),
+                },
+            };
+            conversation.add_message_with_usage(message, Vec::new(), TokenUsage::default());
+        }
+        manager.save_conversation(&conversation).unwrap();
+        manager.save_markdown(&conversation).unwrap();
+        conversation
+    }
+
+    #[test]
+    fn test_rename_conversation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ConversationManager::with_dir(dir.path().to_path_buf());
+        let conversation = saved_conversation(&manager, "Explain lifetimes in Rust");
+
+        manager
+            .rename_conversation(&conversation.id, "  Lifetimes  ")
+            .unwrap();
+        let summaries = manager.list_conversations().unwrap();
+        assert_eq!(summaries[0].title.as_deref(), Some("Lifetimes"));
+        assert_eq!(summaries[0].format_as_table_columns().0, "Lifetimes");
+
+        manager.rename_conversation(&conversation.id, "").unwrap();
+        assert_eq!(manager.list_conversations().unwrap()[0].title, None);
+    }
+
+    #[test]
+    fn test_fork_conversation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ConversationManager::with_dir(dir.path().to_path_buf());
+        let conversation = saved_conversation(&manager, "Explain lifetimes in Rust");
+        manager
+            .rename_conversation(&conversation.id, "Lifetimes")
+            .unwrap();
+
+        let fork = manager.fork_conversation(&conversation.id).unwrap();
+        assert_ne!(fork.id, conversation.id);
+        assert!(fork.id.starts_with("explain-lifetimes-in-rust-"));
+        assert_eq!(fork.title.as_deref(), Some("Lifetimes (fork)"));
+        assert_eq!(fork.messages.len(), 2);
+        assert_eq!(manager.list_conversations().unwrap().len(), 2);
+        assert!(manager.get_markdown_path(&fork).unwrap().exists());
+    }
+
+    #[test]
+    fn test_delete_conversation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = ConversationManager::with_dir(dir.path().to_path_buf());
+        let keep = saved_conversation(&manager, "Keep this one");
+        let delete = saved_conversation(&manager, "Delete this one");
+
+        manager.delete_conversation(&delete.id).unwrap();
+
+        let summaries = manager.list_conversations().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].id, keep.id);
+        assert!(!manager.get_markdown_path(&delete).unwrap().exists());
+        assert!(manager.delete_conversation(&delete.id).is_err());
+    }
+
+    #[test]
+    fn test_load_conversation_rejects_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let conversations_dir = dir.path().join("conversations");
+        let manager = ConversationManager::with_dir(conversations_dir.clone());
+        let outside = Conversation::new("gemini-2.5-flash".to_string());
+        fs::create_dir_all(&conversations_dir).unwrap();
+        fs::write(
+            dir.path().join("outside.json"),
+            serde_json::to_string(&outside).unwrap(),
+        )
+        .unwrap();
+
+        for id in ["../outside", "..\\outside", "sub/outside", ".."] {
+            let err = manager.load_conversation(id).unwrap_err();
+            assert!(err.to_string().contains("Invalid conversation ID"), "{id}");
+        }
+    }
 }
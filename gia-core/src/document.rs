@@ -0,0 +1,703 @@
+//! Local text extraction for office documents and PDFs.
+//!
+//! Converts PDF, DOCX, ODT, PPTX, XLSX/XLS/ODS and CSV files into markdown text
+//! so they can be sent to any provider as regular text content instead of
+//! relying on native document upload (which only Gemini supports).
+
+use anyhow::{Context, Result};
+use calamine::{Reader, open_workbook_auto_from_rs};
+use quick_xml::Reader as XmlReader;
+use quick_xml::events::{BytesRef, BytesStart, Event};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::archive::{open_path_reader, read_path_bytes};
+use crate::constants::{DOCUMENT_EXTENSIONS, PDF_HELPER_TIMEOUT_SECS};
+use crate::logging::{log_debug, log_info};
+
+/// Check if a file is a document we can extract text from, based on its extension
+pub fn is_document_file(path: &Path) -> bool {
+    document_extension(path).is_some()
+}
+
+fn document_extension(path: &Path) -> Option<String> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    DOCUMENT_EXTENSIONS.contains(&ext.as_str()).then_some(ext)
+}
+
+/// Extract the content of a document as markdown text
+pub fn extract_document_text(path: &Path) -> Result<String> {
+    let ext = document_extension(path)
+        .with_context(|| format!("Unsupported document type: {}", path.display()))?;
+
+    log_debug(&format!(
+        "Extracting text from {ext} document: {}",
+        path.display()
+    ));
+
+    // Documents may live inside archives (bundle.zip!/report.docx), so they are always
+    // read through the archive-aware helpers instead of opening the path directly
+    let text = match ext.as_str() {
+        "pdf" => extract_pdf(path)?,
+        "docx" => extract_docx(&read_zip_entry(path, "word/document.xml")?)?,
+        "odt" => extract_odt(&read_zip_entry(path, "content.xml")?)?,
+        "pptx" => extract_pptx(path)?,
+        "xlsx" | "xls" | "ods" => extract_spreadsheet(path)?,
+        "csv" => {
+            let content = crate::input::read_text_file(&path.to_string_lossy())?;
+            csv_to_markdown(&content)
+        }
+        _ => unreachable!("extension checked against DOCUMENT_EXTENSIONS"),
+    };
+
+    log_info(&format!(
+        "Extracted {} characters from document: {}",
+        text.len(),
+        path.display()
+    ));
+
+    Ok(text)
+}
+
+/// Set when this process may start itself as PDF helper (see `run_pdf_helper`)
+static PDF_HELPER_AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Environment variable that starts a gia binary as PDF helper
+const PDF_HELPER_ENV: &str = "GIA_PDF_HELPER";
+
+/// Call first in `main`. When the process was started as PDF helper, this reads
+/// a PDF from stdin, writes its pages as JSON to stdout and returns `Some`; the
+/// caller then exits. Otherwise PDFs are extracted in such a helper process from
+/// now on: pdf-extract panics on some malformed PDFs, and with `panic = "abort"`
+/// that would end the whole program.
+pub fn run_pdf_helper() -> Option<Result<()>> {
+    if std::env::var_os(PDF_HELPER_ENV).is_none() {
+        PDF_HELPER_AVAILABLE.store(true, Ordering::Relaxed);
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    let result = std::io::stdin()
+        .read_to_end(&mut bytes)
+        .context("Failed to read the PDF from stdin")
+        .and_then(|_| pdf_extract::extract_text_from_mem_by_pages(&bytes).context("Invalid PDF"))
+        .and_then(|pages| {
+            serde_json::to_writer(std::io::stdout().lock(), &pages)
+                .context("Failed to write the PDF text")
+        });
+    Some(result)
+}
+
+/// Extract the pages of a PDF in a helper process, or in this process when it
+/// can't start itself as helper (tests, other programs using the library)
+fn extract_pdf_pages(bytes: Vec<u8>) -> Result<Vec<String>> {
+    if !PDF_HELPER_AVAILABLE.load(Ordering::Relaxed) {
+        return pdf_extract::extract_text_from_mem_by_pages(&bytes).context("Invalid PDF");
+    }
+
+    let exe = std::env::current_exe().context("Failed to locate the gia executable")?;
+    let mut child = Command::new(exe)
+        .env(PDF_HELPER_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to start the PDF helper")?;
+
+    // Writing and reading from other threads keeps full pipes from blocking the helper
+    let mut stdin = child.stdin.take().context("PDF helper has no stdin")?;
+    let writer = std::thread::spawn(move || stdin.write_all(&bytes));
+    let stdout_reader = read_pipe(child.stdout.take());
+    let stderr_reader = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + Duration::from_secs(PDF_HELPER_TIMEOUT_SECS);
+    let status = loop {
+        if let Some(status) = child.try_wait().context("Failed to run the PDF helper")? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!(
+                "Invalid PDF (text extraction took longer than {PDF_HELPER_TIMEOUT_SECS} seconds)"
+            );
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    // The helper stops reading when it fails, which is reported below
+    let _ = writer.join();
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    if !status.success() {
+        let message = String::from_utf8_lossy(&stderr);
+        // Skip the backtrace hint printed after a panic message
+        let message = message
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty() && !line.starts_with("note:"));
+        return Err(anyhow::anyhow!(
+            "Invalid PDF ({})",
+            message.unwrap_or("the PDF parser crashed").trim()
+        ));
+    }
+    serde_json::from_slice(&stdout).context("Unexpected output of the PDF helper")
+}
+
+/// Read a pipe of a child process to the end in another thread
+fn read_pipe(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
+fn extract_pdf(path: &Path) -> Result<String> {
+    let bytes = read_path_bytes(&path.to_string_lossy())?;
+    let pages = extract_pdf_pages(bytes)
+        .with_context(|| format!("Failed to extract text from PDF: {}", path.display()))?;
+
+    let mut markdown = String::new();
+    for (index, page) in pages.iter().enumerate() {
+        let page = page.trim();
+        if page.is_empty() {
+            continue;
+        }
+        markdown.push_str(&format!("## Page {}\n\n{}\n\n", index + 1, page));
+    }
+    Ok(markdown.trim_end().to_string())
+}
+
+fn open_zip(path: &Path) -> Result<zip::ZipArchive<Cursor<Vec<u8>>>> {
+    zip::ZipArchive::new(open_path_reader(&path.to_string_lossy())?)
+        .with_context(|| format!("Not a valid office document: {}", path.display()))
+}
+
+fn read_zip_entry(path: &Path, entry_name: &str) -> Result<String> {
+    let mut archive = open_zip(path)?;
+    let mut entry = archive
+        .by_name(entry_name)
+        .with_context(|| format!("Missing '{entry_name}' in {}", path.display()))?;
+    let mut xml = String::new();
+    entry
+        .read_to_string(&mut xml)
+        .with_context(|| format!("Failed to read '{entry_name}' from {}", path.display()))?;
+    Ok(xml)
+}
+
+/// Resolve `&amp;`-style and numeric character references reported by quick-xml
+fn resolve_reference(reference: &BytesRef) -> String {
+    if let Ok(Some(ch)) = reference.resolve_char_ref() {
+        return ch.to_string();
+    }
+    match reference.decode().as_deref() {
+        Ok("amp") => "&",
+        Ok("lt") => "<",
+        Ok("gt") => ">",
+        Ok("quot") => "\"",
+        Ok("apos") => "'",
+        _ => "",
+    }
+    .to_string()
+}
+
+fn attribute_value(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .map(|attr| String::from_utf8_lossy(&attr.value).into_owned())
+}
+
+/// Collects paragraphs and table rows while walking a document's XML
+#[derive(Default)]
+struct MarkdownBuilder {
+    output: String,
+    paragraph: String,
+    prefix: String,
+    row: Vec<String>,
+    table_rows: Vec<Vec<String>>,
+    in_table: bool,
+}
+
+impl MarkdownBuilder {
+    fn push_text(&mut self, text: &str) {
+        self.paragraph.push_str(text);
+    }
+
+    fn end_paragraph(&mut self) {
+        let text = self.paragraph.trim().to_string();
+        self.paragraph.clear();
+        let prefix = std::mem::take(&mut self.prefix);
+
+        if text.is_empty() {
+            return;
+        }
+
+        if self.in_table {
+            // Paragraphs inside a cell are joined into a single cell value
+            if let Some(cell) = self.row.last_mut() {
+                if !cell.is_empty() {
+                    cell.push(' ');
+                }
+                cell.push_str(&text);
+            }
+        } else {
+            self.output.push_str(&prefix);
+            self.output.push_str(&text);
+            self.output.push_str("\n\n");
+        }
+    }
+
+    fn start_table(&mut self) {
+        self.end_paragraph();
+        self.in_table = true;
+        self.table_rows.clear();
+    }
+
+    fn end_table(&mut self) {
+        self.in_table = false;
+        let rows = std::mem::take(&mut self.table_rows);
+        if !rows.is_empty() {
+            self.output.push_str(&rows_to_markdown_table(&rows));
+            self.output.push('\n');
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.end_paragraph();
+        self.output.trim_end().to_string()
+    }
+}
+
+fn extract_docx(xml: &str) -> Result<String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut builder = MarkdownBuilder::default();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event().context("Failed to parse DOCX XML")? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"t" => in_text = true,
+                b"tbl" => builder.start_table(),
+                b"tr" => builder.row.clear(),
+                b"tc" => builder.row.push(String::new()),
+                b"numPr" => builder.prefix = "- ".to_string(),
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"pStyle" => {
+                    if let Some(style) = attribute_value(&e, "w:val") {
+                        builder.prefix = heading_prefix(&style);
+                    }
+                }
+                b"tab" => builder.push_text("\t"),
+                b"br" | b"cr" => builder.push_text("\n"),
+                _ => {}
+            },
+            Event::Text(e) if in_text => builder.push_text(&e.decode()?),
+            Event::GeneralRef(e) if in_text => builder.push_text(&resolve_reference(&e)),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => builder.end_paragraph(),
+                b"tr" => {
+                    let row = std::mem::take(&mut builder.row);
+                    builder.table_rows.push(row);
+                }
+                b"tbl" => builder.end_table(),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+/// Map Word paragraph styles such as "Heading2" or "Title" to markdown heading prefixes
+fn heading_prefix(style: &str) -> String {
+    let lower = style.to_lowercase();
+    if lower == "title" {
+        return "# ".to_string();
+    }
+    if let Some(level) = lower
+        .strip_prefix("heading")
+        .and_then(|l| l.trim().parse::<usize>().ok())
+    {
+        return format!("{} ", "#".repeat(level.clamp(1, 6)));
+    }
+    if lower.starts_with("list") {
+        return "- ".to_string();
+    }
+    String::new()
+}
+
+fn extract_odt(xml: &str) -> Result<String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut builder = MarkdownBuilder::default();
+    let mut in_paragraph = false;
+    let mut list_depth = 0usize;
+
+    loop {
+        match reader.read_event().context("Failed to parse ODT XML")? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"text:h" => {
+                    in_paragraph = true;
+                    let level = attribute_value(&e, "text:outline-level")
+                        .and_then(|l| l.parse::<usize>().ok())
+                        .unwrap_or(1);
+                    builder.prefix = format!("{} ", "#".repeat(level.clamp(1, 6)));
+                }
+                b"text:p" => {
+                    in_paragraph = true;
+                    if list_depth > 0 {
+                        builder.prefix = format!("{}- ", "  ".repeat(list_depth - 1));
+                    }
+                }
+                b"text:list" => list_depth += 1,
+                b"table:table" => builder.start_table(),
+                b"table:table-row" => builder.row.clear(),
+                b"table:table-cell" => builder.row.push(String::new()),
+                _ => {}
+            },
+            Event::Empty(e) => match e.name().as_ref() {
+                b"text:s" => {
+                    let count = attribute_value(&e, "text:c")
+                        .and_then(|c| c.parse::<usize>().ok())
+                        .unwrap_or(1);
+                    builder.push_text(&" ".repeat(count));
+                }
+                b"text:tab" => builder.push_text("\t"),
+                b"text:line-break" => builder.push_text("\n"),
+                b"table:table-cell" => builder.row.push(String::new()),
+                _ => {}
+            },
+            Event::Text(e) if in_paragraph => builder.push_text(&e.decode()?),
+            Event::GeneralRef(e) if in_paragraph => builder.push_text(&resolve_reference(&e)),
+            Event::End(e) => match e.name().as_ref() {
+                b"text:h" | b"text:p" => {
+                    in_paragraph = false;
+                    builder.end_paragraph();
+                }
+                b"text:list" => list_depth = list_depth.saturating_sub(1),
+                b"table:table-row" => {
+                    let row = std::mem::take(&mut builder.row);
+                    builder.table_rows.push(row);
+                }
+                b"table:table" => builder.end_table(),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+fn extract_pptx(path: &Path) -> Result<String> {
+    let mut archive = open_zip(path)?;
+
+    // Slides are stored as ppt/slides/slideN.xml; sort numerically to keep presentation order
+    let mut slides: Vec<(usize, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let number = name
+                .strip_prefix("ppt/slides/slide")?
+                .strip_suffix(".xml")?
+                .parse::<usize>()
+                .ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+    slides.sort();
+
+    let mut markdown = String::new();
+    for (number, name) in slides {
+        let mut xml = String::new();
+        archive
+            .by_name(&name)
+            .with_context(|| format!("Failed to open slide {number}"))?
+            .read_to_string(&mut xml)
+            .with_context(|| format!("Failed to read slide {number}"))?;
+
+        let text = extract_slide_text(&xml)?;
+        markdown.push_str(&format!("## Slide {number}\n\n"));
+        if !text.is_empty() {
+            markdown.push_str(&text);
+            markdown.push_str("\n\n");
+        }
+    }
+
+    Ok(markdown.trim_end().to_string())
+}
+
+fn extract_slide_text(xml: &str) -> Result<String> {
+    let mut reader = XmlReader::from_str(xml);
+    let mut builder = MarkdownBuilder::default();
+    let mut in_text = false;
+
+    loop {
+        match reader.read_event().context("Failed to parse PPTX XML")? {
+            Event::Start(e) if e.local_name().as_ref() == b"t" => in_text = true,
+            Event::Empty(e) if e.local_name().as_ref() == b"br" => builder.push_text("\n"),
+            Event::Text(e) if in_text => builder.push_text(&e.decode()?),
+            Event::GeneralRef(e) if in_text => builder.push_text(&resolve_reference(&e)),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => builder.end_paragraph(),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(builder.finish())
+}
+
+fn extract_spreadsheet(path: &Path) -> Result<String> {
+    let mut workbook = open_workbook_auto_from_rs(open_path_reader(&path.to_string_lossy())?)
+        .with_context(|| format!("Failed to open spreadsheet: {}", path.display()))?;
+
+    let mut markdown = String::new();
+    for sheet_name in workbook.sheet_names() {
+        let range = workbook
+            .worksheet_range(&sheet_name)
+            .with_context(|| format!("Failed to read sheet '{sheet_name}'"))?;
+
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .filter(|row: &Vec<String>| row.iter().any(|cell| !cell.trim().is_empty()))
+            .collect();
+
+        markdown.push_str(&format!("## Sheet: {sheet_name}\n\n"));
+        if rows.is_empty() {
+            markdown.push_str("(empty)\n\n");
+        } else {
+            markdown.push_str(&rows_to_markdown_table(&rows));
+            markdown.push('\n');
+        }
+    }
+
+    Ok(markdown.trim_end().to_string())
+}
+
+/// Convert CSV text into a markdown table (first row is used as header)
+pub fn csv_to_markdown(content: &str) -> String {
+    let rows: Vec<Vec<String>> = parse_csv(content)
+        .into_iter()
+        .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+        .collect();
+
+    if rows.is_empty() {
+        return String::new();
+    }
+
+    rows_to_markdown_table(&rows).trim_end().to_string()
+}
+
+/// Minimal RFC 4180 CSV parser supporting quoted fields, escaped quotes and
+/// auto-detected `,`/`;`/tab delimiters
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let first_line = content.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| first_line.matches(*d).count())
+        .unwrap_or(',');
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Render rows as a markdown table, treating the first row as header
+pub fn rows_to_markdown_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0).max(1);
+
+    let format_row = |row: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|i| {
+                row.get(i)
+                    .map(|cell| cell.replace('|', "\\|").replace(['\n', '\r'], " "))
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut table = format_row(&rows[0]);
+    table.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+    for row in &rows[1..] {
+        table.push_str(&format_row(row));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let file = fs::File::create(path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        for (name, content) in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_is_document_file() {
+        assert!(is_document_file(Path::new("report.pdf")));
+        assert!(is_document_file(Path::new("report.DOCX")));
+        assert!(is_document_file(Path::new("notes.odt")));
+        assert!(is_document_file(Path::new("data.xlsx")));
+        assert!(is_document_file(Path::new("data.csv")));
+        assert!(is_document_file(Path::new("slides.pptx")));
+
+        assert!(!is_document_file(Path::new("main.rs")));
+        assert!(!is_document_file(Path::new("photo.png")));
+        assert!(!is_document_file(Path::new("README")));
+    }
+
+    #[test]
+    fn test_extract_docx() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.docx");
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:body>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Quarterly Report</w:t></w:r></w:p>
+<w:p><w:r><w:t xml:space="preserve">Revenue &amp; costs </w:t></w:r><w:r><w:t>grew.</w:t></w:r></w:p>
+<w:p><w:pPr><w:numPr><w:ilvl w:val="0"/></w:numPr></w:pPr><w:r><w:t>First point</w:t></w:r></w:p>
+<w:tbl>
+<w:tr><w:tc><w:p><w:r><w:t>Name</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Value</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p><w:r><w:t>A</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>1</w:t></w:r></w:p></w:tc></w:tr>
+</w:tbl>
+</w:body>
+</w:document>"#;
+        write_zip(&path, &[("word/document.xml", xml)]);
+
+        let text = extract_document_text(&path).unwrap();
+        assert!(text.contains("# Quarterly Report"));
+        assert!(text.contains("Revenue & costs grew."));
+        assert!(text.contains("- First point"));
+        assert!(text.contains("| Name | Value |"));
+        assert!(text.contains("| A | 1 |"));
+    }
+
+    #[test]
+    fn test_extract_odt() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.odt");
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+<office:body><office:text>
+<text:h text:outline-level="2">Meeting Notes</text:h>
+<text:p>Budget<text:s text:c="2"/>approved</text:p>
+<text:list><text:list-item><text:p>Action item</text:p></text:list-item></text:list>
+</office:text></office:body>
+</office:document-content>"#;
+        write_zip(&path, &[("content.xml", xml)]);
+
+        let text = extract_document_text(&path).unwrap();
+        assert!(text.contains("## Meeting Notes"));
+        assert!(text.contains("Budget  approved"));
+        assert!(text.contains("- Action item"));
+    }
+
+    #[test]
+    fn test_extract_pptx_orders_slides_numerically() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("deck.pptx");
+        let slide = |text: &str| {
+            format!(
+                r#"<p:sld xmlns:p="p" xmlns:a="a"><p:cSld><p:spTree><p:sp><p:txBody><a:p><a:r><a:t>{text}</a:t></a:r></a:p></p:txBody></p:sp></p:spTree></p:cSld></p:sld>"#
+            )
+        };
+        let slide2 = slide("Second");
+        let slide10 = slide("Tenth");
+        let slide1 = slide("First");
+        write_zip(
+            &path,
+            &[
+                ("ppt/slides/slide10.xml", &slide10),
+                ("ppt/slides/slide2.xml", &slide2),
+                ("ppt/slides/slide1.xml", &slide1),
+            ],
+        );
+
+        let text = extract_document_text(&path).unwrap();
+        let first = text.find("First").unwrap();
+        let second = text.find("Second").unwrap();
+        let tenth = text.find("Tenth").unwrap();
+        assert!(first < second && second < tenth);
+        assert!(text.contains("## Slide 10"));
+    }
+
+    #[test]
+    fn test_csv_to_markdown() {
+        let csv = "name,comment\nAlice,\"Hello, world\"\nBob,\"He said \"\"hi\"\"\"\n";
+        let markdown = csv_to_markdown(csv);
+        assert_eq!(
+            markdown,
+            "| name | comment |\n| --- | --- |\n| Alice | Hello, world |\n| Bob | He said \"hi\" |"
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_semicolon_delimiter() {
+        let rows = parse_csv("a;b;c\n1;2;3");
+        assert_eq!(rows, vec![vec!["a", "b", "c"], vec!["1", "2", "3"]]);
+    }
+
+    #[test]
+    fn test_extract_invalid_docx() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.docx");
+        fs::write(&path, "not a zip file").unwrap();
+
+        assert!(extract_document_text(&path).is_err());
+    }
+}
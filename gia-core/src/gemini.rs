@@ -2,11 +2,12 @@ use crate::api_key::validate_api_key_format;
 use crate::constants::GEMINI_API_KEY_URL;
 use crate::conversation::TokenUsage;
 use crate::logging::{log_debug, log_error, log_info, log_trace, log_warn};
-use crate::provider::{AiProvider, AiResponse};
+use crate::progress::{self, Stage};
+use crate::provider::{AiProvider, AiResponse, TextCallback, exec_chat_streaming};
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use genai::Client;
-use genai::chat::{ChatMessage, ChatRequest};
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, Tool};
 use genai::resolver::{AuthData, AuthResolver};
 
 #[derive(Debug)]
@@ -14,6 +15,7 @@ pub struct GeminiClient {
     api_keys: Vec<String>,
     current_key_index: usize,
     model: String,
+    temperature: Option<f64>,
 }
 
 impl GeminiClient {
@@ -57,9 +59,16 @@ impl GeminiClient {
             api_keys,
             current_key_index,
             model,
+            temperature: None,
         })
     }
 
+    /// Set the sampling temperature (None = model default)
+    pub fn with_temperature(mut self, temperature: Option<f64>) -> Self {
+        self.temperature = temperature;
+        self
+    }
+
     fn next_key_index(&self) -> usize {
         (self.current_key_index + 1) % self.api_keys.len()
     }
@@ -87,10 +96,13 @@ impl GeminiClient {
         );
         eprintln!();
 
-        // Ask if user wants to open the API key page
-        eprintln!("Open API key page in browser? (y/N)");
+        // Ask if user wants to open the API key page (only on a terminal: in server
+        // modes stdin carries requests)
         let mut input = String::new();
-        if std::io::stdin().read_line(&mut input).is_ok() {
+        if atty::is(atty::Stream::Stdin) {
+            eprintln!("Open API key page in browser? (y/N)");
+        }
+        if atty::is(atty::Stream::Stdin) && std::io::stdin().read_line(&mut input).is_ok() {
             let response = input.trim().to_lowercase();
             if response == "y" || response == "yes" {
                 if let Err(e) = webbrowser::open(GEMINI_API_KEY_URL) {
@@ -127,8 +139,10 @@ impl GeminiClient {
     async fn try_chat_request_with_messages(
         &self,
         messages: Vec<ChatMessage>,
+        tools: &[Tool],
         api_key: &str,
         key_index: usize,
+        on_text: Option<TextCallback<'_>>,
     ) -> Result<AiResponse> {
         log_info(&format!(
             "Trying API key {}/{} for chat request with {} message(s)",
@@ -149,14 +163,42 @@ impl GeminiClient {
         let client = Client::builder().with_auth_resolver(auth_resolver).build();
 
         // Create the chat request
-        let chat_request = ChatRequest::new(messages);
+        let mut chat_request = ChatRequest::new(messages);
+        if !tools.is_empty() {
+            chat_request = chat_request.with_tools(tools.to_vec());
+        }
         log_trace("=== Full Chat Request ===");
         log_trace(&format!("Model: {}", self.model));
         log_trace(&format!("Request Debug: {:?}", chat_request));
         log_trace("=== End Full Chat Request ===");
 
         // Send the request using genai
-        let chat_response = match client.exec_chat(&self.model, chat_request, None).await {
+        let chat_options = self
+            .temperature
+            .map(|temperature| ChatOptions::default().with_temperature(temperature));
+
+        if let Some(on_text) = on_text {
+            let response = exec_chat_streaming(
+                &client,
+                &self.model,
+                chat_request,
+                chat_options.as_ref(),
+                on_text,
+            )
+            .await
+            .inspect_err(|e| log_debug(&format!("Raw genai stream error: {e:?}")))
+            .context("Failed to send chat request to Gemini API")?;
+            log_info(&format!(
+                "Received streamed response from Gemini API, length: {}",
+                response.content.len()
+            ));
+            return Ok(response);
+        }
+
+        let chat_response = match client
+            .exec_chat(&self.model, chat_request, chat_options.as_ref())
+            .await
+        {
             Ok(response) => response,
             Err(e) => {
                 // Log the raw error before adding context
@@ -184,13 +226,20 @@ impl GeminiClient {
             total_tokens: chat_response.usage.total_tokens.map(|t| t as u32),
         };
 
+        // Tool calls requested by the model (the text may be empty then)
+        let tool_calls: Vec<_> = chat_response.tool_calls().into_iter().cloned().collect();
+
         // Extract the response text
-        let generated_text = chat_response
-            .first_text()
-            .context("Failed to extract text from Gemini response")?;
+        let generated_text = if tool_calls.is_empty() {
+            chat_response
+                .first_text()
+                .context("Failed to extract text from Gemini response")?
+        } else {
+            chat_response.first_text().unwrap_or_default()
+        };
 
         // Check if the generated text is empty or just whitespace
-        if generated_text.trim().is_empty() {
+        if generated_text.trim().is_empty() && tool_calls.is_empty() {
             log_error("Generated text is empty");
             return Err(anyhow::anyhow!(
                 "No content was generated by the AI. The response was empty or contained only whitespace."
@@ -205,15 +254,17 @@ impl GeminiClient {
         Ok(AiResponse {
             content: generated_text.to_string(),
             usage,
+            tool_calls,
         })
     }
-}
 
-#[async_trait]
-impl AiProvider for GeminiClient {
-    async fn generate_content_with_chat_messages(
+    /// Send the request, moving on to the next API key on rate limits and overload
+    /// errors. With `on_text` the response is streamed.
+    async fn generate_with_key_fallback(
         &mut self,
         chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
+        mut on_text: Option<TextCallback<'_>>,
     ) -> Result<AiResponse> {
         log_debug(&format!(
             "Sending chat request to Gemini API with {} message(s)",
@@ -239,8 +290,12 @@ impl AiProvider for GeminiClient {
             match self
                 .try_chat_request_with_messages(
                     chat_messages.clone(),
+                    tools,
                     &current_key,
                     self.current_key_index,
+                    on_text
+                        .as_mut()
+                        .map(|on_text| &mut **on_text as TextCallback<'_>),
                 )
                 .await
             {
@@ -310,6 +365,11 @@ impl AiProvider for GeminiClient {
                             self.current_key_index + 1,
                             total_keys
                         );
+                        progress::report(Stage::KeyFallback {
+                            reason: error_type.to_string(),
+                            key: self.current_key_index + 1,
+                            keys: total_keys,
+                        });
                         log_info(&format!(
                             "Falling back to API key {}/{}",
                             self.current_key_index + 1,
@@ -335,6 +395,28 @@ impl AiProvider for GeminiClient {
             }
         }
     }
+}
+
+#[async_trait]
+impl AiProvider for GeminiClient {
+    async fn generate_content_with_chat_messages(
+        &mut self,
+        chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
+    ) -> Result<AiResponse> {
+        self.generate_with_key_fallback(chat_messages, tools, None)
+            .await
+    }
+
+    async fn generate_content_streaming(
+        &mut self,
+        chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
+        on_text: TextCallback<'_>,
+    ) -> Result<AiResponse> {
+        self.generate_with_key_fallback(chat_messages, tools, Some(on_text))
+            .await
+    }
 
     fn model_name(&self) -> &str {
         &self.model
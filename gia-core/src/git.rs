@@ -0,0 +1,228 @@
+//! Git integration for input sources.
+//!
+//! Provides working tree diffs, staged changes and commit logs as prompt content.
+//! Each source starts with a `--stat` summary for file-level context and is cut
+//! down file by file (or commit by commit) to stay within the size budget.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::constants::get_git_context_budget;
+use crate::logging::{log_debug, log_info};
+
+/// Run a git command in the current directory and return its stdout
+fn run_git(args: &[&str]) -> Result<String> {
+    log_debug(&format!("Running: git {}", args.join(" ")));
+
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .context("Failed to run git (is git installed and in PATH?)")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Split a user's revision range into git arguments. Options such as `--output=<file>`
+/// or `--ext-diff` are refused; `-N` (the last N commits) only where `allow_count` is set.
+fn range_args(range: &str, allow_count: bool) -> Result<Vec<&str>> {
+    range
+        .split_whitespace()
+        .map(|token| {
+            let is_count = token.strip_prefix('-').is_some_and(|count| {
+                !count.is_empty() && count.bytes().all(|b| b.is_ascii_digit())
+            });
+            if token.starts_with('-') && !(allow_count && is_count) {
+                anyhow::bail!(
+                    "Invalid revision range '{range}': options like '{token}' are not allowed"
+                );
+            }
+            Ok(token)
+        })
+        .collect()
+}
+
+/// Diff of the working tree (no range) or of a revision range like `HEAD~3` or `main..feature`
+pub fn git_diff(range: Option<&str>) -> Result<String> {
+    let args = match range {
+        Some(range) => range_args(range, false)?,
+        None => Vec::new(),
+    };
+    collect_diff(&args)
+}
+
+/// Diff of the changes staged for the next commit
+pub fn git_staged() -> Result<String> {
+    collect_diff(&["--cached"])
+}
+
+/// Output options for diffs. External diff drivers and textconv filters configured
+/// in the repository would run arbitrary commands, so they are turned off.
+const DIFF_OPTIONS: [&str; 3] = ["--no-color", "--no-ext-diff", "--no-textconv"];
+
+fn collect_diff(extra_args: &[&str]) -> Result<String> {
+    let mut stat_args = vec!["diff"];
+    stat_args.extend_from_slice(&DIFF_OPTIONS);
+    stat_args.push("--stat");
+    stat_args.extend_from_slice(extra_args);
+    let stat = run_git(&stat_args)?;
+
+    if stat.trim().is_empty() {
+        return Ok(String::new());
+    }
+
+    let mut diff_args = vec!["diff"];
+    diff_args.extend_from_slice(&DIFF_OPTIONS);
+    diff_args.extend_from_slice(extra_args);
+    let diff = run_git(&diff_args)?;
+
+    let budget = get_git_context_budget().saturating_sub(stat.len());
+    let diff = apply_budget(&diff, "diff --git ", budget, "file(s)");
+
+    Ok(format!("Files changed:\n{}\n{}", stat.trim_end(), diff))
+}
+
+/// Commit messages, stats and patches for a revision range like `main..feature` or `-5`
+pub fn git_log(range: &str) -> Result<String> {
+    let mut args = vec!["log"];
+    args.extend_from_slice(&DIFF_OPTIONS);
+    args.extend(["--stat", "--patch"]);
+    args.extend(range_args(range, true)?);
+    let log = run_git(&args)?;
+
+    Ok(apply_budget(
+        &log,
+        "commit ",
+        get_git_context_budget(),
+        "commit(s)",
+    ))
+}
+
+/// Path of `.git/COMMIT_EDITMSG` for the current repository (works for worktrees too)
+pub fn commit_editmsg_path() -> Result<PathBuf> {
+    let path = run_git(&["rev-parse", "--git-path", "COMMIT_EDITMSG"])
+        .context("Not inside a git repository")?;
+    Ok(PathBuf::from(path.trim()))
+}
+
+/// Keep whole chunks (files or commits) while they fit into the budget and list the omitted ones.
+/// A single chunk larger than the budget is truncated.
+fn apply_budget(text: &str, chunk_marker: &str, budget: usize, unit: &str) -> String {
+    if text.len() <= budget {
+        return text.to_string();
+    }
+
+    let mut chunks: Vec<&str> = Vec::new();
+    let mut chunk_start = 0;
+    for (offset, _) in text.match_indices(chunk_marker) {
+        if offset > 0 && text.as_bytes()[offset - 1] == b'\n' {
+            chunks.push(&text[chunk_start..offset]);
+            chunk_start = offset;
+        }
+    }
+    chunks.push(&text[chunk_start..]);
+
+    let mut result = String::new();
+    let mut omitted = Vec::new();
+    for chunk in chunks {
+        if omitted.is_empty() && result.len() + chunk.len() <= budget {
+            result.push_str(chunk);
+        } else if result.is_empty() {
+            let mut end = budget;
+            while !chunk.is_char_boundary(end) {
+                end -= 1;
+            }
+            result.push_str(&chunk[..end]);
+            result.push_str("\n[... truncated ...]\n");
+        } else {
+            omitted.push(chunk_title(chunk, chunk_marker));
+        }
+    }
+
+    if !omitted.is_empty() {
+        log_info(&format!(
+            "Git content exceeds budget of {budget} bytes, omitted {} {unit}",
+            omitted.len()
+        ));
+        result.push_str(&format!(
+            "\n[... {} more {unit} omitted to stay within the size budget of {budget} bytes: {}]\n",
+            omitted.len(),
+            omitted.join(", ")
+        ));
+    }
+
+    result
+}
+
+fn chunk_title(chunk: &str, chunk_marker: &str) -> String {
+    let first_line = chunk.lines().next().unwrap_or_default();
+    let title = first_line.strip_prefix(chunk_marker).unwrap_or(first_line);
+    // "a/src/main.rs b/src/main.rs" -> "src/main.rs"
+    title
+        .rsplit_once(" b/")
+        .map_or(title, |(_, path)| path)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/a.rs b/src/a.rs\n--- a/src/a.rs\n+++ b/src/a.rs\n+one\ndiff --git a/src/b.rs b/src/b.rs\n--- a/src/b.rs\n+++ b/src/b.rs\n+two\ndiff --git a/src/c.rs b/src/c.rs\n+three\n";
+
+    #[test]
+    fn test_apply_budget_within_limit() {
+        assert_eq!(apply_budget(DIFF, "diff --git ", 10_000, "file(s)"), DIFF);
+    }
+
+    #[test]
+    fn test_apply_budget_omits_whole_files() {
+        let result = apply_budget(DIFF, "diff --git ", 90, "file(s)");
+        assert!(result.contains("+one"));
+        assert!(!result.contains("+two"));
+        assert!(result.contains("2 more file(s) omitted"));
+        assert!(result.contains("src/b.rs, src/c.rs"));
+    }
+
+    #[test]
+    fn test_apply_budget_truncates_single_large_chunk() {
+        let result = apply_budget(DIFF, "diff --git ", 20, "file(s)");
+        assert!(result.starts_with("diff --git a/src/a.r"));
+        assert!(result.contains("[... truncated ...]"));
+        assert!(result.contains("2 more file(s) omitted"));
+    }
+
+    #[test]
+    fn test_range_args() {
+        assert_eq!(
+            range_args("main..feature", false).unwrap(),
+            vec!["main..feature"]
+        );
+        assert_eq!(range_args("-5 main", true).unwrap(), vec!["-5", "main"]);
+        assert!(range_args("-5", false).is_err());
+        assert!(range_args("HEAD --output=/tmp/x", false).is_err());
+        assert!(range_args("--ext-diff", true).is_err());
+        assert!(range_args("-", true).is_err());
+    }
+
+    #[test]
+    fn test_chunk_title() {
+        assert_eq!(
+            chunk_title("diff --git a/src/main.rs b/src/main.rs\n", "diff --git "),
+            "src/main.rs"
+        );
+        assert_eq!(
+            chunk_title("commit 1234abcd\nAuthor: someone\n", "commit "),
+            "1234abcd"
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result};
 use base64::Engine;
-use std::fs;
 use std::path::Path;
 
+use crate::archive::read_path_bytes;
 use crate::constants::MEDIA_EXTENSIONS;
 use crate::logging::log_info;
 
@@ -40,8 +40,8 @@ pub fn get_mime_type(file_path: &Path) -> Result<String> {
 pub fn read_media_as_base64(file_path: &str) -> Result<String> {
     log_info(&format!("Reading media file: {file_path}"));
 
-    let media_data =
-        fs::read(file_path).with_context(|| format!("Failed to read media file: {file_path}"))?;
+    let media_data = read_path_bytes(file_path)
+        .with_context(|| format!("Failed to read media file: {file_path}"))?;
 
     let base64_data = base64::engine::general_purpose::STANDARD.encode(&media_data);
 
@@ -0,0 +1,2345 @@
+use anyhow::{Context, Result};
+use chardetng::EncodingDetector;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::archive::{
+    forget_expanded_archives, is_archive_file, list_archive_members, path_size, read_path_bytes,
+    read_path_sample, split_virtual_path,
+};
+use crate::audio::record_audio;
+use crate::cli::{Config, ContentSource, OutputMode};
+use crate::clipboard::{has_clipboard_image, read_clipboard, write_clipboard};
+use crate::constants::{
+    MAX_NOTEBOOK_IMAGES, MEDIA_EXTENSIONS, NOTEBOOK_OUTPUT_LIMIT, get_max_input_file_size,
+    get_summary_threshold,
+};
+use crate::document::{extract_document_text, is_document_file, parse_csv, rows_to_markdown_table};
+use crate::git::{git_diff, git_log, git_staged};
+
+use crate::logging::{log_debug, log_info};
+use crate::provider::ProviderFactory;
+use crate::role::{LoadedDefinition, load_all_roles};
+use crate::selector::{FileSelector, apply_selector, parse_file_selector};
+use crate::template::TemplateContext;
+use crate::webpage::fetch_web_page;
+
+pub fn read_stdin() -> Result<String> {
+    log_debug("Reading from stdin");
+    let mut buffer = Vec::new();
+    io::stdin()
+        .read_to_end(&mut buffer)
+        .context("Failed to read from stdin")?;
+
+    let text = String::from_utf8_lossy(&buffer).to_string();
+    log_info(&format!("Read {} characters from stdin", text.len()));
+    Ok(text)
+}
+
+pub fn read_text_file(file_path: &str) -> Result<String> {
+    log_debug(&format!("Reading text file: {file_path}"));
+
+    // First try to read as bytes (works for archive members too)
+    let bytes =
+        read_path_bytes(file_path).with_context(|| format!("Failed to read file: {file_path}"))?;
+
+    // Try to decode as UTF-8 first
+    match String::from_utf8(bytes.clone()) {
+        Ok(content) => {
+            log_info(&format!(
+                "Read {} characters from file (UTF-8): {}",
+                content.len(),
+                file_path
+            ));
+            Ok(content)
+        }
+        Err(_) => {
+            // If UTF-8 fails, use encoding detection
+            let mut detector = EncodingDetector::new();
+            detector.feed(&bytes, true);
+            let encoding = detector.guess(None, true);
+
+            let (content, _, had_errors) = encoding.decode(&bytes);
+            if had_errors {
+                log_debug(&format!(
+                    "Encoding detection had errors for file: {file_path}"
+                ));
+            }
+
+            log_info(&format!(
+                "Read {} characters from file ({}): {}",
+                content.len(),
+                encoding.name(),
+                file_path
+            ));
+            Ok(content.into_owned())
+        }
+    }
+}
+
+/// How a file given with `-f` is sent to the model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileType {
+    Notebook,
+    Document,
+    Media,
+    Text,
+    Binary,
+}
+
+/// Check if a file is a media file based on its extension
+fn is_media_file_by_extension(path: &Path) -> bool {
+    if let Some(ext) = path.extension()
+        && let Some(ext_str) = ext.to_str()
+    {
+        return MEDIA_EXTENSIONS.contains(&ext_str.to_lowercase().as_str());
+    }
+    false
+}
+
+/// Detect whether a file is a media file, text file, or binary file
+pub fn detect_file_type(path: &Path) -> FileType {
+    // Jupyter notebooks are converted to cells instead of sent as raw JSON
+    if is_notebook_file(path) {
+        return FileType::Notebook;
+    }
+
+    // Documents (pdf, docx, xlsx, ...) are extracted locally to text
+    if is_document_file(path) {
+        return FileType::Document;
+    }
+
+    // Then check if it's a known media file by extension
+    if is_media_file_by_extension(path) {
+        return FileType::Media;
+    }
+
+    // Try to analyze file content to determine if it's text or binary
+    match analyze_file_content(path) {
+        Ok(true) => FileType::Text,
+        Ok(false) => FileType::Binary,
+        Err(_) => {
+            // If we can't read the file, assume it's binary to be safe
+            log_debug(&format!(
+                "Could not analyze file content, treating as binary: {}",
+                path.display()
+            ));
+            FileType::Binary
+        }
+    }
+}
+
+/// Analyze file content to determine if it's likely a text file
+fn analyze_file_content(path: &Path) -> Result<bool> {
+    // Read first 8KB for analysis (enough to detect most text files)
+    const SAMPLE_SIZE: usize = 8192;
+
+    let buffer = read_path_sample(&path.to_string_lossy(), SAMPLE_SIZE)?;
+
+    if buffer.is_empty() {
+        // Empty file, treat as text
+        return Ok(true);
+    }
+
+    Ok(is_text_content(&buffer))
+}
+
+/// Determine if the given bytes represent text content
+fn is_text_content(data: &[u8]) -> bool {
+    // Check for UTF-8/UTF-16 BOM (Byte Order Mark)
+    if has_text_bom(data) {
+        return true;
+    }
+
+    // Check for null bytes (strong indicator of binary content)
+    if data.contains(&0) {
+        return false;
+    }
+
+    // Try UTF-8 decoding first
+    if std::str::from_utf8(data).is_ok() {
+        return true;
+    }
+
+    // For non-UTF-8, be more conservative about binary detection
+    // Count different types of characters
+    let mut printable_count = 0;
+    let mut high_byte_count = 0;
+    let mut control_count = 0;
+
+    for &byte in data {
+        match byte {
+            // ASCII printable + common whitespace
+            0x09 | 0x0A | 0x0D | 0x20..=0x7E => printable_count += 1,
+            // High bytes (could be text encoding, but suspicious in sequences)
+            0x80..=0xFF => high_byte_count += 1,
+            // Control characters
+            _ => control_count += 1,
+        }
+    }
+
+    let total = data.len();
+    let printable_ratio = printable_count as f64 / total as f64;
+    let high_byte_ratio = high_byte_count as f64 / total as f64;
+
+    // If mostly ASCII printable, it's text
+    if printable_ratio > 0.8 {
+        return true;
+    }
+
+    // If high ratio of high bytes (>50%), likely binary
+    if high_byte_ratio > 0.5 {
+        return false;
+    }
+
+    // For mixed content, need decent printable ratio and not too many control chars
+    printable_ratio > 0.6 && (control_count as f64 / total as f64) < 0.1
+}
+
+/// Check for UTF-8 or UTF-16 Byte Order Mark
+fn has_text_bom(data: &[u8]) -> bool {
+    if data.len() >= 3 {
+        // UTF-8 BOM: EF BB BF
+        if data[0] == 0xEF && data[1] == 0xBB && data[2] == 0xBF {
+            return true;
+        }
+    }
+
+    if data.len() >= 2 {
+        // UTF-16 BOM: FF FE or FE FF
+        if (data[0] == 0xFF && data[1] == 0xFE) || (data[0] == 0xFE && data[1] == 0xFF) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Recursively collect all regular files from a directory or return the single file if it's not a directory
+pub fn collect_files_recursive(path: &str) -> Result<Vec<String>> {
+    // A single archive member addressed directly (bundle.zip!/logs/app.log)
+    if let Some((archive_path, _)) = split_virtual_path(path)
+        && Path::new(archive_path).is_file()
+    {
+        return Ok(vec![path.to_string()]);
+    }
+
+    let path_obj = Path::new(path);
+
+    if !path_obj.exists() {
+        return Err(anyhow::anyhow!("Path does not exist: {}", path));
+    }
+
+    if path_obj.is_file() {
+        // Archives are expanded into their members, like directories
+        if is_archive_file(path_obj) {
+            return list_archive_members(path);
+        }
+
+        // If it's a file, return it as-is
+        return Ok(vec![path.to_string()]);
+    }
+
+    if path_obj.is_dir() {
+        // If it's a directory, recursively collect all files
+        let mut files = Vec::new();
+        collect_files_from_dir(path_obj, &mut files)?;
+        files.sort(); // Sort for consistent ordering
+        return Ok(files);
+    }
+
+    Err(anyhow::anyhow!(
+        "Path is neither a file nor directory: {}",
+        path
+    ))
+}
+
+/// Helper function to recursively collect files from a directory
+fn collect_files_from_dir(dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+
+    for entry in entries {
+        let entry = entry
+            .with_context(|| format!("Failed to read directory entry in: {}", dir.display()))?;
+        let path = entry.path();
+
+        if path.is_file() {
+            // Add the file to our collection
+            if let Some(path_str) = path.to_str() {
+                if is_archive_file(&path) {
+                    match list_archive_members(path_str) {
+                        Ok(members) => {
+                            files.extend(members);
+                            continue;
+                        }
+                        Err(e) => log_debug(&format!(
+                            "Could not read archive, treating as regular file: {path_str}: {e}"
+                        )),
+                    }
+                }
+                files.push(path_str.to_string());
+            } else {
+                log_debug(&format!(
+                    "Skipping file with invalid UTF-8 path: {:?}",
+                    path
+                ));
+            }
+        } else if path.is_dir() {
+            // Recursively process subdirectories
+            collect_files_from_dir(&path, files)?;
+        }
+        // Skip symlinks, device files, etc.
+    }
+
+    Ok(())
+}
+
+/// Extensions of structured files that are summarised when they exceed the summary threshold
+const STRUCTURED_EXTENSIONS: &[&str] = &["json", "jsonl", "ndjson", "csv", "tsv", "log"];
+
+/// Sampled JSON in a summary is truncated to this many characters
+const SUMMARY_SAMPLE_LIMIT: usize = 20_000;
+
+/// Jupyter notebook converted to markdown cells, plus images from cell outputs
+struct Notebook {
+    markdown: String,
+    images: Vec<(String, String, String)>, // (label, mime_type, base64 data)
+}
+
+fn is_notebook_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Notebook `source`/`text` fields are either a string or a list of lines
+fn notebook_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(lines)) => {
+            lines.iter().filter_map(|line| line.as_str()).collect()
+        }
+        _ => String::new(),
+    }
+}
+
+/// Keep the beginning and end of a long text, marking how much was cut out
+fn truncate_middle(text: &str, limit: usize) -> String {
+    let char_count = text.chars().count();
+    if char_count <= limit {
+        return text.to_string();
+    }
+
+    let head: String = text.chars().take(limit * 2 / 3).collect();
+    let tail: String = text.chars().skip(char_count - limit / 3).collect();
+    format!(
+        "{}\n[... {} characters truncated ...]\n{}",
+        head.trim_end(),
+        char_count - limit,
+        tail.trim_start()
+    )
+}
+
+/// Convert a Jupyter notebook into markdown and code cells with truncated outputs.
+/// Images in cell outputs (PNG, JPEG) are returned separately.
+fn read_notebook(file_path: &str) -> Result<Notebook> {
+    let content = read_text_file(file_path)?;
+    let notebook: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid notebook JSON: {file_path}"))?;
+
+    let cells = notebook
+        .get("cells")
+        .and_then(|cells| cells.as_array())
+        .with_context(|| format!("Notebook has no cells: {file_path}"))?;
+
+    let language = notebook
+        .pointer("/metadata/kernelspec/language")
+        .or_else(|| notebook.pointer("/metadata/language_info/name"))
+        .and_then(|language| language.as_str())
+        .unwrap_or("python");
+
+    let ansi_escape = regex::Regex::new(r"\x1b\[[0-9;]*[A-Za-z]").expect("valid regex");
+
+    let mut markdown = String::new();
+    let mut images = Vec::new();
+
+    for (index, cell) in cells.iter().enumerate() {
+        let number = index + 1;
+        let source = notebook_text(cell.get("source"));
+        let cell_type = cell
+            .get("cell_type")
+            .and_then(|cell_type| cell_type.as_str())
+            .unwrap_or("raw");
+
+        if cell_type != "code" {
+            if !source.trim().is_empty() {
+                markdown.push_str(&format!(
+                    "**Cell {number} ({cell_type})**\n\n{}\n\n",
+                    source.trim_end()
+                ));
+            }
+            continue;
+        }
+
+        markdown.push_str(&format!(
+            "**Cell {number} (code)**\n\n```{language}\n{}\n```\n\n",
+            source.trim_end()
+        ));
+
+        let mut output_text = String::new();
+        let outputs = cell
+            .get("outputs")
+            .and_then(|outputs| outputs.as_array())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for output in outputs {
+            match output.get("output_type").and_then(|t| t.as_str()) {
+                Some("stream") => output_text.push_str(&notebook_text(output.get("text"))),
+                Some("execute_result" | "display_data") => {
+                    let data = output.get("data");
+                    let image = ["image/png", "image/jpeg"]
+                        .into_iter()
+                        .find_map(|mime_type| {
+                            data.and_then(|data| data.get(mime_type))
+                                .map(|image| (mime_type, notebook_text(Some(image))))
+                        });
+
+                    if let Some((mime_type, base64_data)) = image {
+                        if images.len() < MAX_NOTEBOOK_IMAGES {
+                            let label =
+                                format!("{file_path} (cell {number}, image {})", images.len() + 1);
+                            output_text.push_str(&format!("[Image: {label}]\n"));
+                            images.push((
+                                label,
+                                mime_type.to_string(),
+                                base64_data.split_whitespace().collect(),
+                            ));
+                        } else {
+                            output_text.push_str("[Image omitted]\n");
+                        }
+                    } else {
+                        let text = notebook_text(data.and_then(|data| data.get("text/plain")));
+                        output_text.push_str(&text);
+                        if !text.ends_with('\n') {
+                            output_text.push('\n');
+                        }
+                    }
+                }
+                Some("error") => {
+                    let traceback: Vec<String> = output
+                        .get("traceback")
+                        .and_then(|traceback| traceback.as_array())
+                        .map(Vec::as_slice)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter_map(|line| line.as_str())
+                        .map(|line| ansi_escape.replace_all(line, "").into_owned())
+                        .collect();
+                    if traceback.is_empty() {
+                        output_text.push_str(&format!(
+                            "{}: {}\n",
+                            output
+                                .get("ename")
+                                .and_then(|e| e.as_str())
+                                .unwrap_or("Error"),
+                            output
+                                .get("evalue")
+                                .and_then(|e| e.as_str())
+                                .unwrap_or_default()
+                        ));
+                    } else {
+                        output_text.push_str(&traceback.join("\n"));
+                        output_text.push('\n');
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !output_text.trim().is_empty() {
+            markdown.push_str(&format!(
+                "Output:\n\n```\n{}\n```\n\n",
+                truncate_middle(output_text.trim_end(), NOTEBOOK_OUTPUT_LIMIT)
+            ));
+        }
+    }
+
+    log_info(&format!(
+        "Converted notebook with {} cell(s) and {} image(s): {file_path}",
+        cells.len(),
+        images.len()
+    ));
+
+    Ok(Notebook {
+        markdown: markdown.trim_end().to_string(),
+        images,
+    })
+}
+
+/// Summarise large JSON, CSV and log files instead of sending them verbatim.
+/// Returns `None` for other formats and for files within the summary threshold.
+fn summarize_structured_file(file_path: &str) -> Option<String> {
+    let extension = Path::new(file_path).extension()?.to_str()?.to_lowercase();
+    if !STRUCTURED_EXTENSIONS.contains(&extension.as_str()) {
+        return None;
+    }
+
+    let size = path_size(file_path).ok()?;
+    let threshold = get_summary_threshold();
+    if size <= threshold {
+        return None;
+    }
+
+    let content = read_text_file(file_path).ok()?;
+    log_info(&format!(
+        "Summarising large {extension} file ({size} > {threshold} bytes): {file_path}"
+    ));
+
+    Some(match extension.as_str() {
+        "json" => summarize_json(&content, size).unwrap_or_else(|| summarize_log(&content, size)),
+        "jsonl" | "ndjson" => summarize_json_lines(&content, size),
+        "csv" | "tsv" => summarize_csv(&content, size),
+        _ => summarize_log(&content, size),
+    })
+}
+
+fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
+
+/// Describe the structure of a JSON value as one `path: type` line per field.
+/// Array items are merged so that each field is listed once.
+fn describe_json_schema(
+    value: &serde_json::Value,
+    path: &str,
+    depth: usize,
+    out: &mut Vec<String>,
+) {
+    const MAX_SCHEMA_LINES: usize = 200;
+    const MAX_SCHEMA_DEPTH: usize = 8;
+    const SAMPLED_ITEMS: usize = 100;
+
+    if out.len() >= MAX_SCHEMA_LINES || depth > MAX_SCHEMA_DEPTH {
+        return;
+    }
+
+    match value {
+        serde_json::Value::Object(map) => {
+            out.push(format!("{path}: object ({} keys)", map.len()));
+            for (key, child) in map {
+                describe_json_schema(child, &format!("{path}.{key}"), depth + 1, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            let mut types: Vec<&str> = Vec::new();
+            for item in items.iter().take(SAMPLED_ITEMS) {
+                let type_name = json_type_name(item);
+                if !types.contains(&type_name) {
+                    types.push(type_name);
+                }
+            }
+            if types.is_empty() {
+                out.push(format!("{path}: array[0]"));
+                return;
+            }
+            out.push(format!(
+                "{path}: array[{}] of {}",
+                items.len(),
+                types.join(" | ")
+            ));
+
+            // Merge object items: first non-null value per key
+            let mut merged = serde_json::Map::new();
+            for item in items.iter().take(SAMPLED_ITEMS) {
+                if let serde_json::Value::Object(map) = item {
+                    for (key, child) in map {
+                        let entry = merged.entry(key.clone()).or_insert(serde_json::Value::Null);
+                        if entry.is_null() {
+                            *entry = child.clone();
+                        }
+                    }
+                }
+            }
+            for (key, child) in &merged {
+                describe_json_schema(child, &format!("{path}[].{key}"), depth + 1, out);
+            }
+
+            if let Some(nested) = items.iter().find(|item| item.is_array()) {
+                describe_json_schema(nested, &format!("{path}[]"), depth + 1, out);
+            }
+        }
+        scalar => out.push(format!("{path}: {}", json_type_name(scalar))),
+    }
+}
+
+/// Shrink a JSON value for display: long arrays keep their first 3 and last 2 items,
+/// long strings are shortened
+fn sample_json(value: &serde_json::Value, depth: usize) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        _ if depth > 10 => Value::String("...".to_string()),
+        Value::Array(items) if items.len() > 5 => {
+            let mut sampled: Vec<Value> = items[..3]
+                .iter()
+                .map(|v| sample_json(v, depth + 1))
+                .collect();
+            sampled.push(Value::String(format!(
+                "... {} more items ...",
+                items.len() - 5
+            )));
+            sampled.extend(
+                items[items.len() - 2..]
+                    .iter()
+                    .map(|v| sample_json(v, depth + 1)),
+            );
+            Value::Array(sampled)
+        }
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| sample_json(v, depth + 1)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, child)| (key.clone(), sample_json(child, depth + 1)))
+                .collect(),
+        ),
+        Value::String(text) if text.chars().count() > 200 => {
+            Value::String(format!("{}...", text.chars().take(200).collect::<String>()))
+        }
+        other => other.clone(),
+    }
+}
+
+fn summarize_json(content: &str, size: u64) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+
+    let mut schema = Vec::new();
+    describe_json_schema(&value, "$", 0, &mut schema);
+    let sample = serde_json::to_string_pretty(&sample_json(&value, 0)).ok()?;
+
+    Some(format!(
+        "[Summary of large JSON file ({size} bytes): schema and sampled values; long arrays show their first 3 and last 2 items]\n\n## Schema\n\n```\n{}\n```\n\n## Sample\n\n```json\n{}\n```\n",
+        schema.join("\n"),
+        truncate_middle(&sample, SUMMARY_SAMPLE_LIMIT)
+    ))
+}
+
+fn summarize_json_lines(content: &str, size: u64) -> String {
+    let lines: Vec<&str> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let records: Vec<serde_json::Value> = lines
+        .iter()
+        .take(100)
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+
+    let mut schema = Vec::new();
+    describe_json_schema(&serde_json::Value::Array(records), "$", 0, &mut schema);
+
+    format!(
+        "[Summary of large JSON Lines file ({size} bytes, {} records): schema of the first 100 records plus first and last records]\n\n## Schema\n\n```\n{}\n```\n\n{}",
+        lines.len(),
+        schema.join("\n"),
+        head_tail_lines(&lines, 5, 3)
+    )
+}
+
+fn summarize_csv(content: &str, size: u64) -> String {
+    let rows: Vec<Vec<String>> = parse_csv(content)
+        .into_iter()
+        .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+        .collect();
+
+    let Some((header, data)) = rows.split_first() else {
+        return summarize_log(content, size);
+    };
+
+    let mut columns = vec![vec![
+        "Column".to_string(),
+        "Type".to_string(),
+        "Empty".to_string(),
+        "Min".to_string(),
+        "Max".to_string(),
+        "Example".to_string(),
+    ]];
+
+    for (index, name) in header.iter().enumerate() {
+        let values: Vec<&str> = data
+            .iter()
+            .map(|row| row.get(index).map_or("", |cell| cell.trim()))
+            .collect();
+        let present: Vec<&str> = values.iter().copied().filter(|v| !v.is_empty()).collect();
+        let numbers: Vec<f64> = present
+            .iter()
+            .filter_map(|v| v.parse::<f64>().ok())
+            .collect();
+
+        let column_type = if present.is_empty() {
+            "empty"
+        } else if numbers.len() == present.len() {
+            if present.iter().all(|v| v.parse::<i64>().is_ok()) {
+                "integer"
+            } else {
+                "number"
+            }
+        } else if present
+            .iter()
+            .all(|v| matches!(v.to_lowercase().as_str(), "true" | "false"))
+        {
+            "boolean"
+        } else {
+            "text"
+        };
+
+        let (min, max) = if numbers.len() == present.len() && !numbers.is_empty() {
+            let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            (min.to_string(), max.to_string())
+        } else {
+            (String::new(), String::new())
+        };
+
+        let example: String = present
+            .first()
+            .map_or(String::new(), |v| v.chars().take(50).collect());
+
+        columns.push(vec![
+            name.clone(),
+            column_type.to_string(),
+            (values.len() - present.len()).to_string(),
+            min,
+            max,
+            example,
+        ]);
+    }
+
+    let mut summary = format!(
+        "[Summary of large CSV file ({size} bytes, {} rows, {} columns): column overview plus first and last rows]\n\n## Columns\n\n{}\n",
+        data.len(),
+        header.len(),
+        rows_to_markdown_table(&columns)
+    );
+
+    const HEAD_ROWS: usize = 10;
+    const TAIL_ROWS: usize = 5;
+
+    if data.len() <= HEAD_ROWS + TAIL_ROWS {
+        summary.push_str(&format!("## Rows\n\n{}", rows_to_markdown_table(&rows)));
+    } else {
+        let mut head = vec![header.clone()];
+        head.extend_from_slice(&data[..HEAD_ROWS]);
+        let mut tail = vec![header.clone()];
+        tail.extend_from_slice(&data[data.len() - TAIL_ROWS..]);
+
+        summary.push_str(&format!(
+            "## First {HEAD_ROWS} rows\n\n{}\n[... {} rows omitted ...]\n\n## Last {TAIL_ROWS} rows\n\n{}",
+            rows_to_markdown_table(&head),
+            data.len() - HEAD_ROWS - TAIL_ROWS,
+            rows_to_markdown_table(&tail)
+        ));
+    }
+
+    summary
+}
+
+fn summarize_log(content: &str, size: u64) -> String {
+    const HEAD_LINES: usize = 40;
+    const TAIL_LINES: usize = 80;
+    const MAX_PROBLEM_LINES: usize = 40;
+
+    let lines: Vec<&str> = content.lines().collect();
+    let is_error = |line: &str| {
+        let upper = line.to_uppercase();
+        ["ERROR", "FATAL", "PANIC", "EXCEPTION", "TRACEBACK"]
+            .iter()
+            .any(|level| upper.contains(level))
+    };
+    let is_warning = |line: &str| line.to_uppercase().contains("WARN");
+
+    let error_count = lines.iter().filter(|line| is_error(line)).count();
+    let warning_count = lines.iter().filter(|line| is_warning(line)).count();
+
+    let mut summary = format!(
+        "[Summary of large log file ({size} bytes, {} lines, {error_count} error lines, {warning_count} warning lines): first and last lines plus errors and warnings in between]\n\n",
+        lines.len()
+    );
+
+    if lines.len() <= HEAD_LINES + TAIL_LINES {
+        summary.push_str(&head_tail_lines(&lines, HEAD_LINES, TAIL_LINES));
+        return summary;
+    }
+
+    // Errors and warnings from the part that is not shown verbatim
+    let middle = HEAD_LINES..lines.len() - TAIL_LINES;
+    let problems: Vec<String> = lines[middle.clone()]
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| is_error(line) || is_warning(line))
+        .map(|(offset, line)| format!("{}: {}", middle.start + offset + 1, truncate_line(line)))
+        .collect();
+
+    summary.push_str(&format!(
+        "## First {HEAD_LINES} lines\n\n```\n{}\n```\n\n",
+        lines[..HEAD_LINES]
+            .iter()
+            .map(|line| truncate_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ));
+
+    if !problems.is_empty() {
+        let shown = problems.len().min(MAX_PROBLEM_LINES);
+        summary.push_str(&format!(
+            "## Errors and warnings in lines {}-{} ({shown} of {})\n\n```\n{}\n```\n\n",
+            middle.start + 1,
+            middle.end,
+            problems.len(),
+            problems[..shown].join("\n")
+        ));
+    }
+
+    summary.push_str(&format!(
+        "## Last {TAIL_LINES} lines\n\n```\n{}\n```\n",
+        lines[lines.len() - TAIL_LINES..]
+            .iter()
+            .map(|line| truncate_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    ));
+
+    summary
+}
+
+/// First and last lines of a text as fenced blocks
+fn head_tail_lines(lines: &[&str], head: usize, tail: usize) -> String {
+    let fence = |lines: &[&str]| {
+        lines
+            .iter()
+            .map(|line| truncate_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    if lines.len() <= head + tail {
+        return format!("```\n{}\n```\n", fence(lines));
+    }
+
+    format!(
+        "## First {head} lines\n\n```\n{}\n```\n\n[... {} lines omitted ...]\n\n## Last {tail} lines\n\n```\n{}\n```\n",
+        fence(&lines[..head]),
+        lines.len() - head - tail,
+        fence(&lines[lines.len() - tail..])
+    )
+}
+
+fn truncate_line(line: &str) -> String {
+    const MAX_LINE_LENGTH: usize = 500;
+    if line.chars().count() <= MAX_LINE_LENGTH {
+        line.to_string()
+    } else {
+        format!(
+            "{}...",
+            line.chars().take(MAX_LINE_LENGTH).collect::<String>()
+        )
+    }
+}
+
+/// Add a text file to the ordered content, applying an optional line range or symbol selector
+fn push_text_file(
+    ordered_content: &mut Vec<ContentSource>,
+    file_path: String,
+    content: String,
+    selector: Option<&FileSelector>,
+) {
+    let Some(selector) = selector else {
+        ordered_content.push(ContentSource::TextFile(file_path, content, None));
+        return;
+    };
+
+    match apply_selector(&file_path, &content, selector) {
+        Ok((selected, selection)) => {
+            log_info(&format!("Selected {selection} from {file_path}"));
+            ordered_content.push(ContentSource::TextFile(
+                file_path,
+                selected,
+                Some(selection),
+            ));
+        }
+        Err(e) => {
+            log_debug(&format!("Failed to apply selector to {file_path}: {e}"));
+            eprintln!("Warning: {e}");
+        }
+    }
+}
+
+pub fn get_input_text(config: &mut Config, prompt_override: Option<&str>) -> Result<()> {
+    // Clear any existing ordered content
+    config.ordered_content.clear();
+
+    // Template variables ({{date}}, {{file:path}}, --var ...) are rendered before content is
+    // added, so the rendered text is what gets sent and saved
+    let template = TemplateContext::from_config(config);
+
+    // 0. Role/task definitions (placed first)
+    if !config.roles.is_empty() {
+        log_info(&format!("Loading {} role(s)/task(s)", config.roles.len()));
+        match load_all_roles(&config.roles) {
+            Ok(items) => {
+                for LoadedDefinition {
+                    name,
+                    content,
+                    is_task,
+                    confined_to,
+                } in items
+                {
+                    let item_type = if is_task { "task" } else { "role" };
+                    let source = format!("{item_type} '{name}'");
+                    let content = match &confined_to {
+                        Some(project_dir) => template
+                            .for_definition()
+                            .confined_to(project_dir)
+                            .render(&content, &source)?,
+                        None => template.for_definition().render(&content, &source)?,
+                    };
+                    log_info(&format!("Adding {item_type} to ordered content: {name}"));
+                    config
+                        .ordered_content
+                        .push(ContentSource::RoleDefinition(name, content, is_task));
+                }
+            }
+            Err(e) => {
+                log_debug(&format!("Failed to load roles/tasks: {e}"));
+                eprintln!("Warning: Failed to load roles/tasks: {e}");
+            }
+        }
+    }
+
+    // 1. Command line prompt
+    let prompt_to_use = template.render(prompt_override.unwrap_or(&config.prompt), "prompt")?;
+    let prompt_to_use = prompt_to_use.as_str();
+    if !prompt_to_use.is_empty() {
+        log_info("Adding command line prompt to ordered content");
+        config
+            .ordered_content
+            .push(ContentSource::CommandLinePrompt(prompt_to_use.to_string()));
+    }
+
+    // 2. Audio recording when present
+    if config.record_audio {
+        log_info("Audio recording requested");
+        match record_audio(config.audio_device.as_deref()) {
+            Ok(audio_path) => {
+                log_info(&format!("Audio recorded to: {audio_path}"));
+                config
+                    .ordered_content
+                    .push(ContentSource::AudioRecording(audio_path));
+
+                // If no command line prompt provided, use default audio prompt
+                if prompt_to_use.is_empty() {
+                    let default_audio_prompt = "Your instructions are in prompt.opus";
+                    log_info(&format!(
+                        "Using default audio prompt: {default_audio_prompt}"
+                    ));
+                    config.ordered_content.insert(
+                        0,
+                        ContentSource::CommandLinePrompt(default_audio_prompt.to_string()),
+                    );
+                }
+            }
+            Err(e) => {
+                log_debug(&format!("Audio recording failed: {e}"));
+
+                // Clear clipboard if output mode is clipboard
+                if matches!(config.output_mode, OutputMode::Clipboard) {
+                    log_info("Clearing clipboard due to cancelled audio recording");
+                    if let Err(clear_err) = write_clipboard("") {
+                        log_debug(&format!("Failed to clear clipboard: {clear_err}"));
+                    }
+                }
+
+                // Return error to stop execution
+                return Err(e);
+            }
+        }
+    }
+
+    // 3. Clipboard text when present
+    if config.use_clipboard_input {
+        log_info("Checking clipboard content");
+
+        match has_clipboard_image() {
+            Ok(true) => {
+                log_info("Found image in clipboard - adding to ordered content");
+                config.ordered_content.push(ContentSource::ClipboardImage);
+            }
+            Ok(false) => {
+                log_info("No image in clipboard, checking for text");
+                match read_clipboard() {
+                    Ok(clipboard_input) => {
+                        if !clipboard_input.trim().is_empty() {
+                            log_info("Adding clipboard text to ordered content");
+                            config
+                                .ordered_content
+                                .push(ContentSource::ClipboardText(clipboard_input));
+                        }
+                    }
+                    Err(e) => {
+                        log_debug(&format!("Failed to read clipboard text: {e}"));
+                    }
+                }
+            }
+            Err(e) => {
+                log_debug(&format!("Failed to check clipboard for image: {e}"));
+                // Fallback to trying text
+                match read_clipboard() {
+                    Ok(clipboard_input) => {
+                        if !clipboard_input.trim().is_empty() {
+                            log_info("Adding clipboard text to ordered content (fallback)");
+                            config
+                                .ordered_content
+                                .push(ContentSource::ClipboardText(clipboard_input));
+                        }
+                    }
+                    Err(_) => {
+                        log_debug("Failed to read clipboard text in fallback");
+                    }
+                }
+            }
+        }
+    }
+
+    // 4. Stdin text if present (not in server modes, where stdin carries the requests)
+    if config.read_stdin && atty::isnt(atty::Stream::Stdin) {
+        log_info("Stdin data available - adding to ordered content");
+        let stdin_input = read_stdin()?;
+        if !stdin_input.trim().is_empty() {
+            config
+                .ordered_content
+                .push(ContentSource::StdinText(stdin_input));
+        }
+    } else {
+        log_debug("No stdin data available (terminal input or server mode)");
+    }
+
+    // 5. Git sources (diff, staged changes, log)
+    if let Some(range) = &config.git_diff {
+        let range = (!range.is_empty()).then_some(range.as_str());
+        let description = range.map_or("working tree".to_string(), str::to_string);
+        let diff = git_diff(range)?;
+        if diff.trim().is_empty() {
+            eprintln!("Warning: No changes found for git diff ({description})");
+        } else {
+            log_info(&format!("Adding git diff ({description})"));
+            config
+                .ordered_content
+                .push(ContentSource::GitDiff(description, diff));
+        }
+    }
+
+    if config.git_staged {
+        let diff = git_staged()?;
+        if diff.trim().is_empty() {
+            eprintln!("Warning: No staged changes found (use 'git add' first)");
+        } else {
+            log_info("Adding staged changes");
+            config
+                .ordered_content
+                .push(ContentSource::GitDiff("staged changes".to_string(), diff));
+        }
+    }
+
+    if let Some(range) = &config.git_log {
+        let log = git_log(range)?;
+        if log.trim().is_empty() {
+            eprintln!("Warning: No commits found for git log ({range})");
+        } else {
+            log_info(&format!("Adding git log ({range})"));
+            config
+                .ordered_content
+                .push(ContentSource::GitLog(range.clone(), log));
+        }
+    }
+
+    // 6. Web pages coming with -u option (readable content plus small images)
+    for url in &config.urls {
+        let page = fetch_web_page(url, config.remote_request)?;
+        if page.markdown.trim().is_empty() && page.images.is_empty() {
+            eprintln!("Warning: No readable content found at {url}");
+            continue;
+        }
+
+        log_info(&format!("Adding web page: {url}"));
+        config
+            .ordered_content
+            .push(ContentSource::WebPage(page.url, page.markdown));
+        for image in page.images {
+            config.ordered_content.push(ContentSource::EmbeddedImage(
+                image.source,
+                image.mime_type,
+                image.data,
+            ));
+        }
+    }
+
+    // 7. All files coming with -f option (with recursive directory support)
+    if !config.text_files.is_empty() {
+        log_info(&format!(
+            "Processing {} file path(s)",
+            config.text_files.len()
+        ));
+
+        for file_arg in &config.text_files {
+            // Split off optional selectors (path:120-180 or path#symbol)
+            let (file_path, mut selector) = match parse_file_selector(file_arg) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("Warning: Failed to process path '{file_arg}': {e}");
+                    continue;
+                }
+            };
+            let file_path = &file_path;
+
+            // Collect all files (handles both files and directories recursively)
+            match collect_files_recursive(file_path) {
+                Ok(collected_files) => {
+                    log_info(&format!(
+                        "Collected {} file(s) from path: {}",
+                        collected_files.len(),
+                        file_path
+                    ));
+
+                    if selector.is_some() && collected_files.len() > 1 {
+                        eprintln!(
+                            "Warning: Ignoring selector in '{file_arg}' (only supported for single files)"
+                        );
+                        selector = None;
+                    }
+
+                    let max_file_size = get_max_input_file_size();
+
+                    for actual_file_path in collected_files {
+                        let path = Path::new(&actual_file_path);
+
+                        // Enforce the size limit for files and archive members alike
+                        match path_size(&actual_file_path) {
+                            Ok(size) if size > max_file_size => {
+                                log_info(&format!(
+                                    "Skipping file over size limit ({size} > {max_file_size} bytes): {actual_file_path}"
+                                ));
+                                eprintln!(
+                                    "Warning: Skipping file '{actual_file_path}' ({size} bytes exceeds limit of {max_file_size} bytes, see GIA_MAX_FILE_SIZE)"
+                                );
+                                continue;
+                            }
+                            Ok(_) => {}
+                            Err(e) => {
+                                eprintln!("Warning: Failed to read file '{actual_file_path}': {e}");
+                                continue;
+                            }
+                        }
+
+                        // Large JSON/CSV/log files are summarised instead of sent verbatim
+                        if selector.is_none()
+                            && let Some(summary) = summarize_structured_file(&actual_file_path)
+                        {
+                            config.ordered_content.push(ContentSource::TextFile(
+                                actual_file_path,
+                                summary,
+                                Some("summary".to_string()),
+                            ));
+                            continue;
+                        }
+
+                        match detect_file_type(path) {
+                            FileType::Notebook => match read_notebook(&actual_file_path) {
+                                Ok(notebook) => {
+                                    push_text_file(
+                                        &mut config.ordered_content,
+                                        actual_file_path,
+                                        notebook.markdown,
+                                        selector.as_ref(),
+                                    );
+                                    for (label, mime_type, data) in notebook.images {
+                                        config.ordered_content.push(ContentSource::EmbeddedImage(
+                                            label, mime_type, data,
+                                        ));
+                                    }
+                                }
+                                Err(e) => {
+                                    log_debug(&format!(
+                                        "Failed to read notebook {actual_file_path}: {e}"
+                                    ));
+                                    eprintln!(
+                                        "Warning: Failed to read notebook '{actual_file_path}': {e}"
+                                    );
+                                }
+                            },
+                            FileType::Document
+                                if config.native_documents
+                                    && is_media_file_by_extension(path)
+                                    && ProviderFactory::supports_native_documents(
+                                        &config.model,
+                                    ) =>
+                            {
+                                log_info(&format!(
+                                    "Uploading document natively: {actual_file_path}"
+                                ));
+                                config
+                                    .ordered_content
+                                    .push(ContentSource::ImageFile(actual_file_path));
+                            }
+                            FileType::Document => match extract_document_text(path) {
+                                Ok(document_text) => {
+                                    if !document_text.trim().is_empty() {
+                                        log_info(&format!(
+                                            "Extracted document text, adding to ordered content: {actual_file_path}"
+                                        ));
+                                        push_text_file(
+                                            &mut config.ordered_content,
+                                            actual_file_path,
+                                            document_text,
+                                            selector.as_ref(),
+                                        );
+                                    } else {
+                                        eprintln!(
+                                            "Warning: No text found in document '{actual_file_path}'"
+                                        );
+                                    }
+                                }
+                                Err(e) => {
+                                    log_debug(&format!(
+                                        "Failed to extract document {actual_file_path}: {e}"
+                                    ));
+                                    eprintln!(
+                                        "Warning: Failed to extract text from document '{actual_file_path}': {e}"
+                                    );
+                                }
+                            },
+                            FileType::Media => {
+                                // Handle as media file
+                                log_info(&format!(
+                                    "Auto-detected media file, adding as image: {actual_file_path}"
+                                ));
+                                config
+                                    .ordered_content
+                                    .push(ContentSource::ImageFile(actual_file_path));
+                            }
+                            FileType::Text => {
+                                // Handle as text file
+                                match read_text_file(&actual_file_path) {
+                                    Ok(file_content) => {
+                                        if !file_content.trim().is_empty() {
+                                            log_info(&format!(
+                                                "Auto-detected text file, adding to ordered content: {actual_file_path}"
+                                            ));
+                                            push_text_file(
+                                                &mut config.ordered_content,
+                                                actual_file_path,
+                                                file_content,
+                                                selector.as_ref(),
+                                            );
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log_debug(&format!(
+                                            "Failed to read text file {actual_file_path}: {e}"
+                                        ));
+                                        eprintln!(
+                                            "Warning: Failed to read text file '{actual_file_path}': {e}"
+                                        );
+                                    }
+                                }
+                            }
+                            FileType::Binary => {
+                                // Skip binary files with a warning
+                                log_info(&format!(
+                                    "Skipping binary file (not media or text): {actual_file_path}"
+                                ));
+                                eprintln!(
+                                    "Warning: Skipping binary file '{}' (not a known media type or text file)",
+                                    actual_file_path
+                                );
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    log_debug(&format!(
+                        "Failed to collect files from path {file_path}: {e}"
+                    ));
+                    eprintln!("Warning: Failed to process path '{file_path}': {e}");
+                }
+            }
+        }
+
+        // Archive members are read now (media members are read again when sent)
+        forget_expanded_archives();
+    }
+
+    // Media files are now handled automatically in the -f option processing above
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::OutputMode;
+    use std::fs;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_read_text_file_success() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let content = "Hello, world!\nThis is a test file.";
+        fs::write(temp_file.path(), content).unwrap();
+
+        let result = read_text_file(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(result, content);
+    }
+
+    #[test]
+    fn test_read_text_file_nonexistent() {
+        let result = read_text_file("nonexistent_file.txt");
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Failed to read file")
+        );
+    }
+
+    #[test]
+    fn test_get_input_text_with_files() {
+        let temp_file1 = NamedTempFile::new().unwrap();
+        let temp_file2 = NamedTempFile::new().unwrap();
+
+        let content1 = "Content from file 1";
+        let content2 = "Content from file 2";
+
+        fs::write(temp_file1.path(), content1).unwrap();
+        fs::write(temp_file2.path(), content2).unwrap();
+
+        let mut config = Config {
+            prompt: "Test prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![
+                temp_file1.path().to_str().unwrap().to_string(),
+                temp_file2.path().to_str().unwrap().to_string(),
+            ],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Verify ordered_content has the expected items
+        assert_eq!(config.ordered_content.len(), 3); // prompt + 2 files
+        match &config.ordered_content[0] {
+            ContentSource::CommandLinePrompt(p) => assert_eq!(p, "Test prompt"),
+            _ => panic!("Expected CommandLinePrompt"),
+        }
+        match &config.ordered_content[1] {
+            ContentSource::TextFile(_, c, _) => assert_eq!(c, content1),
+            _ => panic!("Expected TextFile"),
+        }
+        match &config.ordered_content[2] {
+            ContentSource::TextFile(_, c, _) => assert_eq!(c, content2),
+            _ => panic!("Expected TextFile"),
+        }
+    }
+
+    #[test]
+    fn test_collect_files_recursive_single_file() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_str().unwrap();
+
+        let result = collect_files_recursive(file_path).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0], file_path);
+    }
+
+    #[test]
+    fn test_collect_files_recursive_directory() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = temp_dir.path();
+
+        // Create test files
+        let file1 = dir_path.join("file1.txt");
+        let file2 = dir_path.join("file2.txt");
+        let subdir = dir_path.join("subdir");
+        fs::create_dir(&subdir).unwrap();
+        let file3 = subdir.join("file3.txt");
+
+        fs::write(&file1, "content1").unwrap();
+        fs::write(&file2, "content2").unwrap();
+        fs::write(&file3, "content3").unwrap();
+
+        let result = collect_files_recursive(dir_path.to_str().unwrap()).unwrap();
+        assert_eq!(result.len(), 3);
+
+        // Files should be sorted
+        let mut expected = vec![
+            file1.to_str().unwrap().to_string(),
+            file2.to_str().unwrap().to_string(),
+            file3.to_str().unwrap().to_string(),
+        ];
+        expected.sort();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_get_input_text_with_zip_archive() {
+        use std::io::Write;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("logs/app.log", options).unwrap();
+        zip.write_all(b"ERROR disk full").unwrap();
+        zip.start_file("data.bin", options).unwrap();
+        zip.write_all(&[0x00, 0x01, 0xFF, 0xFE]).unwrap();
+        zip.finish().unwrap();
+
+        let archive = archive_path.to_str().unwrap().to_string();
+        let mut config = Config {
+            prompt: "Test prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![archive.clone()],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Should have prompt + the log member (binary member is skipped)
+        assert_eq!(config.ordered_content.len(), 2);
+        match &config.ordered_content[1] {
+            ContentSource::TextFile(path, content, _) => {
+                assert_eq!(path, &format!("{archive}!/logs/app.log"));
+                assert_eq!(content, "ERROR disk full");
+            }
+            _ => panic!("Expected TextFile"),
+        }
+    }
+
+    #[test]
+    fn test_get_input_text_with_selectors() {
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let source_path = temp_dir.path().join("lib.rs");
+        fs::write(
+            &source_path,
+            "fn first() {}\n\nfn second() {\n    first();\n}\n",
+        )
+        .unwrap();
+        let source = source_path.to_str().unwrap().to_string();
+
+        let mut config = Config {
+            prompt: "Test prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![format!("{source}:1-2"), format!("{source}#second")],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        assert_eq!(config.ordered_content.len(), 3);
+        match &config.ordered_content[1] {
+            ContentSource::TextFile(path, content, selection) => {
+                assert_eq!(path, &source);
+                assert_eq!(content, "fn first() {}\n\n");
+                assert_eq!(selection.as_deref(), Some("lines 1-2"));
+            }
+            _ => panic!("Expected TextFile"),
+        }
+        match &config.ordered_content[2] {
+            ContentSource::TextFile(_, content, selection) => {
+                assert_eq!(content, "fn second() {\n    first();\n}\n");
+                assert_eq!(selection.as_deref(), Some("second, lines 3-5"));
+            }
+            _ => panic!("Expected TextFile"),
+        }
+    }
+
+    #[test]
+    fn test_collect_files_recursive_nonexistent() {
+        let result = collect_files_recursive("nonexistent_path");
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Path does not exist")
+        );
+    }
+
+    #[test]
+    fn test_get_input_text_empty_files_list() {
+        let mut config = Config {
+            prompt: "".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        let result = get_input_text(&mut config, None);
+        assert!(result.is_ok());
+
+        // Should have no content since files list is empty
+        assert!(config.ordered_content.is_empty());
+    }
+
+    #[test]
+    fn test_get_input_text_with_prompt_override() {
+        let mut config = Config {
+            prompt: "Original prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        let result = get_input_text(&mut config, Some("Override prompt"));
+        assert!(result.is_ok());
+
+        // Should have 1 content item with the override prompt
+        assert_eq!(config.ordered_content.len(), 1);
+        match &config.ordered_content[0] {
+            ContentSource::CommandLinePrompt(p) => assert_eq!(p, "Override prompt"),
+            _ => panic!("Expected CommandLinePrompt"),
+        }
+    }
+
+    #[test]
+    fn test_is_media_file_by_extension() {
+        use std::path::Path;
+
+        // Test image files
+        assert!(is_media_file_by_extension(Path::new("test.jpg")));
+        assert!(is_media_file_by_extension(Path::new("test.jpeg")));
+        assert!(is_media_file_by_extension(Path::new("test.png")));
+        assert!(is_media_file_by_extension(Path::new("test.webp")));
+        assert!(is_media_file_by_extension(Path::new("test.heic")));
+        assert!(is_media_file_by_extension(Path::new("test.pdf")));
+
+        // Test audio files
+        assert!(is_media_file_by_extension(Path::new("test.ogg")));
+        assert!(is_media_file_by_extension(Path::new("test.opus")));
+        assert!(is_media_file_by_extension(Path::new("test.mp3")));
+        assert!(is_media_file_by_extension(Path::new("test.m4a")));
+
+        // Test video files
+        assert!(is_media_file_by_extension(Path::new("test.mp4")));
+
+        // Test case insensitive
+        assert!(is_media_file_by_extension(Path::new("test.JPG")));
+        assert!(is_media_file_by_extension(Path::new("test.PNG")));
+        assert!(is_media_file_by_extension(Path::new("test.MP4")));
+
+        // Test non-media files
+        assert!(!is_media_file_by_extension(Path::new("test.txt")));
+        assert!(!is_media_file_by_extension(Path::new("test.rs")));
+        assert!(!is_media_file_by_extension(Path::new("test.md")));
+        assert!(!is_media_file_by_extension(Path::new("test.json")));
+        assert!(!is_media_file_by_extension(Path::new("test.xml")));
+
+        // Test files without extension
+        assert!(!is_media_file_by_extension(Path::new("test")));
+        assert!(!is_media_file_by_extension(Path::new("README")));
+    }
+
+    #[test]
+    fn test_is_text_content() {
+        // Test UTF-8 content
+        assert!(is_text_content(b"Hello, world!"));
+        assert!(is_text_content("Hello, 世界!".as_bytes()));
+
+        // Test UTF-8 BOM
+        let utf8_bom = [0xEF, 0xBB, 0xBF, b'H', b'e', b'l', b'l', b'o'];
+        assert!(is_text_content(&utf8_bom));
+
+        // Test UTF-16 BOM
+        let utf16_le_bom = [0xFF, 0xFE, b'H', 0x00, b'i', 0x00];
+        assert!(is_text_content(&utf16_le_bom));
+
+        // Test common text content
+        assert!(is_text_content(
+            b"This is a text file\nwith multiple lines\r\n"
+        ));
+        assert!(is_text_content(b"{\n  \"key\": \"value\"\n}"));
+
+        // Test binary content (contains null bytes)
+        assert!(!is_text_content(&[0x50, 0x4B, 0x03, 0x04, 0x00, 0x00])); // ZIP header
+        assert!(!is_text_content(&[0xFF, 0xD8, 0xFF, 0xE0])); // JPEG header
+
+        // Test mixed content (should be detected as binary due to null bytes)
+        assert!(!is_text_content(&[
+            b'H', b'e', b'l', b'l', b'o', 0x00, b'w', b'o', b'r', b'l', b'd'
+        ]));
+
+        // Test high ratio of high bytes (should be binary)
+        let binary_like = vec![0x80; 100]; // All high bytes
+        assert!(!is_text_content(&binary_like));
+
+        // Test mixed content with some high bytes (should still be text)
+        let mixed_content = [b'H', b'e', b'l', b'l', b'o', 0xE9, b'!', b'\n']; // "Helloé!\n"
+        assert!(is_text_content(&mixed_content));
+    }
+
+    #[test]
+    fn test_has_text_bom() {
+        // UTF-8 BOM
+        assert!(has_text_bom(&[0xEF, 0xBB, 0xBF]));
+        assert!(has_text_bom(&[0xEF, 0xBB, 0xBF, b'H', b'i']));
+
+        // UTF-16 LE BOM
+        assert!(has_text_bom(&[0xFF, 0xFE]));
+        assert!(has_text_bom(&[0xFF, 0xFE, b'H', 0x00]));
+
+        // UTF-16 BE BOM
+        assert!(has_text_bom(&[0xFE, 0xFF]));
+        assert!(has_text_bom(&[0xFE, 0xFF, 0x00, b'H']));
+
+        // No BOM
+        assert!(!has_text_bom(b"Hello, world!"));
+        assert!(!has_text_bom(&[0xEF, 0xBB])); // Incomplete UTF-8 BOM
+        assert!(!has_text_bom(&[0xFF])); // Incomplete UTF-16 BOM
+        assert!(!has_text_bom(&[])); // Empty
+    }
+
+    #[test]
+    fn test_get_input_text_with_mixed_files() {
+        use tempfile::NamedTempFile;
+
+        let temp_text_file = NamedTempFile::new().unwrap();
+        let temp_image_file = NamedTempFile::with_suffix(".jpg").unwrap();
+        let temp_audio_file = NamedTempFile::with_suffix(".mp3").unwrap();
+
+        let text_content = "This is a text file content";
+        fs::write(temp_text_file.path(), text_content).unwrap();
+        fs::write(temp_image_file.path(), b"fake image data").unwrap();
+        fs::write(temp_audio_file.path(), b"fake audio data").unwrap();
+
+        let mut config = Config {
+            prompt: "Test prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![
+                temp_text_file.path().to_str().unwrap().to_string(),
+                temp_image_file.path().to_str().unwrap().to_string(),
+                temp_audio_file.path().to_str().unwrap().to_string(),
+            ],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Should have prompt + 1 text file + 2 media files
+        assert_eq!(config.ordered_content.len(), 4);
+
+        // Check prompt
+        match &config.ordered_content[0] {
+            ContentSource::CommandLinePrompt(p) => assert_eq!(p, "Test prompt"),
+            _ => panic!("Expected CommandLinePrompt"),
+        }
+
+        // Check text file
+        let mut found_text_file = false;
+        let mut found_image_file = false;
+        let mut found_audio_file = false;
+
+        for content in &config.ordered_content[1..] {
+            match content {
+                ContentSource::TextFile(path, content, _) => {
+                    if path == temp_text_file.path().to_str().unwrap() {
+                        assert_eq!(content, text_content);
+                        found_text_file = true;
+                    }
+                }
+                ContentSource::ImageFile(path) => {
+                    if path == temp_image_file.path().to_str().unwrap() {
+                        found_image_file = true;
+                    } else if path == temp_audio_file.path().to_str().unwrap() {
+                        found_audio_file = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        assert!(found_text_file, "Text file should be processed as text");
+        assert!(found_image_file, "Image file should be processed as media");
+        assert!(found_audio_file, "Audio file should be processed as media");
+    }
+
+    #[test]
+    fn test_get_input_text_with_binary_files() {
+        use tempfile::NamedTempFile;
+
+        let temp_text_file = NamedTempFile::new().unwrap();
+        let temp_binary_file = NamedTempFile::with_suffix(".bin").unwrap();
+
+        let text_content = "This is a text file content";
+        let binary_content = vec![0x00, 0x01, 0x02, 0x03, 0xFF, 0xFE, 0xFD]; // Clear binary data
+
+        fs::write(temp_text_file.path(), text_content).unwrap();
+        fs::write(temp_binary_file.path(), &binary_content).unwrap();
+
+        let mut config = Config {
+            prompt: "Test prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![
+                temp_text_file.path().to_str().unwrap().to_string(),
+                temp_binary_file.path().to_str().unwrap().to_string(),
+            ],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Should have prompt + 1 text file (binary file should be skipped)
+        assert_eq!(config.ordered_content.len(), 2);
+
+        // Check prompt
+        match &config.ordered_content[0] {
+            ContentSource::CommandLinePrompt(p) => assert_eq!(p, "Test prompt"),
+            _ => panic!("Expected CommandLinePrompt"),
+        }
+
+        // Check that only the text file was processed
+        match &config.ordered_content[1] {
+            ContentSource::TextFile(path, content, _) => {
+                assert_eq!(path, temp_text_file.path().to_str().unwrap());
+                assert_eq!(content, text_content);
+            }
+            _ => panic!("Expected TextFile"),
+        }
+    }
+
+    #[test]
+    fn test_get_input_text_with_documents() {
+        use tempfile::NamedTempFile;
+
+        let temp_csv_file = NamedTempFile::with_suffix(".csv").unwrap();
+        let temp_pdf_file = NamedTempFile::with_suffix(".pdf").unwrap();
+
+        fs::write(temp_csv_file.path(), "name,count\nApples,3\n").unwrap();
+        fs::write(temp_pdf_file.path(), "%PDF-1.4 placeholder").unwrap();
+
+        let mut config = Config {
+            prompt: "Test prompt".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![
+                temp_csv_file.path().to_str().unwrap().to_string(),
+                temp_pdf_file.path().to_str().unwrap().to_string(),
+            ],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "gemini-2.5-flash-lite".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: true,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Should have prompt + CSV as markdown table + PDF uploaded natively
+        assert_eq!(config.ordered_content.len(), 3);
+
+        match &config.ordered_content[1] {
+            ContentSource::TextFile(path, content, _) => {
+                assert_eq!(path, temp_csv_file.path().to_str().unwrap());
+                assert!(content.contains("| name | count |"));
+                assert!(content.contains("| Apples | 3 |"));
+            }
+            _ => panic!("Expected TextFile"),
+        }
+
+        match &config.ordered_content[2] {
+            ContentSource::ImageFile(path) => {
+                assert_eq!(path, temp_pdf_file.path().to_str().unwrap());
+            }
+            _ => panic!("Expected ImageFile for native PDF upload"),
+        }
+
+        // Providers without native document support never receive the raw PDF
+        config.model = "ollama::llama3.2".to_string();
+        config.ordered_content.clear();
+        get_input_text(&mut config, None).unwrap();
+        assert!(
+            !config
+                .ordered_content
+                .iter()
+                .any(|c| matches!(c, ContentSource::ImageFile(_)))
+        );
+    }
+
+    #[test]
+    fn test_get_input_text_with_directory_containing_mixed_files() {
+        use std::fs;
+        use tempfile::TempDir;
+
+        let temp_dir = TempDir::new().unwrap();
+        let text_file = temp_dir.path().join("document.txt");
+        let image_file = temp_dir.path().join("photo.png");
+        let audio_file = temp_dir.path().join("recording.mp3");
+        let video_file = temp_dir.path().join("movie.mp4");
+
+        let text_content = "Document content";
+        fs::write(&text_file, text_content).unwrap();
+        fs::write(&image_file, b"fake png data").unwrap();
+        fs::write(&audio_file, b"fake mp3 data").unwrap();
+        fs::write(&video_file, b"fake mp4 data").unwrap();
+
+        let mut config = Config {
+            prompt: "Process this directory".to_string(),
+            use_clipboard_input: false,
+            text_files: vec![temp_dir.path().to_str().unwrap().to_string()],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "test-model".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Should have prompt + 1 text file + 3 media files
+        assert_eq!(config.ordered_content.len(), 5);
+
+        // Check prompt
+        match &config.ordered_content[0] {
+            ContentSource::CommandLinePrompt(p) => assert_eq!(p, "Process this directory"),
+            _ => panic!("Expected CommandLinePrompt"),
+        }
+
+        // Verify we have exactly 1 text file and 3 media files
+        let mut text_files_count = 0;
+        let mut media_files_count = 0;
+
+        for content in &config.ordered_content[1..] {
+            match content {
+                ContentSource::TextFile(_, _, _) => text_files_count += 1,
+                ContentSource::ImageFile(_) => media_files_count += 1,
+                _ => {}
+            }
+        }
+
+        assert_eq!(text_files_count, 1, "Should have exactly 1 text file");
+        assert_eq!(media_files_count, 3, "Should have exactly 3 media files");
+    }
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {"cell_type": "markdown", "metadata": {}, "source": ["# Analysis\n", "Load the data."]},
+  {"cell_type": "code", "execution_count": 1, "metadata": {}, "source": "print('hello')",
+   "outputs": [{"output_type": "stream", "name": "stdout", "text": ["hello\n"]}]},
+  {"cell_type": "code", "execution_count": 2, "metadata": {}, "source": ["plot()"],
+   "outputs": [{"output_type": "display_data", "metadata": {},
+     "data": {"image/png": "iVBORw0KGgo=\n", "text/plain": ["<Figure>"]}}]},
+  {"cell_type": "code", "execution_count": 3, "metadata": {}, "source": ["1 / 0"],
+   "outputs": [{"output_type": "error", "ename": "ZeroDivisionError", "evalue": "division by zero",
+     "traceback": ["\u001b[0;31mZeroDivisionError\u001b[0m: division by zero"]}]}
+ ],
+ "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_read_notebook() {
+        let temp_file = NamedTempFile::with_suffix(".ipynb").unwrap();
+        fs::write(temp_file.path(), NOTEBOOK).unwrap();
+        let file_path = temp_file.path().to_str().unwrap();
+
+        let notebook = read_notebook(file_path).unwrap();
+
+        assert!(
+            notebook
+                .markdown
+                .starts_with("**Cell 1 (markdown)**\n\n# Analysis\nLoad the data.")
+        );
+        assert!(notebook.markdown.contains(
+            "**Cell 2 (code)**\n\n```python\nprint('hello')\n```\n\nOutput:\n\n```\nhello\n```"
+        ));
+        assert!(
+            notebook
+                .markdown
+                .contains(&format!("[Image: {file_path} (cell 3, image 1)]"))
+        );
+        assert!(
+            notebook
+                .markdown
+                .contains("ZeroDivisionError: division by zero")
+        );
+        assert!(!notebook.markdown.contains("\u{1b}["));
+        assert!(!notebook.markdown.contains("kernelspec"));
+
+        assert_eq!(notebook.images.len(), 1);
+        assert_eq!(notebook.images[0].1, "image/png");
+        assert_eq!(notebook.images[0].2, "iVBORw0KGgo=");
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        assert_eq!(truncate_middle("short", 10), "short");
+
+        let text = "a".repeat(50) + &"b".repeat(50);
+        let truncated = truncate_middle(&text, 30);
+        assert!(truncated.starts_with(&"a".repeat(20)));
+        assert!(truncated.ends_with(&"b".repeat(10)));
+        assert!(truncated.contains("[... 70 characters truncated ...]"));
+    }
+
+    #[test]
+    fn test_summarize_json() {
+        let items: Vec<String> = (0..100)
+            .map(|i| format!(r#"{{"id": {i}, "name": "item {i}", "tags": ["x"], "parent": null}}"#))
+            .collect();
+        let content = format!(r#"{{"version": 2, "items": [{}]}}"#, items.join(","));
+
+        let summary = summarize_json(&content, content.len() as u64).unwrap();
+
+        assert!(summary.contains("$: object (2 keys)"));
+        assert!(summary.contains("$.items: array[100] of object"));
+        assert!(summary.contains("$.items[].id: number"));
+        assert!(summary.contains("$.items[].tags: array[1] of string"));
+        assert!(summary.contains("$.items[].parent: null"));
+        assert!(summary.contains("\"item 0\""));
+        assert!(summary.contains("\"item 99\""));
+        assert!(summary.contains("... 95 more items ..."));
+        assert!(!summary.contains("\"item 50\""));
+
+        assert!(summarize_json("not json", 8).is_none());
+    }
+
+    #[test]
+    fn test_summarize_csv() {
+        let mut content = "id,price,active,city\n".to_string();
+        for i in 1..=100 {
+            content.push_str(&format!("{i},{}.5,true,City {i}\n", i * 2));
+        }
+
+        let summary = summarize_csv(&content, content.len() as u64);
+
+        assert!(summary.contains("100 rows, 4 columns"));
+        assert!(summary.contains("| id | integer | 0 | 1 | 100 | 1 |"));
+        assert!(summary.contains("| price | number | 0 | 2.5 | 200.5 | 2.5 |"));
+        assert!(summary.contains("| active | boolean |"));
+        assert!(summary.contains("| city | text |"));
+        assert!(summary.contains("| 10 | 20.5 | true | City 10 |"));
+        assert!(summary.contains("[... 85 rows omitted ...]"));
+        assert!(summary.contains("| 100 | 200.5 | true | City 100 |"));
+        assert!(!summary.contains("City 50 |"));
+    }
+
+    #[test]
+    fn test_summarize_log() {
+        let content: String = (1..=500)
+            .map(|i| match i {
+                250 => "2024-01-01 ERROR database connection lost\n".to_string(),
+                300 => "2024-01-01 WARN retrying\n".to_string(),
+                _ => format!("2024-01-01 INFO request {i}\n"),
+            })
+            .collect();
+
+        let summary = summarize_log(&content, content.len() as u64);
+
+        assert!(summary.contains("500 lines, 1 error lines, 1 warning lines"));
+        assert!(summary.contains("request 40\n"));
+        assert!(!summary.contains("request 41\n"));
+        assert!(summary.contains("250: 2024-01-01 ERROR database connection lost"));
+        assert!(summary.contains("300: 2024-01-01 WARN retrying"));
+        assert!(summary.contains("request 421\n"));
+        assert!(summary.contains("request 500"));
+    }
+
+    #[test]
+    fn test_get_input_text_with_notebook_and_large_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let notebook_path = temp_dir.path().join("analysis.ipynb");
+        fs::write(&notebook_path, NOTEBOOK).unwrap();
+
+        let large_json_path = temp_dir.path().join("data.json");
+        let items: Vec<String> = (0..5000)
+            .map(|i| format!(r#"{{"id": {i}, "value": "entry number {i}"}}"#))
+            .collect();
+        fs::write(&large_json_path, format!("[{}]", items.join(","))).unwrap();
+
+        let small_json_path = temp_dir.path().join("small.json");
+        fs::write(&small_json_path, r#"{"a": 1}"#).unwrap();
+
+        let mut config = Config {
+            prompt: String::new(),
+            use_clipboard_input: false,
+            text_files: vec![temp_dir.path().to_str().unwrap().to_string()],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "gemini-2.5-flash-lite".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        // Notebook cells + notebook image + summarised data.json + small.json verbatim
+        assert_eq!(config.ordered_content.len(), 4);
+
+        match &config.ordered_content[0] {
+            ContentSource::TextFile(path, content, None) => {
+                assert!(path.ends_with("analysis.ipynb"));
+                assert!(content.contains("**Cell 2 (code)**"));
+            }
+            other => panic!("Expected notebook TextFile, got {other:?}"),
+        }
+
+        match &config.ordered_content[1] {
+            ContentSource::EmbeddedImage(label, mime_type, _) => {
+                assert!(label.ends_with("analysis.ipynb (cell 3, image 1)"));
+                assert_eq!(mime_type, "image/png");
+            }
+            other => panic!("Expected EmbeddedImage, got {other:?}"),
+        }
+
+        match &config.ordered_content[2] {
+            ContentSource::TextFile(path, content, Some(selection)) => {
+                assert!(path.ends_with("data.json"));
+                assert_eq!(selection, "summary");
+                assert!(content.contains("$: array[5000] of object"));
+                assert!(content.len() < 5000);
+            }
+            other => panic!("Expected summarised TextFile, got {other:?}"),
+        }
+
+        match &config.ordered_content[3] {
+            ContentSource::TextFile(path, content, None) => {
+                assert!(path.ends_with("small.json"));
+                assert_eq!(content, r#"{"a": 1}"#);
+            }
+            other => panic!("Expected TextFile, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_get_input_text_renders_prompt_template() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), "glossary entry").unwrap();
+
+        let mut config = Config {
+            prompt: format!(
+                "Translate to {{{{lang}}}} using {{{{file:{}}}}}",
+                temp_file.path().display()
+            ),
+            use_clipboard_input: false,
+            text_files: vec![],
+            output_mode: OutputMode::Stdout,
+            resume_conversation: None,
+            resume_last: false,
+            list_conversations: None,
+            show_conversation: None,
+            model: "gemini-2.5-flash-lite".to_string(),
+            record_audio: false,
+            roles: vec![],
+            ordered_content: Vec::new(),
+            spinner: false,
+            audio_device: None,
+            list_audio_devices: false,
+            no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: vec![("lang".to_string(), "fr-FR".to_string())],
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
+        };
+
+        get_input_text(&mut config, None).unwrap();
+
+        match &config.ordered_content[0] {
+            ContentSource::CommandLinePrompt(prompt) => {
+                assert_eq!(prompt, "Translate to fr-FR using glossary entry");
+            }
+            other => panic!("Expected CommandLinePrompt, got {other:?}"),
+        }
+
+        // Undefined variables stop the request with a clear error
+        config.prompt = "Write for {{audience}}".to_string();
+        config.ordered_content.clear();
+        let err = get_input_text(&mut config, None).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Missing template variable(s) in prompt: audience")
+        );
+    }
+}
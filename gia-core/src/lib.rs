@@ -0,0 +1,91 @@
+//! Library behind the `gia` command line tool.
+//!
+//! The pieces of a gia request are usable on their own:
+//!
+//! - [`Config`] and [`ContentSource`] describe a request; [`Config::from_arg_list`]
+//!   builds one from gia command line arguments (roles/tasks applied)
+//! - [`input::get_input_text`] collects prompt, files, web pages, git changes etc.
+//!   into `config.ordered_content`
+//! - [`AiProvider`] and [`ProviderFactory`] talk to Gemini (with API key fallback)
+//!   and Ollama (`ollama::model`)
+//! - [`ConversationManager`] loads and saves conversations (~/.gia/conversations)
+//! - [`output::output_text_with_usage`] renders a response like the CLI does
+//!
+//! [`prepare_request`] and [`complete_request`] run the whole pipeline of the CLI:
+//!
+//! ```no_run
+//! use gia_core::{Config, ConversationManager, complete_request, prepare_request};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let args = ["-t", "rust-dev", "-f", "src/main.rs", "--", "Review this"];
+//! let mut config = Config::from_arg_list(&args.map(String::from))?;
+//! config.read_stdin = false; // don't wait for piped input, take API keys from the environment
+//!
+//! let manager = ConversationManager::new()?;
+//! let (mut conversation, api_keys) = prepare_request(&mut config, &manager)?;
+//! let (answer, usage) =
+//!     complete_request(&config, &manager, &mut conversation, &api_keys, None).await?;
+//! println!("{answer}\n[{} tokens, {}]", usage.format_short(), conversation.id);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A provider can also be used directly with genai chat messages:
+//!
+//! ```no_run
+//! use gia_core::{ProviderConfig, ProviderFactory};
+//! use genai::chat::ChatMessage;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mut provider = ProviderFactory::create_provider(ProviderConfig {
+//!     model: "ollama::llama3.2".to_string(),
+//!     api_keys: Vec::new(),
+//!     preferred_api_key_index: 0,
+//!     temperature: None,
+//! })?;
+//! let mut on_text = |text: &str| print!("{text}");
+//! let response = provider
+//!     .generate_content_streaming(vec![ChatMessage::user("Hello")], &[], &mut on_text)
+//!     .await?;
+//! println!("\n{}", response.usage.format_short());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod api_key;
+pub mod app;
+mod archive;
+pub mod audio;
+mod browser_preview;
+pub mod cli;
+mod clipboard;
+pub mod config_file;
+pub mod constants;
+pub mod content_part_wrapper;
+pub mod conversation;
+mod document;
+pub mod gemini;
+mod git;
+mod image;
+pub mod input;
+pub mod logging;
+mod mcp;
+pub mod mcp_server;
+pub mod ollama;
+mod openai_api;
+pub mod output;
+pub mod progress;
+pub mod provider;
+pub mod role;
+mod roles_command;
+mod selector;
+pub mod server;
+mod template;
+pub mod tools;
+mod webpage;
+
+pub use app::{complete_request, prepare_request, run_app};
+pub use cli::{Config, ContentSource, OutputMode};
+pub use conversation::{Conversation, ConversationManager, ConversationSummary, TokenUsage};
+pub use document::run_pdf_helper;
+pub use provider::{AiProvider, AiResponse, ProviderConfig, ProviderFactory, TextCallback};
@@ -1,7 +1,6 @@
 use std::env;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
 use std::sync::Mutex;
 use tracing::{debug, error, info, trace, warn};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
@@ -41,7 +40,7 @@ pub fn setup_conversation_file_logging(conversation_id: &str) -> anyhow::Result<
         return Ok(());
     }
 
-    let conversations_dir = get_conversations_dir()?;
+    let conversations_dir = crate::conversation::ConversationManager::get_conversations_dir()?;
 
     // Ensure directory exists
     if !conversations_dir.exists() {
@@ -67,12 +66,6 @@ pub fn setup_conversation_file_logging(conversation_id: &str) -> anyhow::Result<
     Ok(())
 }
 
-fn get_conversations_dir() -> anyhow::Result<PathBuf> {
-    let home_dir =
-        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
-    Ok(home_dir.join(".gia").join("conversations"))
-}
-
 // Helper to write to log file if enabled
 fn write_to_file(level: &str, target: &str, msg: &str) {
     if let Ok(mut guard) = LOG_FILE.lock()
@@ -0,0 +1,798 @@
+//! Client for MCP (Model Context Protocol) servers configured in `~/.gia/config`.
+//!
+//! Servers are reached over stdio (newline-delimited JSON-RPC on the child's
+//! stdin/stdout) or over streamable HTTP on a loopback address. Their tools are
+//! offered to the model as `<server>__<tool>`, filtered by the server's `allow` list.
+
+use anyhow::{Context, Result};
+use genai::chat::Tool;
+use serde_json::{Value, json};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::Mutex;
+
+use crate::config_file::McpServerConfig;
+use crate::constants::{MCP_PROTOCOL_VERSION, MCP_REQUEST_TIMEOUT_SECS};
+use crate::logging::{log_debug, log_info};
+
+type BoxedReader = Box<dyn AsyncRead + Unpin + Send>;
+type BoxedWriter = Box<dyn AsyncWrite + Unpin + Send>;
+
+/// A tool advertised by an MCP server
+#[derive(Debug, Clone)]
+pub struct McpTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+struct StdioIo {
+    reader: BufReader<BoxedReader>,
+    writer: BoxedWriter,
+}
+
+enum Transport {
+    Stdio {
+        io: Mutex<StdioIo>,
+        child: std::sync::Mutex<Option<tokio::process::Child>>, // killed on drop
+        closed: AtomicBool,                                     // reading or writing failed
+    },
+    Http {
+        agent: ureq::Agent,
+        url: String,
+        session_id: std::sync::Mutex<Option<String>>,
+    },
+}
+
+pub struct McpClient {
+    server: String,
+    config: McpServerConfig,
+    transport: Transport,
+    next_id: AtomicU64,
+    protocol_version: String,
+    tools: Vec<McpTool>,
+}
+
+impl McpClient {
+    /// Start or connect to a configured server and fetch its tools
+    pub async fn connect(server: &str, config: &McpServerConfig) -> Result<Self> {
+        let transport = match (&config.command, &config.url) {
+            (Some(command), _) => {
+                log_info(&format!("Starting MCP server '{server}': {command}"));
+                let mut child = tokio::process::Command::new(command)
+                    .args(&config.args)
+                    .envs(&config.env)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::null())
+                    .kill_on_drop(true)
+                    .spawn()
+                    .with_context(|| {
+                        format!("Failed to start MCP server '{server}' ({command})")
+                    })?;
+                let stdin = child.stdin.take().context("MCP server stdin unavailable")?;
+                let stdout = child
+                    .stdout
+                    .take()
+                    .context("MCP server stdout unavailable")?;
+                Transport::stdio(Box::new(stdout), Box::new(stdin), Some(child))
+            }
+            (None, Some(url)) => {
+                log_info(&format!("Connecting to MCP server '{server}': {url}"));
+                Transport::http(url)?
+            }
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "MCP server '{server}' needs either 'command' or 'url'"
+                ));
+            }
+        };
+
+        Self::initialize(server, config, transport).await
+    }
+
+    async fn initialize(
+        server: &str,
+        config: &McpServerConfig,
+        transport: Transport,
+    ) -> Result<Self> {
+        let mut client = Self {
+            server: server.to_string(),
+            config: config.clone(),
+            transport,
+            next_id: AtomicU64::new(1),
+            protocol_version: MCP_PROTOCOL_VERSION.to_string(),
+            tools: Vec::new(),
+        };
+
+        let result = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": MCP_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "gia", "version": env!("GIA_VERSION")}
+                }),
+            )
+            .await?;
+        if let Some(version) = result.get("protocolVersion").and_then(Value::as_str) {
+            client.protocol_version = version.to_string();
+        }
+        client.notify("notifications/initialized").await?;
+
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({"cursor": cursor}),
+                None => json!({}),
+            };
+            let page = client.request("tools/list", params).await?;
+            for tool in page
+                .get("tools")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                let Some(name) = tool.get("name").and_then(Value::as_str) else {
+                    continue;
+                };
+                if !client.config.allows(name) {
+                    log_debug(&format!(
+                        "MCP tool {server}/{name} is not allowed, skipping"
+                    ));
+                    continue;
+                }
+                client.tools.push(McpTool {
+                    name: name.to_string(),
+                    description: tool
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    input_schema: tool
+                        .get("inputSchema")
+                        .cloned()
+                        .unwrap_or_else(|| json!({"type": "object", "properties": {}})),
+                });
+            }
+
+            let next = page
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            if next.is_none() || next == cursor {
+                break;
+            }
+            cursor = next;
+        }
+
+        log_info(&format!(
+            "MCP server '{server}' offers {} allowed tool(s)",
+            client.tools.len()
+        ));
+        Ok(client)
+    }
+
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// False once the server process has exited or the connection to it broke
+    pub fn is_alive(&self) -> bool {
+        self.transport.is_alive()
+    }
+
+    /// Tool declarations sent to the model
+    pub fn definitions(&self) -> Vec<Tool> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                let description = if tool.description.is_empty() {
+                    format!("Tool '{}' of MCP server '{}'", tool.name, self.server)
+                } else {
+                    format!("[{}] {}", self.server, tool.description)
+                };
+                Tool::new(tool_name(&self.server, &tool.name))
+                    .with_description(description)
+                    .with_schema(model_schema(&tool.input_schema))
+            })
+            .collect()
+    }
+
+    /// The server's tool name for a model-facing tool name, if this server offers it
+    pub fn find_tool(&self, fn_name: &str) -> Option<&str> {
+        self.tools
+            .iter()
+            .find(|tool| tool_name(&self.server, &tool.name) == fn_name)
+            .map(|tool| tool.name.as_str())
+    }
+
+    /// Call a tool and return its text content. Tool errors reported by the server are errors.
+    pub async fn call_tool(&self, name: &str, arguments: &Value) -> Result<String> {
+        if !self.config.allows(name) {
+            return Err(anyhow::anyhow!(
+                "Tool '{name}' of MCP server '{}' is not allowed",
+                self.server
+            ));
+        }
+
+        let arguments = if arguments.is_object() {
+            arguments.clone()
+        } else {
+            json!({})
+        };
+        let result = self
+            .request("tools/call", json!({"name": name, "arguments": arguments}))
+            .await?;
+
+        let text = result
+            .get("content")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .map(content_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if result.get("isError").and_then(Value::as_bool) == Some(true) {
+            return Err(anyhow::anyhow!(if text.is_empty() {
+                "the tool reported an error".to_string()
+            } else {
+                text
+            }));
+        }
+        if text.is_empty()
+            && let Some(structured) = result.get("structuredContent")
+        {
+            return Ok(structured.to_string());
+        }
+        Ok(text)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        log_debug(&format!("MCP '{}' <- {method} (id {id})", self.server));
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(MCP_REQUEST_TIMEOUT_SECS),
+            self.transport
+                .send(&message, Some(id), &self.protocol_version),
+        )
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "MCP server '{}' did not answer {method} within {MCP_REQUEST_TIMEOUT_SECS}s",
+                self.server
+            )
+        })?
+        .with_context(|| format!("MCP {method} request to '{}' failed", self.server))?
+        .ok_or_else(|| anyhow::anyhow!("MCP server '{}' sent no response", self.server))?;
+
+        if let Some(error) = response.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error");
+            return Err(anyhow::anyhow!(
+                "MCP server '{}' rejected {method}: {message}",
+                self.server
+            ));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn notify(&self, method: &str) -> Result<()> {
+        let message = json!({"jsonrpc": "2.0", "method": method});
+        self.transport
+            .send(&message, None, &self.protocol_version)
+            .await
+            .with_context(|| format!("MCP {method} notification to '{}' failed", self.server))?;
+        Ok(())
+    }
+}
+
+impl Transport {
+    fn stdio(
+        reader: BoxedReader,
+        writer: BoxedWriter,
+        child: Option<tokio::process::Child>,
+    ) -> Self {
+        Transport::Stdio {
+            io: Mutex::new(StdioIo {
+                reader: BufReader::new(reader),
+                writer,
+            }),
+            child: std::sync::Mutex::new(child),
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// False once a stdio server has exited or its pipes failed
+    fn is_alive(&self) -> bool {
+        match self {
+            Transport::Stdio { child, closed, .. } => {
+                !closed.load(Ordering::Relaxed)
+                    && child
+                        .lock()
+                        .unwrap()
+                        .as_mut()
+                        .is_none_or(|child| matches!(child.try_wait(), Ok(None)))
+            }
+            Transport::Http { .. } => true,
+        }
+    }
+
+    fn http(url: &str) -> Result<Self> {
+        let parsed =
+            url::Url::parse(url).with_context(|| format!("Invalid MCP server URL: {url}"))?;
+        let is_loopback = match parsed.host() {
+            Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        };
+        if !matches!(parsed.scheme(), "http" | "https") || !is_loopback {
+            return Err(anyhow::anyhow!(
+                "MCP server URL must be http(s) on localhost: {url}"
+            ));
+        }
+
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(MCP_REQUEST_TIMEOUT_SECS)))
+            .proxy(None)
+            .build()
+            .into();
+        Ok(Transport::Http {
+            agent,
+            url: url.to_string(),
+            session_id: std::sync::Mutex::new(None),
+        })
+    }
+
+    /// `send` over the pipes of a stdio server
+    async fn send_stdio(
+        io: &Mutex<StdioIo>,
+        message: &Value,
+        id: Option<u64>,
+    ) -> Result<Option<Value>> {
+        let mut io = io.lock().await;
+        let StdioIo { reader, writer } = &mut *io;
+        write_line(writer, message).await?;
+        let Some(id) = id else {
+            return Ok(None);
+        };
+
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Err(anyhow::anyhow!("MCP server closed the connection"));
+            }
+            let Ok(incoming) = serde_json::from_str::<Value>(line.trim()) else {
+                log_debug(&format!(
+                    "Ignoring non-JSON line from MCP server: {}",
+                    line.trim()
+                ));
+                continue;
+            };
+
+            // Requests and notifications from the server
+            if let Some(method) = incoming.get("method").and_then(Value::as_str) {
+                if let Some(request_id) = incoming.get("id") {
+                    write_line(writer, &server_request_reply(method, request_id)).await?;
+                }
+                continue;
+            }
+            if incoming.get("id").and_then(Value::as_u64) == Some(id) {
+                return Ok(Some(incoming));
+            }
+        }
+    }
+
+    /// Send a message; for requests (`id` set) wait for the matching response
+    async fn send(
+        &self,
+        message: &Value,
+        id: Option<u64>,
+        protocol_version: &str,
+    ) -> Result<Option<Value>> {
+        match self {
+            Transport::Stdio { io, closed, .. } => {
+                let result = Self::send_stdio(io, message, id).await;
+                if result.is_err() {
+                    closed.store(true, Ordering::Relaxed);
+                }
+                result
+            }
+            Transport::Http {
+                agent,
+                url,
+                session_id,
+            } => {
+                let agent = agent.clone();
+                let url = url.clone();
+                let session = session_id.lock().unwrap().clone();
+                let body = serde_json::to_string(message)?;
+                let protocol_version = protocol_version.to_string();
+
+                let (new_session, content_type, text) = tokio::task::spawn_blocking(
+                    move || -> Result<(Option<String>, String, String)> {
+                        let mut request = agent
+                            .post(&url)
+                            .header("Content-Type", "application/json")
+                            .header("Accept", "application/json, text/event-stream")
+                            .header("MCP-Protocol-Version", &protocol_version);
+                        if let Some(session) = &session {
+                            request = request.header("Mcp-Session-Id", session);
+                        }
+                        let mut response = request
+                            .send(body.as_bytes())
+                            .with_context(|| format!("Failed to reach {url}"))?;
+                        let header = |name: &str| {
+                            response
+                                .headers()
+                                .get(name)
+                                .and_then(|value| value.to_str().ok())
+                                .map(str::to_string)
+                        };
+                        let new_session = header("mcp-session-id");
+                        let content_type = header("content-type").unwrap_or_default();
+                        let text = response
+                            .body_mut()
+                            .read_to_string()
+                            .context("Failed to read MCP server response")?;
+                        Ok((new_session, content_type, text))
+                    },
+                )
+                .await
+                .context("MCP HTTP request task failed")??;
+
+                if new_session.is_some() {
+                    *session_id.lock().unwrap() = new_session;
+                }
+                let Some(id) = id else {
+                    return Ok(None);
+                };
+
+                let messages = if content_type.starts_with("text/event-stream") {
+                    parse_sse_messages(&text)
+                } else {
+                    match serde_json::from_str::<Value>(&text)
+                        .context("MCP server sent invalid JSON")?
+                    {
+                        Value::Array(batch) => batch,
+                        single => vec![single],
+                    }
+                };
+                Ok(messages
+                    .into_iter()
+                    .find(|message| message.get("id").and_then(Value::as_u64) == Some(id)))
+            }
+        }
+    }
+}
+
+async fn write_line(writer: &mut BoxedWriter, message: &Value) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reply to a request the server sent us: we answer pings and support nothing else
+fn server_request_reply(method: &str, request_id: &Value) -> Value {
+    if method == "ping" {
+        json!({"jsonrpc": "2.0", "id": request_id, "result": {}})
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "error": {"code": -32601, "message": format!("Method not supported: {method}")}
+        })
+    }
+}
+
+/// JSON-RPC messages carried in the `data:` fields of a server-sent event stream
+fn parse_sse_messages(text: &str) -> Vec<Value> {
+    let mut messages = Vec::new();
+    let mut data = String::new();
+    for line in text.lines().chain(std::iter::once("")) {
+        if let Some(value) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(value.strip_prefix(' ').unwrap_or(value));
+        } else if line.trim().is_empty() && !data.is_empty() {
+            if let Ok(message) = serde_json::from_str(&data) {
+                messages.push(message);
+            }
+            data.clear();
+        }
+    }
+    messages
+}
+
+fn content_text(part: &Value) -> String {
+    let field = |name: &str| part.get(name).and_then(Value::as_str).unwrap_or_default();
+    match field("type") {
+        "text" => field("text").to_string(),
+        "image" | "audio" => format!("[{}: {}]", field("type"), field("mimeType")),
+        "resource" => {
+            let resource = &part["resource"];
+            resource
+                .get("text")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| {
+                    format!(
+                        "[resource: {}]",
+                        resource["uri"].as_str().unwrap_or_default()
+                    )
+                })
+        }
+        "resource_link" => format!("[resource: {}]", field("uri")),
+        _ => part.to_string(),
+    }
+}
+
+/// Model-facing tool name; providers only accept letters, digits, `_` and `-`
+pub fn tool_name(server: &str, tool: &str) -> String {
+    format!("{server}__{tool}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Drop JSON Schema keywords the Gemini function declaration format rejects
+fn model_schema(schema: &Value) -> Value {
+    match schema {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "$schema" | "additionalProperties"))
+                .map(|(key, value)| (key.clone(), model_schema(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(model_schema).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Read, Write};
+    use std::net::TcpListener;
+
+    /// Minimal MCP server answering one line-delimited request at a time
+    fn fake_response(request: &Value) -> Option<Value> {
+        let id = request.get("id")?.clone();
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "initialize" => {
+                json!({"protocolVersion": MCP_PROTOCOL_VERSION, "capabilities": {"tools": {}}})
+            }
+            "tools/list" if request["params"].get("cursor").is_none() => json!({
+                "tools": [{"name": "search", "description": "Search issues",
+                           "inputSchema": {"$schema": "x", "type": "object", "properties": {"query": {"type": "string"}}}}],
+                "nextCursor": "page2"
+            }),
+            "tools/list" => {
+                json!({"tools": [{"name": "delete_issue", "inputSchema": {"type": "object"}}]})
+            }
+            "tools/call" if request["params"]["name"] == "search" => json!({
+                "content": [{"type": "text", "text": format!("found {}", request["params"]["arguments"]["query"])}]
+            }),
+            "tools/call" => json!({"content": [{"type": "text", "text": "boom"}], "isError": true}),
+            _ => {
+                return Some(
+                    json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32601, "message": "nope"}}),
+                );
+            }
+        };
+        Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+    }
+
+    fn spawn_stdio_server() -> (BoxedReader, BoxedWriter) {
+        let (client_side, server_side) = tokio::io::duplex(64 * 1024);
+        let (client_read, client_write) = tokio::io::split(client_side);
+        tokio::spawn(async move {
+            let (server_read, mut server_write) = tokio::io::split(server_side);
+            let mut lines = BufReader::new(server_read).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let request: Value = serde_json::from_str(&line).unwrap();
+                if request.get("method").is_none() {
+                    continue; // our reply to the ping below
+                }
+                if request["method"] == "tools/call" {
+                    // Servers may ping the client while a request is pending
+                    let ping = json!({"jsonrpc": "2.0", "id": "srv-1", "method": "ping"});
+                    server_write
+                        .write_all(format!("{ping}\nnot json\n").as_bytes())
+                        .await
+                        .unwrap();
+                }
+                if let Some(response) = fake_response(&request) {
+                    server_write
+                        .write_all(format!("{response}\n").as_bytes())
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+        (Box::new(client_read), Box::new(client_write))
+    }
+
+    fn server_config(allow: Option<Vec<&str>>) -> McpServerConfig {
+        McpServerConfig {
+            command: Some("fake".to_string()),
+            allow: allow.map(|names| names.into_iter().map(str::to_string).collect()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stdio_client() {
+        let (reader, writer) = spawn_stdio_server();
+        let client = McpClient::initialize(
+            "jira",
+            &server_config(None),
+            Transport::stdio(reader, writer, None),
+        )
+        .await
+        .unwrap();
+
+        let names: Vec<String> = client.definitions().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["jira__search", "jira__delete_issue"]);
+        assert_eq!(client.find_tool("jira__search"), Some("search"));
+        assert!(
+            client.definitions()[0]
+                .schema
+                .as_ref()
+                .unwrap()
+                .get("$schema")
+                .is_none()
+        );
+
+        let text = client
+            .call_tool("search", &json!({"query": "login"}))
+            .await
+            .unwrap();
+        assert_eq!(text, "found \"login\"");
+
+        let err = client
+            .call_tool("delete_issue", &json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "boom");
+    }
+
+    #[tokio::test]
+    async fn test_closed_stdio_server_is_not_alive() {
+        let transport = Transport::stdio(
+            Box::new(tokio::io::empty()),
+            Box::new(tokio::io::sink()),
+            None,
+        );
+        assert!(transport.is_alive());
+        let message = json!({"jsonrpc": "2.0", "id": 1, "method": "ping"});
+        assert!(transport.send(&message, Some(1), "").await.is_err());
+        assert!(!transport.is_alive());
+    }
+
+    #[tokio::test]
+    async fn test_allow_list() {
+        let (reader, writer) = spawn_stdio_server();
+        let client = McpClient::initialize(
+            "jira",
+            &server_config(Some(vec!["search"])),
+            Transport::stdio(reader, writer, None),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(client.definitions().len(), 1);
+        assert_eq!(client.find_tool("jira__delete_issue"), None);
+        let err = client
+            .call_tool("delete_issue", &json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not allowed"));
+    }
+
+    /// Serve HTTP requests with `fake_response`, answering tool calls as an SSE stream
+    fn start_http_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                let mut has_session = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let lower = line.to_lowercase();
+                    if let Some(value) = lower.strip_prefix("content-length:") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                    has_session |= lower.starts_with("mcp-session-id: abc");
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let is_initialize = request["method"] == "initialize";
+                assert!(is_initialize || has_session, "session id missing");
+
+                let (content_type, payload) = match fake_response(&request) {
+                    None => ("application/json", String::new()),
+                    Some(response) if request["method"] == "tools/call" => (
+                        "text/event-stream",
+                        format!("event: message\ndata: {response}\n\n"),
+                    ),
+                    Some(response) => ("application/json", response.to_string()),
+                };
+                let status = if payload.is_empty() {
+                    "202 Accepted"
+                } else {
+                    "200 OK"
+                };
+                let session = if is_initialize {
+                    "Mcp-Session-Id: abc\r\n"
+                } else {
+                    ""
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\n{session}Content-Length: {}\r\nConnection: close\r\n\r\n{payload}",
+                    payload.len()
+                )
+                .unwrap();
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_http_client() {
+        let url = start_http_server();
+        let config = McpServerConfig {
+            url: Some(url),
+            ..Default::default()
+        };
+        let client = McpClient::connect("docs", &config).await.unwrap();
+        assert_eq!(client.definitions().len(), 2);
+
+        let text = client
+            .call_tool("search", &json!({"query": "mcp"}))
+            .await
+            .unwrap();
+        assert_eq!(text, "found \"mcp\"");
+    }
+
+    #[test]
+    fn test_http_requires_loopback() {
+        assert!(Transport::http("http://127.0.0.1:8808/mcp").is_ok());
+        assert!(Transport::http("http://localhost/mcp").is_ok());
+        assert!(Transport::http("http://example.com/mcp").is_err());
+    }
+
+    #[test]
+    fn test_tool_name_and_sse() {
+        assert_eq!(tool_name("my.server", "get issue"), "my_server__get_issue");
+        let messages =
+            parse_sse_messages("event: message\ndata: {\"id\": 1}\n\ndata: {\"id\":\ndata: 2}\n");
+        assert_eq!(messages, vec![json!({"id": 1}), json!({"id": 2})]);
+    }
+}
@@ -0,0 +1,660 @@
+//! `gia mcp-serve`: gia's capabilities as MCP tools over stdio.
+//!
+//! Requests are newline-delimited JSON-RPC messages on stdin, answers go to
+//! stdout (logs stay on stderr). Requests are handled one at a time and go through
+//! the same pipeline as the command line (`prepare_request`/`complete_request`),
+//! so roles, API key fallback and the conversation store are shared with `gia`.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::io::Write;
+use std::path::Path;
+use tabwriter::TabWriter;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use crate::app::{complete_request, prepare_request};
+use crate::cli::Config;
+use crate::constants::MCP_PROTOCOL_VERSION;
+use crate::conversation::{Conversation, ConversationManager};
+use crate::logging::{log_debug, log_info, log_warn};
+use crate::server::check_request_files;
+
+/// Protocol revisions we can answer in
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Audio formats accepted by transcribe_audio_file
+const AUDIO_EXTENSIONS: &[&str] = &["ogg", "opus", "mp3", "m4a"];
+
+const DEFAULT_CONVERSATION_LIMIT: u64 = 20;
+
+pub async fn run_mcp_server() -> Result<()> {
+    let conversation_manager =
+        ConversationManager::new().context("Failed to initialize conversation manager")?;
+    log_info("Serving MCP over stdio");
+    McpServer {
+        conversation_manager,
+    }
+    .serve(tokio::io::stdin(), tokio::io::stdout())
+    .await
+}
+
+struct McpServer {
+    conversation_manager: ConversationManager,
+}
+
+/// JSON-RPC error code and message
+type RpcError = (i64, String);
+
+impl McpServer {
+    async fn serve(
+        &self,
+        reader: impl AsyncRead + Unpin,
+        mut writer: impl AsyncWrite + Unpin,
+    ) -> Result<()> {
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => self.handle_message(&message).await,
+                Err(e) => Some(error_reply(
+                    Value::Null,
+                    (-32700, format!("Parse error: {e}")),
+                )),
+            };
+            if let Some(reply) = reply {
+                writer.write_all(format!("{reply}\n").as_bytes()).await?;
+                writer.flush().await?;
+            }
+        }
+
+        log_info("MCP client closed stdin, stopping server");
+        Ok(())
+    }
+
+    /// Answer a request; notifications and responses get no reply
+    async fn handle_message(&self, message: &Value) -> Option<Value> {
+        let method = message.get("method").and_then(Value::as_str)?;
+        let Some(id) = message.get("id").cloned() else {
+            log_debug(&format!("MCP notification: {method}"));
+            return None;
+        };
+        let params = message.get("params").cloned().unwrap_or_else(|| json!({}));
+
+        let result = match method {
+            "initialize" => Ok(initialize_result(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": tool_definitions()})),
+            "tools/call" => self.call_tool(&params).await,
+            _ => Err((-32601, format!("Method not found: {method}"))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(error) => error_reply(id, error),
+        })
+    }
+
+    /// Run a tool. Failures of the tool itself are reported as an `isError` result.
+    async fn call_tool(&self, params: &Value) -> std::result::Result<Value, RpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((-32602, "Missing tool name".to_string()))?;
+        let empty = json!({});
+        let args = params.get("arguments").unwrap_or(&empty);
+        log_info(&format!("MCP tool call: {name}({args})"));
+
+        let result = match name {
+            "ask" => self.ask(ask_args(args, false)).await,
+            "resume" => self.ask(ask_args(args, true)).await,
+            "transcribe_audio_file" => self.transcribe(args).await,
+            "list_conversations" => self.list_conversations(args).map(|text| vec![text]),
+            "show_conversation" => self.show_conversation(args).map(|text| vec![text]),
+            _ => return Err((-32602, format!("Unknown tool: {name}"))),
+        };
+
+        Ok(match result {
+            Ok(texts) => json!({
+                "content": texts
+                    .into_iter()
+                    .map(|text| json!({"type": "text", "text": text}))
+                    .collect::<Vec<_>>()
+            }),
+            Err(e) => {
+                log_warn(&format!("MCP tool {name} failed: {e:#}"));
+                json!({"content": [{"type": "text", "text": format!("{e:#}")}], "isError": true})
+            }
+        })
+    }
+
+    /// Run a request given as gia arguments; returns the response and a footer with
+    /// the model, conversation id and token usage
+    async fn ask(&self, args: Result<Vec<String>>) -> Result<Vec<String>> {
+        let mut config = Config::from_arg_list(&args?)?;
+        config.read_stdin = false;
+        config.remote_request = true;
+
+        let (mut conversation, api_keys) =
+            prepare_request(&mut config, &self.conversation_manager)?;
+        if config.ordered_content.is_empty() {
+            return Err(anyhow::anyhow!("No input content provided"));
+        }
+        let (response, usage) = complete_request(
+            &config,
+            &self.conversation_manager,
+            &mut conversation,
+            &api_keys,
+            None,
+        )
+        .await?;
+
+        let mut footer = format!("model: {}", config.model);
+        if !config.no_save {
+            footer.push_str(&format!("\nconversation_id: {}", conversation.id));
+        }
+        if usage.total_tokens.is_some() {
+            footer.push_str(&format!("\ntokens: {}", usage.format_short()));
+        }
+        Ok(vec![response, footer])
+    }
+
+    async fn transcribe(&self, args: &Value) -> Result<Vec<String>> {
+        let path =
+            string_arg(args, "path")?.ok_or_else(|| anyhow::anyhow!("Missing argument: path"))?;
+        let extension = Path::new(&path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        if !AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Unsupported audio file: {path} (supported: {})",
+                AUDIO_EXTENSIONS.join(", ")
+            ));
+        }
+        check_request_files(std::slice::from_ref(&path))?;
+        if !Path::new(&path).is_file() {
+            return Err(anyhow::anyhow!("Audio file not found: {path}"));
+        }
+
+        let mut prompt =
+            "Transcribe this audio recording verbatim. Reply with the transcript only.".to_string();
+        if let Some(language) = string_arg(args, "language")? {
+            prompt.push_str(&format!(" The recording is in {language}."));
+        }
+
+        let mut gia_args = vec!["--no-save".to_string(), "-f".to_string(), path];
+        if let Some(model) = string_arg(args, "model")? {
+            gia_args.extend(["-m".to_string(), model]);
+        }
+        gia_args.extend(["--".to_string(), prompt]);
+
+        let mut texts = self.ask(Ok(gia_args)).await?;
+        texts.truncate(1); // the transcript without the model footer
+        Ok(texts)
+    }
+
+    fn list_conversations(&self, args: &Value) -> Result<String> {
+        let limit = args
+            .get("limit")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_CONVERSATION_LIMIT) as usize;
+        let conversations = self.conversation_manager.list_conversations()?;
+        if conversations.is_empty() {
+            return Ok("No saved conversations found.".to_string());
+        }
+
+        let mut tw = TabWriter::new(Vec::new());
+        writeln!(tw, "index\tmessages\tage\tid\tpreview")?;
+        for (index, summary) in conversations.iter().take(limit).enumerate() {
+            let (preview, id, age, messages) = summary.format_as_table_columns();
+            writeln!(tw, "{index}\t{messages}\t{age}\t{id}\t{preview}")?;
+        }
+        let table = tw
+            .into_inner()
+            .map_err(|e| anyhow::anyhow!("Failed to format table: {e}"))?;
+        Ok(String::from_utf8_lossy(&table).into_owned())
+    }
+
+    fn show_conversation(&self, args: &Value) -> Result<String> {
+        let conversation = match string_arg(args, "id")?.filter(|id| !id.is_empty()) {
+            Some(id) => self.conversation_manager.load_conversation(&id)?,
+            None => self
+                .conversation_manager
+                .get_latest_conversation()?
+                .ok_or_else(|| anyhow::anyhow!("No saved conversations found"))?,
+        };
+        Ok(format_transcript(&conversation))
+    }
+}
+
+fn initialize_result(params: &Value) -> Value {
+    let requested = params
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let version = if SUPPORTED_PROTOCOL_VERSIONS.contains(&requested) {
+        requested
+    } else {
+        MCP_PROTOCOL_VERSION
+    };
+
+    json!({
+        "protocolVersion": version,
+        "capabilities": {"tools": {}},
+        "serverInfo": {"name": "gia", "version": env!("GIA_VERSION")},
+        "instructions": "Ask the configured AI models with gia's roles and tasks, and browse or continue saved gia conversations."
+    })
+}
+
+fn tool_definitions() -> Value {
+    let string_list = |description: &str| json!({"type": "array", "items": {"type": "string"}, "description": description});
+    json!([
+        {
+            "name": "ask",
+            "description": "Send a prompt to an AI model through gia, optionally with roles/tasks and files. Starts a new saved conversation and returns the answer and its conversation id.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "prompt": {"type": "string"},
+                    "model": {"type": "string", "description": "e.g. gemini-2.5-pro or ollama::llama3.2 (default: gia's default model or the role's)"},
+                    "roles": string_list("Role/task names (see list in `gia roles list`)"),
+                    "files": string_list("Files or directories to include, relative to the server's working directory"),
+                    "urls": string_list("Web pages to include"),
+                    "no_save": {"type": "boolean", "description": "Don't save the conversation"}
+                },
+                "required": ["prompt"]
+            }
+        },
+        {
+            "name": "resume",
+            "description": "Continue a saved gia conversation with a new prompt",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "prompt": {"type": "string"},
+                    "id": {"type": "string", "description": "Conversation id, id suffix or index from list_conversations (default: latest)"},
+                    "model": {"type": "string"},
+                    "roles": string_list("Role/task names"),
+                    "files": string_list("Files or directories to include"),
+                    "urls": string_list("Web pages to include")
+                },
+                "required": ["prompt"]
+            }
+        },
+        {
+            "name": "list_conversations",
+            "description": "List saved gia conversations, newest first",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": {"type": "integer", "description": "Maximum number of conversations (default: 20)"}
+                }
+            }
+        },
+        {
+            "name": "show_conversation",
+            "description": "Show the prompts and answers of a saved gia conversation",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "id": {"type": "string", "description": "Conversation id, id suffix or index (default: latest)"}
+                }
+            }
+        },
+        {
+            "name": "transcribe_audio_file",
+            "description": "Transcribe an audio file (ogg, opus, mp3, m4a) with an AI model. Nothing is saved.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string"},
+                    "language": {"type": "string", "description": "Spoken language, e.g. German"},
+                    "model": {"type": "string"}
+                },
+                "required": ["path"]
+            }
+        }
+    ])
+}
+
+/// gia command line arguments for an ask/resume tool call
+fn ask_args(args: &Value, resume: bool) -> Result<Vec<String>> {
+    let prompt = string_arg(args, "prompt")?
+        .filter(|prompt| !prompt.trim().is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Missing argument: prompt"))?;
+
+    let mut gia_args = Vec::new();
+    if let Some(model) = string_arg(args, "model")? {
+        gia_args.extend(["-m".to_string(), model]);
+    }
+    for (key, flag) in [("roles", "-t"), ("files", "-f"), ("urls", "-u")] {
+        let values = string_list_arg(args, key)?;
+        // Clients may only send files of the working directory, as over the HTTP API
+        if key == "files" {
+            check_request_files(&values)?;
+        }
+        for value in values {
+            gia_args.extend([flag.to_string(), value]);
+        }
+    }
+    if resume {
+        match string_arg(args, "id")?.filter(|id| !id.is_empty()) {
+            Some(id) => gia_args.push(format!("--resume={id}")),
+            None => gia_args.push("-R".to_string()),
+        }
+    } else if args.get("no_save").and_then(Value::as_bool) == Some(true) {
+        gia_args.push("--no-save".to_string());
+    }
+    gia_args.extend(["--".to_string(), prompt]);
+    Ok(gia_args)
+}
+
+fn string_arg(args: &Value, name: &str) -> Result<Option<String>> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(anyhow::anyhow!("Argument '{name}' must be a string")),
+    }
+}
+
+fn string_list_arg(args: &Value, name: &str) -> Result<Vec<String>> {
+    match args.get(name) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(value)) => Ok(vec![value.clone()]),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow::anyhow!("Argument '{name}' must be a list of strings"))
+            })
+            .collect(),
+        Some(_) => Err(anyhow::anyhow!(
+            "Argument '{name}' must be a list of strings"
+        )),
+    }
+}
+
+/// Prompts and answers of a conversation (file contents and tool results left out)
+fn format_transcript(conversation: &Conversation) -> String {
+    let mut transcript = format!(
+        "Conversation {} ({}, {} messages, updated {})\n",
+        conversation.id,
+        conversation.metadata.model_used,
+        conversation.messages.len(),
+        conversation.updated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    for message in &conversation.messages {
+        let text = match message.role.as_str() {
+            "User" => Conversation::extract_prompt_section(message),
+            "Assistant" => Conversation::extract_text_content(message),
+            _ => continue,
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+        transcript.push_str(&format!("\n## {}\n\n{}\n", message.role, text.trim_end()));
+    }
+    transcript
+}
+
+fn error_reply(id: Value, (code, message): RpcError) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_part_wrapper::{ChatMessageWrapper, MessageContentWrapper};
+    use crate::conversation::TokenUsage;
+    use tempfile::TempDir;
+
+    fn server(dir: &TempDir) -> McpServer {
+        McpServer {
+            conversation_manager: ConversationManager::with_dir(dir.path().to_path_buf()),
+        }
+    }
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({"jsonrpc": "2.0", "id": 7, "method": method, "params": params})
+    }
+
+    fn text_message(role: &str, text: &str) -> ChatMessageWrapper {
+        ChatMessageWrapper {
+            role: role.to_string(),
+            content: MessageContentWrapper::Text {
+                text: text.to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protocol() {
+        let dir = TempDir::new().unwrap();
+        let server = server(&dir);
+
+        let reply = server
+            .handle_message(&request(
+                "initialize",
+                json!({"protocolVersion": "2024-11-05", "capabilities": {}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(reply["id"], 7);
+        assert_eq!(reply["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(reply["result"]["serverInfo"]["name"], "gia");
+
+        let notification = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle_message(&notification).await.is_none());
+
+        let reply = server
+            .handle_message(&request("tools/list", json!({})))
+            .await
+            .unwrap();
+        let names: Vec<&str> = reply["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "ask",
+                "resume",
+                "list_conversations",
+                "show_conversation",
+                "transcribe_audio_file"
+            ]
+        );
+
+        let reply = server
+            .handle_message(&request("resources/list", json!({})))
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], -32601);
+
+        let reply = server
+            .handle_message(&request("tools/call", json!({"name": "delete_everything"})))
+            .await
+            .unwrap();
+        assert_eq!(reply["error"]["code"], -32602);
+    }
+
+    #[tokio::test]
+    async fn test_conversation_tools() {
+        let dir = TempDir::new().unwrap();
+        let server = server(&dir);
+
+        let reply = server
+            .handle_message(&request(
+                "tools/call",
+                json!({"name": "list_conversations", "arguments": {}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            reply["result"]["content"][0]["text"],
+            "No saved conversations found."
+        );
+
+        let mut conversation = Conversation::new("gemini-2.5-flash".to_string());
+        conversation.add_message_with_usage(
+            text_message("User", "What is MCP?"),
+            Vec::new(),
+            TokenUsage::default(),
+        );
+        conversation.add_message_with_usage(
+            text_message("Assistant", "The Model Context Protocol."),
+            Vec::new(),
+            TokenUsage::default(),
+        );
+        server
+            .conversation_manager
+            .save_conversation(&conversation)
+            .unwrap();
+
+        let reply = server
+            .handle_message(&request(
+                "tools/call",
+                json!({"name": "list_conversations", "arguments": {"limit": 5}}),
+            ))
+            .await
+            .unwrap();
+        let table = reply["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(table.contains(&conversation.id));
+        assert!(table.contains("What is MCP?"));
+
+        let reply = server
+            .handle_message(&request(
+                "tools/call",
+                json!({"name": "show_conversation", "arguments": {}}),
+            ))
+            .await
+            .unwrap();
+        let transcript = reply["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(transcript.contains("## User\n\nWhat is MCP?"));
+        assert!(transcript.contains("## Assistant\n\nThe Model Context Protocol."));
+
+        let reply = server
+            .handle_message(&request(
+                "tools/call",
+                json!({"name": "show_conversation", "arguments": {"id": "missing"}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_rejects_other_files() {
+        let dir = TempDir::new().unwrap();
+        let server = server(&dir);
+        let reply = server
+            .handle_message(&request(
+                "tools/call",
+                json!({"name": "transcribe_audio_file", "arguments": {"path": "notes.txt"}}),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(reply["result"]["isError"], true);
+        assert!(
+            reply["result"]["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("Unsupported audio file")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_files_outside_working_directory() {
+        let dir = TempDir::new().unwrap();
+        let server = server(&dir);
+        for (name, arguments) in [
+            (
+                "ask",
+                json!({"prompt": "Read", "files": ["../outside.txt"]}),
+            ),
+            ("transcribe_audio_file", json!({"path": "/tmp/call.ogg"})),
+        ] {
+            let reply = server
+                .handle_message(&request(
+                    "tools/call",
+                    json!({"name": name, "arguments": arguments}),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(reply["result"]["isError"], true);
+            assert!(
+                reply["result"]["content"][0]["text"]
+                    .as_str()
+                    .unwrap()
+                    .contains("Only files inside the server's working directory"),
+                "{reply}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ask_args() {
+        let args = ask_args(
+            &json!({"prompt": "Review", "model": "ollama::llama3.2", "roles": ["rust-dev"], "files": "src/main.rs", "no_save": true}),
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "-m",
+                "ollama::llama3.2",
+                "-t",
+                "rust-dev",
+                "-f",
+                "src/main.rs",
+                "--no-save",
+                "--",
+                "Review"
+            ]
+        );
+
+        let args = ask_args(&json!({"prompt": "-1 more", "id": "ab12"}), true).unwrap();
+        assert_eq!(args, vec!["--resume=ab12", "--", "-1 more"]);
+        let config = Config::from_arg_list(&args).unwrap();
+        assert_eq!(config.prompt, "-1 more");
+        assert_eq!(config.resume_conversation.as_deref(), Some("ab12"));
+
+        assert_eq!(
+            ask_args(&json!({"prompt": "Go on"}), true).unwrap(),
+            vec!["-R", "--", "Go on"]
+        );
+        assert!(ask_args(&json!({}), false).is_err());
+        assert!(ask_args(&json!({"prompt": "x", "roles": [1]}), false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_serve_over_stdio() {
+        let dir = TempDir::new().unwrap();
+        let server = server(&dir);
+        let input = format!(
+            "{}\n\nnot json\n{}\n",
+            request("ping", json!({})),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"})
+        );
+        let mut output = Vec::new();
+        server.serve(input.as_bytes(), &mut output).await.unwrap();
+
+        let replies: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0]["result"], json!({}));
+        assert_eq!(replies[1]["error"]["code"], -32700);
+    }
+}
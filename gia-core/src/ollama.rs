@@ -0,0 +1,242 @@
+//! Ollama provider implementation for local LLM integration.
+//!
+//! # Token Usage Limitation
+//!
+//! The `genai` crate (v0.4) does not currently expose token usage statistics
+//! for Ollama responses. All token counts default to zero. This is a known
+//! limitation of the underlying library, not this implementation.
+
+use crate::constants::DEFAULT_OLLAMA_BASE_URL;
+use crate::conversation::TokenUsage;
+use crate::logging::{log_debug, log_info};
+use crate::provider::{AiProvider, AiResponse, TextCallback, exec_chat_streaming};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use genai::Client;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, Tool};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub struct OllamaClient {
+    model: String,
+    client: Client,
+    temperature: Option<f64>,
+}
+
+impl OllamaClient {
+    pub fn new(model: String) -> Result<Self> {
+        log_info(&format!("Initializing Ollama client with model: {}", model));
+
+        let client = Client::default();
+
+        Ok(Self {
+            model,
+            client,
+            temperature: None,
+        })
+    }
+
+    /// Set the sampling temperature (None = model default)
+    pub fn with_temperature(mut self, temperature: Option<f64>) -> Self {
+        self.temperature = temperature;
+        self
+    }
+}
+
+#[async_trait]
+impl AiProvider for OllamaClient {
+    async fn generate_content_with_chat_messages(
+        &mut self,
+        chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
+    ) -> Result<AiResponse> {
+        log_debug(&format!(
+            "Sending chat request to Ollama API with {} message(s)",
+            chat_messages.len()
+        ));
+
+        let mut chat_req = ChatRequest::new(chat_messages);
+        if !tools.is_empty() {
+            chat_req = chat_req.with_tools(tools.to_vec());
+        }
+
+        let chat_options = self
+            .temperature
+            .map(|temperature| ChatOptions::default().with_temperature(temperature));
+
+        let chat_res = self
+            .client
+            .exec_chat(&self.model, chat_req, chat_options.as_ref())
+            .await
+            .context("Failed to execute Ollama chat request")?;
+
+        let content = chat_res.first_text().unwrap_or("").to_string();
+        let tool_calls: Vec<_> = chat_res.tool_calls().into_iter().cloned().collect();
+
+        if content.trim().is_empty() && tool_calls.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No content was generated by the AI. The response was empty or contained only whitespace."
+            ));
+        }
+
+        log_info(&format!(
+            "Received response from Ollama API, length: {}",
+            content.len()
+        ));
+
+        // genai doesn't expose token usage for Ollama, use default
+        let usage = TokenUsage::default();
+
+        Ok(AiResponse {
+            content,
+            usage,
+            tool_calls,
+        })
+    }
+
+    async fn generate_content_streaming(
+        &mut self,
+        chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
+        on_text: TextCallback<'_>,
+    ) -> Result<AiResponse> {
+        log_debug(&format!(
+            "Streaming chat request to Ollama API with {} message(s)",
+            chat_messages.len()
+        ));
+
+        let mut chat_req = ChatRequest::new(chat_messages);
+        if !tools.is_empty() {
+            chat_req = chat_req.with_tools(tools.to_vec());
+        }
+        let chat_options = self
+            .temperature
+            .map(|temperature| ChatOptions::default().with_temperature(temperature));
+
+        let response = exec_chat_streaming(
+            &self.client,
+            &self.model,
+            chat_req,
+            chat_options.as_ref(),
+            on_text,
+        )
+        .await
+        .context("Failed to execute Ollama chat request")?;
+
+        log_info(&format!(
+            "Received streamed response from Ollama API, length: {}",
+            response.content.len()
+        ));
+        Ok(response)
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "Ollama"
+    }
+}
+
+/// Installed models of the Ollama server (OLLAMA_API_BASE or localhost) as
+/// `ollama::name`; empty if Ollama isn't reachable
+pub fn fetch_ollama_models() -> Vec<String> {
+    let base_url =
+        std::env::var("OLLAMA_API_BASE").unwrap_or_else(|_| DEFAULT_OLLAMA_BASE_URL.to_string());
+    let url = format!("{}/api/tags", base_url.trim_end_matches('/'));
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(2)))
+        .build()
+        .into();
+    let body = match agent
+        .get(&url)
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+    {
+        Ok(body) => body,
+        Err(e) => {
+            // Ollama may not be running
+            log_debug(&format!("Ollama models not available from {url}: {e}"));
+            return Vec::new();
+        }
+    };
+
+    let tags: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    tags["models"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| model["name"].as_str())
+        .map(|name| format!("ollama::{name}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use genai::chat::{ChatMessage, MessageContent};
+    use serial_test::serial;
+
+    #[tokio::test]
+    async fn test_ollama_client_creation() {
+        let model = "llama3.2".to_string();
+        let client = OllamaClient::new(model.clone());
+        assert!(client.is_ok());
+
+        let client = client.unwrap();
+        assert_eq!(client.model_name(), &model);
+        assert_eq!(client.provider_name(), "Ollama");
+    }
+
+    #[tokio::test]
+    async fn test_ollama_error_handling() {
+        let mut client = OllamaClient::new("llama3.2".to_string()).unwrap();
+
+        let messages = vec![ChatMessage {
+            role: genai::chat::ChatRole::User,
+            content: MessageContent::from_text(""),
+            options: None,
+        }];
+
+        // Will fail with connection error or empty response error
+        let result = client
+            .generate_content_with_chat_messages(messages, &[])
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_fetch_ollama_models() {
+        use std::io::{Read, Write};
+
+        // Fake Ollama answering one /api/tags request
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let size = stream.read(&mut request).unwrap();
+            assert!(String::from_utf8_lossy(&request[..size]).starts_with("GET /api/tags "));
+            let body = r#"{"models":[{"name":"llama3.2:latest"},{"name":"qwen3:8b"}]}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+
+        unsafe { std::env::set_var("OLLAMA_API_BASE", format!("{base_url}/")) };
+        let models = fetch_ollama_models();
+        server.join().unwrap();
+        unsafe { std::env::set_var("OLLAMA_API_BASE", "http://127.0.0.1:9") };
+        let unreachable = fetch_ollama_models();
+        unsafe { std::env::remove_var("OLLAMA_API_BASE") };
+
+        assert_eq!(models, vec!["ollama::llama3.2:latest", "ollama::qwen3:8b"]);
+        assert!(unreachable.is_empty());
+    }
+}
@@ -0,0 +1,566 @@
+//! OpenAI-compatible endpoints of `gia serve` (`/v1/chat/completions` and
+//! `/v1/models`), so clients that only speak the OpenAI API can use gia's
+//! providers: Gemini with API key fallback and Ollama (`ollama::model`).
+//!
+//! The client sends the whole chat history with every request; roles, files and
+//! tools of gia are not involved. With `--save-proxied` each exchange is saved as
+//! a new gia conversation.
+
+use anyhow::{Context, Result};
+use axum::Json;
+use axum::extract::State;
+use axum::extract::rejection::JsonRejection;
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use futures::StreamExt;
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::convert::Infallible;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::constants::{GEMINI_MODELS, get_default_model};
+use crate::content_part_wrapper::{ChatMessageWrapper, ContentPartWrapper, MessageContentWrapper};
+use crate::conversation::{Conversation, TokenUsage};
+use crate::logging::{log_info, log_warn};
+use crate::provider::{AiProvider, ProviderConfig, ProviderFactory, TextCallback};
+use crate::server::ServerState;
+
+/// An error answered in OpenAI's format
+#[derive(Debug)]
+struct OpenAiError(StatusCode, String);
+
+impl IntoResponse for OpenAiError {
+    fn into_response(self) -> Response {
+        let error_type = if self.0.is_client_error() {
+            "invalid_request_error"
+        } else {
+            "server_error"
+        };
+        let body = json!({"error": {"message": self.1, "type": error_type, "code": null}});
+        (self.0, Json(body)).into_response()
+    }
+}
+
+impl From<anyhow::Error> for OpenAiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"))
+    }
+}
+
+fn invalid_request(e: impl std::fmt::Display) -> OpenAiError {
+    OpenAiError(StatusCode::BAD_REQUEST, e.to_string())
+}
+
+/// Body of `POST /v1/chat/completions`; other OpenAI parameters are ignored
+#[derive(Debug, Deserialize)]
+struct CompletionRequest {
+    #[serde(default)]
+    model: Option<String>,
+    messages: Vec<OpenAiMessage>,
+    #[serde(default)]
+    stream: bool,
+    #[serde(default)]
+    stream_options: Option<StreamOptions>,
+    #[serde(default)]
+    temperature: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamOptions {
+    #[serde(default)]
+    include_usage: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<Value>, // a string or a list of parts
+}
+
+impl OpenAiMessage {
+    /// The message as stored in gia conversations
+    fn to_wrapper(&self) -> Result<ChatMessageWrapper> {
+        let role = match self.role.as_str() {
+            "system" | "developer" => "System",
+            "user" => "User",
+            "assistant" => "Assistant",
+            other => return Err(anyhow::anyhow!("Unsupported message role: {other}")),
+        };
+
+        let content = match &self.content {
+            None | Some(Value::Null) => MessageContentWrapper::Text {
+                text: String::new(),
+            },
+            Some(Value::String(text)) => MessageContentWrapper::Text { text: text.clone() },
+            Some(Value::Array(parts)) => MessageContentWrapper::Parts {
+                parts: parts.iter().map(content_part).collect::<Result<_>>()?,
+            },
+            Some(_) => {
+                return Err(anyhow::anyhow!(
+                    "Message content must be a string or a list"
+                ));
+            }
+        };
+
+        Ok(ChatMessageWrapper {
+            role: role.to_string(),
+            content,
+        })
+    }
+}
+
+/// A text or image part; images must be inline `data:` URLs
+fn content_part(part: &Value) -> Result<ContentPartWrapper> {
+    match part["type"].as_str() {
+        Some("text") => Ok(ContentPartWrapper::Text(
+            part["text"].as_str().unwrap_or_default().to_string(),
+        )),
+        Some("image_url") => {
+            let url = part["image_url"]["url"]
+                .as_str()
+                .or_else(|| part["image_url"].as_str())
+                .unwrap_or_default();
+            let (mime_type, data) = url
+                .strip_prefix("data:")
+                .and_then(|rest| rest.split_once(";base64,"))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Only base64 data: URLs are supported for images")
+                })?;
+            Ok(ContentPartWrapper::Image {
+                path: None,
+                mime_type: mime_type.to_string(),
+                data: data.to_string(),
+            })
+        }
+        other => Err(anyhow::anyhow!(
+            "Unsupported content part type: {}",
+            other.unwrap_or("none")
+        )),
+    }
+}
+
+/// `GET /v1/models`: the Gemini models and the models installed in Ollama
+pub async fn list_models() -> Json<Value> {
+    let ollama_models = tokio::task::spawn_blocking(crate::ollama::fetch_ollama_models)
+        .await
+        .unwrap_or_default();
+
+    let mut models: Vec<String> = GEMINI_MODELS.iter().map(|m| m.to_string()).collect();
+    let default_model = get_default_model();
+    if !models.contains(&default_model) && !default_model.starts_with("ollama::") {
+        models.push(default_model);
+    }
+    models.extend(ollama_models);
+
+    let data: Vec<Value> = models
+        .into_iter()
+        .map(|id| {
+            let owned_by = if id.starts_with("ollama::") {
+                "ollama"
+            } else {
+                "google"
+            };
+            json!({"id": id, "object": "model", "created": 0, "owned_by": owned_by})
+        })
+        .collect();
+    Json(json!({"object": "list", "data": data}))
+}
+
+/// `POST /v1/chat/completions`
+pub async fn chat_completions(
+    State(state): State<ServerState>,
+    payload: Result<Json<Value>, JsonRejection>,
+) -> Response {
+    let body = match payload {
+        Ok(Json(body)) => body,
+        Err(e) => {
+            return OpenAiError(
+                e.status(),
+                format!("Invalid request body: {}", e.body_text()),
+            )
+            .into_response();
+        }
+    };
+    match start_completion(body) {
+        Ok((request, messages, provider)) => {
+            if request.stream {
+                stream_completion(state, request, messages, provider).into_response()
+            } else {
+                match run_completion(&state, &request, messages, provider, None).await {
+                    Ok(completion) => Json(completion.to_json()).into_response(),
+                    Err(e) => e.into_response(),
+                }
+            }
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+type StartedCompletion = (
+    CompletionRequest,
+    Vec<ChatMessageWrapper>,
+    Box<dyn AiProvider>,
+);
+
+/// Parse the request and create the provider for its model
+fn start_completion(body: Value) -> Result<StartedCompletion, OpenAiError> {
+    let mut request: CompletionRequest = serde_json::from_value(body)
+        .map_err(|e| invalid_request(format!("Invalid request body: {e}")))?;
+    let model = request
+        .model
+        .take()
+        .filter(|model| !model.is_empty())
+        .unwrap_or_else(get_default_model);
+    if !ProviderFactory::is_supported_model(&model) {
+        return Err(invalid_request(format!("Unsupported model: {model}")));
+    }
+    let messages: Vec<ChatMessageWrapper> = request
+        .messages
+        .iter()
+        .map(OpenAiMessage::to_wrapper)
+        .collect::<Result<_>>()
+        .map_err(invalid_request)?;
+    if !messages.iter().any(|message| message.role == "User") {
+        return Err(invalid_request("No user message"));
+    }
+
+    let api_keys = if model.to_lowercase().starts_with("ollama::") {
+        Vec::new()
+    } else {
+        crate::api_key::get_configured_api_keys().ok_or_else(|| {
+            anyhow::anyhow!(
+                "No API keys found. Set GEMINI_API_KEY (pipe-separated for fallback) or api_keys in ~/.gia/config."
+            )
+        })?
+    };
+    let preferred_api_key_index = if api_keys.is_empty() {
+        0
+    } else {
+        rand::thread_rng().gen_range(0..api_keys.len())
+    };
+    let provider = ProviderFactory::create_provider(ProviderConfig {
+        model: model.clone(),
+        api_keys,
+        preferred_api_key_index,
+        temperature: request.temperature,
+    })?;
+
+    log_info(&format!(
+        "OpenAI-compatible request with model {model} and {} message(s)",
+        messages.len()
+    ));
+    request.model = Some(model);
+    Ok((request, messages, provider))
+}
+
+struct Completion {
+    id: String,
+    model: String,
+    content: String,
+    usage: TokenUsage,
+}
+
+impl Completion {
+    fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": chrono::Utc::now().timestamp(),
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": self.content},
+                "finish_reason": "stop"
+            }],
+            "usage": usage_json(&self.usage)
+        })
+    }
+}
+
+/// Send the messages to the provider and save the exchange if configured
+async fn run_completion(
+    state: &ServerState,
+    request: &CompletionRequest,
+    messages: Vec<ChatMessageWrapper>,
+    mut provider: Box<dyn AiProvider>,
+    on_text: Option<TextCallback<'_>>,
+) -> Result<Completion, OpenAiError> {
+    let chat_messages = messages
+        .iter()
+        .map(ChatMessageWrapper::to_genai_chat_message)
+        .collect::<Result<Vec<_>>>()?;
+    let response = match on_text {
+        Some(on_text) => {
+            provider
+                .generate_content_streaming(chat_messages, &[], on_text)
+                .await
+        }
+        None => {
+            provider
+                .generate_content_with_chat_messages(chat_messages, &[])
+                .await
+        }
+    }
+    .context("Failed to generate content")?;
+
+    let model = request.model.clone().unwrap_or_default();
+    if state.save_proxied {
+        let conversation = proxied_conversation(
+            &model,
+            messages,
+            &response.content,
+            response.usage,
+            provider.current_api_key_index().unwrap_or(0),
+        );
+        if let Err(e) = state.conversation_manager.save_conversation(&conversation) {
+            log_warn(&format!("Failed to save proxied conversation: {e:#}"));
+        } else {
+            log_info(&format!("Saved proxied exchange as {}", conversation.id));
+        }
+    }
+
+    Ok(Completion {
+        id: format!("chatcmpl-{}", uuid::Uuid::new_v4().simple()),
+        model,
+        content: response.content,
+        usage: response.usage,
+    })
+}
+
+/// Run the completion in the background and answer with `chat.completion.chunk`
+/// events, ending with `[DONE]`
+fn stream_completion(
+    state: ServerState,
+    request: CompletionRequest,
+    messages: Vec<ChatMessageWrapper>,
+    provider: Box<dyn AiProvider>,
+) -> impl IntoResponse {
+    let id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+    let model = request.model.clone().unwrap_or_default();
+    let created = chrono::Utc::now().timestamp();
+    let chunk = move |delta: Value, finish_reason: Option<&str>| {
+        json!({
+            "id": id,
+            "object": "chat.completion.chunk",
+            "created": created,
+            "model": model,
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}]
+        })
+    };
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let send = |data: Value| {
+            let _ = sender.send(Event::default().data(data.to_string()));
+        };
+        send(chunk(json!({"role": "assistant", "content": ""}), None));
+
+        let text_chunk = chunk.clone();
+        let text_sender = sender.clone();
+        let mut on_text = move |text: &str| {
+            let data = text_chunk(json!({"content": text}), None);
+            let _ = text_sender.send(Event::default().data(data.to_string()));
+        };
+        match run_completion(&state, &request, messages, provider, Some(&mut on_text)).await {
+            Ok(completion) => {
+                send(chunk(json!({}), Some("stop")));
+                if request
+                    .stream_options
+                    .as_ref()
+                    .is_some_and(|options| options.include_usage)
+                {
+                    let mut usage = chunk(json!({}), None);
+                    usage["choices"] = json!([]);
+                    usage["usage"] = usage_json(&completion.usage);
+                    send(usage);
+                }
+            }
+            Err(OpenAiError(_, message)) => {
+                log_warn(&format!("Streamed completion failed: {message}"));
+                send(json!({"error": {"message": message, "type": "server_error", "code": null}}));
+            }
+        }
+        let _ = sender.send(Event::default().data("[DONE]"));
+    });
+
+    let events = UnboundedReceiverStream::new(receiver).map(Ok::<_, Infallible>);
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn usage_json(usage: &TokenUsage) -> Value {
+    json!({
+        "prompt_tokens": usage.prompt_tokens.unwrap_or(0),
+        "completion_tokens": usage.completion_tokens.unwrap_or(0),
+        "total_tokens": usage.total_tokens.unwrap_or(0)
+    })
+}
+
+/// The proxied messages and the answer as a new gia conversation
+fn proxied_conversation(
+    model: &str,
+    messages: Vec<ChatMessageWrapper>,
+    answer: &str,
+    usage: TokenUsage,
+    api_key_index: usize,
+) -> Conversation {
+    let first_prompt = messages
+        .iter()
+        .find(|message| message.role == "User")
+        .map(Conversation::extract_text_content)
+        .unwrap_or_default();
+    let mut conversation =
+        Conversation::new_with_prompt(model.to_string(), &first_prompt, api_key_index);
+    for message in messages {
+        conversation.add_message_with_usage(message, Vec::new(), TokenUsage::default());
+    }
+    conversation.add_message_with_usage(
+        ChatMessageWrapper {
+            role: "Assistant".to_string(),
+            content: MessageContentWrapper::Text {
+                text: answer.to_string(),
+            },
+        },
+        Vec::new(),
+        usage,
+    );
+    conversation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn test_message_conversion() {
+        let request: CompletionRequest = serde_json::from_value(json!({
+            "model": "ollama::llama3.2",
+            "max_tokens": 100,
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "What is this?"},
+                    {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0K"}}
+                ]},
+                {"role": "assistant", "content": null}
+            ]
+        }))
+        .unwrap();
+        let messages: Vec<ChatMessageWrapper> = request
+            .messages
+            .iter()
+            .map(|message| message.to_wrapper().unwrap())
+            .collect();
+        assert_eq!(messages[0].role, "System");
+        assert_eq!(messages[1].role, "User");
+        let MessageContentWrapper::Parts { parts } = &messages[1].content else {
+            panic!("expected parts");
+        };
+        assert!(matches!(&parts[0], ContentPartWrapper::Text(text) if text == "What is this?"));
+        assert!(matches!(
+            &parts[1],
+            ContentPartWrapper::Image { mime_type, data, .. } if mime_type == "image/png" && data == "iVBORw0K"
+        ));
+        assert_eq!(messages[2].role, "Assistant");
+
+        let remote_image =
+            json!({"type": "image_url", "image_url": {"url": "https://example.com/a.png"}});
+        assert!(content_part(&remote_image).is_err());
+        let tool = OpenAiMessage {
+            role: "tool".to_string(),
+            content: Some(json!("42")),
+        };
+        assert!(tool.to_wrapper().is_err());
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let error = |body: Value| match start_completion(body.clone()) {
+            Ok(_) => panic!("expected an error for {body}"),
+            Err(OpenAiError(status, message)) => (status, message),
+        };
+
+        let (status, message) = error(json!({"model": "gpt-4o"}));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.starts_with("Invalid request body"));
+
+        let (_, message) = error(json!({"model": "openai::gpt-4o", "messages": []}));
+        assert_eq!(message, "Unsupported model: openai::gpt-4o");
+
+        let (_, message) = error(json!({
+            "model": "ollama::llama3.2",
+            "messages": [{"role": "system", "content": "Be brief."}]
+        }));
+        assert_eq!(message, "No user message");
+
+        let response = error(json!({"messages": []})).0.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let (request, messages, provider) = start_completion(
+            json!({"model": "ollama::llama3.2", "messages": [{"role": "user", "content": "Hi"}], "stream": true}),
+        )
+        .unwrap();
+        assert!(request.stream);
+        assert_eq!(request.model.as_deref(), Some("ollama::llama3.2"));
+        assert_eq!(messages.len(), 1);
+        assert_eq!(provider.provider_name(), "Ollama");
+    }
+
+    #[test]
+    fn test_proxied_conversation() {
+        let messages = vec![
+            OpenAiMessage {
+                role: "system".to_string(),
+                content: Some(json!("Be brief.")),
+            }
+            .to_wrapper()
+            .unwrap(),
+            OpenAiMessage {
+                role: "user".to_string(),
+                content: Some(json!("Explain server-sent events")),
+            }
+            .to_wrapper()
+            .unwrap(),
+        ];
+        let usage = TokenUsage {
+            prompt_tokens: Some(10),
+            completion_tokens: Some(5),
+            total_tokens: Some(15),
+        };
+        let conversation =
+            proxied_conversation("gemini-2.5-flash", messages, "A one-way stream.", usage, 1);
+
+        assert!(conversation.id.starts_with("explain-server-sent-events-"));
+        assert_eq!(conversation.messages.len(), 3);
+        assert_eq!(conversation.messages[2].role, "Assistant");
+        assert_eq!(conversation.metadata.api_key_index, 1);
+        assert_eq!(
+            conversation.metadata.token_usage_per_message[2].total_tokens,
+            Some(15)
+        );
+        assert_eq!(usage_json(&usage)["completion_tokens"], 5);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_list_models_without_ollama() {
+        unsafe { std::env::set_var("OLLAMA_API_BASE", "http://127.0.0.1:9") };
+        unsafe { std::env::remove_var("GIA_DEFAULT_MODEL") };
+        let Json(models) = list_models().await;
+        unsafe { std::env::remove_var("OLLAMA_API_BASE") };
+
+        assert_eq!(models["object"], "list");
+        let ids: Vec<&str> = models["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|model| model["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, GEMINI_MODELS);
+        assert_eq!(models["data"][0]["owned_by"], "google");
+    }
+}
@@ -4,10 +4,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tts::Tts;
 
-use crate::browser_preview::{FooterMetadata, open_markdown_preview};
+pub use crate::browser_preview::FooterMetadata;
+use crate::browser_preview::open_markdown_preview;
 use crate::cli::{Config, ContentSource, OutputMode};
 use crate::clipboard::write_clipboard;
+use crate::config_file::{is_language_tag, load_settings};
 use crate::conversation::{Conversation, TokenUsage};
+use crate::git::commit_editmsg_path;
 use crate::logging::{log_error, log_info, log_trace};
 
 #[cfg(not(target_os = "macos"))]
@@ -85,6 +88,9 @@ fn wrap_text(text: &str, width: usize) -> String {
 }
 
 pub fn get_outputs_dir() -> Result<PathBuf> {
+    if let Some(dir) = load_settings().outputs_dir {
+        return Ok(dir);
+    }
     let home_dir =
         dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
     Ok(home_dir.join(".gia").join("outputs"))
@@ -97,6 +103,7 @@ fn show_audio_completion_notification(output_mode: &OutputMode) {
         OutputMode::TempFileWithPreview => "Recording complete! Preview opened in browser.",
         OutputMode::Stdout => "Recording complete! Check your terminal.",
         OutputMode::Tts(_) => "Recording complete! Playing audio response.",
+        OutputMode::CommitMessage => "Recording complete! Commit message written.",
     };
 
     #[cfg(target_os = "macos")]
@@ -124,7 +131,8 @@ fn show_audio_completion_notification(output_mode: &OutputMode) {
     }
 }
 
-fn build_footer_metadata(config: &Config, token_usage: Option<TokenUsage>) -> FooterMetadata {
+/// Collect the footer details of a response from the request config
+pub fn build_footer_metadata(config: &Config, token_usage: Option<TokenUsage>) -> FooterMetadata {
     // Parse provider and model from config.model
     let (provider_name, model_name) = if config.model.contains("::") {
         let parts: Vec<&str> = config.model.splitn(2, "::").collect();
@@ -136,6 +144,8 @@ fn build_footer_metadata(config: &Config, token_usage: Option<TokenUsage>) -> Fo
     // Extract file information from ordered_content
     let mut image_files = Vec::new();
     let mut text_files = Vec::new();
+    let mut git_sources = Vec::new();
+    let mut web_pages = Vec::new();
     let mut has_clipboard = false;
     let mut has_audio = false;
     let mut has_stdin = false;
@@ -156,11 +166,25 @@ fn build_footer_metadata(config: &Config, token_usage: Option<TokenUsage>) -> Fo
                     image_files.push(filename.to_string_lossy().to_string());
                 }
             }
-            ContentSource::TextFile(path, _) => {
+            ContentSource::TextFile(path, _, _) => {
                 if let Some(filename) = Path::new(path).file_name() {
                     text_files.push(filename.to_string_lossy().to_string());
                 }
             }
+            ContentSource::GitDiff(description, _) => {
+                git_sources.push(format!("diff {description}"));
+            }
+            ContentSource::GitLog(range, _) => {
+                git_sources.push(format!("log {range}"));
+            }
+            ContentSource::WebPage(url, _) => {
+                web_pages.push(url.clone());
+            }
+            ContentSource::EmbeddedImage(source, _, _) => {
+                if let Some(filename) = source.rsplit('/').next().filter(|name| !name.is_empty()) {
+                    image_files.push(filename.to_string());
+                }
+            }
             ContentSource::ClipboardText(_) | ContentSource::ClipboardImage => {
                 has_clipboard = true;
             }
@@ -180,6 +204,8 @@ fn build_footer_metadata(config: &Config, token_usage: Option<TokenUsage>) -> Fo
         timestamp: Utc::now(),
         image_files,
         text_files,
+        git_sources,
+        web_pages,
         has_clipboard,
         has_audio,
         has_stdin,
@@ -268,6 +294,25 @@ pub fn output_text_with_usage(
             // Then speak using TTS
             speak_and_wait(&plain_text, lang)
         }
+        OutputMode::CommitMessage => {
+            let commit_msg_path = commit_editmsg_path()?;
+            log_info(&format!(
+                "Writing commit message to: {}",
+                commit_msg_path.display()
+            ));
+
+            let message = strip_code_fence(text);
+            fs::write(&commit_msg_path, format!("{message}\n"))
+                .with_context(|| format!("Failed to write {}", commit_msg_path.display()))?;
+
+            println!("{message}");
+            eprintln!(
+                "Commit message written to {} (use: git commit -e -F {})",
+                commit_msg_path.display(),
+                commit_msg_path.display()
+            );
+            Ok(())
+        }
     };
 
     // Show notification only if audio recording was used AND output is to clipboard AND spinner is not active
@@ -279,18 +324,49 @@ pub fn output_text_with_usage(
     result
 }
 
+/// Remove a surrounding markdown code fence that models sometimes add despite instructions
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.split_once('\n'))
+        .and_then(|(_, body)| body.trim_end().strip_suffix("```"))
+        .map_or(trimmed, str::trim)
+}
+
 // Function removed - now in conversation.rs as Conversation::extract_prompt_section()
 
+/// Names and languages of the installed TTS voices
+pub fn tts_voices() -> Result<Vec<(String, String)>> {
+    let tts = Tts::default()?;
+    Ok(tts
+        .voices()?
+        .iter()
+        .map(|voice| (voice.name(), voice.language().to_string()))
+        .collect())
+}
+
+/// Whether a voice of `voice_language` speaks `lang` ("de-DE"); other regions count too.
+/// Nothing matches an invalid `lang`, which leaves the default voice.
+fn speaks_language(voice_language: &str, lang: &str) -> bool {
+    if !is_language_tag(lang) {
+        return false;
+    }
+    let voice_language = voice_language.to_lowercase();
+    let lang = lang.to_lowercase();
+    voice_language.starts_with(&lang) || voice_language.starts_with(&lang[..2])
+}
+
 fn setup_tts_voice(tts: &mut Tts, lang: &str) -> Result<()> {
     let voices = tts.voices()?;
-    let target_voice = voices.iter().find(|v| {
-        v.language()
-            .to_lowercase()
-            .starts_with(&lang.to_lowercase())
-            || v.language()
-                .to_lowercase()
-                .starts_with(&lang[..2].to_lowercase())
-    });
+    let language_voice = |v: &&tts::Voice| speaks_language(&v.language(), lang);
+    // The voice from the settings wins if it speaks the requested language
+    let settings_voice = load_settings().tts_voice;
+    let target_voice = voices
+        .iter()
+        .filter(language_voice)
+        .find(|v| Some(v.name()) == settings_voice)
+        .or_else(|| voices.iter().find(language_voice));
 
     if let Some(voice) = target_voice {
         tts.set_voice(voice)?;
@@ -376,6 +452,17 @@ pub fn speak_conversation(conversation: &Conversation, lang: &str) -> Result<()>
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_speaks_language() {
+        assert!(speaks_language("de-DE", "de-DE"));
+        assert!(speaks_language("en-GB", "en-US"));
+        assert!(speaks_language("en-US", "EN"));
+        assert!(!speaks_language("de-DE", "en-US"));
+        assert!(!speaks_language("en-US", "e"));
+        assert!(!speaks_language("en-US", "én-US"));
+        assert!(!speaks_language("en-US", ""));
+    }
+
     #[test]
     fn test_wrap_text_basic() {
         let text = "This is a simple line that should be wrapped at the specified width limit.";
@@ -423,6 +510,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(
+            strip_code_fence("```\nAdd parser\n\nDetails\n```\n"),
+            "Add parser\n\nDetails"
+        );
+        assert_eq!(strip_code_fence("```text\nFix bug\n```"), "Fix bug");
+        assert_eq!(strip_code_fence("  Fix bug\n"), "Fix bug");
+    }
+
     #[test]
     fn test_get_outputs_dir() {
         let result = get_outputs_dir();
@@ -449,13 +546,27 @@ mod tests {
             roles: vec!["assistant".to_string()],
             ordered_content: vec![
                 ContentSource::ImageFile("test.jpg".to_string()),
-                ContentSource::TextFile("file.txt".to_string(), "content".to_string()),
+                ContentSource::TextFile("file.txt".to_string(), "content".to_string(), None),
                 ContentSource::ClipboardText("clipboard".to_string()),
             ],
             spinner: false,
             audio_device: None,
             list_audio_devices: false,
             no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
         };
 
         let metadata = build_footer_metadata(&config, None);
@@ -487,6 +598,20 @@ mod tests {
             audio_device: None,
             list_audio_devices: false,
             no_save: false,
+            native_documents: false,
+            git_diff: None,
+            git_staged: false,
+            git_log: None,
+            urls: Vec::new(),
+            vars: Vec::new(),
+            temperature: None,
+            tools: false,
+            allowed_commands: Vec::new(),
+            mcp_servers: Vec::new(),
+            mcp_serve: false,
+            read_stdin: true,
+            serve: None,
+            remote_request: false,
         };
 
         let metadata = build_footer_metadata(&config, None);
@@ -0,0 +1,274 @@
+//! Progress of a request shown in the giagui overlay (`gia --spinner`).
+//!
+//! gia writes one JSON object per line to the overlay's stdin, e.g.
+//! `{"stage":"waiting","model":"gemini-2.5-flash"}`. The overlay answers
+//! `{"command":"cancel"}` on its stdout when the user cancels the request.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use tokio::sync::watch;
+
+use crate::logging::{log_debug, log_info};
+
+/// What the request is doing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum Stage {
+    Recording {
+        device: String,
+    },
+    Converting,
+    Uploading {
+        bytes: usize,
+    }, // request with media, until the response starts
+    Waiting {
+        model: String,
+    },
+    Streaming {
+        chars: usize,
+    },
+    KeyFallback {
+        reason: String,
+        key: usize,
+        keys: usize,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl Stage {
+    /// Text shown in the overlay
+    pub fn text(&self) -> String {
+        match self {
+            Stage::Recording { device } => format!("Recording from {device}"),
+            Stage::Converting => "Converting audio".to_string(),
+            Stage::Uploading { bytes } => format!("Uploading {}", format_bytes(*bytes)),
+            Stage::Waiting { model } => format!("Waiting for {model}"),
+            Stage::Streaming { chars } => format!("Receiving response ({chars} characters)"),
+            Stage::KeyFallback { reason, key, keys } => {
+                format!("{reason}, trying API key {key}/{keys}")
+            }
+            Stage::Error { message } => format!("Error: {message}"),
+        }
+    }
+
+    /// Whether the overlay can cancel the request now. The recording is
+    /// stopped or cancelled in its own dialog.
+    pub fn cancellable(&self) -> bool {
+        matches!(
+            self,
+            Stage::Uploading { .. }
+                | Stage::Waiting { .. }
+                | Stage::Streaming { .. }
+                | Stage::KeyFallback { .. }
+        )
+    }
+}
+
+/// What the overlay asks gia to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum OverlayCommand {
+    Cancel,
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
+    }
+}
+
+/// Input of the running overlay; stages are reported from wherever they happen
+static OVERLAY: Mutex<Option<OverlayInput>> = Mutex::new(None);
+
+struct OverlayInput {
+    stdin: ChildStdin,
+    failed: bool, // an error was reported, the overlay shows it before it closes
+}
+
+/// Report the stage of the request to the overlay, if one is shown
+pub fn report(stage: Stage) {
+    let mut overlay = OVERLAY.lock().unwrap();
+    let Some(input) = overlay.as_mut() else {
+        return;
+    };
+    let Ok(line) = serde_json::to_string(&stage) else {
+        return;
+    };
+    input.failed = matches!(stage, Stage::Error { .. });
+    if writeln!(input.stdin, "{line}")
+        .and_then(|()| input.stdin.flush())
+        .is_err()
+    {
+        log_debug("Progress overlay closed");
+        *overlay = None;
+    }
+}
+
+/// The giagui overlay showing the progress of the request
+pub struct ProgressOverlay {
+    child: Option<Child>,
+    cancelled: watch::Receiver<bool>,
+}
+
+impl ProgressOverlay {
+    /// Start `giagui --spinner`. Without giagui the request runs without overlay.
+    pub fn start() -> Self {
+        let (cancel, cancelled) = watch::channel(false);
+        let child = match Command::new("giagui")
+            .arg("--spinner")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+        {
+            Ok(mut child) => {
+                if let Some(stdout) = child.stdout.take() {
+                    thread::spawn(move || read_commands(BufReader::new(stdout), cancel));
+                }
+                *OVERLAY.lock().unwrap() = child.stdin.take().map(|stdin| OverlayInput {
+                    stdin,
+                    failed: false,
+                });
+                Some(child)
+            }
+            Err(e) => {
+                log_debug(&format!("No progress overlay: {e}"));
+                None
+            }
+        };
+        Self { child, cancelled }
+    }
+
+    /// Completes when the user cancels the request in the overlay
+    pub async fn cancelled(&self) {
+        let mut cancelled = self.cancelled.clone();
+        if cancelled.wait_for(|cancelled| *cancelled).await.is_err() {
+            // The overlay closed without cancelling
+            std::future::pending::<()>().await;
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Close the overlay; an error is shown in it for a moment first
+    pub fn finish(self, error: Option<&anyhow::Error>) {
+        if let Some(error) = error {
+            let message = error.root_cause().to_string();
+            if !self.is_cancelled() && !message.contains("cancelled by user") {
+                report(Stage::Error { message });
+            }
+        }
+    }
+}
+
+impl Drop for ProgressOverlay {
+    fn drop(&mut self) {
+        // Closing its stdin ends the overlay
+        let failed = OVERLAY
+            .lock()
+            .unwrap()
+            .take()
+            .is_some_and(|input| input.failed);
+        if let Some(mut child) = self.child.take()
+            && !failed
+        {
+            let _ = child.kill(); // Silently ignore kill errors
+            let _ = child.wait(); // Silently ignore wait errors
+        }
+    }
+}
+
+fn read_commands(reader: impl BufRead, cancel: watch::Sender<bool>) {
+    for line in reader.lines().map_while(Result::ok) {
+        match serde_json::from_str::<OverlayCommand>(&line) {
+            Ok(OverlayCommand::Cancel) => {
+                log_info("Request cancelled in the progress overlay");
+                let _ = cancel.send(true);
+            }
+            Err(e) => log_debug(&format!("Ignoring overlay message '{line}': {e}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_stage_json() {
+        let stage = Stage::Waiting {
+            model: "gemini-2.5-flash".to_string(),
+        };
+        let line = serde_json::to_string(&stage).unwrap();
+        assert_eq!(line, r#"{"stage":"waiting","model":"gemini-2.5-flash"}"#);
+        assert_eq!(serde_json::from_str::<Stage>(&line).unwrap(), stage);
+        assert_eq!(
+            serde_json::to_string(&Stage::KeyFallback {
+                reason: "Rate limit (429)".to_string(),
+                key: 2,
+                keys: 3
+            })
+            .unwrap(),
+            r#"{"stage":"key_fallback","reason":"Rate limit (429)","key":2,"keys":3}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Stage>(r#"{"stage":"converting"}"#).unwrap(),
+            Stage::Converting
+        );
+        assert_eq!(
+            serde_json::to_string(&OverlayCommand::Cancel).unwrap(),
+            r#"{"command":"cancel"}"#
+        );
+    }
+
+    #[test]
+    fn test_stage_text() {
+        assert_eq!(
+            Stage::Uploading { bytes: 2_500_000 }.text(),
+            "Uploading 2.4 MB"
+        );
+        assert_eq!(Stage::Uploading { bytes: 1500 }.text(), "Uploading 2 KB");
+        assert_eq!(
+            Stage::KeyFallback {
+                reason: "Model overloaded (503)".to_string(),
+                key: 2,
+                keys: 3
+            }
+            .text(),
+            "Model overloaded (503), trying API key 2/3"
+        );
+        assert!(Stage::Streaming { chars: 10 }.cancellable());
+        assert!(
+            !Stage::Recording {
+                device: "Mic".to_string()
+            }
+            .cancellable()
+        );
+    }
+
+    #[test]
+    fn test_read_commands() {
+        let (cancel, cancelled) = watch::channel(false);
+        read_commands(Cursor::new("garbage\n"), cancel);
+        assert!(!*cancelled.borrow());
+
+        let (cancel, cancelled) = watch::channel(false);
+        read_commands(Cursor::new("{\"command\":\"cancel\"}\n"), cancel);
+        assert!(*cancelled.borrow());
+    }
+
+    #[test]
+    fn test_report_without_overlay() {
+        // Nothing to report to, must not panic
+        report(Stage::Converting);
+    }
+}
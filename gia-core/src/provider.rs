@@ -1,6 +1,8 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use genai::chat::ChatMessage;
+use futures::StreamExt;
+use genai::Client;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest, ChatStreamEvent, Tool, ToolCall};
 use std::fmt::Debug;
 
 use crate::conversation::TokenUsage;
@@ -10,17 +12,40 @@ use crate::conversation::TokenUsage;
 pub struct AiResponse {
     pub content: String,
     pub usage: TokenUsage,
+    pub tool_calls: Vec<ToolCall>, // tools the model wants to call (content may be empty)
 }
 
+/// Receives the response text piece by piece while it is generated
+pub type TextCallback<'a> = &'a mut (dyn FnMut(&str) + Send);
+
 /// Generic AI provider trait for abstraction across different AI services
 #[async_trait]
 pub trait AiProvider: Debug + Send + Sync {
-    /// Generate content from chat messages with usage information
+    /// Generate content from chat messages with usage information.
+    /// `tools` are offered to the model, which may answer with tool calls instead of text.
     async fn generate_content_with_chat_messages(
         &mut self,
         chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
     ) -> Result<AiResponse>;
 
+    /// Like `generate_content_with_chat_messages`, but passes the text to `on_text` while
+    /// it is generated. Providers without streaming support pass it all at once.
+    async fn generate_content_streaming(
+        &mut self,
+        chat_messages: Vec<ChatMessage>,
+        tools: &[Tool],
+        on_text: TextCallback<'_>,
+    ) -> Result<AiResponse> {
+        let response = self
+            .generate_content_with_chat_messages(chat_messages, tools)
+            .await?;
+        if !response.content.is_empty() {
+            on_text(&response.content);
+        }
+        Ok(response)
+    }
+
     /// Get the model name being used
     fn model_name(&self) -> &str;
 
@@ -28,18 +53,73 @@ pub trait AiProvider: Debug + Send + Sync {
     fn provider_name(&self) -> &str;
 
     /// Get the current API key index (for caching), if applicable
-    #[allow(dead_code)]
     fn current_api_key_index(&self) -> Option<usize> {
         None
     }
 }
 
+/// Send a chat request as a stream, passing text chunks to `on_text` as they arrive.
+/// Usage and tool calls are collected from the end of the stream.
+pub async fn exec_chat_streaming(
+    client: &Client,
+    model: &str,
+    chat_request: ChatRequest,
+    chat_options: Option<&ChatOptions>,
+    on_text: TextCallback<'_>,
+) -> Result<AiResponse> {
+    let chat_options = chat_options
+        .cloned()
+        .unwrap_or_default()
+        .with_capture_usage(true)
+        .with_capture_tool_calls(true);
+    let mut stream = client
+        .exec_chat_stream(model, chat_request, Some(&chat_options))
+        .await?
+        .stream;
+
+    let mut content = String::new();
+    let mut usage = TokenUsage::default();
+    let mut tool_calls = Vec::new();
+    while let Some(event) = stream.next().await {
+        match event? {
+            ChatStreamEvent::Chunk(chunk) => {
+                on_text(&chunk.content);
+                content.push_str(&chunk.content);
+            }
+            ChatStreamEvent::End(end) => {
+                if let Some(captured) = &end.captured_usage {
+                    usage = TokenUsage {
+                        prompt_tokens: captured.prompt_tokens.map(|t| t as u32),
+                        completion_tokens: captured.completion_tokens.map(|t| t as u32),
+                        total_tokens: captured.total_tokens.map(|t| t as u32),
+                    };
+                }
+                tool_calls = end.captured_into_tool_calls().unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+
+    if content.trim().is_empty() && tool_calls.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No content was generated by the AI. The response was empty or contained only whitespace."
+        ));
+    }
+
+    Ok(AiResponse {
+        content,
+        usage,
+        tool_calls,
+    })
+}
+
 /// Configuration for creating AI providers
 #[derive(Debug, Clone)]
 pub struct ProviderConfig {
     pub model: String,
     pub api_keys: Vec<String>,
     pub preferred_api_key_index: usize,
+    pub temperature: Option<f64>,
 }
 
 /// Factory for creating AI providers
@@ -63,11 +143,13 @@ impl ProviderFactory {
                     model_name.to_string(),
                     config.api_keys,
                     config.preferred_api_key_index,
-                )?;
+                )?
+                .with_temperature(config.temperature);
                 Ok(Box::new(client))
             }
             "ollama" => {
-                let client = crate::ollama::OllamaClient::new(model_name.to_string())?;
+                let client = crate::ollama::OllamaClient::new(model_name.to_string())?
+                    .with_temperature(config.temperature);
                 Ok(Box::new(client))
             }
             // Future providers can be added here:
@@ -78,6 +160,23 @@ impl ProviderFactory {
             )),
         }
     }
+
+    /// Check whether the provider behind a model string accepts documents such as
+    /// PDFs as native binary uploads (currently only Gemini)
+    pub fn supports_native_documents(model: &str) -> bool {
+        let provider_name = model
+            .split_once("::")
+            .map_or("gemini", |(provider, _)| provider);
+        provider_name.eq_ignore_ascii_case("gemini")
+    }
+
+    /// Check whether a model string names a supported provider
+    pub fn is_supported_model(model: &str) -> bool {
+        let provider_name = model
+            .split_once("::")
+            .map_or("gemini", |(provider, _)| provider);
+        matches!(provider_name.to_lowercase().as_str(), "gemini" | "ollama")
+    }
 }
 
 #[cfg(test)]
@@ -90,18 +189,40 @@ mod tests {
             model: "ollama::llama3.2".to_string(),
             api_keys: Vec::new(),
             preferred_api_key_index: 0,
+            temperature: None,
         };
         let result = ProviderFactory::create_provider(config);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().provider_name(), "Ollama");
     }
 
+    #[test]
+    fn test_supports_native_documents() {
+        assert!(ProviderFactory::supports_native_documents(
+            "gemini-2.5-flash-lite"
+        ));
+        assert!(ProviderFactory::supports_native_documents(
+            "gemini::gemini-2.5-pro"
+        ));
+        assert!(!ProviderFactory::supports_native_documents(
+            "ollama::llama3.2"
+        ));
+    }
+
+    #[test]
+    fn test_is_supported_model() {
+        assert!(ProviderFactory::is_supported_model("gemini-2.5-pro"));
+        assert!(ProviderFactory::is_supported_model("Ollama::llama3.2"));
+        assert!(!ProviderFactory::is_supported_model("openai::gpt-4o"));
+    }
+
     #[test]
     fn test_unsupported_provider() {
         let config = ProviderConfig {
             model: "unknown::model".to_string(),
             api_keys: Vec::new(),
             preferred_api_key_index: 0,
+            temperature: None,
         };
         let result = ProviderFactory::create_provider(config);
         assert!(result.is_err());
@@ -118,6 +239,7 @@ mod tests {
                 "AIzaSyKey3ForTesting123456789012345".to_string(),
             ],
             preferred_api_key_index: 2,
+            temperature: None,
         };
 
         assert_eq!(config.preferred_api_key_index, 2);
@@ -131,6 +253,7 @@ mod tests {
             model: "gemini-2.5-flash".to_string(),
             api_keys: vec!["AIzaSyKey1ForTesting123456789012345".to_string()],
             preferred_api_key_index: 1,
+            temperature: None,
         };
 
         let cloned = original.clone();
@@ -0,0 +1,1216 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::constants::{get_extra_role_dirs, get_trusted_projects};
+use crate::logging::{log_debug, log_info, log_warn};
+use crate::provider::ProviderFactory;
+
+// Default task definitions embedded in the binary
+const DEFAULT_EN_TASK: &str = r"
+transscript
+
+translate to englisch
+
+correct the englisch
+
+no time codes
+
+no markdown
+
+only one version
+
+no explanations
+";
+
+const DEFAULT_DE_TASK: &str = r"
+übersetze ins Deutsche wenn nötig
+
+korregiere mein Deutsch wenn nötig
+
+KEINE Zeitstempel
+
+KEIN markdown
+
+KEINE Erklärungen
+
+einfach nur eine Version
+";
+
+const DEFAULT_COMMIT_MESSAGE_TASK: &str = r"
+write a git commit message for the changes below
+
+first line: imperative summary, max 72 characters
+
+blank line, then a short body explaining what changed and why (only if needed)
+
+wrap the body at 72 characters
+
+no markdown, no code fences
+
+no explanations, only the commit message
+";
+
+/// Name of the built-in task that writes its output to .git/COMMIT_EDITMSG
+pub const COMMIT_MESSAGE_TASK: &str = "commit-message";
+
+/// Load a role or task definition file
+/// Searches first in ~/.gia/roles/<name>.md, then in ~/.gia/tasks/<name>.md
+#[cfg(test)]
+pub fn load_role_file(name: &str) -> Result<String> {
+    log_debug(&format!("Loading role/task file: {name}"));
+
+    // Try roles directory first
+    let role_path = get_role_path(name)?;
+    if role_path.exists() {
+        let content = fs::read_to_string(&role_path)
+            .with_context(|| format!("Failed to read role file: {}", role_path.display()))?;
+
+        log_info(&format!(
+            "Loaded role '{}' with {} characters from: {}",
+            name,
+            content.len(),
+            role_path.display()
+        ));
+
+        return Ok(content);
+    }
+
+    // Try tasks directory
+    let task_path = get_task_path(name)?;
+    if task_path.exists() {
+        let content = fs::read_to_string(&task_path)
+            .with_context(|| format!("Failed to read task file: {}", task_path.display()))?;
+
+        log_info(&format!(
+            "Loaded task '{}' with {} characters from: {}",
+            name,
+            content.len(),
+            task_path.display()
+        ));
+
+        return Ok(content);
+    }
+
+    // Neither found
+    log_warn(&format!(
+        "Role/task file not found. Tried: {role_path:?} and {task_path:?}"
+    ));
+    Err(anyhow::anyhow!(
+        "Role/task '{}' not found at: {} or {}",
+        name,
+        role_path.display(),
+        task_path.display()
+    ))
+}
+
+/// A loaded role/task definition without its front-matter
+pub struct LoadedDefinition {
+    pub name: String,
+    pub content: String,
+    pub is_task: bool,
+    pub confined_to: Option<PathBuf>, // project directory of an untrusted project definition
+}
+
+/// Load all role/task definition files for the given names.
+/// Included definitions are placed before the definition that includes them.
+pub fn load_all_roles(names: &[String]) -> Result<Vec<LoadedDefinition>> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    log_info(&format!("Loading {} role(s)/task(s)", names.len()));
+
+    let resolved = resolve_definitions(names)?;
+    for name in &resolved.missing {
+        log_warn(&format!("Failed to load role/task '{name}': not found"));
+        eprintln!("Warning: Failed to load role/task '{name}': not found");
+    }
+
+    for definition in &resolved.definitions {
+        if let Some(shadowed) = &definition.shadows {
+            log_warn(&format!(
+                "Project definition {} shadows {}",
+                definition.path.display(),
+                shadowed.display()
+            ));
+            eprintln!(
+                "Warning: Using '{}' from the project ({}) instead of {}",
+                definition.name,
+                definition.path.display(),
+                shadowed.display()
+            );
+        }
+    }
+
+    let items: Vec<LoadedDefinition> = resolved
+        .definitions
+        .into_iter()
+        .map(|definition| LoadedDefinition {
+            content: strip_front_matter(&definition.content),
+            name: definition.name,
+            is_task: definition.is_task,
+            confined_to: definition.confined_to,
+        })
+        .collect();
+
+    log_info(&format!(
+        "Successfully loaded {} role(s)/task(s)",
+        items.len()
+    ));
+    Ok(items)
+}
+
+/// Output mode requested by a role/task front-matter
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RoleOutput {
+    Stdout,
+    Clipboard,
+    Tts,
+    Browser,
+}
+
+/// Settings from the YAML front-matter at the top of a role/task file:
+///
+/// ```text
+/// ---
+/// description: Translate into German # shown by `gia roles list`
+/// model: gemini-2.5-pro
+/// temperature: 0.2
+/// output: clipboard   # stdout, clipboard, tts or browser
+/// tts_language: de-DE
+/// no_save: true
+/// files: [glossary.md] # used when no -f is given, relative to the role file
+/// include: [rust-dev, security-audit] # other roles/tasks placed before this one
+/// ---
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoleSettings {
+    pub description: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f64>,
+    pub output: Option<RoleOutput>,
+    pub tts_language: Option<String>,
+    pub no_save: Option<bool>,
+    pub files: Vec<String>,
+    pub include: Vec<String>,
+}
+
+impl RoleSettings {
+    /// Combine with the settings of a later role/task (later values win, files are appended).
+    /// Includes are resolved before merging and are not combined.
+    fn merge(&mut self, other: RoleSettings) {
+        if other.description.is_some() {
+            self.description = other.description;
+        }
+        if other.model.is_some() {
+            self.model = other.model;
+        }
+        if other.temperature.is_some() {
+            self.temperature = other.temperature;
+        }
+        if other.output.is_some() {
+            self.output = other.output;
+        }
+        if other.tts_language.is_some() {
+            self.tts_language = other.tts_language;
+        }
+        if other.no_save.is_some() {
+            self.no_save = other.no_save;
+        }
+        self.files.extend(other.files);
+    }
+}
+
+/// Split a definition into its YAML front-matter (between `---` lines) and the body
+fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    let content = content.trim_start_matches('\u{feff}');
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if matches!(line.trim_end(), "---" | "...") {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    // No closing delimiter: not a front-matter block
+    (None, content)
+}
+
+/// Definition text without its front-matter
+fn strip_front_matter(content: &str) -> String {
+    split_front_matter(content).1.to_string()
+}
+
+/// Parse the front-matter of a definition file. Relative `files` are resolved
+/// against the directory of the definition file; with `confined_to` they must
+/// stay inside that (project) directory.
+fn parse_role_settings(
+    content: &str,
+    path: &Path,
+    confined_to: Option<&Path>,
+) -> Result<RoleSettings> {
+    let Some(yaml) = split_front_matter(content).0 else {
+        return Ok(RoleSettings::default());
+    };
+    if yaml.trim().is_empty() {
+        return Ok(RoleSettings::default());
+    }
+
+    let mut settings: RoleSettings = serde_yaml::from_str(yaml)
+        .with_context(|| format!("Invalid front-matter in {}", path.display()))?;
+
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    settings.files = settings
+        .files
+        .iter()
+        .map(|file| {
+            let file_path = match file.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                None => base_dir.join(file),
+            };
+            if let Some(project_dir) = confined_to
+                && !is_inside(&file_path, project_dir)
+            {
+                return Err(anyhow::anyhow!(
+                    "{} may only use files inside the project {} (see GIA_TRUSTED_PROJECTS): {file}",
+                    path.display(),
+                    project_dir.display()
+                ));
+            }
+            Ok(file_path.to_string_lossy().into_owned())
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(settings)
+}
+
+/// Load and combine the front-matter settings of the given roles/tasks (and their includes).
+/// Missing definitions are skipped here (they are reported when the roles are loaded).
+pub fn load_role_settings(names: &[String]) -> Result<RoleSettings> {
+    let mut settings = RoleSettings::default();
+
+    for definition in resolve_definitions(names)?.definitions {
+        if definition.settings != RoleSettings::default() {
+            log_info(&format!(
+                "Loaded front-matter settings from {}: {:?}",
+                definition.path.display(),
+                definition.settings
+            ));
+        }
+        settings.merge(definition.settings);
+    }
+
+    Ok(settings)
+}
+
+/// A definition reached from the requested names, with its includes resolved
+struct ResolvedDefinition {
+    name: String,
+    path: PathBuf,
+    is_task: bool,
+    content: String,
+    settings: RoleSettings,
+    confined_to: Option<PathBuf>, // project directory of an untrusted project definition
+    shadows: Option<PathBuf>,     // definition of the same name outside the project
+}
+
+#[derive(Default)]
+struct ResolvedRoles {
+    definitions: Vec<ResolvedDefinition>, // includes first, each file once
+    missing: Vec<String>,
+}
+
+fn resolve_definitions(names: &[String]) -> Result<ResolvedRoles> {
+    resolve_definitions_in(&definition_roots()?, names)
+}
+
+fn resolve_definitions_in(roots: &DefinitionRoots, names: &[String]) -> Result<ResolvedRoles> {
+    let mut resolved = ResolvedRoles::default();
+    let mut stack = Vec::new();
+    for name in names {
+        resolve_definition(roots, name, &mut stack, &mut resolved)?;
+    }
+    Ok(resolved)
+}
+
+/// Resolve one definition and, depth-first, its includes. `stack` holds the
+/// chain of definitions currently being resolved and is used to detect cycles.
+fn resolve_definition(
+    roots: &DefinitionRoots,
+    name: &str,
+    stack: &mut Vec<(String, PathBuf)>,
+    resolved: &mut ResolvedRoles,
+) -> Result<()> {
+    check_definition_name(name)?;
+    let Some((path, is_task)) = find_definition_in(&roots.dirs, name) else {
+        if !resolved.missing.iter().any(|missing| missing == name) {
+            resolved.missing.push(name.to_string());
+        }
+        return Ok(());
+    };
+
+    if stack.iter().any(|(_, parent)| *parent == path) {
+        let chain: Vec<&str> = stack
+            .iter()
+            .map(|(parent, _)| parent.as_str())
+            .chain(std::iter::once(name))
+            .collect();
+        return Err(anyhow::anyhow!(
+            "Role/task include cycle: {}",
+            chain.join(" -> ")
+        ));
+    }
+
+    // Already added (requested twice or included by several definitions)
+    if resolved.definitions.iter().any(|d| d.path == path) {
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read role/task file: {}", path.display()))?;
+    let project_dir = roots.project_dir(&path);
+    let confined_to = project_dir.filter(|_| !roots.project_trusted);
+    let settings = parse_role_settings(&content, &path, confined_to.as_deref())?;
+    let shadows = roots
+        .project_dir(&path)
+        .and_then(|_| find_definition_in(&roots.dirs[1..], name))
+        .map(|(shadowed, _)| shadowed);
+
+    log_info(&format!(
+        "Role/task trace: {}{} '{name}' from {}",
+        "  ".repeat(stack.len()),
+        if is_task { "task" } else { "role" },
+        path.display()
+    ));
+
+    stack.push((name.to_string(), path.clone()));
+    for include in &settings.include {
+        resolve_definition(roots, include, stack, resolved)?;
+    }
+    stack.pop();
+
+    resolved.definitions.push(ResolvedDefinition {
+        name: name.to_string(),
+        path,
+        is_task,
+        content,
+        settings,
+        confined_to,
+        shadows,
+    });
+    Ok(())
+}
+
+/// A role or task definition file found on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoleDefinition {
+    pub name: String,
+    pub is_task: bool,
+    pub description: String,
+    pub path: PathBuf,
+}
+
+/// Template for `gia roles new`
+const NEW_DEFINITION_TEMPLATE: &str = r"---
+description: {description}
+# model: gemini-2.5-pro
+# temperature: 0.2
+# output: clipboard   # stdout, clipboard, tts or browser
+# tts_language: de-DE
+# no_save: true
+# files: [notes.md]   # used when no -f is given, relative to this file
+---
+{body}
+";
+
+/// Find the definition file for a role/task name. Directories are searched in
+/// order (project, GIA_ROLE_DIRS, home); within each, roles come before tasks.
+/// Returns the path and whether it is a task.
+pub fn find_definition(name: &str) -> Result<Option<(PathBuf, bool)>> {
+    check_definition_name(name)?;
+    Ok(find_definition_in(&definition_roots()?.dirs, name))
+}
+
+/// Role/task names are file names: no paths, no `..`
+fn check_definition_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) || name.contains("..")
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid role/task name '{name}' (use a plain name without path)"
+        ));
+    }
+    Ok(())
+}
+
+fn find_definition_in(roots: &[PathBuf], name: &str) -> Option<(PathBuf, bool)> {
+    let file_name = format!("{name}.md");
+    roots.iter().find_map(|root| {
+        [("roles", false), ("tasks", true)]
+            .into_iter()
+            .map(|(subdir, is_task)| (root.join(subdir).join(&file_name), is_task))
+            .find(|(path, _)| path.is_file())
+    })
+}
+
+/// List all role and task definitions, roles first, each sorted by name.
+/// Definitions shadowed by an earlier directory are left out.
+pub fn list_definitions() -> Result<Vec<RoleDefinition>> {
+    let mut definitions: Vec<RoleDefinition> = Vec::new();
+
+    for root in definition_roots()?.dirs {
+        for (subdir, is_task) in [("roles", false), ("tasks", true)] {
+            let Ok(entries) = fs::read_dir(root.join(subdir)) else {
+                continue;
+            };
+
+            for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+                if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
+                    continue;
+                }
+                let Some(name) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+                    continue;
+                };
+                if definitions.iter().any(|d| d.name == name) {
+                    log_debug(&format!("Skipping shadowed definition: {}", path.display()));
+                    continue;
+                }
+
+                let description = match fs::read_to_string(&path) {
+                    Ok(content) => definition_description(&content),
+                    Err(e) => {
+                        log_warn(&format!("Failed to read {}: {e}", path.display()));
+                        String::new()
+                    }
+                };
+                definitions.push(RoleDefinition {
+                    name,
+                    is_task,
+                    description,
+                    path,
+                });
+            }
+        }
+    }
+
+    definitions.sort_by_key(|definition| (definition.is_task, definition.name.to_lowercase()));
+    Ok(definitions)
+}
+
+/// Description of a definition: `description` from the front-matter,
+/// otherwise the first non-empty line of the body
+pub fn definition_description(content: &str) -> String {
+    let (front_matter, body) = split_front_matter(content);
+
+    if let Some(yaml) = front_matter
+        && let Ok(serde_yaml::Value::Mapping(map)) = serde_yaml::from_str(yaml)
+        && let Some(description) = map.get("description").and_then(|v| v.as_str())
+    {
+        return description.trim().to_string();
+    }
+
+    body.lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Create a new role (or task) definition from the template and return its path.
+/// `local` creates it in the project's .gia directory (./.gia if there is none).
+pub fn create_definition(name: &str, is_task: bool, local: bool) -> Result<PathBuf> {
+    if check_definition_name(name).is_err() || name.ends_with(".md") {
+        return Err(anyhow::anyhow!(
+            "Invalid role/task name '{name}' (use a plain name without path or .md)"
+        ));
+    }
+
+    let root = if local {
+        let cwd = std::env::current_dir().context("Failed to get current directory")?;
+        find_project_root(&cwd, &get_home_root()?).unwrap_or_else(|| cwd.join(".gia"))
+    } else {
+        get_home_root()?
+    };
+
+    if let Some((path, _)) = find_definition_in(std::slice::from_ref(&root), name) {
+        return Err(anyhow::anyhow!(
+            "Role/task '{name}' already exists: {}",
+            path.display()
+        ));
+    }
+
+    let subdir = if is_task { "tasks" } else { "roles" };
+    let path = root.join(subdir).join(format!("{name}.md"));
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+
+    let (description, body) = if is_task {
+        (
+            format!("{name} task"),
+            "Describe what should be done with the input.",
+        )
+    } else {
+        (format!("{name} role"), "You are ...")
+    };
+    let content = NEW_DEFINITION_TEMPLATE
+        .replace("{description}", &description)
+        .replace("{body}", body);
+    fs::write(&path, content)
+        .with_context(|| format!("Failed to write definition file: {}", path.display()))?;
+    log_info(&format!("Created definition file: {}", path.display()));
+
+    Ok(path)
+}
+
+/// Check a definition file and return the problems found (empty if valid)
+pub fn validate_definition(path: &Path) -> Vec<String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => return vec![format!("cannot be read: {e}")],
+    };
+
+    let confined_to = match definition_roots() {
+        Ok(roots) if !roots.project_trusted => roots.project_dir(path),
+        _ => None,
+    };
+    let mut problems = Vec::new();
+    match parse_role_settings(&content, path, confined_to.as_deref()) {
+        Ok(settings) => {
+            if let Some(model) = &settings.model
+                && !ProviderFactory::is_supported_model(model)
+            {
+                problems.push(format!("unsupported provider in model '{model}'"));
+            }
+            if let Some(temperature) = settings.temperature
+                && !(0.0..=2.0).contains(&temperature)
+            {
+                problems.push(format!("temperature {temperature} is outside 0.0..=2.0"));
+            }
+            for file in &settings.files {
+                if !Path::new(file).exists() {
+                    problems.push(format!("default file not found: {file}"));
+                }
+            }
+            if !settings.include.is_empty() {
+                problems.extend(include_problems(path, &settings.include));
+            }
+        }
+        Err(e) => problems.push(format!("{:#}", e)),
+    }
+
+    if split_front_matter(&content).1.trim().is_empty() {
+        problems.push("definition text is empty".to_string());
+    }
+
+    problems
+}
+
+/// Check that the includes of a definition exist and do not lead back to it
+fn include_problems(path: &Path, includes: &[String]) -> Vec<String> {
+    let roots = match definition_roots() {
+        Ok(roots) => roots,
+        Err(e) => return vec![format!("{e:#}")],
+    };
+
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut stack = vec![(name, path.to_path_buf())];
+    let mut resolved = ResolvedRoles::default();
+    let mut problems = Vec::new();
+
+    for include in includes {
+        if let Err(e) = resolve_definition(&roots, include, &mut stack, &mut resolved) {
+            problems.push(format!("{e:#}"));
+            break;
+        }
+    }
+    for missing in resolved.missing {
+        problems.push(format!("included role/task not found: {missing}"));
+    }
+    problems
+}
+
+/// Directories holding roles/ and tasks/, in search order
+struct DefinitionRoots {
+    dirs: Vec<PathBuf>,
+    /// The project's .gia, if any; it comes first in `dirs`
+    project_root: Option<PathBuf>,
+    /// Listed in GIA_TRUSTED_PROJECTS: its definitions may use files outside
+    /// the project and `{{env:VAR}}`
+    project_trusted: bool,
+}
+
+impl DefinitionRoots {
+    /// The project directory if `path` is a definition from the project's .gia
+    fn project_dir(&self, path: &Path) -> Option<PathBuf> {
+        let project_root = self.project_root.as_ref()?;
+        path.starts_with(project_root)
+            .then(|| project_root.parent().map(Path::to_path_buf))
+            .flatten()
+    }
+}
+
+/// The project's .gia (nearest one above the current directory), GIA_ROLE_DIRS,
+/// then ~/.gia
+fn definition_roots() -> Result<DefinitionRoots> {
+    let home_root = get_home_root()?;
+    let mut dirs = Vec::new();
+
+    let project_root = std::env::current_dir()
+        .ok()
+        .and_then(|cwd| find_project_root(&cwd, &home_root));
+    let project_trusted = project_root
+        .as_deref()
+        .and_then(Path::parent)
+        .is_some_and(|project_dir| is_trusted_project(project_dir, &get_trusted_projects()));
+    dirs.extend(project_root.clone());
+    for dir in get_extra_role_dirs() {
+        if !dirs.contains(&dir) && dir != home_root {
+            dirs.push(dir);
+        }
+    }
+    dirs.push(home_root);
+
+    log_debug(&format!(
+        "Role/task search directories: {dirs:?} (project trusted: {project_trusted})"
+    ));
+    Ok(DefinitionRoots {
+        dirs,
+        project_root,
+        project_trusted,
+    })
+}
+
+fn is_trusted_project(project_dir: &Path, trusted: &[PathBuf]) -> bool {
+    let Ok(project_dir) = project_dir.canonicalize() else {
+        return false;
+    };
+    trusted
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .any(|dir| project_dir.starts_with(dir))
+}
+
+/// Whether `path` stays inside `dir`, also after resolving `..` and symlinks
+pub(crate) fn is_inside(path: &Path, dir: &Path) -> bool {
+    let Ok(dir) = dir.canonicalize() else {
+        return false;
+    };
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return false;
+                }
+            }
+            Component::CurDir => {}
+            component => normalized.push(component),
+        }
+    }
+    // Resolve symlinks in the part of the path that exists
+    let Some(existing) = normalized.ancestors().find(|ancestor| ancestor.exists()) else {
+        return false;
+    };
+    let rest = normalized.strip_prefix(existing).unwrap_or(Path::new(""));
+    existing
+        .canonicalize()
+        .is_ok_and(|existing| existing.join(rest).starts_with(&dir))
+}
+
+/// Nearest .gia directory in `start` or one of its parents, ignoring ~/.gia
+fn find_project_root(start: &Path, home_root: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(".gia"))
+        .find(|candidate| candidate.is_dir() && candidate != home_root)
+}
+
+/// The ~/.gia directory
+fn get_home_root() -> Result<PathBuf> {
+    let home_dir =
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+    Ok(home_dir.join(".gia"))
+}
+
+/// Get the path to a role or task definition file in ~/.gia
+#[cfg(test)]
+fn get_definition_path(name: &str, subdir: &str) -> Result<PathBuf> {
+    let definition_dir = get_home_root()?.join(subdir);
+    let definition_file = format!("{}.md", name);
+    Ok(definition_dir.join(definition_file))
+}
+
+/// Get the path to a role definition file
+#[cfg(test)]
+fn get_role_path(role_name: &str) -> Result<PathBuf> {
+    get_definition_path(role_name, "roles")
+}
+
+/// Get the path to a task definition file
+#[cfg(test)]
+fn get_task_path(task_name: &str) -> Result<PathBuf> {
+    get_definition_path(task_name, "tasks")
+}
+
+/// Ensure default task files exist, creating them if needed
+/// This is called at startup to ensure EN.md, DE.md and commit-message.md exist
+pub fn ensure_default_tasks() -> Result<()> {
+    let home_dir =
+        dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
+
+    let tasks_dir = home_dir.join(".gia").join("tasks");
+
+    // Create tasks directory if it doesn't exist
+    if !tasks_dir.exists() {
+        fs::create_dir_all(&tasks_dir)?;
+        log_info(&format!("Created tasks directory: {}", tasks_dir.display()));
+    }
+
+    // Ensure EN.md exists
+    let en_path = tasks_dir.join("EN.md");
+    if !en_path.exists() {
+        fs::write(&en_path, DEFAULT_EN_TASK)?;
+        log_info(&format!("Created default task file: {}", en_path.display()));
+    }
+
+    // Ensure DE.md exists
+    let de_path = tasks_dir.join("DE.md");
+    if !de_path.exists() {
+        fs::write(&de_path, DEFAULT_DE_TASK)?;
+        log_info(&format!("Created default task file: {}", de_path.display()));
+    }
+
+    // Ensure commit-message.md exists
+    let commit_message_path = tasks_dir.join(format!("{COMMIT_MESSAGE_TASK}.md"));
+    if !commit_message_path.exists() {
+        fs::write(&commit_message_path, DEFAULT_COMMIT_MESSAGE_TASK)?;
+        log_info(&format!(
+            "Created default task file: {}",
+            commit_message_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_get_role_path() {
+        let result = get_role_path("rust-dev");
+        assert!(result.is_ok());
+
+        let path = result.unwrap();
+        assert!(path.to_string_lossy().contains(".gia"));
+        assert!(path.to_string_lossy().contains("roles"));
+        assert!(path.to_string_lossy().ends_with("rust-dev.md"));
+    }
+
+    #[test]
+    fn test_load_role_file_not_found() {
+        let result = load_role_file("nonexistent-role-xyz123");
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Role/task 'nonexistent-role-xyz123' not found")
+        );
+    }
+
+    #[test]
+    fn test_get_task_path() {
+        let result = get_task_path("my-task");
+        assert!(result.is_ok());
+
+        let path = result.unwrap();
+        assert!(path.to_string_lossy().contains(".gia"));
+        assert!(path.to_string_lossy().contains("tasks"));
+        assert!(path.to_string_lossy().ends_with("my-task.md"));
+    }
+
+    #[test]
+    fn test_load_all_roles_empty() {
+        let result = load_all_roles(&[]);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_load_role_file_success() {
+        // Create a temporary directory
+        let temp_dir = TempDir::new().unwrap();
+        let role_content = "# Rust Developer Role\n\nYou are an expert Rust developer.";
+
+        // Create a role file
+        let role_path = temp_dir.path().join("test-role.md");
+        fs::write(&role_path, role_content).unwrap();
+
+        // Read it back (this tests the file reading logic, not the path resolution)
+        let content = fs::read_to_string(&role_path).unwrap();
+        assert_eq!(content, role_content);
+    }
+
+    #[test]
+    fn test_split_front_matter() {
+        let content = "---\nmodel: gemini-2.5-pro\n---\nYou are a translator.\n";
+        assert_eq!(
+            split_front_matter(content),
+            (Some("model: gemini-2.5-pro\n"), "You are a translator.\n")
+        );
+
+        // No front-matter, or no closing delimiter
+        assert_eq!(split_front_matter("Plain role"), (None, "Plain role"));
+        assert_eq!(
+            split_front_matter("---\nnot closed"),
+            (None, "---\nnot closed")
+        );
+        assert_eq!(
+            strip_front_matter("---\r\nno_save: true\r\n---\r\nBody"),
+            "Body"
+        );
+    }
+
+    #[test]
+    fn test_parse_role_settings() {
+        let content = "---\nmodel: ollama::llama3.2\ntemperature: 0.2\noutput: tts\ntts_language: en-US\nno_save: true\nfiles:\n  - glossary.md\n  - /abs/notes.md\n---\nTranscribe.";
+        let settings =
+            parse_role_settings(content, Path::new("/home/me/.gia/tasks/t.md"), None).unwrap();
+
+        assert_eq!(settings.model.as_deref(), Some("ollama::llama3.2"));
+        assert_eq!(settings.temperature, Some(0.2));
+        assert_eq!(settings.output, Some(RoleOutput::Tts));
+        assert_eq!(settings.tts_language.as_deref(), Some("en-US"));
+        assert_eq!(settings.no_save, Some(true));
+        assert_eq!(
+            settings.files,
+            vec!["/home/me/.gia/tasks/glossary.md", "/abs/notes.md"]
+        );
+
+        // Unknown keys and invalid values are reported with the file name
+        let err = parse_role_settings("---\nmodle: x\n---\n", Path::new("r.md"), None).unwrap_err();
+        assert!(format!("{err:#}").contains("Invalid front-matter in r.md"));
+        assert!(
+            parse_role_settings("---\noutput: speaker\n---\n", Path::new("r.md"), None).is_err()
+        );
+
+        assert_eq!(
+            parse_role_settings("No front-matter", Path::new("r.md"), None).unwrap(),
+            RoleSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_merge_role_settings() {
+        let mut settings = RoleSettings {
+            model: Some("a".to_string()),
+            no_save: Some(true),
+            files: vec!["one.md".to_string()],
+            ..Default::default()
+        };
+        settings.merge(RoleSettings {
+            model: Some("b".to_string()),
+            output: Some(RoleOutput::Clipboard),
+            files: vec!["two.md".to_string()],
+            ..Default::default()
+        });
+
+        assert_eq!(settings.model.as_deref(), Some("b"));
+        assert_eq!(settings.no_save, Some(true));
+        assert_eq!(settings.output, Some(RoleOutput::Clipboard));
+        assert_eq!(settings.files, vec!["one.md", "two.md"]);
+    }
+
+    #[test]
+    fn test_definition_description() {
+        assert_eq!(
+            definition_description("---\ndescription: Translate to German\nmodel: x\n---\nBody"),
+            "Translate to German"
+        );
+        assert_eq!(
+            definition_description("\n\n# Rust reviewer\nReview the code."),
+            "Rust reviewer"
+        );
+        assert_eq!(
+            definition_description("---\nno_save: true\n---\n\nTranscribe only\n"),
+            "Transcribe only"
+        );
+        assert_eq!(definition_description(""), "");
+    }
+
+    #[test]
+    fn test_validate_definition() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let valid = temp_dir.path().join("valid.md");
+        fs::write(
+            &valid,
+            "---\nmodel: ollama::llama3.2\n---\nYou are a reviewer.",
+        )
+        .unwrap();
+        assert!(validate_definition(&valid).is_empty());
+
+        let invalid = temp_dir.path().join("invalid.md");
+        fs::write(
+            &invalid,
+            "---\nmodel: openai::gpt-4o\ntemperature: 5\nfiles: [missing.md]\n---\n",
+        )
+        .unwrap();
+        let problems = validate_definition(&invalid);
+        assert_eq!(problems.len(), 4, "{problems:?}");
+        assert!(problems[0].contains("unsupported provider"));
+        assert!(problems[2].contains("missing.md"));
+        assert_eq!(problems[3], "definition text is empty");
+
+        let broken = temp_dir.path().join("broken.md");
+        fs::write(&broken, "---\nmodle: x\n---\nBody").unwrap();
+        let problems = validate_definition(&broken);
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("Invalid front-matter"));
+
+        assert!(!validate_definition(&temp_dir.path().join("none.md")).is_empty());
+    }
+
+    fn write_definition(root: &Path, subdir: &str, name: &str, content: &str) -> PathBuf {
+        let dir = root.join(subdir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{name}.md"));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_find_definition_search_order() {
+        let project = TempDir::new().unwrap();
+        let home = TempDir::new().unwrap();
+        let roots = vec![project.path().to_path_buf(), home.path().to_path_buf()];
+
+        let home_role = write_definition(home.path(), "roles", "reviewer", "home");
+        let project_task = write_definition(project.path(), "tasks", "reviewer", "project");
+        write_definition(home.path(), "tasks", "only-home", "home task");
+
+        // The project directory wins, even when it has a task and home a role
+        assert_eq!(
+            find_definition_in(&roots, "reviewer"),
+            Some((project_task, true))
+        );
+        assert_eq!(
+            find_definition_in(&roots[1..], "reviewer"),
+            Some((home_role, false))
+        );
+        assert!(find_definition_in(&roots, "only-home").is_some());
+        assert!(find_definition_in(&roots, "missing").is_none());
+    }
+
+    #[test]
+    fn test_find_project_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_gia = temp_dir.path().join("project").join(".gia");
+        let nested = temp_dir.path().join("project").join("src").join("bin");
+        fs::create_dir_all(&project_gia).unwrap();
+        fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(
+            find_project_root(&nested, Path::new("/nonexistent/.gia")),
+            Some(project_gia.clone())
+        );
+        // ~/.gia itself is not a project directory
+        assert_eq!(find_project_root(&nested, &project_gia), None);
+    }
+
+    #[test]
+    fn test_resolve_includes() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let roots = DefinitionRoots {
+            dirs: vec![root.to_path_buf()],
+            project_root: None,
+            project_trusted: false,
+        };
+
+        write_definition(root, "roles", "rust-dev", "---\nmodel: a\n---\nRust");
+        write_definition(
+            root,
+            "tasks",
+            "security-audit",
+            "---\ninclude: [rust-dev]\ntemperature: 0.1\n---\nAudit",
+        );
+        write_definition(
+            root,
+            "roles",
+            "rust-security",
+            "---\ninclude: [rust-dev, security-audit, missing]\nmodel: b\n---\nBoth",
+        );
+
+        let resolved = resolve_definitions_in(&roots, &["rust-security".to_string()]).unwrap();
+        let names: Vec<&str> = resolved
+            .definitions
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        // Includes come first, rust-dev only once
+        assert_eq!(names, vec!["rust-dev", "security-audit", "rust-security"]);
+        assert!(resolved.definitions[1].is_task);
+        assert_eq!(resolved.missing, vec!["missing"]);
+
+        let mut settings = RoleSettings::default();
+        for definition in resolved.definitions {
+            settings.merge(definition.settings);
+        }
+        assert_eq!(settings.model.as_deref(), Some("b"));
+        assert_eq!(settings.temperature, Some(0.1));
+
+        // Cycles are reported with the include chain
+        write_definition(root, "roles", "a", "---\ninclude: [b]\n---\nA");
+        write_definition(root, "roles", "b", "---\ninclude: [a]\n---\nB");
+        let err = resolve_definitions_in(&roots, &["a".to_string()])
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "Role/task include cycle: a -> b -> a");
+    }
+
+    #[test]
+    fn test_project_definitions_are_confined() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_dir = temp_dir.path().join("project");
+        let project_root = project_dir.join(".gia");
+        let home_root = temp_dir.path().join("home");
+        fs::create_dir_all(&project_root).unwrap();
+        fs::write(project_dir.join("README.md"), "readme").unwrap();
+        fs::write(temp_dir.path().join("secret.txt"), "secret").unwrap();
+
+        write_definition(&home_root, "roles", "reviewer", "home reviewer");
+        write_definition(
+            &project_root,
+            "roles",
+            "reviewer",
+            "---\nfiles: [../../README.md]\n---\nProject reviewer",
+        );
+        write_definition(
+            &project_root,
+            "roles",
+            "thief",
+            "---\nfiles: [../../../secret.txt]\n---\nSteal",
+        );
+        write_definition(
+            &project_root,
+            "roles",
+            "home-thief",
+            "---\nfiles: [~/.ssh/id_rsa]\n---\nSteal",
+        );
+
+        let mut roots = DefinitionRoots {
+            dirs: vec![project_root.clone(), home_root.clone()],
+            project_root: Some(project_root.clone()),
+            project_trusted: false,
+        };
+
+        let resolved = resolve_definitions_in(&roots, &["reviewer".to_string()]).unwrap();
+        let definition = &resolved.definitions[0];
+        assert_eq!(
+            definition.content,
+            "---\nfiles: [../../README.md]\n---\nProject reviewer"
+        );
+        assert_eq!(
+            definition.confined_to.as_deref(),
+            Some(project_dir.as_path())
+        );
+        assert_eq!(
+            definition.shadows,
+            Some(home_root.join("roles").join("reviewer.md"))
+        );
+
+        for name in ["thief", "home-thief"] {
+            let err = resolve_definitions_in(&roots, &[name.to_string()])
+                .err()
+                .unwrap();
+            assert!(
+                format!("{err:#}").contains("may only use files inside the project"),
+                "{err:#}"
+            );
+        }
+
+        roots.project_trusted = true;
+        let resolved = resolve_definitions_in(&roots, &["thief".to_string()]).unwrap();
+        assert_eq!(resolved.definitions[0].confined_to, None);
+    }
+
+    #[test]
+    fn test_definition_names_are_plain() {
+        let temp_dir = TempDir::new().unwrap();
+        let roots = DefinitionRoots {
+            dirs: vec![temp_dir.path().join(".gia")],
+            project_root: None,
+            project_trusted: false,
+        };
+        write_definition(temp_dir.path(), "roles", "outside", "Outside");
+        write_definition(
+            &temp_dir.path().join(".gia"),
+            "roles",
+            "sneaky",
+            "---\ninclude: [../../roles/outside]\n---\nSneaky",
+        );
+
+        for name in ["../roles/outside", "a/b", "..", ".hidden", ""] {
+            assert!(check_definition_name(name).is_err(), "{name}");
+            assert!(resolve_definitions_in(&roots, &[name.to_string()]).is_err());
+        }
+        assert!(check_definition_name("rust-dev").is_ok());
+        assert!(resolve_definitions_in(&roots, &["sneaky".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_is_inside() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+
+        assert!(is_inside(&project.join("src/main.rs"), &project));
+        assert!(is_inside(&project.join("src/../notes.md"), &project));
+        assert!(!is_inside(&project.join("../secret.txt"), &project));
+        assert!(!is_inside(Path::new("/etc/passwd"), &project));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(temp_dir.path(), project.join("escape")).unwrap();
+            assert!(!is_inside(&project.join("escape/secret.txt"), &project));
+        }
+    }
+
+    #[test]
+    fn test_is_trusted_project() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("work").join("project");
+        fs::create_dir_all(&project).unwrap();
+
+        assert!(is_trusted_project(
+            &project,
+            &[temp_dir.path().join("work")]
+        ));
+        assert!(is_trusted_project(&project, std::slice::from_ref(&project)));
+        assert!(!is_trusted_project(&project, &[]));
+        assert!(!is_trusted_project(
+            &project,
+            &[temp_dir.path().join("other")]
+        ));
+    }
+}
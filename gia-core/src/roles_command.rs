@@ -0,0 +1,230 @@
+//! `gia roles` subcommands: list, show, create, edit, remove and validate
+//! role/task definitions (project .gia/, GIA_ROLE_DIRS and ~/.gia).
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tabwriter::TabWriter;
+
+use crate::logging::log_info;
+use crate::role::{
+    RoleDefinition, create_definition, find_definition, list_definitions, validate_definition,
+};
+
+pub fn run_roles_command(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("list", sub)) => list(sub.get_flag("names")),
+        Some(("show", sub)) => show(required_name(sub)),
+        Some(("new", sub)) => {
+            let path = create_definition(
+                required_name(sub),
+                sub.get_flag("task"),
+                sub.get_flag("local"),
+            )?;
+            println!("Created {}", path.display());
+            if sub.get_flag("edit") {
+                open_in_editor(&path)?;
+            }
+            Ok(())
+        }
+        Some(("edit", sub)) => open_in_editor(&existing_path(required_name(sub))?),
+        Some(("rm", sub)) => remove(required_name(sub), sub.get_flag("yes")),
+        Some(("validate", sub)) => {
+            let names: Vec<String> = sub
+                .get_many::<String>("name")
+                .unwrap_or_default()
+                .cloned()
+                .collect();
+            validate(&names)
+        }
+        _ => list(false),
+    }
+}
+
+fn required_name(matches: &ArgMatches) -> &str {
+    matches
+        .get_one::<String>("name")
+        .map(String::as_str)
+        .expect("name is required")
+}
+
+fn type_label(is_task: bool) -> &'static str {
+    if is_task { "task" } else { "role" }
+}
+
+fn existing_path(name: &str) -> Result<PathBuf> {
+    find_definition(name)?
+        .map(|(path, _)| path)
+        .ok_or_else(|| anyhow::anyhow!("Role/task '{name}' not found (see 'gia roles list')"))
+}
+
+fn list(names_only: bool) -> Result<()> {
+    let definitions = list_definitions()?;
+
+    if names_only {
+        for definition in &definitions {
+            println!("{}", definition.name);
+        }
+        return Ok(());
+    }
+
+    if definitions.is_empty() {
+        println!("No roles or tasks found. Create one with 'gia roles new <name>'.");
+        return Ok(());
+    }
+
+    let mut tw = TabWriter::new(io::stdout());
+    writeln!(tw, "NAME\tTYPE\tDESCRIPTION\tPATH")?;
+    for RoleDefinition {
+        name,
+        is_task,
+        description,
+        path,
+    } in &definitions
+    {
+        writeln!(
+            tw,
+            "{name}\t{}\t{}\t{}",
+            type_label(*is_task),
+            truncate(description, 60),
+            path.display()
+        )?;
+    }
+    tw.flush()?;
+    Ok(())
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let shortened: String = text.chars().take(max_chars - 3).collect();
+        format!("{shortened}...")
+    }
+}
+
+fn show(name: &str) -> Result<()> {
+    let path = existing_path(name)?;
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read definition file: {}", path.display()))?;
+    println!("# {}", path.display());
+    print!("{content}");
+    if !content.ends_with('\n') {
+        println!();
+    }
+    Ok(())
+}
+
+fn remove(name: &str, yes: bool) -> Result<()> {
+    let path = existing_path(name)?;
+
+    if !yes {
+        if atty::isnt(atty::Stream::Stdin) {
+            return Err(anyhow::anyhow!(
+                "Refusing to delete {} without confirmation (use --yes)",
+                path.display()
+            ));
+        }
+        print!("Delete {}? [y/N] ", path.display());
+        io::stdout().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            println!("Cancelled");
+            return Ok(());
+        }
+    }
+
+    fs::remove_file(&path)
+        .with_context(|| format!("Failed to delete definition file: {}", path.display()))?;
+    log_info(&format!("Deleted definition file: {}", path.display()));
+    println!("Deleted {}", path.display());
+    Ok(())
+}
+
+fn validate(names: &[String]) -> Result<()> {
+    let definitions = if names.is_empty() {
+        list_definitions()?
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                let (path, is_task) = find_definition(name)?.ok_or_else(|| {
+                    anyhow::anyhow!("Role/task '{name}' not found (see 'gia roles list')")
+                })?;
+                Ok(RoleDefinition {
+                    name: name.clone(),
+                    is_task,
+                    description: String::new(),
+                    path,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    let mut invalid = 0;
+    for definition in &definitions {
+        let problems = validate_definition(&definition.path);
+        let label = format!("{} '{}'", type_label(definition.is_task), definition.name);
+        if problems.is_empty() {
+            println!("ok     {label}");
+        } else {
+            invalid += 1;
+            println!("error  {label} ({})", definition.path.display());
+            for problem in problems {
+                println!("       - {problem}");
+            }
+        }
+    }
+
+    if invalid > 0 {
+        return Err(anyhow::anyhow!(
+            "{invalid} of {} definition(s) have problems",
+            definitions.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Open a file in $VISUAL / $EDITOR (falls back to notepad on Windows, vi elsewhere)
+fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    // Allow editors with arguments, e.g. EDITOR="code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{editor}'"))?;
+
+    if !status.success() {
+        return Err(anyhow::anyhow!("Editor '{editor}' exited with {status}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a longer description", 10), "a longe...");
+    }
+}
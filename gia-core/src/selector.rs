@@ -0,0 +1,569 @@
+//! Line range and symbol selectors for file inputs.
+//!
+//! Supports `-f path:120-180` (line range) and `-f path#fn_name` (symbol) so only
+//! the relevant part of a large file is sent. Symbol lookup uses a simple
+//! line-based parser for Rust, Python and JavaScript/TypeScript.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+use crate::logging::log_debug;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileSelector {
+    /// 1-based inclusive line range; `end` of `None` means "until end of file"
+    Lines {
+        start: usize,
+        end: Option<usize>,
+    },
+    Symbol(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    Python,
+    JavaScript,
+}
+
+/// Split a `-f` argument into path and optional selector.
+///
+/// Existing paths are never split, so files containing `:` or `#` keep working.
+pub fn parse_file_selector(arg: &str) -> Result<(String, Option<FileSelector>)> {
+    if arg.is_empty() || Path::new(arg).exists() {
+        return Ok((arg.to_string(), None));
+    }
+
+    if let Some((path, symbol)) = arg.rsplit_once('#')
+        && !path.is_empty()
+        && is_identifier(symbol)
+    {
+        return Ok((
+            path.to_string(),
+            Some(FileSelector::Symbol(symbol.to_string())),
+        ));
+    }
+
+    if let Some((path, range)) = arg.rsplit_once(':')
+        && !path.is_empty()
+        && !range.is_empty()
+        && range.chars().all(|c| c.is_ascii_digit() || c == '-')
+    {
+        return Ok((path.to_string(), Some(parse_line_range(range)?)));
+    }
+
+    Ok((arg.to_string(), None))
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn parse_line_range(range: &str) -> Result<FileSelector> {
+    let invalid = || format!("Invalid line range '{range}' (expected e.g. 120-180, 120- or 120)");
+
+    let (start, end) = match range.split_once('-') {
+        Some((start, "")) => (start.parse::<usize>().with_context(invalid)?, None),
+        Some((start, end)) => (
+            start.parse::<usize>().with_context(invalid)?,
+            Some(end.parse::<usize>().with_context(invalid)?),
+        ),
+        None => {
+            let line = range.parse::<usize>().with_context(invalid)?;
+            (line, Some(line))
+        }
+    };
+
+    if start == 0 || end.is_some_and(|end| end < start) {
+        return Err(anyhow::anyhow!(invalid()));
+    }
+
+    Ok(FileSelector::Lines { start, end })
+}
+
+/// Apply a selector to file content.
+///
+/// Returns the selected text and a label for headers and resources, e.g. "lines 120-180".
+pub fn apply_selector(
+    path: &str,
+    content: &str,
+    selector: &FileSelector,
+) -> Result<(String, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    let (start, end, label_prefix) = match selector {
+        FileSelector::Lines { start, end } => {
+            if *start > lines.len() {
+                return Err(anyhow::anyhow!(
+                    "Line {start} is beyond the end of {path} ({} lines)",
+                    lines.len()
+                ));
+            }
+            let end = end.unwrap_or(lines.len()).min(lines.len());
+            (*start, end, String::new())
+        }
+        FileSelector::Symbol(name) => {
+            let language = language_for_path(path).with_context(|| {
+                format!(
+                    "Symbol selection is only supported for Rust, Python and JavaScript/TypeScript files: {path}"
+                )
+            })?;
+            let (start, end) = find_symbol(&lines, language, name)
+                .with_context(|| format!("Symbol '{name}' not found in {path}"))?;
+            (start + 1, end + 1, format!("{name}, "))
+        }
+    };
+
+    log_debug(&format!("Selected lines {start}-{end} from {path}"));
+
+    let mut selected = lines[start - 1..end].join("\n");
+    selected.push('\n');
+    Ok((selected, format!("{label_prefix}lines {start}-{end}")))
+}
+
+fn language_for_path(path: &str) -> Option<Language> {
+    let ext = Path::new(path).extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "rs" => Some(Language::Rust),
+        "py" | "pyi" => Some(Language::Python),
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => Some(Language::JavaScript),
+        _ => None,
+    }
+}
+
+fn definition_patterns(language: Language, name: &str) -> Vec<Regex> {
+    let name = regex::escape(name);
+    let patterns = match language {
+        Language::Rust => vec![
+            format!(
+                r#"^\s*(pub(\([^)]*\))?\s+)?((async|const|unsafe|default|extern(\s+"[^"]*")?)\s+)*(fn|struct|enum|trait|union|mod|type|const|static)\s+{name}\b"#
+            ),
+            format!(r"^\s*macro_rules!\s+{name}\b"),
+            format!(r"^\s*(unsafe\s+)?impl\b(<[^>]*>)?\s+([\w:<>, ]+\s+for\s+)?{name}\b"),
+        ],
+        Language::Python => vec![format!(r"^\s*((async\s+)?def|class)\s+{name}\b")],
+        Language::JavaScript => vec![
+            format!(r"^\s*(export\s+)?(default\s+)?(async\s+)?function\s*\*?\s*{name}\b"),
+            format!(
+                r"^\s*(export\s+)?(default\s+)?(declare\s+)?(abstract\s+)?(class|interface|enum|type)\s+{name}\b"
+            ),
+            format!(r"^\s*(export\s+)?(const|let|var)\s+{name}\s*[=:]"),
+            format!(
+                r"^\s*((static|async|get|set|public|private|protected|readonly|override)\s+)*{name}\s*(<[^>]*>)?\([^)]*\)?\s*(:\s*[^={{]+)?\{{"
+            ),
+        ],
+    };
+
+    patterns
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("valid symbol pattern"))
+        .collect()
+}
+
+/// Find the 0-based inclusive line range of a symbol definition
+fn find_symbol(lines: &[&str], language: Language, name: &str) -> Option<(usize, usize)> {
+    let patterns = definition_patterns(language, name);
+
+    // Patterns are ordered by preference (e.g. a struct definition wins over its impl block)
+    let def_line = patterns
+        .iter()
+        .find_map(|pattern| lines.iter().position(|line| pattern.is_match(line)))?;
+
+    let end = match language {
+        Language::Python => find_indented_block_end(lines, def_line),
+        Language::Rust | Language::JavaScript => find_brace_block_end(lines, def_line, language),
+    };
+
+    Some((include_leading_annotations(lines, def_line, language), end))
+}
+
+/// Extend the selection upwards over doc comments, attributes and decorators
+fn include_leading_annotations(lines: &[&str], def_line: usize, language: Language) -> usize {
+    let mut start = def_line;
+    while start > 0 {
+        let previous = lines[start - 1].trim_start();
+        let is_annotation = match language {
+            Language::Rust => previous.starts_with("///") || previous.starts_with("#["),
+            Language::Python => previous.starts_with('@'),
+            Language::JavaScript => {
+                previous.starts_with("/**")
+                    || previous.starts_with('*')
+                    || previous.starts_with('@')
+            }
+        };
+        if !is_annotation {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+/// Python blocks end at the first non-blank line indented at or below the definition.
+/// A signature spanning several lines belongs to the definition until its brackets close.
+fn find_indented_block_end(lines: &[&str], def_line: usize) -> usize {
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let def_indent = indent(lines[def_line]);
+
+    let mut header_end = def_line;
+    let mut nesting = 0isize;
+    for (index, line) in lines.iter().enumerate().skip(def_line) {
+        header_end = index;
+        nesting += line
+            .chars()
+            .map(|c| match c {
+                '(' | '[' | '{' => 1,
+                ')' | ']' | '}' => -1,
+                _ => 0,
+            })
+            .sum::<isize>();
+        if nesting <= 0 {
+            break;
+        }
+    }
+
+    let mut end = header_end;
+    for (index, line) in lines.iter().enumerate().skip(header_end + 1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= def_indent {
+            break;
+        }
+        end = index;
+    }
+    end
+}
+
+/// Brace-delimited blocks end where the first opened brace is closed again.
+/// Strings and comments are skipped so braces inside them don't count.
+fn find_brace_block_end(lines: &[&str], def_line: usize, language: Language) -> usize {
+    let mut depth = 0usize;
+    let mut nesting = 0usize; // parentheses and brackets, e.g. `[u8; 4]` in signatures
+    let mut opened = false;
+    let mut in_block_comment = false;
+    let mut string_delimiter: Option<char> = None;
+    let mut raw_string_hashes: Option<usize> = None; // Rust `r#"..."#`
+
+    for (index, line) in lines.iter().enumerate().skip(def_line) {
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if in_block_comment {
+                if c == '*' && chars.peek() == Some(&'/') {
+                    chars.next();
+                    in_block_comment = false;
+                }
+                continue;
+            }
+            if let Some(hashes) = raw_string_hashes {
+                if c == '"' && skip_hashes(&mut chars, hashes) {
+                    raw_string_hashes = None;
+                }
+                continue;
+            }
+            if let Some(delimiter) = string_delimiter {
+                if c == '\\' {
+                    chars.next();
+                } else if c == delimiter {
+                    string_delimiter = None;
+                }
+                continue;
+            }
+            match c {
+                '/' if chars.peek() == Some(&'/') => break,
+                '/' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    in_block_comment = true;
+                }
+                '"' => string_delimiter = Some('"'),
+                '\'' | '`' if language == Language::JavaScript => string_delimiter = Some(c),
+                '\'' if language == Language::Rust => skip_char_literal(&mut chars),
+                'r' if language == Language::Rust => {
+                    let mut lookahead = chars.clone();
+                    let mut hashes = 0;
+                    while lookahead.next_if_eq(&'#').is_some() {
+                        hashes += 1;
+                    }
+                    if lookahead.next() == Some('"') {
+                        chars = lookahead;
+                        raw_string_hashes = Some(hashes);
+                    }
+                }
+                '(' | '[' => nesting += 1,
+                ')' | ']' => nesting = nesting.saturating_sub(1),
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    if opened && depth == 0 {
+                        return index;
+                    }
+                }
+                // Declarations without a body, e.g. `struct Marker;` or `const X = 1;`
+                ';' if !opened && nesting == 0 => return index,
+                _ => {}
+            }
+        }
+        // Template literals in JS may span lines; regular strings may not
+        if string_delimiter.is_some_and(|d| d != '`') && language == Language::JavaScript {
+            string_delimiter = None;
+        }
+    }
+
+    lines.len().saturating_sub(1)
+}
+
+/// Skip a Rust char literal (`'x'`, `'\''`, `'\u{7B}'`) after its opening quote.
+/// Anything else is a lifetime such as `'a` and is left alone.
+fn skip_char_literal(chars: &mut Peekable<Chars>) {
+    let mut lookahead = chars.clone();
+    let closed = match lookahead.next() {
+        Some('\\') => {
+            lookahead.next();
+            // Hex digits and braces of `\x7f` and `\u{..}` escapes
+            loop {
+                match lookahead.next() {
+                    Some('\'') => break true,
+                    Some(c) if c.is_ascii_hexdigit() || c == '{' || c == '}' => {}
+                    _ => break false,
+                }
+            }
+        }
+        Some(_) => lookahead.next() == Some('\''),
+        None => false,
+    };
+    if closed {
+        *chars = lookahead;
+    }
+}
+
+/// Consume `count` `#` characters, closing a raw string, if they come next
+fn skip_hashes(chars: &mut Peekable<Chars>, count: usize) -> bool {
+    let mut lookahead = chars.clone();
+    if (0..count).all(|_| lookahead.next() == Some('#')) {
+        *chars = lookahead;
+        return true;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUST_SOURCE: &str = r#"use std::fmt;
+
+/// A marker type
+#[derive(Debug)]
+pub struct Marker;
+
+pub fn helper(value: &str, _buf: [u8; 4]) -> String {
+    let braces = "{ not a block";
+    if value.is_empty() {
+        return String::new();
+    }
+    format!("{value}{braces}")
+}
+
+impl fmt::Display for Marker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "marker")
+    }
+}
+"#;
+
+    #[test]
+    fn test_parse_line_range_selector() {
+        assert_eq!(
+            parse_file_selector("src/main.rs:120-180").unwrap(),
+            (
+                "src/main.rs".to_string(),
+                Some(FileSelector::Lines {
+                    start: 120,
+                    end: Some(180)
+                })
+            )
+        );
+        assert_eq!(
+            parse_file_selector("src/main.rs:42").unwrap().1,
+            Some(FileSelector::Lines {
+                start: 42,
+                end: Some(42)
+            })
+        );
+        assert_eq!(
+            parse_file_selector("src/main.rs:42-").unwrap().1,
+            Some(FileSelector::Lines {
+                start: 42,
+                end: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_line_range() {
+        assert!(parse_file_selector("src/main.rs:0-10").is_err());
+        assert!(parse_file_selector("src/main.rs:20-10").is_err());
+        assert!(parse_file_selector("src/main.rs:1-2-3").is_err());
+    }
+
+    #[test]
+    fn test_parse_symbol_selector() {
+        assert_eq!(
+            parse_file_selector("src/app.rs#run_app").unwrap(),
+            (
+                "src/app.rs".to_string(),
+                Some(FileSelector::Symbol("run_app".to_string()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_without_selector() {
+        assert_eq!(
+            parse_file_selector("notes.txt").unwrap(),
+            ("notes.txt".to_string(), None)
+        );
+        // Windows drive letters are not line ranges
+        assert_eq!(
+            parse_file_selector(r"C:\notes.txt").unwrap(),
+            (r"C:\notes.txt".to_string(), None)
+        );
+        // Existing files are never split
+        let temp_file = tempfile::NamedTempFile::with_suffix("#1").unwrap();
+        let path = temp_file.path().to_str().unwrap();
+        assert_eq!(parse_file_selector(path).unwrap(), (path.to_string(), None));
+    }
+
+    #[test]
+    fn test_apply_line_range() {
+        let content = "one\ntwo\nthree\nfour\n";
+        let selector = FileSelector::Lines {
+            start: 2,
+            end: Some(3),
+        };
+        let (text, label) = apply_selector("file.txt", content, &selector).unwrap();
+        assert_eq!(text, "two\nthree\n");
+        assert_eq!(label, "lines 2-3");
+
+        // End is clamped to the file length
+        let selector = FileSelector::Lines {
+            start: 3,
+            end: Some(100),
+        };
+        let (text, label) = apply_selector("file.txt", content, &selector).unwrap();
+        assert_eq!(text, "three\nfour\n");
+        assert_eq!(label, "lines 3-4");
+
+        let selector = FileSelector::Lines {
+            start: 10,
+            end: None,
+        };
+        assert!(apply_selector("file.txt", content, &selector).is_err());
+    }
+
+    #[test]
+    fn test_rust_symbol_selection() {
+        let selector = FileSelector::Symbol("helper".to_string());
+        let (text, label) = apply_selector("lib.rs", RUST_SOURCE, &selector).unwrap();
+        assert!(text.starts_with("pub fn helper"));
+        assert!(text.trim_end().ends_with('}'));
+        assert!(text.contains("format!"));
+        assert!(!text.contains("impl"));
+        assert_eq!(label, "helper, lines 7-13");
+    }
+
+    #[test]
+    fn test_rust_symbol_includes_attributes_and_unit_struct() {
+        let selector = FileSelector::Symbol("Marker".to_string());
+        let (text, _) = apply_selector("lib.rs", RUST_SOURCE, &selector).unwrap();
+        assert_eq!(
+            text,
+            "/// A marker type\n#[derive(Debug)]\npub struct Marker;\n"
+        );
+    }
+
+    #[test]
+    fn test_python_symbol_selection() {
+        let source = "import os\n\n@cache\ndef load(path):\n    with open(path) as f:\n\n        return f.read()\n\ndef other():\n    pass\n";
+        let selector = FileSelector::Symbol("load".to_string());
+        let (text, label) = apply_selector("tool.py", source, &selector).unwrap();
+        assert_eq!(
+            text,
+            "@cache\ndef load(path):\n    with open(path) as f:\n\n        return f.read()\n"
+        );
+        assert_eq!(label, "load, lines 3-7");
+    }
+
+    #[test]
+    fn test_rust_char_literals_and_raw_strings() {
+        let source = r##"fn is_quote(c: char) -> bool { c == '"' }
+
+fn is_escape(c: char) -> bool {
+    c == '\'' || c == '\u{7B}' || c == '{'
+}
+
+fn longest<'a>(a: &'a str, b: &'a str) -> &'a str {
+    if a.len() > b.len() { a } else { b }
+}
+
+fn pattern() -> &'static str {
+    r#"{ "unclosed"#
+}
+
+fn after() {}
+"##;
+        let select = |name: &str| {
+            let selector = FileSelector::Symbol(name.to_string());
+            apply_selector("lib.rs", source, &selector).unwrap().1
+        };
+        assert_eq!(select("is_quote"), "is_quote, lines 1-1");
+        assert_eq!(select("is_escape"), "is_escape, lines 3-5");
+        assert_eq!(select("longest"), "longest, lines 7-9");
+        assert_eq!(select("pattern"), "pattern, lines 11-13");
+    }
+
+    #[test]
+    fn test_python_multiline_signature() {
+        let source = "def load(\n    path,\n    mode=\"r\",\n):\n    return open(path, mode)\n\ndef other():\n    pass\n";
+        let selector = FileSelector::Symbol("load".to_string());
+        let (text, label) = apply_selector("tool.py", source, &selector).unwrap();
+        assert_eq!(
+            text,
+            "def load(\n    path,\n    mode=\"r\",\n):\n    return open(path, mode)\n"
+        );
+        assert_eq!(label, "load, lines 1-5");
+    }
+
+    #[test]
+    fn test_javascript_symbol_selection() {
+        let source = "const a = 1;\n\nexport async function fetchData(url) {\n  const text = `}`;\n  return fetch(url);\n}\n\nclass Store {\n  get(key) {\n    return this.items[key];\n  }\n}\n";
+        let selector = FileSelector::Symbol("fetchData".to_string());
+        let (text, _) = apply_selector("api.js", source, &selector).unwrap();
+        assert_eq!(
+            text,
+            "export async function fetchData(url) {\n  const text = `}`;\n  return fetch(url);\n}\n"
+        );
+
+        let selector = FileSelector::Symbol("get".to_string());
+        let (text, label) = apply_selector("api.ts", source, &selector).unwrap();
+        assert_eq!(text, "  get(key) {\n    return this.items[key];\n  }\n");
+        assert_eq!(label, "get, lines 9-11");
+    }
+
+    #[test]
+    fn test_symbol_errors() {
+        let selector = FileSelector::Symbol("missing".to_string());
+        assert!(apply_selector("lib.rs", RUST_SOURCE, &selector).is_err());
+
+        let selector = FileSelector::Symbol("helper".to_string());
+        assert!(apply_selector("notes.txt", "helper", &selector).is_err());
+    }
+}
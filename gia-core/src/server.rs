@@ -0,0 +1,766 @@
+//! `gia serve`: a small HTTP/JSON API for chat requests and saved conversations.
+//!
+//! - `POST /chat` runs a request (JSON or, with `"stream": true` or
+//!   `Accept: text/event-stream`, server-sent events)
+//! - `GET /conversations` lists saved conversations, newest first
+//! - `GET /conversations/{id}` returns a saved conversation
+//! - `POST /v1/chat/completions` and `GET /v1/models` speak the OpenAI API
+//!   (see `openai_api`)
+//!
+//! Requests go through the same pipeline as the command line
+//! (`prepare_request`/`complete_request`), so roles, API key fallback and the
+//! conversation store are shared with `gia`. The server binds to localhost unless
+//! a token is set; with a token every request needs `Authorization: Bearer TOKEN`.
+//! Without a token only requests addressed to localhost (`Host`, `Origin`) are
+//! answered, so web pages can't reach the API through DNS rebinding or cross-site
+//! requests. Request bodies must be `application/json`, and `files` must stay
+//! inside the server's working directory.
+
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::IpAddr;
+use std::path::Component;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedMutexGuard, mpsc};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use url::Url;
+
+use crate::app::{complete_request, prepare_request};
+use crate::cli::{Config, ServeOptions};
+use crate::conversation::{ConversationManager, ConversationSummary};
+use crate::logging::{log_info, log_warn};
+use crate::openai_api;
+use crate::provider::TextCallback;
+use crate::selector::parse_file_selector;
+
+const DEFAULT_CONVERSATION_LIMIT: usize = 20;
+
+pub async fn run_server(options: ServeOptions) -> Result<()> {
+    if options.token.is_none() && !is_loopback(&options.host) {
+        return Err(anyhow::anyhow!(
+            "Refusing to serve on {} without a token. Use --token or GIA_SERVE_TOKEN.",
+            options.host
+        ));
+    }
+
+    let conversation_manager =
+        ConversationManager::new().context("Failed to initialize conversation manager")?;
+    let listener = tokio::net::TcpListener::bind((options.host.as_str(), options.port))
+        .await
+        .with_context(|| format!("Failed to bind {}:{}", options.host, options.port))?;
+    let address = listener.local_addr()?;
+    log_info(&format!("Serving HTTP API on {address}"));
+    eprintln!("gia API listening on http://{address}");
+
+    let state = ServerState {
+        conversation_manager: Arc::new(conversation_manager),
+        token: options.token.map(Arc::from),
+        save_proxied: options.save_proxied,
+        conversation_locks: ConversationLocks::default(),
+    };
+    axum::serve(listener, router(state))
+        .await
+        .context("HTTP server failed")
+}
+
+#[derive(Clone)]
+pub struct ServerState {
+    pub conversation_manager: Arc<ConversationManager>,
+    pub token: Option<Arc<str>>,
+    pub save_proxied: bool, // save OpenAI-compatible exchanges as conversations
+    pub conversation_locks: ConversationLocks,
+}
+
+/// One lock per resumed conversation, so requests continuing it run one after
+/// the other instead of overwriting each other's history
+#[derive(Clone, Default)]
+pub struct ConversationLocks(Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>);
+
+impl ConversationLocks {
+    async fn lock(&self, id: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.0.lock().unwrap();
+            // Forget the locks nobody holds or waits for
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            Arc::clone(locks.entry(id.to_string()).or_default())
+        };
+        lock.lock_owned().await
+    }
+}
+
+fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/chat", post(chat))
+        .route("/conversations", get(list_conversations))
+        .route("/conversations/{id}", get(show_conversation))
+        .route("/v1/models", get(openai_api::list_models))
+        .route("/v1/chat/completions", post(openai_api::chat_completions))
+        .layer(middleware::from_fn_with_state(state.clone(), check_access))
+        .with_state(state)
+}
+
+/// An error answered as `{"error": message}`
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, axum::Json(json!({"error": self.1}))).into_response()
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}"))
+    }
+}
+
+fn bad_request(e: impl std::fmt::Display) -> ApiError {
+    ApiError(StatusCode::BAD_REQUEST, e.to_string())
+}
+
+/// A body that isn't JSON: 415 without the JSON content type, 400 otherwise
+fn invalid_body(rejection: JsonRejection) -> ApiError {
+    let status = match &rejection {
+        JsonRejection::MissingJsonContentType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    ApiError(
+        status,
+        format!("Invalid request body: {}", rejection.body_text()),
+    )
+}
+
+async fn check_access(State(state): State<ServerState>, request: Request, next: Next) -> Response {
+    match &state.token {
+        Some(token) => {
+            let authorized = request
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()));
+            if !authorized {
+                log_warn(&format!(
+                    "Rejected unauthorized request: {} {}",
+                    request.method(),
+                    request.uri()
+                ));
+                return ApiError(
+                    StatusCode::UNAUTHORIZED,
+                    "Missing or wrong token".to_string(),
+                )
+                .into_response();
+            }
+        }
+        None if !is_local_request(&request) => {
+            log_warn(&format!(
+                "Rejected request not addressed to localhost: {} {} (Host {:?}, Origin {:?})",
+                request.method(),
+                request.uri(),
+                request.headers().get(header::HOST),
+                request.headers().get(header::ORIGIN)
+            ));
+            return ApiError(
+                StatusCode::FORBIDDEN,
+                "Only requests to localhost are allowed without a token".to_string(),
+            )
+            .into_response();
+        }
+        None => {}
+    }
+    next.run(request).await
+}
+
+/// Whether the request is addressed to localhost and, when sent by a web page,
+/// comes from a page on localhost. A foreign `Host` means DNS rebinding, a
+/// foreign `Origin` a cross-site request.
+fn is_local_request(request: &Request) -> bool {
+    let headers = request.headers();
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            request
+                .uri()
+                .authority()
+                .map(|authority| authority.as_str())
+        });
+    let origin = headers.get(header::ORIGIN).map(|value| {
+        value
+            .to_str()
+            .ok()
+            .and_then(|origin| Url::parse(origin).ok())
+    });
+
+    let host_is_local = host
+        .and_then(|host| Url::parse(&format!("http://{host}")).ok())
+        .is_some_and(|url| url_is_loopback(&url));
+    let origin_is_local = match origin {
+        None => true,
+        Some(origin) => origin.is_some_and(|url| url_is_loopback(&url)),
+    };
+    host_is_local && origin_is_local
+}
+
+fn url_is_loopback(url: &Url) -> bool {
+    url.host_str()
+        .is_some_and(|host| is_loopback(host.trim_start_matches('[').trim_end_matches(']')))
+}
+
+/// Compare without stopping at the first difference, so the time taken tells
+/// nothing about the token
+fn constant_time_eq(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Files of a request must stay inside the server's working directory: no
+/// absolute paths, no `~` and no `..`, and no symlinks leading out of it
+pub(crate) fn check_request_files(files: &[String]) -> Result<()> {
+    let working_dir = std::env::current_dir()
+        .and_then(|dir| dir.canonicalize())
+        .context("Failed to get the working directory")?;
+    for file in files {
+        let (path, _) = parse_file_selector(file)?;
+        let path = std::path::Path::new(&path);
+        let outside = || {
+            anyhow::anyhow!("Only files inside the server's working directory are allowed: {file}")
+        };
+        if path.starts_with("~")
+            || !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(outside());
+        }
+        // Archive members and selectors aren't on disk; check what exists of the path
+        if let Some(existing) = path
+            .ancestors()
+            .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.exists())
+            && !existing.canonicalize()?.starts_with(&working_dir)
+        {
+            return Err(outside());
+        }
+    }
+    Ok(())
+}
+
+/// Body of `POST /chat`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ChatRequest {
+    prompt: String,
+    model: Option<String>,
+    roles: Vec<String>,
+    files: Vec<String>, // relative to the server's working directory
+    urls: Vec<String>,
+    resume: Option<String>, // conversation id, suffix or index; "" = latest
+    no_save: bool,
+    stream: bool,
+}
+
+impl ChatRequest {
+    /// The request as gia command line arguments
+    fn gia_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(model) = &self.model {
+            args.extend(["-m".to_string(), model.clone()]);
+        }
+        for (values, flag) in [(&self.roles, "-t"), (&self.files, "-f"), (&self.urls, "-u")] {
+            for value in values {
+                args.extend([flag.to_string(), value.clone()]);
+            }
+        }
+        match self.resume.as_deref() {
+            Some("") => args.push("-R".to_string()),
+            Some(id) => args.push(format!("--resume={id}")),
+            None => {}
+        }
+        if self.no_save {
+            args.push("--no-save".to_string());
+        }
+        args.extend(["--".to_string(), self.prompt.clone()]);
+        args
+    }
+}
+
+async fn chat(
+    State(state): State<ServerState>,
+    headers: HeaderMap,
+    payload: Result<axum::Json<ChatRequest>, JsonRejection>,
+) -> Response {
+    let request = match payload {
+        Ok(axum::Json(request)) => request,
+        Err(e) => return invalid_body(e).into_response(),
+    };
+    if request.prompt.trim().is_empty() {
+        return bad_request("Missing prompt").into_response();
+    }
+    if let Err(e) = check_request_files(&request.files) {
+        return ApiError(StatusCode::FORBIDDEN, format!("{e:#}")).into_response();
+    }
+    let mut config = match Config::from_arg_list(&request.gia_args()) {
+        Ok(config) => config,
+        Err(e) => return bad_request(format!("{e:#}")).into_response(),
+    };
+    config.read_stdin = false;
+    config.remote_request = true;
+    log_info(&format!("HTTP chat request with model {}", config.model));
+
+    let wants_events = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+    if !(request.stream || wants_events) {
+        return match run_chat(&state, config, None).await {
+            Ok(result) => axum::Json(result).into_response(),
+            Err(e) => e.into_response(),
+        };
+    }
+
+    // Stream `chunk` events while the answer is generated, then `done` or `error`
+    let (sender, receiver) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let chunk_sender = sender.clone();
+        let mut on_text = move |text: &str| {
+            let data = json!({"text": text}).to_string();
+            let _ = chunk_sender.send(Event::default().event("chunk").data(data));
+        };
+        let event = match run_chat(&state, config, Some(&mut on_text)).await {
+            Ok(result) => Event::default().event("done").data(result.to_string()),
+            Err(ApiError(_, error)) => Event::default()
+                .event("error")
+                .data(json!({"error": error}).to_string()),
+        };
+        let _ = sender.send(event);
+    });
+    let events = UnboundedReceiverStream::new(receiver).map(Ok::<_, Infallible>);
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Run a chat request; returns the response, conversation id, model and token usage
+async fn run_chat(
+    state: &ServerState,
+    mut config: Config,
+    on_text: Option<TextCallback<'_>>,
+) -> Result<Value, ApiError> {
+    // Gathering input reads files and fetches web pages, so keep it off the runtime
+    let conversation_manager = state.conversation_manager.clone();
+    let (config, mut conversation, api_keys) = tokio::task::spawn_blocking(move || {
+        let (conversation, api_keys) = prepare_request(&mut config, &conversation_manager)?;
+        anyhow::Ok((config, conversation, api_keys))
+    })
+    .await
+    .context("Request task failed")??;
+    if config.ordered_content.is_empty() {
+        return Err(bad_request("No input content provided"));
+    }
+
+    // Continue the history as saved by the previous request on this conversation
+    let resuming = config.resume_last || config.resume_conversation.is_some();
+    let _conversation_lock = if resuming && !config.no_save {
+        let lock = state.conversation_locks.lock(&conversation.id).await;
+        if let Ok(saved) = state
+            .conversation_manager
+            .load_conversation(&conversation.id)
+        {
+            conversation = saved;
+        }
+        Some(lock)
+    } else {
+        None
+    };
+
+    let (response, usage) = complete_request(
+        &config,
+        &state.conversation_manager,
+        &mut conversation,
+        &api_keys,
+        on_text,
+    )
+    .await?;
+
+    Ok(json!({
+        "response": response,
+        "conversation_id": (!config.no_save).then_some(&conversation.id),
+        "model": config.model,
+        "usage": usage,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListQuery {
+    limit: Option<usize>, // 0 = all
+}
+
+async fn list_conversations(
+    State(state): State<ServerState>,
+    Query(query): Query<ListQuery>,
+) -> Result<axum::Json<Value>, ApiError> {
+    let limit = match query.limit.unwrap_or(DEFAULT_CONVERSATION_LIMIT) {
+        0 => usize::MAX,
+        limit => limit,
+    };
+    let conversations = state.conversation_manager.list_conversations()?;
+    Ok(axum::Json(Value::Array(
+        conversations.iter().take(limit).map(summary_json).collect(),
+    )))
+}
+
+async fn show_conversation(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> Result<axum::Json<Value>, ApiError> {
+    let conversation = state
+        .conversation_manager
+        .load_conversation(&id)
+        .map_err(|e| ApiError(StatusCode::NOT_FOUND, format!("{e:#}")))?;
+    Ok(axum::Json(
+        serde_json::to_value(&conversation).context("Failed to serialize conversation")?,
+    ))
+}
+
+fn summary_json(summary: &ConversationSummary) -> Value {
+    json!({
+        "id": summary.id,
+        "title": summary.title,
+        "created_at": summary.created_at,
+        "updated_at": summary.updated_at,
+        "message_count": summary.message_count,
+        "preview": summary.first_user_message,
+    })
+}
+
+fn is_loopback(host: &str) -> bool {
+    host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::content_part_wrapper::{ChatMessageWrapper, MessageContentWrapper};
+    use crate::conversation::{Conversation, TokenUsage};
+    use tempfile::TempDir;
+
+    /// Serve on a free local port; returns the base URL
+    async fn start_server(dir: &TempDir, token: Option<&str>) -> String {
+        let state = ServerState {
+            conversation_manager: Arc::new(ConversationManager::with_dir(dir.path().to_path_buf())),
+            token: token.map(Arc::from),
+            save_proxied: false,
+            conversation_locks: ConversationLocks::default(),
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await });
+        format!("http://{address}")
+    }
+
+    /// Send a request with ureq; returns status and JSON body
+    async fn send(method: &str, url: String, token: Option<&str>, body: &str) -> (u16, Value) {
+        let method = method.to_string();
+        let token = token.map(str::to_string);
+        let body = body.to_string();
+        tokio::task::spawn_blocking(move || {
+            let agent: ureq::Agent = ureq::Agent::config_builder()
+                .proxy(None)
+                .http_status_as_error(false)
+                .build()
+                .into();
+            let authorization = token.map(|token| format!("Bearer {token}"));
+            let mut response = if method == "POST" {
+                let mut request = agent.post(&url).header("Content-Type", "application/json");
+                if let Some(authorization) = &authorization {
+                    request = request.header("Authorization", authorization);
+                }
+                request.send(body.as_str()).unwrap()
+            } else {
+                let mut request = agent.get(&url);
+                if let Some(authorization) = &authorization {
+                    request = request.header("Authorization", authorization);
+                }
+                request.call().unwrap()
+            };
+            let status = response.status().as_u16();
+            let body = response.body_mut().read_to_string().unwrap();
+            let json = serde_json::from_str(&body).unwrap_or(Value::Null);
+            (status, json)
+        })
+        .await
+        .unwrap()
+    }
+
+    fn text_message(role: &str, text: &str) -> ChatMessageWrapper {
+        ChatMessageWrapper {
+            role: role.to_string(),
+            content: MessageContentWrapper::Text {
+                text: text.to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_conversation_routes() {
+        let dir = TempDir::new().unwrap();
+        let base = start_server(&dir, None).await;
+
+        let (status, list) = send("GET", format!("{base}/conversations"), None, "").await;
+        assert_eq!(status, 200);
+        assert_eq!(list, json!([]));
+
+        let mut conversation = Conversation::new("gemini-2.5-flash".to_string());
+        conversation.add_message_with_usage(
+            text_message("User", "What is SSE?"),
+            Vec::new(),
+            TokenUsage::default(),
+        );
+        ConversationManager::with_dir(dir.path().to_path_buf())
+            .save_conversation(&conversation)
+            .unwrap();
+
+        let (status, list) = send("GET", format!("{base}/conversations?limit=5"), None, "").await;
+        assert_eq!(status, 200);
+        assert_eq!(list[0]["id"], conversation.id);
+        assert_eq!(list[0]["message_count"], 1);
+        assert_eq!(list[0]["preview"], "What is SSE?");
+
+        let url = format!("{base}/conversations/{}", conversation.id);
+        let (status, shown) = send("GET", url, None, "").await;
+        assert_eq!(status, 200);
+        assert_eq!(shown["id"], conversation.id);
+        assert_eq!(shown["metadata"]["model_used"], "gemini-2.5-flash");
+
+        let (status, error) = send("GET", format!("{base}/conversations/missing"), None, "").await;
+        assert_eq!(status, 404);
+        assert!(error["error"].as_str().unwrap().contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_token_and_bad_requests() {
+        let dir = TempDir::new().unwrap();
+        let base = start_server(&dir, Some("s3cret")).await;
+
+        let (status, error) = send("GET", format!("{base}/conversations"), None, "").await;
+        assert_eq!(status, 401);
+        assert_eq!(error["error"], "Missing or wrong token");
+        let (status, _) = send("GET", format!("{base}/conversations"), Some("wrong"), "").await;
+        assert_eq!(status, 401);
+        let (status, _) = send("GET", format!("{base}/conversations"), Some("s3cret"), "").await;
+        assert_eq!(status, 200);
+
+        let url = format!("{base}/chat");
+        let (status, error) = send("POST", url.clone(), Some("s3cret"), "{}").await;
+        assert_eq!(status, 400);
+        assert_eq!(error["error"], "Missing prompt");
+        let (status, error) = send("POST", url, Some("s3cret"), r#"{"prompt": 1}"#).await;
+        assert_eq!(status, 400);
+        assert!(
+            error["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid request body")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_cannot_read_server_data() {
+        let dir = TempDir::new().unwrap();
+        let base = start_server(&dir, None).await;
+        let chat = |request: Value| {
+            let url = format!("{base}/chat");
+            async move { send("POST", url, None, &request.to_string()).await }
+        };
+
+        for prompt in [
+            "{{env:GEMINI_API_KEY}}",
+            "{{file:/etc/passwd}}",
+            "{{clipboard}}",
+        ] {
+            let (status, error) =
+                chat(json!({"prompt": prompt, "model": "ollama::llama3.2", "no_save": true})).await;
+            assert_eq!(status, 500);
+            let error = error["error"].as_str().unwrap();
+            assert!(error.contains("HTTP API or MCP"), "{prompt}: {error}");
+        }
+
+        for url in [
+            "file:///etc/passwd",
+            "http://127.0.0.1:9/",
+            "http://169.254.169.254/",
+        ] {
+            let (status, error) = chat(json!({
+                "prompt": "Summarize",
+                "model": "ollama::llama3.2",
+                "urls": [url],
+                "no_save": true,
+            }))
+            .await;
+            assert_eq!(status, 500);
+            let error = error["error"].as_str().unwrap();
+            assert!(
+                error.contains("HTTP API or MCP") || error.contains("is a local address"),
+                "{url}: {error}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_requires_json() {
+        let dir = TempDir::new().unwrap();
+        let base = start_server(&dir, None).await;
+
+        // A form or text/plain POST needs no CORS preflight, so any web page could send it
+        let status = tokio::task::spawn_blocking(move || {
+            let agent: ureq::Agent = ureq::Agent::config_builder()
+                .proxy(None)
+                .http_status_as_error(false)
+                .build()
+                .into();
+            agent
+                .post(&format!("{base}/chat"))
+                .header("Content-Type", "text/plain")
+                .send(r#"{"prompt": "Hi"}"#)
+                .unwrap()
+                .status()
+                .as_u16()
+        })
+        .await
+        .unwrap();
+        assert_eq!(status, 415);
+    }
+
+    #[test]
+    fn test_is_local_request() {
+        let request = |host: &str, origin: Option<&str>| {
+            let mut builder = Request::builder()
+                .uri("/conversations")
+                .header("Host", host);
+            if let Some(origin) = origin {
+                builder = builder.header("Origin", origin);
+            }
+            builder.body(axum::body::Body::empty()).unwrap()
+        };
+        assert!(is_local_request(&request("127.0.0.1:8080", None)));
+        assert!(is_local_request(&request("localhost:8080", None)));
+        assert!(is_local_request(&request("[::1]:8080", None)));
+        assert!(is_local_request(&request(
+            "localhost:8080",
+            Some("http://localhost:3000")
+        )));
+        // DNS rebinding: the page's name now resolves to 127.0.0.1
+        assert!(!is_local_request(&request("evil.example:8080", None)));
+        // Cross-site request from a web page
+        assert!(!is_local_request(&request(
+            "127.0.0.1:8080",
+            Some("https://evil.example")
+        )));
+        assert!(!is_local_request(&request("127.0.0.1:8080", Some("null"))));
+    }
+
+    #[test]
+    fn test_check_request_files() {
+        assert!(check_request_files(&["src/lib.rs".to_string()]).is_ok());
+        assert!(check_request_files(&["./src/lib.rs:1-10".to_string()]).is_ok());
+        assert!(check_request_files(&["src/missing.rs#main".to_string()]).is_ok());
+        for file in [
+            "/etc/passwd",
+            "~/.ssh/id_rsa",
+            "../Cargo.toml",
+            "src/../../x",
+        ] {
+            assert!(
+                check_request_files(&[file.to_string()]).is_err(),
+                "{file} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3creT", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret!", b"s3cret"));
+        assert!(!constant_time_eq(b"", b"s3cret"));
+    }
+
+    #[tokio::test]
+    async fn test_conversation_locks() {
+        let locks = ConversationLocks::default();
+        let first = locks.lock("a").await;
+        let timeout = std::time::Duration::from_millis(50);
+        assert!(
+            tokio::time::timeout(timeout, locks.lock("a"))
+                .await
+                .is_err()
+        );
+        assert!(tokio::time::timeout(timeout, locks.lock("b")).await.is_ok());
+        drop(first);
+        assert!(tokio::time::timeout(timeout, locks.lock("a")).await.is_ok());
+    }
+
+    #[test]
+    fn test_chat_request_args() {
+        let request: ChatRequest = serde_json::from_value(json!({
+            "prompt": "-v explain",
+            "model": "ollama::llama3.2",
+            "roles": ["rust-dev"],
+            "files": ["src/main.rs"],
+            "resume": "",
+        }))
+        .unwrap();
+        let args = request.gia_args();
+        assert_eq!(
+            args,
+            vec![
+                "-m",
+                "ollama::llama3.2",
+                "-t",
+                "rust-dev",
+                "-f",
+                "src/main.rs",
+                "-R",
+                "--",
+                "-v explain"
+            ]
+        );
+        let config = Config::from_arg_list(&args).unwrap();
+        assert_eq!(config.prompt, "-v explain");
+        assert!(config.resume_last);
+
+        let request: ChatRequest =
+            serde_json::from_value(json!({"prompt": "Go on", "resume": "ab12", "no_save": true}))
+                .unwrap();
+        assert_eq!(
+            request.gia_args(),
+            vec!["--resume=ab12", "--no-save", "--", "Go on"]
+        );
+        assert!(serde_json::from_value::<ChatRequest>(json!({"promt": "typo"})).is_err());
+    }
+
+    #[test]
+    fn test_is_loopback() {
+        assert!(is_loopback("127.0.0.1"));
+        assert!(is_loopback("::1"));
+        assert!(is_loopback("localhost"));
+        assert!(!is_loopback("0.0.0.0"));
+        assert!(!is_loopback("192.168.1.10"));
+    }
+}
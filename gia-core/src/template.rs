@@ -0,0 +1,365 @@
+//! Template variables for prompts and role/task files.
+//!
+//! `{{name}}` placeholders are replaced before the text is sent (and saved):
+//! `{{date}}`, `{{time}}`, `{{lang}}`, `{{clipboard}}`, `{{file:path}}`, `{{env:VAR}}`
+//! and any variable given with `--var key=value`. `\{{` keeps the braces literally,
+//! any other unknown placeholder is an error.
+//! Definitions from an untrusted project may only include files of the project
+//! and can't read environment variables. Prompts of requests over the HTTP API or
+//! MCP can't use `{{file:...}}`, `{{env:...}}` or `{{clipboard}}` at all.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use crate::cli::{Config, OutputMode};
+use crate::clipboard::read_clipboard;
+use crate::input::read_text_file;
+use crate::logging::log_debug;
+use crate::role::is_inside;
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)(?::([^}]*?))?\s*\}\}").expect("valid regex")
+});
+
+#[derive(Clone)]
+pub struct TemplateContext {
+    vars: HashMap<String, String>,
+    tts_language: Option<String>,
+    confined_to: Option<PathBuf>, // project directory of an untrusted definition
+    remote: bool,                 // true = prompt of a request over the HTTP API or MCP
+}
+
+impl TemplateContext {
+    pub fn from_config(config: &Config) -> Self {
+        let tts_language = match &config.output_mode {
+            OutputMode::Tts(language) => Some(language.clone()),
+            _ => None,
+        };
+
+        Self {
+            vars: config.vars.iter().cloned().collect(),
+            tts_language,
+            confined_to: None,
+            remote: config.remote_request,
+        }
+    }
+
+    /// The context for role/task files. They are files of this machine, so remote
+    /// requests may use their functions.
+    pub fn for_definition(&self) -> Self {
+        Self {
+            remote: false,
+            ..self.clone()
+        }
+    }
+
+    /// The context for a definition of an untrusted project
+    pub fn confined_to(&self, project_dir: &Path) -> Self {
+        Self {
+            confined_to: Some(project_dir.to_path_buf()),
+            ..self.clone()
+        }
+    }
+
+    /// Replace all placeholders in `text`. `source` names the text in error messages.
+    pub fn render(&self, text: &str, source: &str) -> Result<String> {
+        let mut result = String::with_capacity(text.len());
+        let mut missing = Vec::new();
+        let mut last_end = 0;
+
+        for captures in PLACEHOLDER.captures_iter(text) {
+            let whole = captures.get(0).expect("match");
+            let name = &captures[1];
+            let argument = captures.get(2).map(|m| m.as_str().trim());
+
+            result.push_str(&text[last_end..whole.start()]);
+            last_end = whole.end();
+
+            // Escaped placeholder: \{{name}} -> {{name}}
+            if text[..whole.start()].ends_with('\\') {
+                result.pop();
+                result.push_str(whole.as_str());
+                continue;
+            }
+
+            match self.resolve(name, argument, source)? {
+                Some(value) => result.push_str(&value),
+                None => {
+                    let display = argument.map_or(name.to_string(), |arg| format!("{name}:{arg}"));
+                    if !missing.contains(&display) {
+                        missing.push(display);
+                    }
+                }
+            }
+        }
+        result.push_str(&text[last_end..]);
+
+        if !missing.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Missing template variable(s) in {source}: {}. Set them with --var name=value",
+                missing.join(", ")
+            ));
+        }
+
+        Ok(result)
+    }
+
+    /// Value of a placeholder, `None` if the variable is not defined
+    fn resolve(&self, name: &str, argument: Option<&str>, source: &str) -> Result<Option<String>> {
+        // Remote clients must not read this machine's files, environment or clipboard
+        if self.remote
+            && matches!(
+                (name, argument),
+                ("file" | "env", Some(_)) | ("clipboard", None)
+            )
+        {
+            let display = argument.map_or(name.to_string(), |arg| format!("{name}:{arg}"));
+            return Err(anyhow::anyhow!(
+                "{{{{{display}}}}} can't be used in the {source} of requests over the HTTP API or MCP"
+            ));
+        }
+
+        if let Some(argument) = argument {
+            return match name {
+                "file" => {
+                    let path = expand_home(argument);
+                    if let Some(project_dir) = &self.confined_to {
+                        let absolute = std::env::current_dir()
+                            .context("Failed to get current directory")?
+                            .join(&path);
+                        if !is_inside(&absolute, project_dir) {
+                            return Err(anyhow::anyhow!(
+                                "{source} may only include files inside the project {} (see GIA_TRUSTED_PROJECTS): {{{{file:{argument}}}}}",
+                                project_dir.display()
+                            ));
+                        }
+                    }
+                    log_debug(&format!("Including file in {source}: {path}"));
+                    read_text_file(&path).map(Some).with_context(|| {
+                        format!("Failed to include {{{{file:{argument}}}}} in {source}")
+                    })
+                }
+                "env" => match &self.confined_to {
+                    Some(project_dir) => Err(anyhow::anyhow!(
+                        "{source} from the project {} can't read {{{{env:{argument}}}}} (see GIA_TRUSTED_PROJECTS)",
+                        project_dir.display()
+                    )),
+                    None => Ok(std::env::var(argument).ok()),
+                },
+                _ => Err(anyhow::anyhow!(
+                    "Unknown template function '{name}:' in {source} (supported: file:, env:)"
+                )),
+            };
+        }
+
+        // Variables from --var take precedence over built-ins
+        if let Some(value) = self.vars.get(name) {
+            return Ok(Some(value.clone()));
+        }
+
+        match name {
+            "date" => Ok(Some(chrono::Local::now().format("%Y-%m-%d").to_string())),
+            "time" => Ok(Some(chrono::Local::now().format("%H:%M").to_string())),
+            "lang" => Ok(self.tts_language.clone().or_else(system_language)),
+            "clipboard" => read_clipboard().map(Some).with_context(|| {
+                format!("Failed to read clipboard for {{{{clipboard}}}} in {source}")
+            }),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Language tag from the LANG environment variable ("de_DE.UTF-8" -> "de-DE")
+fn system_language() -> Option<String> {
+    let lang = std::env::var("LANG").ok()?;
+    let tag = lang.split(['.', '@']).next()?.replace('_', "-");
+    if tag.is_empty() || tag == "C" || tag == "POSIX" {
+        None
+    } else {
+        Some(tag)
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = dirs::home_dir()
+    {
+        return home.join(rest).to_string_lossy().into_owned();
+    }
+    path.to_string()
+}
+
+/// Parse a `--var key=value` argument
+pub fn parse_var(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{arg}'"))?;
+    let key = key.trim();
+    let valid = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(format!("invalid variable name '{key}'"));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn context(vars: &[(&str, &str)]) -> TemplateContext {
+        TemplateContext {
+            vars: vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            tts_language: None,
+            confined_to: None,
+            remote: false,
+        }
+    }
+
+    #[test]
+    fn test_render_variables() {
+        let ctx = context(&[("lang", "de-DE"), ("name", "Ada")]);
+        let rendered = ctx
+            .render("Translate to {{lang}} for {{ name }}.", "prompt")
+            .unwrap();
+        assert_eq!(rendered, "Translate to de-DE for Ada.");
+
+        let date = ctx.render("{{date}}", "prompt").unwrap();
+        assert_eq!(date, chrono::Local::now().format("%Y-%m-%d").to_string());
+
+        // Text without placeholders is unchanged
+        assert_eq!(
+            ctx.render("a { b } {{ }}", "prompt").unwrap(),
+            "a { b } {{ }}"
+        );
+    }
+
+    #[test]
+    fn test_render_missing_variables() {
+        let ctx = context(&[]);
+        let err = ctx
+            .render(
+                "{{audience}} {{tone}} {{audience}} {{env:GIA_TEST_UNSET_VAR}}",
+                "task 'x'",
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("task 'x'"));
+        assert!(err.contains("audience, tone, env:GIA_TEST_UNSET_VAR"));
+        assert!(err.contains("--var"));
+    }
+
+    #[test]
+    fn test_escape_keeps_unknown_placeholders() {
+        let ctx = context(&[("name", "Ada")]);
+        assert_eq!(
+            ctx.render("{{name}} wrote \\{{user}} and \\{{t:x}}", "prompt")
+                .unwrap(),
+            "Ada wrote {{user}} and {{t:x}}"
+        );
+        let err = ctx.render("{{t:x}}", "prompt").unwrap_err();
+        assert!(err.to_string().contains("Unknown template function 't:'"));
+    }
+
+    #[test]
+    fn test_value_ending_in_backslash_does_not_escape() {
+        let ctx = context(&[("dir", "C:\\temp\\"), ("file", "notes.txt")]);
+        assert_eq!(
+            ctx.render("{{dir}}{{file}}", "prompt").unwrap(),
+            "C:\\temp\\notes.txt"
+        );
+    }
+
+    #[test]
+    fn test_render_file_env_and_escape() {
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(temp_file.path(), "included text").unwrap();
+        let path = temp_file.path().to_str().unwrap();
+
+        let ctx = context(&[]);
+        let rendered = ctx
+            .render(
+                &format!("Start {{{{file:{path}}}}} \\{{{{date}}}} {{{{env:PATH}}}}"),
+                "prompt",
+            )
+            .unwrap();
+        assert!(rendered.starts_with("Start included text {{date}} "));
+        assert!(rendered.ends_with(&std::env::var("PATH").unwrap()));
+
+        let err = ctx
+            .render("{{file:/nonexistent/gia.txt}}", "prompt")
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to include {{file:/nonexistent/gia.txt}}"));
+
+        assert!(ctx.render("{{shell:ls}}", "prompt").is_err());
+    }
+
+    #[test]
+    fn test_render_confined_to_project() {
+        let project = tempfile::TempDir::new().unwrap();
+        let inside = project.path().join("notes.md");
+        std::fs::write(&inside, "project notes").unwrap();
+        let outside = NamedTempFile::new().unwrap();
+        std::fs::write(outside.path(), "secret").unwrap();
+
+        let ctx = context(&[]).confined_to(project.path());
+        assert_eq!(
+            ctx.render(&format!("{{{{file:{}}}}}", inside.display()), "role 'r'")
+                .unwrap(),
+            "project notes"
+        );
+        let err = ctx
+            .render(
+                &format!("{{{{file:{}}}}}", outside.path().display()),
+                "role 'r'",
+            )
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("only include files inside the project")
+        );
+        assert!(ctx.render("{{file:~/.ssh/id_rsa}}", "role 'r'").is_err());
+
+        let err = ctx.render("{{env:PATH}}", "role 'r'").unwrap_err();
+        assert!(err.to_string().contains("can't read {{env:PATH}}"));
+    }
+
+    #[test]
+    fn test_remote_prompt_has_no_functions() {
+        let ctx = TemplateContext {
+            remote: true,
+            ..context(&[])
+        };
+        for prompt in ["{{env:PATH}}", "{{file:/etc/passwd}}", "{{clipboard}}"] {
+            let err = ctx.render(prompt, "prompt").unwrap_err();
+            assert!(err.to_string().contains("HTTP API or MCP"), "{err}");
+        }
+        assert_eq!(
+            ctx.render("{{date}}", "prompt").unwrap(),
+            chrono::Local::now().format("%Y-%m-%d").to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_var() {
+        assert_eq!(
+            parse_var("lang=de-DE").unwrap(),
+            ("lang".to_string(), "de-DE".to_string())
+        );
+        assert_eq!(
+            parse_var("query=a=b").unwrap(),
+            ("query".to_string(), "a=b".to_string())
+        );
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("1x=y").is_err());
+    }
+}
@@ -0,0 +1,606 @@
+//! Local tools the model can call with `--tools`.
+//!
+//! File tools only see the current working directory and its subdirectories
+//! (symlinks are not followed). `run_command` runs a program without a shell, only
+//! if it was allowed with `--allow-command` and confirmed on the terminal;
+//! `get_clipboard` needs a confirmation too. Tools of MCP servers from
+//! `~/.gia/config` are added next to them (see mcp.rs).
+
+use anyhow::{Context, Result};
+use genai::chat::{Tool, ToolCall};
+use regex::Regex;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clipboard::read_clipboard;
+use crate::config_file::load_config_file;
+use crate::constants::{
+    MAX_GREP_FILE_SIZE, MAX_GREP_MATCHES, TOOL_COMMAND_TIMEOUT_SECS, TOOL_OUTPUT_LIMIT,
+};
+use crate::input::read_text_file;
+use crate::logging::{log_debug, log_info, log_warn};
+use crate::mcp::McpClient;
+
+/// Directories skipped by `grep` when walking the tree (besides hidden ones)
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+pub struct ToolRegistry {
+    root: PathBuf,
+    allowed_commands: Vec<String>,
+    mcp_clients: Vec<McpClient>,
+}
+
+/// Working directory, allowed commands and requested MCP servers of a registry
+type RegistryKey = (PathBuf, Vec<String>, Vec<String>);
+
+/// Registries built so far, so MCP servers are started once per process
+/// instead of once per request
+static REGISTRIES: tokio::sync::Mutex<Vec<(RegistryKey, Arc<ToolRegistry>)>> =
+    tokio::sync::Mutex::const_new(Vec::new());
+
+impl ToolRegistry {
+    /// The registry for these settings, built on first use and shared afterwards.
+    /// A registry with a crashed MCP server is built again.
+    pub async fn shared(allowed_commands: &[String], mcp_servers: &[String]) -> Result<Arc<Self>> {
+        let root = std::env::current_dir()
+            .and_then(|dir| dir.canonicalize())
+            .context("Failed to get current directory")?;
+        let key = (root, allowed_commands.to_vec(), mcp_servers.to_vec());
+        let mut registries = REGISTRIES.lock().await;
+        registries.retain(|(_, registry)| registry.mcp_clients.iter().all(McpClient::is_alive));
+        if let Some((_, registry)) = registries.iter().find(|(existing, _)| *existing == key) {
+            return Ok(Arc::clone(registry));
+        }
+        let registry = Arc::new(Self::new(key.0.clone(), allowed_commands, mcp_servers).await?);
+        registries.push((key, Arc::clone(&registry)));
+        Ok(registry)
+    }
+
+    /// Local tools plus the MCP servers named with `--mcp` and those marked `default: true`.
+    /// A server that fails to start is reported and left out.
+    async fn new(
+        root: PathBuf,
+        allowed_commands: &[String],
+        mcp_servers: &[String],
+    ) -> Result<Self> {
+        let mut registry = Self::with_root(root, allowed_commands);
+
+        let config = load_config_file()?;
+        if let Some(unknown) = mcp_servers
+            .iter()
+            .find(|name| !config.mcp_servers.contains_key(*name))
+        {
+            return Err(anyhow::anyhow!(
+                "MCP server '{unknown}' is not configured in ~/.gia/config"
+            ));
+        }
+
+        for (name, server) in &config.mcp_servers {
+            if !server.default && !mcp_servers.contains(name) {
+                continue;
+            }
+            match McpClient::connect(name, server).await {
+                Ok(client) => registry.mcp_clients.push(client),
+                Err(e) => {
+                    log_warn(&format!("MCP server '{name}' unavailable: {e:#}"));
+                    eprintln!("Warning: MCP server '{name}' unavailable: {e:#}");
+                }
+            }
+        }
+
+        Ok(registry)
+    }
+
+    fn with_root(root: PathBuf, allowed_commands: &[String]) -> Self {
+        Self {
+            root,
+            allowed_commands: allowed_commands.to_vec(),
+            mcp_clients: Vec::new(),
+        }
+    }
+
+    /// `server/tool` when the model-facing name belongs to an MCP server
+    pub fn mcp_tool(&self, fn_name: &str) -> Option<String> {
+        self.mcp_clients.iter().find_map(|client| {
+            client
+                .find_tool(fn_name)
+                .map(|tool| format!("{}/{tool}", client.server()))
+        })
+    }
+
+    /// Tool declarations sent to the model
+    pub fn definitions(&self) -> Vec<Tool> {
+        let mut tools = vec![
+            Tool::new("read_file")
+                .with_description(
+                    "Read a text file in the working directory, optionally a line range",
+                )
+                .with_schema(json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Path relative to the working directory"},
+                        "start_line": {"type": "integer", "description": "First line (1-based)"},
+                        "end_line": {"type": "integer", "description": "Last line (inclusive)"}
+                    },
+                    "required": ["path"]
+                })),
+            Tool::new("list_dir")
+                .with_description("List the entries of a directory in the working directory")
+                .with_schema(json!({
+                    "type": "object",
+                    "properties": {
+                        "path": {"type": "string", "description": "Directory relative to the working directory (default: .)"}
+                    }
+                })),
+            Tool::new("grep")
+                .with_description(
+                    "Search text files below a path for a regular expression and return matching lines",
+                )
+                .with_schema(json!({
+                    "type": "object",
+                    "properties": {
+                        "pattern": {"type": "string", "description": "Regular expression"},
+                        "path": {"type": "string", "description": "File or directory (default: .)"}
+                    },
+                    "required": ["pattern"]
+                })),
+            Tool::new("get_clipboard")
+                .with_description("Get the current text content of the clipboard")
+                .with_schema(json!({"type": "object", "properties": {}})),
+        ];
+
+        if !self.allowed_commands.is_empty() {
+            tools.push(
+                Tool::new("run_command")
+                    .with_description(format!(
+                        "Run a command in the working directory (no shell) and return its output. Allowed programs: {}",
+                        self.allowed_commands.join(", ")
+                    ))
+                    .with_schema(json!({
+                        "type": "object",
+                        "properties": {
+                            "program": {"type": "string"},
+                            "args": {"type": "array", "items": {"type": "string"}}
+                        },
+                        "required": ["program"]
+                    })),
+            );
+        }
+
+        for client in &self.mcp_clients {
+            tools.extend(client.definitions());
+        }
+
+        tools
+    }
+
+    /// Run a tool call. Failures are returned as text so the model can react to them.
+    pub async fn execute(&self, call: &ToolCall) -> String {
+        log_info(&format!(
+            "Tool call: {}({})",
+            call.fn_name, call.fn_arguments
+        ));
+        eprintln!("🔧 {}({})", call.fn_name, call.fn_arguments);
+
+        let args = &call.fn_arguments;
+        let result = match call.fn_name.as_str() {
+            "read_file" => self.read_file(args),
+            "list_dir" => self.list_dir(args),
+            "grep" => self.grep(args),
+            "get_clipboard" => self.get_clipboard().await,
+            "run_command" => self.run_command(args).await,
+            name => match self
+                .mcp_clients
+                .iter()
+                .find_map(|client| client.find_tool(name).map(|tool| (client, tool)))
+            {
+                Some((client, tool)) => client.call_tool(tool, args).await,
+                None => Err(anyhow::anyhow!("Unknown tool: {name}")),
+            },
+        };
+
+        match result {
+            Ok(output) => truncate_output(output),
+            Err(e) => {
+                log_warn(&format!("Tool {} failed: {e:#}", call.fn_name));
+                format!("Error: {e:#}")
+            }
+        }
+    }
+
+    /// Resolve a path argument and make sure it stays inside the working directory
+    fn resolve_path(&self, path: &str) -> Result<PathBuf> {
+        let resolved = self
+            .root
+            .join(path)
+            .canonicalize()
+            .with_context(|| format!("Path not found: {path}"))?;
+        if !resolved.starts_with(&self.root) {
+            return Err(anyhow::anyhow!(
+                "Access denied: {path} is outside the working directory"
+            ));
+        }
+        Ok(resolved)
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        if relative.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            relative.to_string_lossy().replace('\\', "/")
+        }
+    }
+
+    fn read_file(&self, args: &Value) -> Result<String> {
+        let path = self.resolve_path(string_arg(args, "path")?)?;
+        let content = read_text_file(&path.to_string_lossy())?;
+
+        let start = args.get("start_line").and_then(Value::as_u64);
+        let end = args.get("end_line").and_then(Value::as_u64);
+        if start.is_none() && end.is_none() {
+            return Ok(content);
+        }
+
+        let start = start.unwrap_or(1).max(1) as usize;
+        let end = end.map_or(usize::MAX, |end| end as usize);
+        Ok(content
+            .lines()
+            .enumerate()
+            .filter(|(index, _)| (start..=end).contains(&(index + 1)))
+            .map(|(index, line)| format!("{}: {line}", index + 1))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn list_dir(&self, args: &Value) -> Result<String> {
+        let path_arg = args.get("path").and_then(Value::as_str).unwrap_or(".");
+        let dir = self.resolve_path(path_arg)?;
+
+        let mut entries: Vec<String> = fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory: {path_arg}"))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if entry.path().is_dir() {
+                    format!("{name}/")
+                } else {
+                    name
+                }
+            })
+            .collect();
+        entries.sort();
+
+        if entries.is_empty() {
+            return Ok(format!("{} is empty", self.display_path(&dir)));
+        }
+        Ok(entries.join("\n"))
+    }
+
+    fn grep(&self, args: &Value) -> Result<String> {
+        let pattern = string_arg(args, "pattern")?;
+        let regex = Regex::new(pattern).with_context(|| format!("Invalid pattern: {pattern}"))?;
+        let path_arg = args.get("path").and_then(Value::as_str).unwrap_or(".");
+        let start = self.resolve_path(path_arg)?;
+
+        let mut files = Vec::new();
+        collect_searchable_files(&start, &mut files);
+        files.sort();
+
+        let mut matches = Vec::new();
+        'files: for file in files {
+            if fs::metadata(&file).is_ok_and(|metadata| metadata.len() > MAX_GREP_FILE_SIZE) {
+                log_debug(&format!("grep: skipping large file {}", file.display()));
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&file) else {
+                continue; // binary or unreadable
+            };
+            for (index, line) in content.lines().enumerate() {
+                if regex.is_match(line) {
+                    matches.push(format!(
+                        "{}:{}: {}",
+                        self.display_path(&file),
+                        index + 1,
+                        line.trim_end()
+                    ));
+                    if matches.len() >= MAX_GREP_MATCHES {
+                        matches.push(format!("(stopped after {MAX_GREP_MATCHES} matches)"));
+                        break 'files;
+                    }
+                }
+            }
+        }
+
+        if matches.is_empty() {
+            return Ok(format!("No matches for {pattern}"));
+        }
+        Ok(matches.join("\n"))
+    }
+
+    async fn get_clipboard(&self) -> Result<String> {
+        if !confirm_on_terminal("Let the model read the clipboard?").await {
+            return Err(anyhow::anyhow!("The user declined to share the clipboard"));
+        }
+        read_clipboard().context("Failed to read clipboard")
+    }
+
+    async fn run_command(&self, args: &Value) -> Result<String> {
+        let program = string_arg(args, "program")?;
+        let command_args: Vec<String> = args
+            .get("args")
+            .and_then(Value::as_array)
+            .map(|values| {
+                values
+                    .iter()
+                    .map(|v| v.as_str().map_or_else(|| v.to_string(), str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if !self
+            .allowed_commands
+            .iter()
+            .any(|allowed| allowed == program)
+        {
+            return Err(anyhow::anyhow!(
+                "Command '{program}' is not allowed (allowed: {})",
+                self.allowed_commands.join(", ")
+            ));
+        }
+
+        let command_line = std::iter::once(program.to_string())
+            .chain(command_args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !confirm_on_terminal(&format!("Run `{command_line}`?")).await {
+            return Err(anyhow::anyhow!("The user declined to run `{command_line}`"));
+        }
+
+        log_info(&format!("Running command: {command_line}"));
+        let output = tokio::time::timeout(
+            Duration::from_secs(TOOL_COMMAND_TIMEOUT_SECS),
+            tokio::process::Command::new(program)
+                .args(&command_args)
+                .current_dir(&self.root)
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!("`{command_line}` timed out after {TOOL_COMMAND_TIMEOUT_SECS}s")
+        })?
+        .with_context(|| format!("Failed to run `{command_line}`"))?;
+
+        let mut result = format!("Exit status: {}\n", output.status);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !stdout.trim().is_empty() {
+            result.push_str(&format!("\nstdout:\n{}\n", stdout.trim_end()));
+        }
+        if !stderr.trim().is_empty() {
+            result.push_str(&format!("\nstderr:\n{}\n", stderr.trim_end()));
+        }
+        Ok(result)
+    }
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow::anyhow!("Missing argument: {name}"))
+}
+
+fn truncate_output(output: String) -> String {
+    if output.len() <= TOOL_OUTPUT_LIMIT {
+        return output;
+    }
+    let mut end = TOOL_OUTPUT_LIMIT;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n\n[output truncated: {} of {} bytes shown]",
+        &output[..end],
+        end,
+        output.len()
+    )
+}
+
+/// Files below `path` (or `path` itself), skipping hidden and build directories.
+/// Symlinks are skipped: they could lead out of the working directory or into a cycle.
+fn collect_searchable_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(path) else {
+        log_debug(&format!(
+            "Skipping unreadable directory: {}",
+            path.display()
+        ));
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let entry_path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_symlink() {
+            log_debug(&format!("grep: skipping symlink {}", entry_path.display()));
+        } else if file_type.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_searchable_files(&entry_path, files);
+            }
+        } else if file_type.is_file() {
+            files.push(entry_path);
+        }
+    }
+}
+
+/// Ask a yes/no question on the controlling terminal (stdin may carry input content).
+/// Returns false when there is no terminal.
+async fn confirm_on_terminal(question: &str) -> bool {
+    let question = question.to_string();
+    tokio::task::spawn_blocking(move || confirm_blocking(&question))
+        .await
+        .unwrap_or(false)
+}
+
+fn confirm_blocking(question: &str) -> bool {
+    let tty = if cfg!(windows) { "CONIN$" } else { "/dev/tty" };
+    let Ok(terminal) = fs::File::open(tty) else {
+        log_warn("No terminal available to confirm the command");
+        return false;
+    };
+
+    eprint!("{question} [y/N] ");
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    if BufReader::new(terminal).read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn registry(dir: &TempDir) -> ToolRegistry {
+        ToolRegistry::with_root(dir.path().canonicalize().unwrap(), &[])
+    }
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall {
+            call_id: name.to_string(),
+            fn_name: name.to_string(),
+            fn_arguments: arguments,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_tools() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(
+            dir.path().join("src/main.rs"),
+            "fn main() {\n    run();\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join(".git/config"), "fn hidden()").unwrap();
+        let tools = registry(&dir);
+
+        let listing = tools.execute(&call("list_dir", json!({}))).await;
+        assert_eq!(listing, ".git/\nsrc/");
+
+        let content = tools
+            .execute(&call(
+                "read_file",
+                json!({"path": "src/main.rs", "start_line": 2, "end_line": 2}),
+            ))
+            .await;
+        assert_eq!(content, "2:     run();");
+
+        let matches = tools
+            .execute(&call("grep", json!({"pattern": "fn \\w+"})))
+            .await;
+        assert_eq!(matches, "src/main.rs:1: fn main() {");
+    }
+
+    #[tokio::test]
+    async fn test_paths_outside_working_directory_are_denied() {
+        let dir = TempDir::new().unwrap();
+        let tools = registry(&dir);
+
+        let result = tools
+            .execute(&call("read_file", json!({"path": "../../etc/hostname"})))
+            .await;
+        assert!(result.starts_with("Error:"));
+
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        let result = tools
+            .execute(&call(
+                "read_file",
+                json!({"path": outside.path().join("secret.txt")}),
+            ))
+            .await;
+        assert!(result.contains("outside the working directory"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_grep_skips_symlinks() {
+        let dir = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        fs::write(outside.path().join("secret.txt"), "password=hunter2").unwrap();
+        fs::write(dir.path().join("notes.txt"), "password=in-tree").unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("escape")).unwrap();
+        // A cycle would recurse forever if links were followed
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("loop")).unwrap();
+        let tools = registry(&dir);
+
+        let matches = tools
+            .execute(&call("grep", json!({"pattern": "password"})))
+            .await;
+        assert_eq!(matches, "notes.txt:1: password=in-tree");
+    }
+
+    #[tokio::test]
+    async fn test_grep_skips_large_files() {
+        let dir = TempDir::new().unwrap();
+        let large = "needle\n".repeat(MAX_GREP_FILE_SIZE as usize / 7 + 1);
+        fs::write(dir.path().join("large.log"), large).unwrap();
+        let tools = registry(&dir);
+
+        let matches = tools
+            .execute(&call("grep", json!({"pattern": "needle"})))
+            .await;
+        assert_eq!(matches, "No matches for needle");
+    }
+
+    #[tokio::test]
+    async fn test_run_command_requires_allow_list() {
+        let dir = TempDir::new().unwrap();
+        let tools = registry(&dir);
+        assert!(
+            !tools
+                .definitions()
+                .iter()
+                .any(|tool| tool.name == "run_command")
+        );
+
+        let result = tools
+            .execute(&call(
+                "run_command",
+                json!({"program": "rm", "args": ["-rf", "."]}),
+            ))
+            .await;
+        assert!(result.contains("not allowed"));
+
+        let tools = ToolRegistry::with_root(dir.path().to_path_buf(), &["git".to_string()]);
+        assert!(
+            tools
+                .definitions()
+                .iter()
+                .any(|tool| tool.name == "run_command")
+        );
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        let long = "é".repeat(TOOL_OUTPUT_LIMIT);
+        let truncated = truncate_output(long);
+        assert!(truncated.contains("[output truncated"));
+        assert_eq!(truncate_output("short".to_string()), "short");
+    }
+}
@@ -0,0 +1,992 @@
+//! Web page input for `-u <url>`.
+//!
+//! Fetches `http(s)://` pages (or reads `file://` HTML), keeps the main content
+//! (`<article>`, `<main>` or `<body>`), drops navigation, scripts and similar
+//! boilerplate, and converts the rest to markdown. Small images referenced by the
+//! content are downloaded so they can be sent as image parts.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use ureq::http::Uri;
+use ureq::unversioned::resolver::{DefaultResolver, ResolvedSocketAddrs, Resolver};
+use ureq::unversioned::transport::{DefaultConnector, NextTimeout};
+use url::Url;
+
+use crate::constants::{MAX_WEB_IMAGE_SIZE, MAX_WEB_IMAGES, get_max_input_file_size};
+use crate::logging::{log_debug, log_info};
+
+/// Elements that never contain readable page content
+const SKIPPED_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "select", "input", "textarea", "svg", "canvas", "iframe", "object", "embed",
+];
+
+/// Class/id fragments that mark boilerplate blocks (cookie banners, sidebars, ...)
+const BOILERPLATE_MARKERS: &[&str] = &[
+    "cookie",
+    "consent",
+    "advert",
+    "sidebar",
+    "breadcrumb",
+    "newsletter",
+    "popup",
+    "modal",
+    "social",
+];
+
+/// Image types accepted by the providers as inline image parts
+const WEB_IMAGE_MIME_TYPES: &[&str] = &["image/jpeg", "image/png", "image/webp", "image/heic"];
+
+#[derive(Debug, Clone)]
+pub struct WebImage {
+    pub source: String,
+    pub mime_type: String,
+    pub data: String, // base64
+}
+
+#[derive(Debug, Clone)]
+pub struct WebPage {
+    pub url: String,
+    pub markdown: String,
+    pub images: Vec<WebImage>,
+}
+
+/// Fetch a web page (or `file://` HTML) and convert its readable content to markdown.
+/// With `public_only` (requests over the HTTP API or MCP), only `http(s)://` pages
+/// on public addresses are fetched, and their images are treated the same way.
+pub fn fetch_web_page(url: &str, public_only: bool) -> Result<WebPage> {
+    let parsed_url = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+
+    let (base_url, content_type, body) = match parsed_url.scheme() {
+        "file" if public_only => {
+            return Err(anyhow::anyhow!(
+                "file:// URLs can't be used in requests over the HTTP API or MCP: {url}"
+            ));
+        }
+        "file" => {
+            let path = parsed_url
+                .to_file_path()
+                .map_err(|_| anyhow::anyhow!("Invalid file URL: {url}"))?;
+            let html = crate::input::read_text_file(&path.to_string_lossy())?;
+            (parsed_url, "text/html".to_string(), html.into_bytes())
+        }
+        "http" | "https" => fetch_http(url, get_max_input_file_size(), !public_only)?,
+        scheme => {
+            return Err(anyhow::anyhow!(
+                "Unsupported URL scheme '{scheme}' (use http, https or file): {url}"
+            ));
+        }
+    };
+
+    let text = String::from_utf8_lossy(&body).into_owned();
+
+    // Non-HTML responses (plain text, JSON, markdown) are used as-is
+    if !content_type.contains("html") {
+        log_info(&format!(
+            "Fetched {} characters of {content_type} from {url}",
+            text.len()
+        ));
+        return Ok(WebPage {
+            url: url.to_string(),
+            markdown: text,
+            images: Vec::new(),
+        });
+    }
+
+    let (markdown, image_sources) = html_to_markdown(&text, &base_url);
+    let access = if public_only {
+        ImageAccess::PublicNetwork
+    } else {
+        ImageAccess::for_page(&base_url)
+    };
+    let images = fetch_images(&image_sources, access);
+
+    log_info(&format!(
+        "Extracted {} characters and {} image(s) from {url}",
+        markdown.len(),
+        images.len()
+    ));
+
+    Ok(WebPage {
+        url: url.to_string(),
+        markdown,
+        images,
+    })
+}
+
+const MAX_REDIRECTS: usize = 10;
+
+/// The response body is larger than the limit of the read
+#[derive(Debug)]
+struct BodyTooLarge {
+    limit: u64,
+}
+
+impl std::fmt::Display for BodyTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "response body exceeds the limit of {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for BodyTooLarge {}
+
+/// GET a URL, returning the final URL (after redirects), content type and body.
+/// Without `allow_local_hosts`, every hop must be a public address.
+fn fetch_http(url: &str, max_size: u64, allow_local_hosts: bool) -> Result<(Url, String, Vec<u8>)> {
+    log_debug(&format!("Fetching URL: {url}"));
+
+    // Redirects are followed here so that every hop can be checked
+    let config = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .max_redirects(0);
+    let agent: ureq::Agent = if allow_local_hosts {
+        config.build().into()
+    } else {
+        // Connect directly: a proxy would resolve the host again on its own
+        ureq::Agent::with_parts(
+            config.proxy(None).build(),
+            DefaultConnector::new(),
+            PublicResolver::default(),
+        )
+    };
+
+    let mut final_url = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let mut redirects = 0;
+    let mut response = loop {
+        let response = agent
+            .get(final_url.as_str())
+            .header("User-Agent", concat!("gia/", env!("GIA_VERSION")))
+            .call()
+            .with_context(|| format!("Failed to fetch URL: {url}"))?;
+        if !response.status().is_redirection() {
+            break response;
+        }
+
+        redirects += 1;
+        if redirects > MAX_REDIRECTS {
+            anyhow::bail!("Too many redirects: {url}");
+        }
+        let location = response
+            .headers()
+            .get("location")
+            .and_then(|value| value.to_str().ok())
+            .with_context(|| format!("Redirect without location from {final_url}"))?;
+        final_url = final_url
+            .join(location)
+            .with_context(|| format!("Invalid redirect from {final_url}: {location}"))?;
+        log_debug(&format!("Following redirect to {final_url}"));
+    };
+
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or(value)
+                .trim()
+                .to_lowercase()
+        })
+        .unwrap_or_default();
+
+    let body = response
+        .body_mut()
+        .with_config()
+        .limit(max_size)
+        .read_to_vec()
+        .map_err(|e| match e {
+            ureq::Error::BodyExceedsLimit(_) => {
+                anyhow::Error::new(BodyTooLarge { limit: max_size })
+            }
+            e => anyhow::Error::new(e),
+        })
+        .with_context(|| format!("Failed to read response from {url}"))?;
+
+    Ok((final_url, content_type, body))
+}
+
+/// A host resolved to an address on this machine or in the local network
+#[derive(Debug)]
+struct LocalAddress {
+    host: String,
+    ip: IpAddr,
+}
+
+impl std::fmt::Display for LocalAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "refusing to connect to {}: {} is a local address",
+            self.host, self.ip
+        )
+    }
+}
+
+impl std::error::Error for LocalAddress {}
+
+/// Resolves host names like ureq's default resolver, but refuses hosts on this
+/// machine or in the local network. The connection is made to the addresses
+/// returned here, so a host can't pass the check and resolve to a local address
+/// when connecting (DNS rebinding).
+#[derive(Debug, Default)]
+struct PublicResolver(DefaultResolver);
+
+impl Resolver for PublicResolver {
+    fn resolve(
+        &self,
+        uri: &Uri,
+        config: &ureq::config::Config,
+        timeout: NextTimeout,
+    ) -> Result<ResolvedSocketAddrs, ureq::Error> {
+        let addresses = self.0.resolve(uri, config, timeout)?;
+        if let Some(address) = addresses.iter().find(|address| is_local_ip(address.ip())) {
+            return Err(ureq::Error::Other(Box::new(LocalAddress {
+                host: uri.host().unwrap_or_default().to_string(),
+                ip: address.ip(),
+            })));
+        }
+        Ok(addresses)
+    }
+}
+
+fn is_local_host(url: &Url) -> bool {
+    url.socket_addrs(|| None)
+        .is_ok_and(|addresses| addresses.iter().any(|address| is_local_ip(address.ip())))
+}
+
+/// Addresses that aren't on the public internet: loopback, link-local, private, shared
+/// (carrier-grade NAT), benchmarking, multicast and reserved addresses, also when
+/// embedded in IPv6 (IPv4-mapped and NAT64)
+fn is_local_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_multicast()
+                || first == 0 // "this network", including 0.0.0.0
+                || first >= 240 // reserved and broadcast
+                || (first == 100 && (second & 0xc0) == 64) // 100.64.0.0/10
+                || (first == 198 && (second & 0xfe) == 18) // 198.18.0.0/15
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            let nat64 = segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (segments[0] & 0xfe00) == 0xfc00 // unique local
+                || (segments[0] & 0xffc0) == 0xfe80 // link-local
+                || (nat64 && is_local_ip(IpAddr::V4(Ipv4Addr::from_bits(ip.to_bits() as u32))))
+                || ip
+                    .to_ipv4_mapped()
+                    .is_some_and(|ip| is_local_ip(IpAddr::V4(ip)))
+        }
+    }
+}
+
+/// What the images of a page may reference: a remote page must not read local
+/// files or reach services on this machine and in the local network
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ImageAccess {
+    Everything,    // file:// page
+    LocalNetwork,  // page on this machine or in the local network
+    PublicNetwork, // any other page
+}
+
+impl ImageAccess {
+    fn for_page(page_url: &Url) -> Self {
+        if page_url.scheme() == "file" {
+            ImageAccess::Everything
+        } else if is_local_host(page_url) {
+            ImageAccess::LocalNetwork
+        } else {
+            ImageAccess::PublicNetwork
+        }
+    }
+}
+
+fn fetch_images(sources: &[String], access: ImageAccess) -> Vec<WebImage> {
+    let mut images = Vec::new();
+
+    for source in sources {
+        if images.len() >= MAX_WEB_IMAGES {
+            log_debug(&format!(
+                "Image limit of {MAX_WEB_IMAGES} reached, skipping remaining images"
+            ));
+            break;
+        }
+
+        match fetch_image(source, access) {
+            Ok(Some(image)) => images.push(image),
+            Ok(None) => log_debug(&format!("Skipping unsupported or large image: {source}")),
+            Err(e) => log_debug(&format!("Failed to fetch image {source}: {e}")),
+        }
+    }
+
+    images
+}
+
+/// Fetch a single image; returns `None` for unsupported types or images over the size limit
+fn fetch_image(source: &str, access: ImageAccess) -> Result<Option<WebImage>> {
+    let url = Url::parse(source)?;
+
+    let (mime_type, bytes) = match url.scheme() {
+        "data" => {
+            // data:image/png;base64,....
+            let Some((header, payload)) = url.path().split_once(',') else {
+                return Ok(None);
+            };
+            let Some(mime_type) = header.strip_suffix(";base64") else {
+                return Ok(None);
+            };
+            let bytes = base64::engine::general_purpose::STANDARD.decode(payload.trim())?;
+            (mime_type.to_lowercase(), bytes)
+        }
+        "file" => {
+            if access != ImageAccess::Everything {
+                anyhow::bail!("Refusing to read a local file for a remote page: {source}");
+            }
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow::anyhow!("Invalid file URL: {source}"))?;
+            if std::fs::metadata(&path)?.len() > MAX_WEB_IMAGE_SIZE {
+                return Ok(None);
+            }
+            let mime_type = crate::image::get_mime_type(&path)?;
+            (mime_type, std::fs::read(&path)?)
+        }
+        "http" | "https" => {
+            let allow_local_hosts = access != ImageAccess::PublicNetwork;
+            let (_, content_type, bytes) =
+                match fetch_http(source, MAX_WEB_IMAGE_SIZE, allow_local_hosts) {
+                    Ok(result) => result,
+                    // Body over the limit: not a "small" image
+                    Err(e) if e.downcast_ref::<BodyTooLarge>().is_some() => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+            let mime_type = if content_type.starts_with("image/") {
+                content_type
+            } else {
+                crate::image::get_mime_type(std::path::Path::new(url.path()))?
+            };
+            (mime_type, bytes)
+        }
+        _ => return Ok(None),
+    };
+
+    if !WEB_IMAGE_MIME_TYPES.contains(&mime_type.as_str())
+        || bytes.len() as u64 > MAX_WEB_IMAGE_SIZE
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(WebImage {
+        source: source.to_string(),
+        mime_type,
+        data: base64::engine::general_purpose::STANDARD.encode(&bytes),
+    }))
+}
+
+/// Convert the main content of an HTML document to markdown.
+/// Returns the markdown and the absolute URLs of images found in the content.
+pub fn html_to_markdown(html: &str, base_url: &Url) -> (String, Vec<String>) {
+    let document = Html::parse_document(html);
+
+    let mut converter = MarkdownConverter {
+        base_url,
+        output: String::new(),
+        images: Vec::new(),
+        list_stack: Vec::new(),
+        in_pre: false,
+    };
+
+    let title_selector = Selector::parse("title").expect("valid selector");
+    if let Some(title) = document.select(&title_selector).next() {
+        let title = collapse_whitespace(&title.text().collect::<String>());
+        if !title.trim().is_empty() {
+            converter
+                .output
+                .push_str(&format!("# {}\n\n", title.trim()));
+        }
+    }
+
+    // Prefer the most specific content container the page offers
+    let root = ["article", "main", "[role=main]", "body"]
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| document.select(&selector).next())
+        .unwrap_or_else(|| document.root_element());
+
+    converter.convert_children(root);
+
+    (normalize_markdown(&converter.output), converter.images)
+}
+
+struct MarkdownConverter<'a> {
+    base_url: &'a Url,
+    output: String,
+    images: Vec<String>,
+    list_stack: Vec<Option<usize>>, // None = unordered, Some(n) = next ordered number
+    in_pre: bool,
+}
+
+impl MarkdownConverter<'_> {
+    fn convert_children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.push_text(text),
+                Node::Element(_) => {
+                    if let Some(child_element) = ElementRef::wrap(child) {
+                        self.convert_element(child_element);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if self.in_pre {
+            self.output.push_str(text);
+            return;
+        }
+
+        let collapsed = collapse_whitespace(text);
+        // Avoid leading spaces at line starts and double spaces between inline elements
+        if self.output.is_empty() || self.output.ends_with(char::is_whitespace) {
+            self.output.push_str(collapsed.trim_start());
+        } else {
+            self.output.push_str(&collapsed);
+        }
+    }
+
+    fn start_block(&mut self) {
+        // Block content directly inside a list item stays on the marker line
+        let current_line = self.output.rsplit('\n').next().unwrap_or_default().trim();
+        if current_line == "-"
+            || current_line
+                .strip_suffix('.')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        {
+            return;
+        }
+
+        let trimmed_len = self.output.trim_end_matches(' ').len();
+        self.output.truncate(trimmed_len);
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") {
+            if self.output.ends_with('\n') {
+                self.output.push('\n');
+            } else {
+                self.output.push_str("\n\n");
+            }
+        }
+    }
+
+    fn inline_text(&self, element: ElementRef) -> String {
+        collapse_whitespace(&element.text().collect::<String>())
+            .trim()
+            .to_string()
+    }
+
+    fn is_boilerplate(element: ElementRef) -> bool {
+        let value = element.value();
+        if SKIPPED_TAGS.contains(&value.name()) || value.attr("hidden").is_some() {
+            return true;
+        }
+        if value.attr("aria-hidden") == Some("true") {
+            return true;
+        }
+        let class_and_id = format!(
+            "{} {}",
+            value.attr("class").unwrap_or_default(),
+            value.attr("id").unwrap_or_default()
+        )
+        .to_lowercase();
+        BOILERPLATE_MARKERS
+            .iter()
+            .any(|marker| class_and_id.contains(marker))
+    }
+
+    fn convert_element(&mut self, element: ElementRef) {
+        if Self::is_boilerplate(element) {
+            return;
+        }
+
+        let name = element.value().name();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = self.inline_text(element);
+                if !text.is_empty() {
+                    self.start_block();
+                    self.output
+                        .push_str(&format!("{} {}\n\n", "#".repeat(level), text));
+                }
+            }
+            "p" | "div" | "section" | "article" | "main" | "figure" | "figcaption" | "dl"
+            | "dd" | "dt" => {
+                self.start_block();
+                self.convert_children(element);
+                self.start_block();
+            }
+            "br" => self.output.push('\n'),
+            "hr" => {
+                self.start_block();
+                self.output.push_str("---\n\n");
+            }
+            "ul" | "ol" => {
+                // Nested lists continue the parent list without blank lines
+                let nested = !self.list_stack.is_empty();
+                if !nested {
+                    self.start_block();
+                }
+                self.list_stack
+                    .push(if name == "ol" { Some(1) } else { None });
+                self.convert_children(element);
+                self.list_stack.pop();
+                if !nested {
+                    self.start_block();
+                }
+            }
+            "li" => {
+                let indent = "  ".repeat(self.list_stack.len().saturating_sub(1));
+                let marker = match self.list_stack.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                let trimmed_len = self.output.trim_end_matches(' ').len();
+                self.output.truncate(trimmed_len);
+                if !self.output.is_empty() && !self.output.ends_with('\n') {
+                    self.output.push('\n');
+                }
+                self.output.push_str(&indent);
+                self.output.push_str(&marker);
+                self.convert_children(element);
+                if !self.output.ends_with('\n') {
+                    self.output.push('\n');
+                }
+            }
+            "pre" => {
+                self.start_block();
+                self.output.push_str("```\n");
+                self.in_pre = true;
+                self.convert_children(element);
+                self.in_pre = false;
+                if !self.output.ends_with('\n') {
+                    self.output.push('\n');
+                }
+                self.output.push_str("```\n\n");
+            }
+            "code" if !self.in_pre => {
+                let text = self.inline_text(element);
+                if !text.is_empty() {
+                    self.push_text(" ");
+                    self.output.push_str(&format!("`{text}`"));
+                }
+            }
+            "strong" | "b" => self.wrap_inline(element, "**"),
+            "em" | "i" => self.wrap_inline(element, "*"),
+            "a" => {
+                let text = self.inline_text(element);
+                let href = element
+                    .value()
+                    .attr("href")
+                    .filter(|href| !href.starts_with("javascript:") && !href.starts_with('#'))
+                    .and_then(|href| self.base_url.join(href).ok());
+                match href {
+                    Some(href) if !text.is_empty() => {
+                        self.push_text(" ");
+                        self.output.push_str(&format!("[{text}]({href})"));
+                    }
+                    _ => self.convert_children(element),
+                }
+            }
+            "img" => {
+                let Some(src) = element
+                    .value()
+                    .attr("src")
+                    .and_then(|src| self.base_url.join(src).ok())
+                else {
+                    return;
+                };
+                // Tracking pixels and spacers
+                if ["width", "height"]
+                    .iter()
+                    .any(|attr| element.value().attr(attr) == Some("1"))
+                {
+                    return;
+                }
+                let alt = element.value().attr("alt").unwrap_or_default().trim();
+                self.push_text(" ");
+                if src.scheme() == "data" {
+                    self.output.push_str(&format!("![{alt}](embedded image)"));
+                } else {
+                    self.output.push_str(&format!("![{alt}]({src})"));
+                }
+                self.images.push(src.to_string());
+            }
+            "blockquote" => {
+                self.start_block();
+                let start = self.output.len();
+                self.convert_children(element);
+                let quoted: String = self.output[start..]
+                    .trim()
+                    .lines()
+                    .map(|line| format!("> {line}\n"))
+                    .collect();
+                self.output.truncate(start);
+                self.output.push_str(&quoted);
+                self.output.push('\n');
+            }
+            "table" => {
+                self.start_block();
+                self.convert_table(element);
+                self.start_block();
+            }
+            _ => self.convert_children(element),
+        }
+    }
+
+    fn wrap_inline(&mut self, element: ElementRef, marker: &str) {
+        let text = self.inline_text(element);
+        if !text.is_empty() {
+            self.push_text(" ");
+            self.output.push_str(&format!("{marker}{text}{marker}"));
+        }
+    }
+
+    fn convert_table(&mut self, table: ElementRef) {
+        let row_selector = Selector::parse("tr").expect("valid selector");
+        let cell_selector = Selector::parse("th, td").expect("valid selector");
+
+        let rows: Vec<Vec<String>> = table
+            .select(&row_selector)
+            .map(|row| {
+                row.select(&cell_selector)
+                    .map(|cell| self.inline_text(cell).replace('|', "\\|"))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for (index, row) in rows.iter().enumerate() {
+            let cells: Vec<&str> = (0..columns)
+                .map(|i| row.get(i).map_or("", String::as_str))
+                .collect();
+            self.output
+                .push_str(&format!("| {} |\n", cells.join(" | ")));
+            if index == 0 {
+                self.output
+                    .push_str(&format!("|{}\n", " --- |".repeat(columns)));
+            }
+        }
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last_was_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_was_space {
+                result.push(' ');
+            }
+            last_was_space = true;
+        } else {
+            result.push(c);
+            last_was_space = false;
+        }
+    }
+    result
+}
+
+/// Trim trailing spaces and collapse runs of blank lines (code blocks are left untouched)
+fn normalize_markdown(markdown: &str) -> String {
+    let mut result = String::new();
+    let mut in_code_block = false;
+    let mut blank_lines = 0;
+
+    for line in markdown.lines() {
+        if line.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        let line = if in_code_block { line } else { line.trim_end() };
+        if line.is_empty() && !in_code_block {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+
+    result.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>Release  Notes</title><style>body { color: red; }</style></head>
+<body>
+<nav><a href="/">Home</a> <a href="/blog">Blog</a></nav>
+<div class="cookie-banner">We use cookies</div>
+<article>
+  <h1>Version 2.0</h1>
+  <p>This release adds <strong>streaming</strong> and a
+     <a href="/docs/streaming">new guide</a>.</p>
+  <ul><li>Faster startup</li><li>Smaller binary</li></ul>
+  <pre><code>cargo install gia
+gia --help</code></pre>
+  <img src="/diagram.png" alt="Architecture">
+  <img src="/pixel.gif" width="1" height="1">
+  <table><tr><th>Flag</th><th>Meaning</th></tr><tr><td>-u</td><td>URL input</td></tr></table>
+  <script>trackVisitor();</script>
+</article>
+<footer>Copyright</footer>
+</body>
+</html>"#;
+
+    /// Serve a fixed set of paths on a local port; returns the base URL
+    fn start_server(routes: Vec<(&'static str, &'static str, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                // Drain headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let response = match routes.iter().find(|(route, _, _)| *route == path) {
+                    Some((_, content_type, body)) => {
+                        let mut response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            body.len()
+                        )
+                        .into_bytes();
+                        response.extend_from_slice(body);
+                        response
+                    }
+                    None => {
+                        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_vec()
+                    }
+                };
+                stream.write_all(&response).unwrap();
+            }
+        });
+
+        format!("http://{address}")
+    }
+
+    #[test]
+    fn test_html_to_markdown_strips_boilerplate() {
+        let base_url = Url::parse("https://example.com/blog/post").unwrap();
+        let (markdown, images) = html_to_markdown(PAGE, &base_url);
+
+        assert!(markdown.starts_with("# Release Notes"));
+        assert!(markdown.contains("# Version 2.0"));
+        assert!(markdown.contains(
+            "This release adds **streaming** and a [new guide](https://example.com/docs/streaming)."
+        ));
+        assert!(markdown.contains("- Faster startup\n- Smaller binary"));
+        assert!(markdown.contains("```\ncargo install gia\ngia --help\n```"));
+        assert!(markdown.contains("| Flag | Meaning |\n| --- | --- |\n| -u | URL input |"));
+
+        assert!(!markdown.contains("Home"));
+        assert!(!markdown.contains("cookies"));
+        assert!(!markdown.contains("trackVisitor"));
+        assert!(!markdown.contains("Copyright"));
+        assert!(!markdown.contains("color: red"));
+
+        assert_eq!(images, vec!["https://example.com/diagram.png".to_string()]);
+    }
+
+    #[test]
+    fn test_ordered_and_nested_lists() {
+        let base_url = Url::parse("https://example.com/").unwrap();
+        let html =
+            "<body><ol><li>First<ul><li>Nested</li></ul></li><li><p>Second</p></li></ol></body>";
+        let (markdown, _) = html_to_markdown(html, &base_url);
+        assert_eq!(markdown, "1. First\n  - Nested\n2. Second");
+    }
+
+    #[test]
+    fn test_fetch_web_page_from_local_server() {
+        let png = vec![0x89, b'P', b'N', b'G', 0, 0, 0, 0];
+        let base = start_server(vec![
+            (
+                "/page",
+                "text/html; charset=utf-8",
+                PAGE.as_bytes().to_vec(),
+            ),
+            ("/diagram.png", "image/png", png.clone()),
+        ]);
+
+        let page = fetch_web_page(&format!("{base}/page"), false).unwrap();
+        assert!(page.markdown.contains("# Version 2.0"));
+        assert!(
+            page.markdown
+                .contains(&format!("[new guide]({base}/docs/streaming)"))
+        );
+
+        assert_eq!(page.images.len(), 1);
+        assert_eq!(page.images[0].source, format!("{base}/diagram.png"));
+        assert_eq!(page.images[0].mime_type, "image/png");
+        assert_eq!(
+            page.images[0].data,
+            base64::engine::general_purpose::STANDARD.encode(&png)
+        );
+    }
+
+    #[test]
+    fn test_fetch_plain_text_and_errors() {
+        let base = start_server(vec![("/notes.txt", "text/plain", b"just <text>".to_vec())]);
+
+        let page = fetch_web_page(&format!("{base}/notes.txt"), false).unwrap();
+        assert_eq!(page.markdown, "just <text>");
+        assert!(page.images.is_empty());
+
+        assert!(fetch_web_page(&format!("{base}/missing"), false).is_err());
+        assert!(fetch_web_page("ftp://example.com/file", false).is_err());
+        assert!(fetch_web_page("not a url", false).is_err());
+
+        // Requests over the HTTP API or MCP only reach public pages
+        assert!(fetch_web_page(&format!("{base}/notes.txt"), true).is_err());
+        assert!(fetch_web_page("file:///etc/hostname", true).is_err());
+    }
+
+    #[test]
+    fn test_remote_page_cannot_reach_local_images() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let image_path = temp_dir.path().join("secret.png");
+        std::fs::write(&image_path, [1, 2, 3]).unwrap();
+        let file_source = Url::from_file_path(&image_path).unwrap().to_string();
+        let base = start_server(vec![("/shot.png", "image/png", vec![1, 2, 3])]);
+        let http_source = format!("{base}/shot.png");
+
+        let public_page = Url::parse("http://93.184.215.14/page").unwrap();
+        let access = ImageAccess::for_page(&public_page);
+        assert_eq!(access, ImageAccess::PublicNetwork);
+        assert!(fetch_image(&file_source, access).is_err());
+        assert!(fetch_image(&http_source, access).is_err());
+        assert!(fetch_image("http://[::1]/shot.png", access).is_err());
+        assert!(fetch_image("http://192.168.1.1/shot.png", access).is_err());
+        assert!(fetch_image("http://169.254.169.254/latest", access).is_err());
+
+        let local_page = Url::parse(&format!("{base}/page")).unwrap();
+        let access = ImageAccess::for_page(&local_page);
+        assert_eq!(access, ImageAccess::LocalNetwork);
+        assert!(fetch_image(&file_source, access).is_err());
+        assert!(fetch_image(&http_source, access).unwrap().is_some());
+
+        let access = ImageAccess::for_page(&Url::from_file_path(temp_dir.path()).unwrap());
+        assert_eq!(access, ImageAccess::Everything);
+        assert!(fetch_image(&file_source, access).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_public_fetch_checks_resolved_addresses() {
+        // Names are checked where they are resolved for the connection
+        let error = fetch_http("http://localhost:9/", 1024, false).unwrap_err();
+        assert!(
+            format!("{error:#}").contains("is a local address"),
+            "{error:#}"
+        );
+    }
+
+    #[test]
+    fn test_is_local_ip() {
+        let cases = [
+            ("127.0.0.1", true),
+            ("10.1.2.3", true),
+            ("192.168.1.1", true),
+            ("169.254.169.254", true),
+            ("0.0.0.0", true),
+            ("0.1.2.3", true),
+            ("100.64.0.1", true),
+            ("100.127.255.255", true),
+            ("198.18.0.1", true),
+            ("198.19.255.255", true),
+            ("224.0.0.1", true),
+            ("240.0.0.1", true),
+            ("255.255.255.255", true),
+            ("::1", true),
+            ("::", true),
+            ("fd00::1", true),
+            ("fe80::1", true),
+            ("ff02::1", true),
+            ("::ffff:127.0.0.1", true),
+            ("64:ff9b::7f00:1", true),
+            ("64:ff9b::a9fe:a9fe", true),
+            ("8.8.8.8", false),
+            ("100.128.0.1", false),
+            ("198.20.0.1", false),
+            ("2606:4700::1111", false),
+            ("::ffff:8.8.8.8", false),
+            ("64:ff9b::808:808", false),
+        ];
+        for (ip, local) in cases {
+            assert_eq!(is_local_ip(ip.parse().unwrap()), local, "{ip}");
+        }
+    }
+
+    #[test]
+    fn test_large_image_is_skipped() {
+        let large = vec![0u8; MAX_WEB_IMAGE_SIZE as usize + 1];
+        let base = start_server(vec![("/large.png", "image/png", large)]);
+        let access = ImageAccess::LocalNetwork;
+        assert!(
+            fetch_image(&format!("{base}/large.png"), access)
+                .unwrap()
+                .is_none()
+        );
+        // Other read errors are not mistaken for the size limit
+        assert!(fetch_image(&format!("{base}/missing-limit.png"), access).is_err());
+    }
+
+    #[test]
+    fn test_fetch_file_url() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let html_path = temp_dir.path().join("page.html");
+        std::fs::write(
+            &html_path,
+            "<html><body><main><p>Local page</p><img src=\"shot.png\"></main></body></html>",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("shot.png"), [1, 2, 3]).unwrap();
+
+        let url = Url::from_file_path(&html_path).unwrap().to_string();
+        let page = fetch_web_page(&url, false).unwrap();
+
+        assert!(page.markdown.starts_with("Local page"));
+        assert_eq!(page.images.len(), 1);
+        assert_eq!(page.images[0].mime_type, "image/png");
+    }
+}
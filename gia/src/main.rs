@@ -1,35 +1,20 @@
-mod api_key;
-mod app;
-mod audio;
-mod browser_preview;
-mod cli;
-mod clipboard;
-mod constants;
-mod content_part_wrapper;
-mod conversation;
-mod gemini;
-mod image;
-mod input;
-mod logging;
-mod ollama;
-mod output;
-mod provider;
-mod role;
-mod spinner;
-
 use anyhow::Result;
 use notify_rust::Notification;
 
-use crate::app::run_app;
-use crate::cli::Config;
-use crate::logging::init_logging;
+use gia_core::cli::{Config, OutputMode};
+use gia_core::logging::init_logging;
+use gia_core::role::ensure_default_tasks;
+use gia_core::{run_app, run_pdf_helper};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    if let Some(result) = run_pdf_helper() {
+        return result;
+    }
     init_logging();
 
     // Ensure default task files exist (EN.md and DE.md)
-    if let Err(e) = role::ensure_default_tasks() {
+    if let Err(e) = ensure_default_tasks() {
         eprintln!("Warning: Failed to create default task files: {}", e);
     }
 
@@ -45,8 +30,8 @@ async fn main() -> Result<()> {
             source = cause.source();
         }
 
-        // Check if this is a user-cancelled recording (not an actual error)
-        let is_user_cancelled = last_cause.contains("Recording cancelled by user");
+        // Check if the user cancelled the recording or the request (not an actual error)
+        let is_user_cancelled = last_cause.contains("cancelled by user");
 
         // Extract the most relevant part of the error message
         // Look for "Request failed with status code" pattern
@@ -62,14 +47,14 @@ async fn main() -> Result<()> {
         };
 
         // Clear clipboard if output mode is clipboard
-        if matches!(config.output_mode, crate::cli::OutputMode::Clipboard) {
+        if matches!(config.output_mode, OutputMode::Clipboard) {
             use arboard::Clipboard;
             if let Ok(mut clipboard) = Clipboard::new() {
                 let _ = clipboard.clear();
             }
         }
 
-        // Show error notification (skip for user-cancelled recording)
+        // Show error notification (skip when cancelled by the user)
         if !is_user_cancelled {
             #[cfg(target_os = "macos")]
             {
@@ -425,3 +425,224 @@ fn test_stdin_input_simulation() {
     let stderr = str::from_utf8(&output.stderr).unwrap();
     assert!(stderr.contains("API key") || stderr.contains("GEMINI_API_KEY"));
 }
+
+#[test]
+fn test_roles_subcommands() {
+    let config = TestConfig::new();
+    let home = config.temp_path();
+    let gia = |args: &[&str]| {
+        config
+            .gia_command()
+            .args(args)
+            .env("HOME", home)
+            .env("USERPROFILE", home)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute gia roles")
+    };
+
+    let output = gia(&["roles", "new", "reviewer"]);
+    assert!(output.status.success(), "{output:?}");
+    let role_path = home.join(".gia").join("roles").join("reviewer.md");
+    assert!(role_path.exists());
+
+    // The default tasks are created at startup and listed with the new role
+    let output = gia(&["roles", "list"]);
+    assert!(output.status.success());
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    assert!(stdout.contains("NAME"));
+    assert!(stdout.contains("reviewer"));
+    assert!(stdout.contains("reviewer role"));
+    assert!(stdout.contains("commit-message"));
+    assert!(stdout.contains("task"));
+
+    let output = gia(&["roles", "validate", "reviewer"]);
+    assert!(output.status.success(), "{output:?}");
+
+    std::fs::write(&role_path, "---\nmodle: x\n---\nYou review code.").unwrap();
+    let output = gia(&["roles", "validate"]);
+    assert!(!output.status.success());
+    assert!(
+        str::from_utf8(&output.stdout)
+            .unwrap()
+            .contains("Invalid front-matter")
+    );
+
+    // Deleting without a terminal requires --yes
+    assert!(!gia(&["roles", "rm", "reviewer"]).status.success());
+    assert!(gia(&["roles", "rm", "reviewer", "--yes"]).status.success());
+    assert!(!role_path.exists());
+}
+
+#[test]
+fn test_project_local_roles() {
+    let config = TestConfig::new();
+    let home = config.temp_path().join("home");
+    let project = config.temp_path().join("project");
+    let nested = project.join("src");
+    std::fs::create_dir_all(project.join(".gia").join("roles")).unwrap();
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(
+        project.join(".gia").join("roles").join("project-dev.md"),
+        "---\ndescription: Project conventions\ninclude: [DE]\n---\nFollow the project style.",
+    )
+    .unwrap();
+
+    let gia = |args: &[&str]| {
+        config
+            .gia_command()
+            .args(args)
+            .current_dir(&nested)
+            .env("HOME", &home)
+            .env("USERPROFILE", &home)
+            .env_remove("GIA_ROLE_DIRS")
+            .stdin(std::process::Stdio::null())
+            .output()
+            .expect("Failed to execute gia roles")
+    };
+
+    let output = gia(&["roles", "list"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    assert!(stdout.contains("Project conventions"));
+    assert!(stdout.contains("DE"));
+
+    // The include of the built-in DE task resolves from the home directory
+    let output = gia(&["roles", "validate", "project-dev"]);
+    assert!(output.status.success(), "{output:?}");
+
+    let output = gia(&["roles", "new", "local-task", "--task", "--local"]);
+    assert!(output.status.success(), "{output:?}");
+    assert!(
+        project
+            .join(".gia")
+            .join("tasks")
+            .join("local-task.md")
+            .exists()
+    );
+}
+
+#[test]
+fn test_mcp_serve() {
+    use std::io::Write;
+
+    let config = TestConfig::new();
+    let home = config.temp_path();
+    let mut child = config
+        .gia_command()
+        .arg("mcp-serve")
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("Failed to start gia mcp-serve");
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#,
+        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"list_conversations","arguments":{}}}"#,
+    ];
+    let mut stdin = child.stdin.take().unwrap();
+    for request in requests {
+        writeln!(stdin, "{request}").unwrap();
+    }
+    drop(stdin); // the server stops at end of input
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3, "{stdout}");
+    assert!(lines[0].contains(r#""protocolVersion":"2025-03-26""#));
+    assert!(lines[1].contains(r#""name":"ask""#));
+    assert!(lines[1].contains(r#""name":"transcribe_audio_file""#));
+    assert!(lines[2].contains("No saved conversations found."));
+}
+
+#[test]
+fn test_serve_requires_token_off_localhost() {
+    let config = TestConfig::new();
+    let home = config.temp_path();
+    let output = config
+        .gia_command()
+        .args(["serve", "--host", "0.0.0.0", "--port", "0"])
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .env_remove("GIA_SERVE_TOKEN")
+        .output()
+        .expect("Failed to run gia serve");
+
+    assert!(!output.status.success());
+    let stderr = str::from_utf8(&output.stderr).unwrap();
+    assert!(stderr.contains("without a token"), "{stderr}");
+}
+
+/// A one-page PDF showing `text` in Helvetica
+fn minimal_pdf(text: &str) -> Vec<u8> {
+    let stream = format!("BT /F1 12 Tf 72 720 Td ({text}) Tj ET");
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> >> >>".to_string(),
+        format!("<< /Length {} >>\nstream\n{stream}\nendstream", stream.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{object}\nendobj\n", index + 1).as_bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").as_bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+#[test]
+fn test_pdf_helper() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let config = TestConfig::new();
+    let run_helper = |pdf: &[u8]| {
+        let mut child = config
+            .gia_command()
+            .env("GIA_PDF_HELPER", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to start the PDF helper");
+        child.stdin.take().unwrap().write_all(pdf).unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let output = run_helper(&minimal_pdf("Hello from a PDF"));
+    assert!(output.status.success(), "{output:?}");
+    // The pages as a JSON array of strings
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    assert!(
+        stdout.starts_with("[\"") && stdout.ends_with("\"]"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("Hello from a PDF"), "{stdout}");
+
+    // A broken PDF only ends the helper
+    let output = run_helper(b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\n");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,282 @@
+//! Files, folders and pasted images attached to the next prompt, shown as removable chips.
+
+use anyhow::{Context, Result, bail};
+use arboard::{Clipboard, ImageData};
+use base64::Engine;
+use eframe::egui;
+use gia_core::ContentSource;
+use gia_core::input::{FileType, detect_file_type};
+use std::io::Cursor;
+use std::path::PathBuf;
+
+const THUMBNAIL_SIZE: u32 = 32;
+
+enum AttachmentKind {
+    Folder,
+    File(FileType),
+    PastedImage(ImageData<'static>),
+}
+
+pub struct Attachment {
+    kind: AttachmentKind,
+    path: Option<PathBuf>,
+    thumbnail: Option<egui::TextureHandle>,
+}
+
+impl Attachment {
+    /// Attach a dropped file or folder; files are classified like `gia -f` does
+    pub fn from_path(ctx: &egui::Context, path: PathBuf) -> Result<Self> {
+        let kind = if path.is_dir() {
+            AttachmentKind::Folder
+        } else if path.is_file() {
+            match detect_file_type(&path) {
+                FileType::Binary => bail!(
+                    "'{}' is a binary file (not a known media type or text file)",
+                    path.display()
+                ),
+                file_type => AttachmentKind::File(file_type),
+            }
+        } else {
+            bail!("'{}' is neither a file nor a folder", path.display());
+        };
+
+        let thumbnail = match kind {
+            AttachmentKind::File(FileType::Media) => image::open(&path)
+                .ok()
+                .map(|image| thumbnail_texture(ctx, &path.to_string_lossy(), image)),
+            _ => None,
+        };
+
+        Ok(Self {
+            kind,
+            path: Some(path),
+            thumbnail,
+        })
+    }
+
+    /// Attach the image currently in the clipboard
+    pub fn from_clipboard(ctx: &egui::Context) -> Result<Self> {
+        let image = Clipboard::new()
+            .and_then(|mut clipboard| clipboard.get_image())
+            .context("No image in the clipboard")?
+            .to_owned_img();
+
+        let thumbnail = image::RgbaImage::from_raw(
+            image.width as u32,
+            image.height as u32,
+            image.bytes.to_vec(),
+        )
+        .map(|rgba| thumbnail_texture(ctx, "pasted_image", rgba.into()));
+
+        Ok(Self {
+            kind: AttachmentKind::PastedImage(image),
+            path: None,
+            thumbnail,
+        })
+    }
+
+    fn icon(&self) -> &'static str {
+        match self.kind {
+            AttachmentKind::Folder => "📁",
+            AttachmentKind::File(FileType::Media) => "🖼",
+            AttachmentKind::File(FileType::Document) => "📑",
+            AttachmentKind::File(FileType::Notebook) => "📓",
+            AttachmentKind::File(_) => "📄",
+            AttachmentKind::PastedImage(_) => "📋",
+        }
+    }
+
+    fn name(&self) -> String {
+        match &self.path {
+            Some(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string()),
+            None => "Pasted image".to_string(),
+        }
+    }
+
+    fn description(&self) -> String {
+        let kind = match self.kind {
+            AttachmentKind::Folder => "Folder, all files inside are attached",
+            AttachmentKind::File(FileType::Media) => "Media file, sent as is",
+            AttachmentKind::File(FileType::Document) => "Document, its text is extracted",
+            AttachmentKind::File(FileType::Notebook) => "Notebook, converted to cells",
+            AttachmentKind::File(_) => "Text file",
+            AttachmentKind::PastedImage(_) => "Pasted image, sent with the prompt",
+        };
+        match &self.path {
+            Some(path) => format!("{}\n{}", path.display(), kind),
+            None => kind.to_string(),
+        }
+    }
+}
+
+/// Thumbnail texture of an image, at most THUMBNAIL_SIZE pixels wide and high
+fn thumbnail_texture(
+    ctx: &egui::Context,
+    name: &str,
+    image: image::DynamicImage,
+) -> egui::TextureHandle {
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+    let size = [thumbnail.width() as usize, thumbnail.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, thumbnail.as_raw());
+    ctx.load_texture(name, color_image, egui::TextureOptions::LINEAR)
+}
+
+/// `-f` arguments for the attached files and folders
+pub fn attachment_args(attachments: &[Attachment]) -> Vec<String> {
+    attachments
+        .iter()
+        .filter_map(|attachment| attachment.path.as_deref())
+        .map(|path| format!("-f{}", path.display()))
+        .collect()
+}
+
+/// The pasted image, if any
+pub fn pasted_image(attachments: &[Attachment]) -> Option<ImageData<'static>> {
+    attachments
+        .iter()
+        .find_map(|attachment| match &attachment.kind {
+            AttachmentKind::PastedImage(image) => Some(image.clone()),
+            _ => None,
+        })
+}
+
+/// Content of a pasted image, encoded as PNG like gia encodes clipboard images
+pub fn pasted_image_content(image: &ImageData) -> Result<ContentSource> {
+    let rgba = image::RgbaImage::from_raw(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.to_vec(),
+    )
+    .context("Pasted image has an invalid size")?;
+    let mut png = Vec::new();
+    rgba.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .context("Failed to encode the pasted image as PNG")?;
+    Ok(ContentSource::EmbeddedImage(
+        "pasted image".to_string(),
+        "image/png".to_string(),
+        base64::engine::general_purpose::STANDARD.encode(&png),
+    ))
+}
+
+/// Add an attachment; a newly pasted image replaces the previous one since
+/// there is only one clipboard image per request
+pub fn add_attachment(attachments: &mut Vec<Attachment>, attachment: Attachment) {
+    if matches!(attachment.kind, AttachmentKind::PastedImage(_)) {
+        attachments.retain(|a| !matches!(a.kind, AttachmentKind::PastedImage(_)));
+    } else if attachments
+        .iter()
+        .any(|a| a.path.is_some() && a.path == attachment.path)
+    {
+        return;
+    }
+    attachments.push(attachment);
+}
+
+/// Show the attachments as chips; returns the index of a removed one
+pub fn show_chips(ui: &mut egui::Ui, attachments: &[Attachment]) -> Option<usize> {
+    let mut removed = None;
+    ui.horizontal_wrapped(|ui| {
+        for (index, attachment) in attachments.iter().enumerate() {
+            egui::Frame::group(ui.style())
+                .inner_margin(egui::Margin::symmetric(4, 2))
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        match &attachment.thumbnail {
+                            Some(texture) => {
+                                ui.image((texture.id(), texture.size_vec2()));
+                            }
+                            None => {
+                                ui.label(attachment.icon());
+                            }
+                        }
+                        ui.label(attachment.name())
+                            .on_hover_text(attachment.description());
+                        if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                });
+        }
+    });
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pasted(width: usize) -> Attachment {
+        Attachment {
+            kind: AttachmentKind::PastedImage(ImageData {
+                width,
+                height: 1,
+                bytes: vec![0; width * 4].into(),
+            }),
+            path: None,
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn test_from_path_detects_kind() {
+        let ctx = egui::Context::default();
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "hello").unwrap();
+        let binary = dir.path().join("data.bin");
+        std::fs::write(&binary, [0u8, 1, 2, 0, 255]).unwrap();
+
+        let attachment = Attachment::from_path(&ctx, text.clone()).unwrap();
+        assert!(matches!(
+            attachment.kind,
+            AttachmentKind::File(FileType::Text)
+        ));
+        assert_eq!(attachment.name(), "notes.txt");
+
+        let folder = Attachment::from_path(&ctx, dir.path().to_path_buf()).unwrap();
+        assert!(matches!(folder.kind, AttachmentKind::Folder));
+
+        let err = Attachment::from_path(&ctx, binary).err().unwrap();
+        assert!(err.to_string().contains("binary file"));
+        assert!(Attachment::from_path(&ctx, dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_attachment_args() {
+        let ctx = egui::Context::default();
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}").unwrap();
+
+        let mut attachments = Vec::new();
+        add_attachment(
+            &mut attachments,
+            Attachment::from_path(&ctx, file.clone()).unwrap(),
+        );
+        add_attachment(
+            &mut attachments,
+            Attachment::from_path(&ctx, file.clone()).unwrap(),
+        );
+        add_attachment(&mut attachments, pasted(1));
+        add_attachment(&mut attachments, pasted(2));
+
+        assert_eq!(attachments.len(), 2);
+        assert!(matches!(
+            pasted_image(&attachments),
+            Some(image) if image.width == 2
+        ));
+        let content = pasted_image_content(&pasted_image(&attachments).unwrap()).unwrap();
+        assert!(matches!(
+            content,
+            ContentSource::EmbeddedImage(_, mime_type, data)
+                if mime_type == "image/png" && !data.is_empty()
+        ));
+        assert_eq!(
+            attachment_args(&attachments),
+            vec![format!("-f{}", file.display())]
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Conversation history sidebar: search, rename, fork and delete saved conversations.
+
+use anyhow::Result;
+use eframe::egui;
+use gia_core::{Conversation, ConversationManager, ConversationSummary};
+
+/// What the user did in the sidebar that the main view has to follow
+pub enum HistoryAction {
+    Select(String),
+    Deleted(String),
+}
+
+#[derive(Default)]
+pub struct HistoryPanel {
+    pub open: bool,
+    search: String,
+    summaries: Vec<ConversationSummary>,
+    renaming: Option<(String, String)>, // (id, new title)
+    confirm_delete: Option<String>,
+    error: Option<String>,
+}
+
+impl HistoryPanel {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        if self.open {
+            self.refresh();
+        }
+    }
+
+    /// Reload the conversation list from disk
+    pub fn refresh(&mut self) {
+        match ConversationManager::new().and_then(|manager| manager.list_conversations()) {
+            Ok(summaries) => {
+                self.summaries = summaries;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("{:#}", e)),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, selected: Option<&str>) -> Option<HistoryAction> {
+        let mut action = None;
+
+        egui::SidePanel::left("history")
+            .default_width(240.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("History");
+                    if ui.button("🔄").on_hover_text("Reload").clicked() {
+                        self.refresh();
+                    }
+                });
+                ui.add(egui::TextEdit::singleline(&mut self.search).hint_text("🔍 Search"));
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let summaries: Vec<_> = self
+                        .summaries
+                        .iter()
+                        .filter(|summary| matches_search(summary, &self.search))
+                        .cloned()
+                        .collect();
+                    for summary in summaries {
+                        if let Some(result) = self.show_entry(ui, &summary, selected) {
+                            action = Some(result);
+                        }
+                    }
+                });
+            });
+
+        action
+    }
+
+    fn show_entry(
+        &mut self,
+        ui: &mut egui::Ui,
+        summary: &ConversationSummary,
+        selected: Option<&str>,
+    ) -> Option<HistoryAction> {
+        let id = summary.id.clone();
+
+        if let Some((renaming_id, title)) = &mut self.renaming
+            && *renaming_id == id
+        {
+            let response = ui.text_edit_singleline(title);
+            response.request_focus();
+            if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                self.renaming = None;
+            } else if response.lost_focus() {
+                let title = title.clone();
+                self.renaming = None;
+                self.run(|manager| manager.rename_conversation(&id, &title).map(|_| ()));
+            }
+            return None;
+        }
+
+        if self.confirm_delete.as_deref() == Some(id.as_str()) {
+            let mut action = None;
+            ui.horizontal(|ui| {
+                ui.label("Delete conversation?");
+                if ui.button("Delete").clicked() {
+                    self.confirm_delete = None;
+                    if self.run(|manager| manager.delete_conversation(&id)) {
+                        action = Some(HistoryAction::Deleted(id.clone()));
+                    }
+                }
+                if ui.button("Cancel").clicked() {
+                    self.confirm_delete = None;
+                }
+            });
+            return action;
+        }
+
+        let (preview, _, age, messages) = summary.format_as_table_columns();
+        let response = ui
+            .selectable_label(
+                selected == Some(id.as_str()),
+                format!("{preview}\n{age} · {messages} messages"),
+            )
+            .on_hover_text(format!("{id}\nRight-click to rename, fork or delete"));
+
+        let mut action = None;
+        if response.clicked() {
+            action = Some(HistoryAction::Select(id.clone()));
+        }
+        response.context_menu(|ui| {
+            if ui.button("✏ Rename").clicked() {
+                self.renaming = Some((id.clone(), summary.title.clone().unwrap_or_default()));
+                ui.close_menu();
+            }
+            if ui.button("⑂ Fork").clicked() {
+                match ConversationManager::new().and_then(|manager| manager.fork_conversation(&id))
+                {
+                    Ok(fork) => {
+                        self.refresh();
+                        action = Some(HistoryAction::Select(fork.id));
+                    }
+                    Err(e) => self.error = Some(format!("{:#}", e)),
+                }
+                ui.close_menu();
+            }
+            if ui.button("🗑 Delete").clicked() {
+                self.confirm_delete = Some(id.clone());
+                ui.close_menu();
+            }
+        });
+        action
+    }
+
+    /// Run a change on the conversations and reload the list; errors are shown in the panel
+    fn run(&mut self, change: impl FnOnce(&ConversationManager) -> Result<()>) -> bool {
+        match ConversationManager::new().and_then(|manager| change(&manager)) {
+            Ok(()) => {
+                self.refresh();
+                true
+            }
+            Err(e) => {
+                self.error = Some(format!("{:#}", e));
+                false
+            }
+        }
+    }
+}
+
+/// Whether a conversation matches the search text (title, first prompt or id)
+fn matches_search(summary: &ConversationSummary, search: &str) -> bool {
+    let search = search.trim().to_lowercase();
+    search.is_empty()
+        || [
+            summary.title.as_deref(),
+            summary.first_user_message.as_deref(),
+            Some(summary.id.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|text| text.to_lowercase().contains(&search))
+}
+
+/// The chat of a conversation as markdown for the response view
+pub fn chat_markdown(conversation: &Conversation) -> String {
+    let mut markdown = format!(
+        "### {}\n\n",
+        conversation.title.as_deref().unwrap_or(&conversation.id)
+    );
+
+    for message in &conversation.messages {
+        match message.role.as_str() {
+            "User" => {
+                let prompt = Conversation::extract_prompt_section(message);
+                let prompt = if prompt.trim().is_empty() {
+                    "*(attachments only)*".to_string()
+                } else {
+                    prompt
+                };
+                markdown.push_str(&format!("#### 👤 You\n\n{prompt}\n\n"));
+            }
+            "Assistant" => {
+                let text = Conversation::extract_text_content(message);
+                if !text.trim().is_empty() {
+                    markdown.push_str(&format!("#### 🤖 Assistant\n\n{text}\n\n"));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gia_core::TokenUsage;
+    use gia_core::content_part_wrapper::{ChatMessageWrapper, MessageContentWrapper};
+
+    fn conversation() -> Conversation {
+        let mut conversation =
+            Conversation::new_with_prompt("test-model".to_string(), "Explain lifetimes", 0);
+        for (role, text) in [
+            ("User", "Explain lifetimes"),
+            ("Tool", "ignored"),
+            ("Assistant", "They describe how long references live."),
+        ] {
+            let message = ChatMessageWrapper {
+                role: role.to_string(),
+                content: MessageContentWrapper::Text {
+                    text: text.to_string(),
+                },
+            };
+            conversation.add_message_with_usage(message, Vec::new(), TokenUsage::default());
+        }
+        conversation
+    }
+
+    #[test]
+    fn test_chat_markdown() {
+        let mut conversation = conversation();
+        let markdown = chat_markdown(&conversation);
+        assert!(markdown.starts_with(&format!("### {}\n", conversation.id)));
+        assert!(markdown.contains("#### 👤 You\n\nExplain lifetimes\n"));
+        assert!(markdown.contains("#### 🤖 Assistant\n\nThey describe how long references live."));
+        assert!(!markdown.contains("ignored"));
+
+        conversation.title = Some("Lifetimes".to_string());
+        assert!(chat_markdown(&conversation).starts_with("### Lifetimes\n"));
+    }
+
+    #[test]
+    fn test_matches_search() {
+        let mut summary = ConversationSummary::from_conversation(&conversation());
+        assert!(matches_search(&summary, ""));
+        assert!(matches_search(&summary, "LIFETIMES"));
+        assert!(matches_search(
+            &summary,
+            &summary.id[summary.id.len() - 4..]
+        ));
+        assert!(!matches_search(&summary, "borrow checker"));
+
+        summary.title = Some("Borrow checker notes".to_string());
+        assert!(matches_search(&summary, "borrow checker"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Global hotkeys of the tray mode. On Wayland they are bound through the
+//! GlobalShortcuts portal; otherwise X11 grabs (or the Windows and macOS APIs) are used.
+
+use anyhow::{Context, Result};
+use gia_core::config_file::HotkeyAction;
+use gia_core::logging::log_warn;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// The hotkey of an action was pressed or released
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotkeyEvent {
+    pub action: String,
+    pub pressed: bool,
+}
+
+pub type EventHandler = Arc<dyn Fn(HotkeyEvent) + Send + Sync>;
+
+/// Registered hotkeys; they stay bound while this is alive
+pub struct Hotkeys {
+    pub backend: &'static str,
+    _manager: Option<GlobalHotKeyManager>,
+}
+
+/// Bind the hotkeys of all actions. Must be called on the thread that runs the
+/// event loop (Windows and macOS need it).
+pub fn register(
+    actions: &BTreeMap<String, HotkeyAction>,
+    runtime: &tokio::runtime::Handle,
+    on_event: EventHandler,
+) -> Result<Hotkeys> {
+    #[cfg(target_os = "linux")]
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        match runtime.block_on(portal::bind(actions, Arc::clone(&on_event))) {
+            Ok(()) => {
+                return Ok(Hotkeys {
+                    backend: "GlobalShortcuts portal",
+                    _manager: None,
+                });
+            }
+            Err(e) => {
+                log_warn(&format!("GlobalShortcuts portal not available: {e:#}"));
+                eprintln!(
+                    "Warning: GlobalShortcuts portal not available ({e:#}), hotkeys only work in X11 windows"
+                );
+            }
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = runtime;
+
+    register_native(actions, on_event)
+}
+
+fn register_native(
+    actions: &BTreeMap<String, HotkeyAction>,
+    on_event: EventHandler,
+) -> Result<Hotkeys> {
+    let manager = GlobalHotKeyManager::new().context("Failed to start global hotkeys")?;
+
+    let mut names = HashMap::new();
+    for (name, action) in actions {
+        let hotkey = parse_keys(&action.keys)?;
+        if let Some(other) = names.insert(hotkey.id(), name.clone()) {
+            anyhow::bail!("Hotkeys '{other}' and '{name}' use the same keys");
+        }
+        manager
+            .register(hotkey)
+            .with_context(|| format!("Failed to register {} for '{name}'", action.keys))?;
+    }
+
+    GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+        if let Some(name) = names.get(&event.id()) {
+            on_event(HotkeyEvent {
+                action: name.clone(),
+                pressed: event.state() == HotKeyState::Pressed,
+            });
+        }
+    }));
+
+    Ok(Hotkeys {
+        backend: if cfg!(target_os = "linux") {
+            "X11"
+        } else {
+            "system"
+        },
+        _manager: Some(manager),
+    })
+}
+
+/// Parse keys like "Ctrl+Alt+T"
+pub fn parse_keys(keys: &str) -> Result<HotKey> {
+    keys.parse()
+        .with_context(|| format!("Invalid hotkey '{keys}' (e.g. Ctrl+Alt+T, Super+F9)"))
+}
+
+/// Preferred trigger for the portal in the XDG shortcuts format, e.g. "CTRL+ALT+t"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn portal_trigger(keys: &str) -> Result<String> {
+    // Reject what the native backends would reject
+    parse_keys(keys)?;
+
+    let parts: Vec<&str> = keys.split('+').map(str::trim).collect();
+    let (key, modifiers) = parts.split_last().context("Empty hotkey")?;
+    let mut trigger: Vec<String> = modifiers
+        .iter()
+        .map(|modifier| match modifier.to_uppercase().as_str() {
+            "CONTROL" | "CTRL" | "COMMANDORCONTROL" | "CMDORCTRL" => "CTRL".to_string(),
+            "ALT" | "OPTION" => "ALT".to_string(),
+            "SUPER" | "CMD" | "COMMAND" => "LOGO".to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+    trigger.push(if key.chars().count() == 1 {
+        key.to_lowercase()
+    } else {
+        key.to_string()
+    });
+    Ok(trigger.join("+"))
+}
+
+#[cfg(target_os = "linux")]
+mod portal {
+    use super::{EventHandler, HotkeyEvent, portal_trigger};
+    use anyhow::Result;
+    use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
+    use futures_util::StreamExt;
+    use gia_core::config_file::HotkeyAction;
+    use gia_core::logging::log_info;
+    use std::collections::BTreeMap;
+
+    /// Bind the shortcuts (the desktop may ask the user to confirm them) and
+    /// forward their activations for as long as the runtime runs
+    pub async fn bind(
+        actions: &BTreeMap<String, HotkeyAction>,
+        on_event: EventHandler,
+    ) -> Result<()> {
+        let portal = GlobalShortcuts::new().await?;
+        let session = portal.create_session().await?;
+
+        let mut shortcuts = Vec::new();
+        for (name, action) in actions {
+            let trigger = portal_trigger(&action.keys)?;
+            shortcuts.push(
+                NewShortcut::new(name.as_str(), crate::tray::describe(action))
+                    .preferred_trigger(trigger.as_str()),
+            );
+        }
+        let bound = portal
+            .bind_shortcuts(&session, &shortcuts, None)
+            .await?
+            .response()?;
+        for shortcut in bound.shortcuts() {
+            log_info(&format!(
+                "Bound shortcut '{}' to {}",
+                shortcut.id(),
+                shortcut.trigger_description()
+            ));
+        }
+
+        let activated = portal
+            .receive_activated()
+            .await?
+            .map(|signal| (signal.shortcut_id().to_string(), true));
+        let deactivated = portal
+            .receive_deactivated()
+            .await?
+            .map(|signal| (signal.shortcut_id().to_string(), false));
+        let mut events = futures_util::stream::select(activated, deactivated);
+
+        tokio::spawn(async move {
+            // The shortcuts stay bound while the session is open
+            let _session = session;
+            while let Some((action, pressed)) = events.next().await {
+                on_event(HotkeyEvent { action, pressed });
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() {
+        assert!(parse_keys("Ctrl+Alt+T").is_ok());
+        assert!(parse_keys("super+F9").is_ok());
+        assert_eq!(
+            parse_keys("Alt+U").unwrap().id(),
+            parse_keys("alt+u").unwrap().id()
+        );
+        assert!(parse_keys("Alt+ü").is_err());
+        assert!(parse_keys("Ctrl+").is_err());
+    }
+
+    #[test]
+    fn test_portal_trigger() {
+        assert_eq!(portal_trigger("Ctrl+Alt+T").unwrap(), "CTRL+ALT+t");
+        assert_eq!(portal_trigger("Super+Shift+F9").unwrap(), "LOGO+SHIFT+F9");
+        assert_eq!(portal_trigger("Alt+1").unwrap(), "ALT+1");
+        assert!(portal_trigger("Hyper+T").is_err());
+    }
+}
@@ -1,10 +1,26 @@
+mod attachments;
+mod history;
+mod hotkeys;
+mod overlay;
+mod recorder;
+mod runner;
+mod session;
+mod settings;
+mod tray;
+
 use arboard::Clipboard;
+use attachments::Attachment;
 use clap::Parser;
 use eframe::egui;
+use egui_commonmark::CommonMarkViewer;
+use gia_core::{Config, ConversationManager};
+use history::{HistoryAction, HistoryPanel};
+use recorder::{RecorderAction, Recording};
 use serde::Deserialize;
+use session::Session;
+use settings::SettingsWindow;
 use std::fs;
 
-use std::process::Command;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
@@ -21,18 +37,25 @@ struct OllamaModel {
 
 const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
 
-/// Get default model from environment variable or default
+/// Get default model from environment variable, the settings or default
 fn get_default_model() -> String {
-    std::env::var("GIA_DEFAULT_MODEL").unwrap_or_else(|_| "gemini-2.5-flash-lite".to_string())
+    std::env::var("GIA_DEFAULT_MODEL")
+        .ok()
+        .or_else(|| gia_core::config_file::load_settings().default_model)
+        .unwrap_or_else(|| "gemini-2.5-flash-lite".to_string())
 }
 
 /// GIA GUI - Graphical user interface for the GIA command-line tool
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Display only a spinner until the process is killed
+    /// Display the progress overlay of `gia --spinner` (stages on stdin)
     #[arg(short, long)]
     spinner: bool,
+
+    /// Run in the background with the global hotkeys of ~/.gia/config
+    #[arg(long)]
+    tray: bool,
 }
 
 /// Fetch available Ollama models from local Ollama instance (blocking).
@@ -96,15 +119,22 @@ fn fetch_ollama_models() -> Vec<String> {
 }
 
 fn main() -> eframe::Result<()> {
+    if let Some(result) = gia_core::run_pdf_helper() {
+        if let Err(e) = result {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     let args = Args::parse();
     let version = env!("GIA_VERSION");
     let title = format!("GIA GUI - v{}", version);
 
     if args.spinner {
-        // Spinner-only mode: small window with just a spinner, no decorations, centered
+        // Progress overlay: small window without decorations, centered
         let options = eframe::NativeOptions {
             viewport: egui::ViewportBuilder::default()
-                .with_inner_size([150.0, 150.0])
+                .with_inner_size(overlay::OVERLAY_SIZE)
                 .with_resizable(false)
                 .with_decorations(false)
                 .with_transparent(true)
@@ -128,11 +158,45 @@ fn main() -> eframe::Result<()> {
                 visuals.popup_shadow = egui::epaint::Shadow::NONE;
                 cc.egui_ctx.set_visuals(visuals);
 
-                Ok(Box::new(SpinnerApp::default()))
+                Ok(Box::new(overlay::OverlayApp::new(&cc.egui_ctx)))
+            }),
+        )
+    } else if args.tray {
+        // Tray mode: hotkey actions, a small status window and a tray icon
+        gia_core::logging::init_logging();
+
+        let actions = match gia_core::config_file::load_config_file() {
+            Ok(config) if !config.hotkeys.is_empty() => config.hotkeys,
+            Ok(_) => {
+                eprintln!("Error: No hotkeys in ~/.gia/config (see README, Tray mode)");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                std::process::exit(1);
+            }
+        };
+
+        let options = eframe::NativeOptions {
+            viewport: egui::ViewportBuilder::default()
+                .with_inner_size([480.0, 260.0])
+                .with_icon(load_icon()),
+            ..Default::default()
+        };
+
+        eframe::run_native(
+            "GIA hotkeys",
+            options,
+            Box::new(|cc| {
+                let theme = gia_core::config_file::load_settings().theme;
+                settings::apply_theme(&cc.egui_ctx, theme.as_deref());
+                Ok(Box::new(tray::TrayApp::new(&cc.egui_ctx, actions)))
             }),
         )
     } else {
         // Normal GUI mode
+        gia_core::logging::init_logging();
+
         let options = eframe::NativeOptions {
             viewport: egui::ViewportBuilder::default()
                 .with_inner_size([800.0, 600.0])
@@ -143,7 +207,11 @@ fn main() -> eframe::Result<()> {
         eframe::run_native(
             &title,
             options,
-            Box::new(|_cc| Ok(Box::new(GiaApp::default()))),
+            Box::new(|cc| {
+                let theme = gia_core::config_file::load_settings().theme;
+                settings::apply_theme(&cc.egui_ctx, theme.as_deref());
+                Ok(Box::new(GiaApp::default()))
+            }),
         )
     }
 }
@@ -162,94 +230,26 @@ fn load_icon() -> egui::IconData {
     }
 }
 
-struct SpinnerApp {
-    animation_time: f64,
-}
-
-impl Default for SpinnerApp {
-    fn default() -> Self {
-        Self {
-            animation_time: 0.0,
-        }
-    }
-}
-
-impl eframe::App for SpinnerApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Update animation time
-        self.animation_time += ctx.input(|i| i.stable_dt as f64);
-        ctx.request_repaint();
-
-        // Set background to be completely transparent
-        ctx.set_pixels_per_point(1.0);
-
-        // Set the visuals to have a transparent background
-        let mut visuals = ctx.style().visuals.clone();
-        visuals.window_fill = egui::Color32::TRANSPARENT;
-        visuals.panel_fill = egui::Color32::TRANSPARENT;
-        visuals.extreme_bg_color = egui::Color32::TRANSPARENT;
-        ctx.set_visuals(visuals);
-
-        // Make background fully transparent
-        egui::CentralPanel::default()
-            .frame(
-                egui::Frame::new()
-                    .fill(egui::Color32::TRANSPARENT)
-                    .inner_margin(egui::Margin::ZERO)
-                    .outer_margin(egui::Margin::ZERO),
-            )
-            .show(ctx, |ui| {
-                // Draw animated spinner directly on transparent background
-                let num_dots = 8;
-                let radius = 20.0;
-                let dot_radius = 4.0;
-                let center = egui::pos2(75.0, 75.0); // Center of 150x150 window
-
-                for i in 0..num_dots {
-                    let angle = (self.animation_time * 2.0) as f32
-                        + (i as f32 * std::f32::consts::TAU / num_dots as f32);
-                    let x = center.x + angle.cos() * radius;
-                    let y = center.y + angle.sin() * radius;
-
-                    let opacity =
-                        ((self.animation_time * 3.0 + i as f64 * 0.5).sin() * 0.5 + 0.5) as f32;
-                    let color = egui::Color32::from_rgba_unmultiplied(
-                        100,
-                        150,
-                        255,
-                        (opacity * 255.0) as u8,
-                    );
-
-                    ui.painter()
-                        .circle_filled(egui::pos2(x, y), dot_radius, color);
-                }
-            });
-    }
-}
-
 struct GiaApp {
-    prompt: String,
-    options: String,
-    use_clipboard: bool,
-    browser_output: bool,
-    resume: bool,
-    response: String,
+    sessions: Vec<Session>,
+    active: usize,
+    next_session_id: u64,
+    history: HistoryPanel,
+    settings: SettingsWindow,
     first_frame: bool,
-    model: String,
-    task: String,
-    role: String,
     tasks: Vec<String>,
     roles: Vec<String>,
     ollama_models: Arc<Mutex<Vec<String>>>,
-    is_executing: Arc<Mutex<bool>>,
-    is_recording: Arc<Mutex<bool>>,
+    is_transcribing: Arc<Mutex<bool>>,
+    recording: Option<Recording>,
+    recording_role: (String, String), // role and prompt of the last recording, used by push-to-talk
+    recording_session: u64,           // tab that gets the transcript
     animation_time: f64,
-    pending_response: Arc<Mutex<Option<String>>>,
-    pending_recording: Arc<Mutex<Option<String>>>,
-    tts_enabled: bool,
+    runtime: tokio::runtime::Runtime,
+    raw_response: bool,
+    pending_recording: Arc<Mutex<Option<(u64, String)>>>, // (tab, transcript)
     tts_language: String,
     logo_texture: Option<egui::TextureHandle>,
-    clear_prompt_on_next_record: bool,
 }
 
 impl Default for GiaApp {
@@ -267,28 +267,25 @@ impl Default for GiaApp {
         });
 
         Self {
-            prompt: String::new(),
-            options: String::new(),
-            use_clipboard: false,
-            browser_output: false,
-            resume: false,
-            response: String::new(),
+            sessions: vec![Session::new(0, get_default_model())],
+            active: 0,
+            next_session_id: 1,
+            history: HistoryPanel::default(),
+            settings: SettingsWindow::default(),
             first_frame: true,
-            model: get_default_model(),
-            task: String::new(),
-            role: String::new(),
             tasks,
             roles,
             ollama_models,
-            is_executing: Arc::new(Mutex::new(false)),
-            is_recording: Arc::new(Mutex::new(false)),
+            is_transcribing: Arc::new(Mutex::new(false)),
+            recording: None,
+            recording_role: ("EN".to_string(), "Transcript".to_string()),
+            recording_session: 0,
             animation_time: 0.0,
-            pending_response: Arc::new(Mutex::new(None)),
+            runtime: tokio::runtime::Runtime::new().expect("Failed to start tokio runtime"),
+            raw_response: false,
             pending_recording: Arc::new(Mutex::new(None)),
-            tts_enabled: false,
-            tts_language: "de-DE".to_string(),
+            tts_language: gia_core::constants::get_default_tts_language(),
             logo_texture: None,
-            clear_prompt_on_next_record: false,
         }
     }
 }
@@ -320,32 +317,35 @@ fn load_md_files(subdir: &str) -> Vec<String> {
 impl eframe::App for GiaApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Cache mutex values at the start
-        let is_executing = *self.is_executing.lock().unwrap();
-        let is_recording = *self.is_recording.lock().unwrap();
-
-        // Check for pending response
-        if let Ok(mut pending) = self.pending_response.lock()
-            && let Some(response) = pending.take()
-        {
-            self.response = response;
+        let is_executing = self.sessions[self.active].is_executing();
+        let is_transcribing = *self.is_transcribing.lock().unwrap();
+
+        // Streamed text, finished requests and queued prompts of all tabs
+        let handle = self.runtime.handle().clone();
+        let mut any_executing = false;
+        for (index, session) in self.sessions.iter_mut().enumerate() {
+            if session.poll(&handle).is_some() {
+                session.unread = index != self.active;
+                if self.history.open {
+                    self.history.refresh();
+                }
+            }
+            any_executing |= session.is_executing();
         }
 
         // Check for pending recording
         if let Ok(mut pending) = self.pending_recording.lock()
-            && let Some(recording_text) = pending.take()
+            && let Some((session_id, recording_text)) = pending.take()
+            && let Some(session) = self.sessions.iter_mut().find(|s| s.id == session_id)
         {
-            // Check if we should clear the prompt first
-            if self.clear_prompt_on_next_record {
-                self.prompt.clear();
-                self.clear_prompt_on_next_record = false; // Reset flag after use
-            } else if !self.prompt.is_empty() {
-                self.prompt.push(' ');
-            }
-            self.prompt.push_str(&recording_text);
+            session.add_transcript(&recording_text);
         }
 
-        // Request repaint for animation (use cached values)
-        if is_executing || is_recording {
+        // Request repaint for animation and tab progress (use cached values)
+        if self.recording.is_some() || any_executing {
+            ctx.request_repaint();
+        }
+        if is_executing || is_transcribing {
             self.animation_time += ctx.input(|i| i.stable_dt as f64);
             ctx.request_repaint();
         }
@@ -353,10 +353,6 @@ impl eframe::App for GiaApp {
         // Handle keyboard shortcuts
         if ctx.input(|i| i.key_pressed(egui::Key::Enter) && i.modifiers.ctrl) {
             self.send_prompt();
-            // Set flag to clear prompt on next record
-            if self.resume {
-                self.clear_prompt_on_next_record = true;
-            }
         }
         if ctx.input(|i| i.key_pressed(egui::Key::L) && i.modifiers.ctrl) {
             self.clear_form();
@@ -370,18 +366,51 @@ impl eframe::App for GiaApp {
         if ctx.input(|i| i.key_pressed(egui::Key::F1)) {
             self.show_help();
         }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            if self.recording.is_some() {
+                self.cancel_recording();
+            } else if is_executing {
+                self.cancel_request();
+            }
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::I) && i.modifiers.ctrl) {
+            self.paste_image(ctx);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::H) && i.modifiers.ctrl) {
+            self.history.toggle();
+        }
+        // Tab shortcuts
+        if ctx.input(|i| i.key_pressed(egui::Key::T) && i.modifiers.ctrl) {
+            self.new_tab();
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::W) && i.modifiers.ctrl) {
+            self.close_tab(self.active);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::PageDown) && i.modifiers.ctrl) {
+            self.select_tab((self.active + 1) % self.sessions.len());
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::PageUp) && i.modifiers.ctrl) {
+            self.select_tab((self.active + self.sessions.len() - 1) % self.sessions.len());
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Comma) && i.modifiers.ctrl) {
+            self.settings.toggle();
+        }
         // Checkbox shortcuts
         if ctx.input(|i| i.key_pressed(egui::Key::Num1) && i.modifiers.ctrl) {
-            self.resume = !self.resume;
+            let session = self.session();
+            session.resume = !session.resume;
         }
         if ctx.input(|i| i.key_pressed(egui::Key::Num2) && i.modifiers.ctrl) {
-            self.use_clipboard = !self.use_clipboard;
+            let session = self.session();
+            session.use_clipboard = !session.use_clipboard;
         }
         if ctx.input(|i| i.key_pressed(egui::Key::Num3) && i.modifiers.ctrl) {
-            self.browser_output = !self.browser_output;
+            let session = self.session();
+            session.browser_output = !session.browser_output;
         }
         if ctx.input(|i| i.key_pressed(egui::Key::Num4) && i.modifiers.ctrl) {
-            self.tts_enabled = !self.tts_enabled;
+            let session = self.session();
+            session.tts_enabled = !session.tts_enabled;
         }
         // Audio recording shortcuts
         if ctx.input(|i| i.key_pressed(egui::Key::E) && i.modifiers.ctrl) {
@@ -390,6 +419,53 @@ impl eframe::App for GiaApp {
         if ctx.input(|i| i.key_pressed(egui::Key::D) && i.modifiers.ctrl) {
             self.record_audio("DE", "Transkribiere");
         }
+        // Push-to-talk: record while F2 is held, with the language of the last recording
+        if self.recording.is_none() && ctx.input(|i| i.key_pressed(egui::Key::F2)) {
+            let (role, prompt) = self.recording_role.clone();
+            self.start_recording(&role, &prompt, true);
+        }
+        if self.recording.as_ref().is_some_and(|r| r.push_to_talk)
+            && ctx.input(|i| i.key_released(egui::Key::F2))
+        {
+            self.stop_recording();
+        }
+
+        self.show_tabs(ctx);
+
+        if !self.sessions[self.active].footer_lines.is_empty() {
+            egui::TopBottomPanel::bottom("response_footer").show(ctx, |ui| {
+                for line in &self.sessions[self.active].footer_lines {
+                    ui.weak(line);
+                }
+            });
+        }
+
+        if self.settings.open {
+            let ollama_models = self.ollama_models.lock().unwrap().clone();
+            if let Some(saved) = self
+                .settings
+                .show(ctx, &ollama_models, self.runtime.handle())
+            {
+                // Tabs without a conversation yet start with the new default model
+                let model = get_default_model();
+                for session in &mut self.sessions {
+                    if session.selected_conversation.is_none() && !session.is_executing() {
+                        session.model = model.clone();
+                    }
+                }
+                self.tts_language = gia_core::constants::get_default_tts_language();
+                settings::apply_theme(ctx, saved.theme.as_deref());
+            }
+        }
+
+        if self.history.open
+            && let Some(action) = self.history.show(
+                ctx,
+                self.sessions[self.active].selected_conversation.as_deref(),
+            )
+        {
+            self.handle_history_action(action);
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical(|ui| {
@@ -398,16 +474,27 @@ impl eframe::App for GiaApp {
                     ui.horizontal(|ui| {
                         ui.label("Prompt:");
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui
+                                .button("🖼")
+                                .on_hover_text("Attach the image in the clipboard (Ctrl+I)")
+                                .clicked()
+                            {
+                                self.paste_image(ctx);
+                            }
                             if ui
                                 .button("🎤 DE")
-                                .on_hover_text("German transcription using role: DE (Ctrl+D)")
+                                .on_hover_text(
+                                    "German transcription using role: DE (Ctrl+D; again to stop)",
+                                )
                                 .clicked()
                             {
                                 self.record_audio("DE", "Transkribiere");
                             }
                             if ui
                                 .button("🎤 EN")
-                                .on_hover_text("English transcription using role: EN (Ctrl+E)")
+                                .on_hover_text(
+                                    "English transcription using role: EN (Ctrl+E; again to stop)",
+                                )
                                 .clicked()
                             {
                                 self.record_audio("EN", "Transcript");
@@ -418,13 +505,13 @@ impl eframe::App for GiaApp {
                         .max_height(60.0)
                         .show(ui, |ui| {
                             let response = ui.add(
-                                egui::TextEdit::multiline(&mut self.prompt)
+                                egui::TextEdit::multiline(&mut self.sessions[self.active].prompt)
                                     .desired_width(f32::INFINITY)
                                     .desired_rows(3),
                             );
                             // Reset flag if user starts typing in prompt
                             if response.changed() {
-                                self.clear_prompt_on_next_record = false;
+                                self.sessions[self.active].clear_prompt_on_next_record = false;
                             }
                             response
                         })
@@ -440,19 +527,29 @@ impl eframe::App for GiaApp {
                 // Handle drag and drop
                 if !ctx.input(|i| i.raw.dropped_files.is_empty()) {
                     let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
-                    for file in dropped_files {
-                        if let Some(path) = file.path
-                            && let Some(path_str) = path.to_str()
-                        {
-                            let option_line = format!("-f{}", path_str);
-
-                            if !self.options.is_empty() && !self.options.ends_with('\n') {
-                                self.options.push('\n');
+                    for path in dropped_files.into_iter().filter_map(|file| file.path) {
+                        match Attachment::from_path(ctx, path) {
+                            Ok(attachment) => attachments::add_attachment(
+                                &mut self.sessions[self.active].attachments,
+                                attachment,
+                            ),
+                            Err(e) => {
+                                self.sessions[self.active].last_error =
+                                    Some(format!("Not attached: {:#}", e))
                             }
-                            self.options.push_str(&option_line);
                         }
                     }
                 }
+                if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+                    ui.label("📎 Drop files or folders to attach them");
+                }
+
+                // Attachment chips
+                if let Some(index) =
+                    attachments::show_chips(ui, &self.sessions[self.active].attachments)
+                {
+                    self.sessions[self.active].attachments.remove(index);
+                }
 
                 ui.add_space(10.0);
 
@@ -461,16 +558,27 @@ impl eframe::App for GiaApp {
                     // Checkboxes
                     ui.group(|ui| {
                         ui.vertical(|ui| {
-                            ui.checkbox(&mut self.resume, "📥 Resume last conversation")
-                                .on_hover_text("Resume the most recent conversation (-R) [Ctrl+1]");
-                            ui.checkbox(&mut self.use_clipboard, "📥 Use clipboard input")
+                            let session = &mut self.sessions[self.active];
+                            match &session.selected_conversation {
+                                Some(id) => ui
+                                    .checkbox(&mut session.resume, format!("📥 Resume {id}"))
+                                    .on_hover_text(
+                                        "Resume the selected conversation (--resume) [Ctrl+1]",
+                                    ),
+                                None => ui
+                                    .checkbox(&mut session.resume, "📥 Resume conversation")
+                                    .on_hover_text(
+                                        "Continue this tab's conversation; the first prompt starts a new one [Ctrl+1]",
+                                    ),
+                            };
+                            ui.checkbox(&mut session.use_clipboard, "📥 Use clipboard input")
                                 .on_hover_text("Use content from clipboard as input (-c) [Ctrl+2]");
                             ui.add_space(3.0);
-                            ui.checkbox(&mut self.browser_output, "📤 Browser output")
+                            ui.checkbox(&mut session.browser_output, "📤 Browser output")
                                 .on_hover_text(
                                     "Open response in browser (--browser-output) [Ctrl+3]",
                                 );
-                            ui.checkbox(&mut self.tts_enabled, "📤 Text-to-Speech")
+                            ui.checkbox(&mut session.tts_enabled, "📤 Text-to-Speech")
                                 .on_hover_text("Enable text-to-speech output (--tts) [Ctrl+4]");
                         });
                     });
@@ -504,26 +612,32 @@ impl eframe::App for GiaApp {
 
                         if logo_response.clicked() {
                             self.send_prompt();
-                            // Set flag to clear prompt on next record
-                            if self.resume {
-                                self.clear_prompt_on_next_record = true;
-                            }
                         }
                         ui.add_space(10.0);
                     }
 
-                    // Custom options input
+                    // Custom options input; defaults belong in the settings window
                     ui.vertical(|ui| {
-                        ui.label("GIA Command Line Options: (Drop files or folders here)");
-                        let options_lines = self.options.lines().count().clamp(1, 10);
-                        egui::ScrollArea::vertical()
-                            .max_height(200.0)
+                        egui::CollapsingHeader::new("Extra command line options")
+                            .default_open(!self.sessions[self.active].options.is_empty())
                             .show(ui, |ui| {
-                                ui.add(
-                                    egui::TextEdit::multiline(&mut self.options)
-                                        .desired_width(f32::INFINITY)
-                                        .desired_rows(options_lines),
-                                )
+                                let options_lines = self.sessions[self.active]
+                                    .options
+                                    .lines()
+                                    .count()
+                                    .clamp(1, 10);
+                                egui::ScrollArea::vertical()
+                                    .max_height(200.0)
+                                    .show(ui, |ui| {
+                                        ui.add(
+                                            egui::TextEdit::multiline(
+                                                &mut self.sessions[self.active].options,
+                                            )
+                                            .desired_width(f32::INFINITY)
+                                            .desired_rows(options_lines)
+                                            .hint_text("One gia option per line"),
+                                        )
+                                    });
                             });
                         ui.horizontal(|ui| {
                             // Left column: Model and TTS Language
@@ -532,21 +646,21 @@ impl eframe::App for GiaApp {
                                     .horizontal(|ui| {
                                         ui.label("💡");
                                         egui::ComboBox::from_id_salt("model_selector")
-                                            .selected_text(&self.model)
+                                            .selected_text(&self.sessions[self.active].model)
                                             .show_ui(ui, |ui| {
                                                 ui.label("Gemini Models:");
                                                 ui.selectable_value(
-                                                    &mut self.model,
+                                                    &mut self.sessions[self.active].model,
                                                     "gemini-2.5-pro".to_string(),
                                                     "Gemini 2.5 Pro",
                                                 );
                                                 ui.selectable_value(
-                                                    &mut self.model,
+                                                    &mut self.sessions[self.active].model,
                                                     "gemini-2.5-flash".to_string(),
                                                     "Gemini 2.5 Flash",
                                                 );
                                                 ui.selectable_value(
-                                                    &mut self.model,
+                                                    &mut self.sessions[self.active].model,
                                                     "gemini-2.5-flash-lite".to_string(),
                                                     "Gemini 2.5 Flash-Lite",
                                                 );
@@ -564,7 +678,7 @@ impl eframe::App for GiaApp {
                                                             .strip_prefix("ollama::")
                                                             .unwrap_or(model);
                                                         ui.selectable_value(
-                                                            &mut self.model,
+                                                            &mut self.sessions[self.active].model,
                                                             model.clone(),
                                                             format!("Ollama {}", display_name),
                                                         );
@@ -602,20 +716,22 @@ impl eframe::App for GiaApp {
                                 ui.horizontal(|ui| {
                                     ui.label("📋");
                                     egui::ComboBox::from_id_salt("task_selector")
-                                        .selected_text(if self.task.is_empty() {
-                                            "Select Task"
-                                        } else {
-                                            &self.task
-                                        })
+                                        .selected_text(
+                                            if self.sessions[self.active].task.is_empty() {
+                                                "Select Task"
+                                            } else {
+                                                &self.sessions[self.active].task
+                                            },
+                                        )
                                         .show_ui(ui, |ui| {
                                             ui.selectable_value(
-                                                &mut self.task,
+                                                &mut self.sessions[self.active].task,
                                                 String::new(),
                                                 "None",
                                             );
                                             for task in &self.tasks {
                                                 ui.selectable_value(
-                                                    &mut self.task,
+                                                    &mut self.sessions[self.active].task,
                                                     task.clone(),
                                                     task,
                                                 );
@@ -626,20 +742,22 @@ impl eframe::App for GiaApp {
                                 ui.horizontal(|ui| {
                                     ui.label("👤");
                                     egui::ComboBox::from_id_salt("role_selector")
-                                        .selected_text(if self.role.is_empty() {
-                                            "Select Role"
-                                        } else {
-                                            &self.role
-                                        })
+                                        .selected_text(
+                                            if self.sessions[self.active].role.is_empty() {
+                                                "Select Role"
+                                            } else {
+                                                &self.sessions[self.active].role
+                                            },
+                                        )
                                         .show_ui(ui, |ui| {
                                             ui.selectable_value(
-                                                &mut self.role,
+                                                &mut self.sessions[self.active].role,
                                                 String::new(),
                                                 "None",
                                             );
                                             for role in &self.roles {
                                                 ui.selectable_value(
-                                                    &mut self.role,
+                                                    &mut self.sessions[self.active].role,
                                                     role.clone(),
                                                     role,
                                                 );
@@ -665,10 +783,6 @@ impl eframe::App for GiaApp {
                         .clicked()
                     {
                         self.send_prompt();
-                        // Set flag to clear prompt on next record
-                        if self.resume {
-                            self.clear_prompt_on_next_record = true;
-                        }
                     }
                     if ui
                         .button("❌")
@@ -691,6 +805,20 @@ impl eframe::App for GiaApp {
                     {
                         self.show_conversation();
                     }
+                    if ui
+                        .add(egui::Button::new("📚").selected(self.history.open))
+                        .on_hover_text("Conversation history (Ctrl+H)")
+                        .clicked()
+                    {
+                        self.history.toggle();
+                    }
+                    if ui
+                        .add(egui::Button::new("⚙").selected(self.settings.open))
+                        .on_hover_text("Settings (Ctrl+,)")
+                        .clicked()
+                    {
+                        self.settings.toggle();
+                    }
                     if ui
                         .button("❓")
                         .on_hover_text("Show GIA help (F1)")
@@ -698,17 +826,26 @@ impl eframe::App for GiaApp {
                     {
                         self.show_help();
                     }
+                    ui.toggle_value(&mut self.raw_response, "📝")
+                        .on_hover_text("Show the response as editable markdown text");
                 });
 
                 ui.add_space(5.0);
 
+                // Recorder with level meter and waveform while recording
+                let recorder_action = self.recording.as_ref().and_then(|r| r.show(ui));
+                match recorder_action {
+                    Some(RecorderAction::Stop) => self.stop_recording(),
+                    Some(RecorderAction::Cancel) => self.cancel_recording(),
+                    None => {}
+                }
+
                 // Animation during execution (use cached value)
-                if is_executing || is_recording {
+                if is_executing || is_transcribing {
                     ui.horizontal(|ui| {
-                        let label_text = if is_recording {
-                            "Recording Audio - Wait for message box before speaking"
-                        } else {
-                            "Executing GIA"
+                        let label_text = match self.sessions[self.active].elapsed() {
+                            Some(elapsed) if is_executing => format!("Executing GIA {elapsed}"),
+                            _ => "Transcribing audio".to_string(),
                         };
                         ui.label(label_text);
 
@@ -736,181 +873,320 @@ impl eframe::App for GiaApp {
                             ui.painter()
                                 .circle_filled(egui::pos2(x, y), dot_radius, color);
                         }
+
+                        if is_executing {
+                            ui.add_space(50.0);
+                            if ui
+                                .button("⏹")
+                                .on_hover_text("Cancel the request (Esc)")
+                                .clicked()
+                            {
+                                self.cancel_request();
+                            }
+                        }
                     });
                 }
 
-                // Response box - use remaining space
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    ui.add_sized(
-                        ui.available_size(),
-                        egui::TextEdit::multiline(&mut self.response)
-                            .font(egui::TextStyle::Monospace),
-                    );
-                });
+                // Prompts waiting for the running request of this tab
+                let queued = self.sessions[self.active].queued_prompts();
+                if !queued.is_empty() {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label("Queued:");
+                        for (index, preview) in queued.iter().enumerate() {
+                            ui.weak(format!("{}. {}", index + 1, preview));
+                            if ui
+                                .small_button("✖")
+                                .on_hover_text("Remove from the queue")
+                                .clicked()
+                            {
+                                self.sessions[self.active].remove_queued(index);
+                            }
+                        }
+                    });
+                }
+
+                if let Some(error) = &self.sessions[self.active].last_error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                }
+
+                // Response box - use remaining space, follow the text while it streams
+                egui::ScrollArea::vertical()
+                    .auto_shrink(false)
+                    .stick_to_bottom(is_executing)
+                    .show(ui, |ui| {
+                        let session = &mut self.sessions[self.active];
+                        if self.raw_response {
+                            ui.add_sized(
+                                ui.available_size(),
+                                egui::TextEdit::multiline(&mut session.response)
+                                    .font(egui::TextStyle::Monospace),
+                            );
+                        } else {
+                            CommonMarkViewer::new().show(
+                                ui,
+                                &mut session.markdown_cache,
+                                &session.response,
+                            );
+                        }
+                    });
             });
         });
     }
 }
 
 impl GiaApp {
-    fn send_prompt(&mut self) {
-        self.execute_gia();
+    fn session(&mut self) -> &mut Session {
+        &mut self.sessions[self.active]
     }
 
-    fn execute_gia(&mut self) {
-        let mut args = vec![];
+    /// Send the prompt of the current tab, queued if the tab is still busy
+    fn send_prompt(&mut self) {
+        let handle = self.runtime.handle().clone();
+        let tts_language = self.tts_language.clone();
+        self.session().send(&handle, &tts_language);
+    }
 
-        if self.use_clipboard {
-            args.push("-c".to_string());
-        }
-        if self.browser_output {
-            args.push("--browser-output".to_string());
-        }
-        if self.resume {
-            args.push("-R".to_string());
-        }
+    fn cancel_request(&mut self) {
+        let handle = self.runtime.handle().clone();
+        self.session().cancel(&handle);
+    }
 
-        // Add model option
-        args.push("--model".to_string());
-        args.push(self.model.clone());
+    /// Open a new tab with the default model and switch to it
+    fn new_tab(&mut self) {
+        self.sessions
+            .push(Session::new(self.next_session_id, get_default_model()));
+        self.next_session_id += 1;
+        self.select_tab(self.sessions.len() - 1);
+    }
 
-        // Add task option if selected
-        if !self.task.is_empty() {
-            args.push("-t".to_string());
-            args.push(self.task.clone());
-        }
+    fn select_tab(&mut self, index: usize) {
+        self.active = index;
+        self.session().unread = false;
+        self.first_frame = true; // focus the prompt of the tab
+    }
 
-        // Add role option if selected
-        if !self.role.is_empty() {
-            args.push("--role".to_string());
-            args.push(self.role.clone());
+    /// Close a tab, cancelling its requests; the last tab is cleared instead
+    fn close_tab(&mut self, index: usize) {
+        self.sessions[index].cancel_all();
+        if self.sessions.len() == 1 {
+            let model = get_default_model();
+            self.sessions[0] = Session::new(self.next_session_id, model);
+            self.next_session_id += 1;
+            return;
         }
-
-        // Add TTS option if enabled
-        if self.tts_enabled {
-            args.push(format!("--tts={}", self.tts_language));
+        self.sessions.remove(index);
+        if self.active > index || self.active == self.sessions.len() {
+            self.active -= 1;
         }
+    }
 
-        // Add custom options from options field
-        for line in self.options.lines() {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                args.push(trimmed.to_string());
-            }
+    /// Tab bar with per-tab progress and queued prompts
+    fn show_tabs(&mut self, ctx: &egui::Context) {
+        let mut selected = None;
+        let mut closed = None;
+        egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (index, session) in self.sessions.iter().enumerate() {
+                    let response = ui
+                        .selectable_label(index == self.active, session.label())
+                        .on_hover_text(
+                            session
+                                .selected_conversation
+                                .as_deref()
+                                .map(|id| format!("{} · {}", session.model, id))
+                                .unwrap_or_else(|| session.model.clone()),
+                        );
+                    if response.clicked() {
+                        selected = Some(index);
+                    }
+                    if response.middle_clicked()
+                        || ui
+                            .small_button("✖")
+                            .on_hover_text("Close tab (Ctrl+W)")
+                            .clicked()
+                    {
+                        closed = Some(index);
+                    }
+                    ui.separator();
+                }
+                if ui.button("➕").on_hover_text("New tab (Ctrl+T)").clicked() {
+                    self.new_tab();
+                }
+            });
+        });
+        if let Some(index) = selected {
+            self.select_tab(index);
         }
-
-        if !self.prompt.is_empty() {
-            args.push(self.prompt.clone());
+        if let Some(index) = closed {
+            self.close_tab(index);
         }
+    }
 
-        // Clear task and role selections, uncheck clipboard, clear options, and enable resume after sending
-        self.task.clear();
-        self.role.clear();
-        self.options.clear();
-        self.use_clipboard = false;
-        self.resume = true;
-
-        // Start animation
-        *self.is_executing.lock().unwrap() = true;
-        self.animation_time = 0.0;
+    fn clear_form(&mut self) {
+        self.session().clear();
+    }
 
-        let is_executing = Arc::clone(&self.is_executing);
-        let pending_response = Arc::clone(&self.pending_response);
+    fn paste_image(&mut self, ctx: &egui::Context) {
+        let session = self.session();
+        match Attachment::from_clipboard(ctx) {
+            Ok(attachment) => attachments::add_attachment(&mut session.attachments, attachment),
+            Err(e) => session.last_error = Some(format!("Not attached: {:#}", e)),
+        }
+    }
 
-        thread::spawn(move || {
-            let result = match Command::new("gia").args(&args).output() {
-                Ok(output) => {
-                    let mut response = String::from_utf8_lossy(&output.stdout).to_string();
-                    if !output.stderr.is_empty() {
-                        response.push_str("\n\nErrors/Logging:\n");
-                        response.push_str(&String::from_utf8_lossy(&output.stderr));
+    fn handle_history_action(&mut self, action: HistoryAction) {
+        match action {
+            HistoryAction::Select(id) => {
+                // A tab that already has the conversation is shown; otherwise it opens
+                // in this tab, or in a new one while this tab is busy
+                if let Some(index) = self
+                    .sessions
+                    .iter()
+                    .position(|s| s.selected_conversation.as_deref() == Some(id.as_str()))
+                {
+                    self.select_tab(index);
+                    return;
+                }
+                match ConversationManager::new().and_then(|manager| manager.load_conversation(&id))
+                {
+                    Ok(conversation) => {
+                        if self.session().is_executing() {
+                            self.new_tab();
+                        }
+                        self.session().open_conversation(&conversation);
                     }
-                    response
+                    Err(e) => self.session().last_error = Some(format!("Error: {:#}", e)),
                 }
-                Err(e) => format!("Error executing gia: {}", e),
-            };
-
-            *pending_response.lock().unwrap() = Some(result);
-            *is_executing.lock().unwrap() = false;
-        });
-    }
-
-    fn clear_form(&mut self) {
-        self.prompt.clear();
-        self.options.clear();
-        self.response.clear();
-        self.use_clipboard = false;
-        self.browser_output = false;
-        self.resume = false;
-        self.clear_prompt_on_next_record = false;
+            }
+            HistoryAction::Deleted(id) => {
+                for session in &mut self.sessions {
+                    if session.selected_conversation.as_deref() == Some(id.as_str()) {
+                        session.selected_conversation = None;
+                        session.resume = false;
+                        session.response.clear();
+                        session.footer_lines.clear();
+                    }
+                }
+            }
+        }
     }
 
     fn copy_response(&mut self) {
         if let Ok(mut clipboard) = Clipboard::new() {
-            let _ = clipboard.set_text(&self.response);
+            let _ = clipboard.set_text(&self.sessions[self.active].response);
         }
     }
 
+    /// Open the tab's conversation (or the latest one) in the browser
     fn show_conversation(&mut self) {
-        let mut args = vec!["--show-conversation".to_string()];
+        let session = &self.sessions[self.active];
+        let id = session.selected_conversation.clone();
+        let tts_language = session.tts_enabled.then(|| self.tts_language.clone());
+        let pending_error = session.error_sink();
+
+        self.runtime.spawn(async move {
+            if let Err(e) = runner::show_conversation(id, tts_language).await {
+                *pending_error.lock().unwrap() = Some(format!("Error: {:#}", e));
+            }
+        });
+    }
 
-        if self.tts_enabled {
-            args.push(format!("--tts={}", self.tts_language));
-        }
+    fn show_help(&mut self) {
+        let session = self.session();
+        session.response = format!("```text\n{}\n```", Config::help_text());
+        session.footer_lines.clear();
+        session.last_error = None;
+    }
 
-        let _ = Command::new("gia").args(args).spawn();
+    /// Start recording in the window, or stop a running recording
+    fn record_audio(&mut self, role: &str, prompt: &str) {
+        if self.recording.is_some() {
+            self.stop_recording();
+        } else {
+            self.start_recording(role, prompt, false);
+        }
     }
 
-    fn show_help(&mut self) {
-        match Command::new("gia").arg("--help").output() {
-            Ok(output) => {
-                self.response = String::from_utf8_lossy(&output.stdout).to_string();
-                if !output.stderr.is_empty() {
-                    self.response.push_str("\n\nErrors/Logging:\n");
-                    self.response
-                        .push_str(&String::from_utf8_lossy(&output.stderr));
-                }
-            }
-            Err(e) => {
-                self.response = format!("Error executing gia: {}", e);
+    fn start_recording(&mut self, role: &str, prompt: &str, push_to_talk: bool) {
+        if *self.is_transcribing.lock().unwrap() {
+            return;
+        }
+        match Recording::start(role, prompt, push_to_talk) {
+            Ok(recording) => {
+                self.recording = Some(recording);
+                self.recording_role = (role.to_string(), prompt.to_string());
+                self.recording_session = self.sessions[self.active].id;
+                self.session().last_error = None;
             }
+            Err(e) => self.session().last_error = Some(format!("Recording failed: {:#}", e)),
         }
     }
 
-    fn record_audio(&mut self, role: &str, prompt: &str) {
-        // Start recording animation
-        *self.is_recording.lock().unwrap() = true;
+    fn cancel_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            recording.cancel();
+        }
+    }
+
+    /// Stop recording and transcribe it with the recording's role into the
+    /// prompt of the tab it was started in
+    fn stop_recording(&mut self) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        let args: Vec<String> = [
+            "--role",
+            &recording.role,
+            "--no-save",
+            "--",
+            &recording.prompt,
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let wav_path = match recording.finish() {
+            Ok(path) => path,
+            Err(e) => {
+                self.session().last_error = Some(format!("Recording failed: {:#}", e));
+                return;
+            }
+        };
+
+        *self.is_transcribing.lock().unwrap() = true;
         self.animation_time = 0.0;
 
-        let is_recording = Arc::clone(&self.is_recording);
+        let session_id = self.recording_session;
+        let is_transcribing = Arc::clone(&self.is_transcribing);
         let pending_recording = Arc::clone(&self.pending_recording);
-        let role = role.to_string();
-        let prompt = prompt.to_string();
+        let pending_error = self
+            .sessions
+            .iter()
+            .find(|s| s.id == session_id)
+            .unwrap_or(&self.sessions[self.active])
+            .error_sink();
+
+        self.runtime.spawn(async move {
+            let result = async {
+                let opus_path =
+                    tokio::task::spawn_blocking(move || gia_core::audio::wav_to_opus(&wav_path))
+                        .await??;
+                let config = runner::request_config(&args)?;
+                runner::run_with_recording(config, opus_path).await
+            }
+            .await;
 
-        thread::spawn(move || {
-            let result = match Command::new("gia")
-                .args(["--record-audio", "--role", &role, "--no-save", &prompt])
-                .output()
-            {
-                Ok(output) => {
-                    let audio_text = String::from_utf8_lossy(&output.stdout);
-                    let trimmed_text = audio_text.trim();
-                    if !trimmed_text.is_empty() {
-                        trimmed_text.to_string()
-                    } else {
-                        String::new()
+            match result {
+                Ok(response) => {
+                    let text = response.text.trim();
+                    if !text.is_empty() {
+                        *pending_recording.lock().unwrap() = Some((session_id, text.to_string()));
                     }
                 }
-                Err(_e) => {
-                    // Return empty string on error - we'll handle this in the UI
-                    String::new()
+                Err(e) => {
+                    *pending_error.lock().unwrap() = Some(format!("Error: {:#}", e));
                 }
-            };
-
-            if !result.is_empty() {
-                *pending_recording.lock().unwrap() = Some(result);
             }
-            *is_recording.lock().unwrap() = false;
+            *is_transcribing.lock().unwrap() = false;
         });
     }
 }
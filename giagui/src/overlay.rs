@@ -0,0 +1,176 @@
+//! Progress overlay of `gia --spinner`: the stage of the request read from stdin,
+//! elapsed time and a cancel button that tells gia on stdout to abort the request.
+
+use crate::recorder::format_elapsed;
+use eframe::egui;
+use gia_core::progress::{OverlayCommand, Stage};
+use std::io::{BufRead, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const OVERLAY_SIZE: [f32; 2] = [300.0, 110.0];
+const ERROR_DISPLAY: Duration = Duration::from_secs(4);
+
+#[derive(Default)]
+struct OverlayState {
+    stage: Option<Stage>,
+    closed: Option<Instant>, // gia closed our stdin
+}
+
+pub struct OverlayApp {
+    started: Instant,
+    state: Arc<Mutex<OverlayState>>,
+    cancelling: bool,
+    on_top: Option<bool>,
+}
+
+impl OverlayApp {
+    pub fn new(ctx: &egui::Context) -> Self {
+        let state = Arc::new(Mutex::new(OverlayState::default()));
+
+        let reader_state = Arc::clone(&state);
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            read_stages(std::io::stdin().lock(), |stage| {
+                reader_state.lock().unwrap().stage = Some(stage);
+                ctx.request_repaint();
+            });
+            reader_state.lock().unwrap().closed = Some(Instant::now());
+            ctx.request_repaint();
+        });
+
+        Self {
+            started: Instant::now(),
+            state,
+            cancelling: false,
+            on_top: None,
+        }
+    }
+
+    fn cancel(&mut self) {
+        let line = serde_json::to_string(&OverlayCommand::Cancel).unwrap_or_default();
+        let mut stdout = std::io::stdout();
+        if let Err(e) = writeln!(stdout, "{line}").and_then(|()| stdout.flush()) {
+            eprintln!("Warning: Failed to cancel the request: {}", e);
+            return;
+        }
+        self.cancelling = true;
+    }
+}
+
+/// Stages sent by gia, one JSON object per line; other lines are ignored
+fn read_stages(reader: impl BufRead, mut on_stage: impl FnMut(Stage)) {
+    for line in reader.lines().map_while(Result::ok) {
+        if let Ok(stage) = serde_json::from_str(&line) {
+            on_stage(stage);
+        }
+    }
+}
+
+impl eframe::App for OverlayApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        ctx.request_repaint();
+        ctx.set_pixels_per_point(1.0);
+
+        let (stage, closed) = {
+            let state = self.state.lock().unwrap();
+            (state.stage.clone(), state.closed)
+        };
+
+        // Without any stage gia is an older version or the overlay was started by
+        // hand; it then stays until it is killed, like the plain spinner did
+        if let (Some(stage), Some(closed)) = (&stage, closed) {
+            let showing_error =
+                matches!(stage, Stage::Error { .. }) && closed.elapsed() < ERROR_DISPLAY;
+            if !showing_error {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+
+        // Stay behind the recording dialog, on top of everything afterwards
+        let on_top = !matches!(stage, Some(Stage::Recording { .. }));
+        if self.on_top != Some(on_top) {
+            self.on_top = Some(on_top);
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(if on_top {
+                egui::WindowLevel::AlwaysOnTop
+            } else {
+                egui::WindowLevel::Normal
+            }));
+        }
+
+        egui::CentralPanel::default()
+            .frame(
+                egui::Frame::new()
+                    .fill(egui::Color32::from_black_alpha(210))
+                    .corner_radius(12.0)
+                    .inner_margin(egui::Margin::same(12)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    spinner_dots(ui, self.started.elapsed().as_secs_f64());
+                    ui.vertical(|ui| {
+                        let text = stage
+                            .as_ref()
+                            .map(Stage::text)
+                            .unwrap_or_else(|| "Working".to_string());
+                        if matches!(stage, Some(Stage::Error { .. })) {
+                            ui.colored_label(egui::Color32::from_rgb(255, 110, 110), text);
+                        } else {
+                            ui.colored_label(egui::Color32::WHITE, text);
+                        }
+                        ui.colored_label(
+                            egui::Color32::GRAY,
+                            format_elapsed(self.started.elapsed()),
+                        );
+                        if self.cancelling {
+                            ui.colored_label(egui::Color32::GRAY, "Cancelling…");
+                        } else if stage.as_ref().is_some_and(Stage::cancellable)
+                            && ui.button("✖ Cancel").clicked()
+                        {
+                            self.cancel();
+                        }
+                    });
+                });
+            });
+    }
+}
+
+/// Animated ring of dots
+fn spinner_dots(ui: &mut egui::Ui, time: f64) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(60.0, 60.0), egui::Sense::hover());
+    let num_dots = 8;
+    let radius = 20.0;
+    let dot_radius = 4.0;
+    let center = rect.center();
+
+    for i in 0..num_dots {
+        let angle = (time * 2.0) as f32 + (i as f32 * std::f32::consts::TAU / num_dots as f32);
+        let x = center.x + angle.cos() * radius;
+        let y = center.y + angle.sin() * radius;
+
+        let opacity = ((time * 3.0 + i as f64 * 0.5).sin() * 0.5 + 0.5) as f32;
+        let color = egui::Color32::from_rgba_unmultiplied(100, 150, 255, (opacity * 255.0) as u8);
+
+        ui.painter()
+            .circle_filled(egui::pos2(x, y), dot_radius, color);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_stages() {
+        let input =
+            "{\"stage\":\"converting\"}\nnot json\n{\"stage\":\"streaming\",\"chars\":42}\n";
+        let mut stages = Vec::new();
+        read_stages(Cursor::new(input), |stage| stages.push(stage));
+        assert_eq!(
+            stages,
+            vec![Stage::Converting, Stage::Streaming { chars: 42 }]
+        );
+    }
+}
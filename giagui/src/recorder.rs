@@ -0,0 +1,178 @@
+//! In-window audio recording: level meter, waveform, elapsed time, stop and cancel.
+
+use anyhow::Result;
+use eframe::egui;
+use gia_core::audio::Recorder;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const METER_WIDTH: f32 = 120.0;
+const WAVEFORM_HEIGHT: f32 = 36.0;
+const BAR_WIDTH: f32 = 3.0;
+
+/// What the user did in the recorder
+pub enum RecorderAction {
+    Stop,
+    Cancel,
+}
+
+/// A recording running in the window and the transcription request it is for
+pub struct Recording {
+    recorder: Recorder,
+    pub role: String,
+    pub prompt: String,
+    pub push_to_talk: bool, // stops when the hotkey is released
+}
+
+impl Recording {
+    /// Start recording from the configured audio device
+    pub fn start(role: &str, prompt: &str, push_to_talk: bool) -> Result<Self> {
+        Ok(Self {
+            recorder: Recorder::start(None)?,
+            role: role.to_string(),
+            prompt: prompt.to_string(),
+            push_to_talk,
+        })
+    }
+
+    /// Stop recording; returns the WAV file to transcribe
+    pub fn finish(self) -> Result<PathBuf> {
+        self.recorder.stop()
+    }
+
+    pub fn cancel(self) {
+        self.recorder.cancel();
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui) -> Option<RecorderAction> {
+        let mut action = None;
+        let level = self.recorder.level();
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::from_rgb(220, 60, 60), "⏺");
+                ui.label(format_elapsed(self.recorder.elapsed()));
+                ui.weak(self.recorder.device_name());
+                if self.push_to_talk {
+                    ui.weak("· release F2 to stop");
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui
+                        .button("✖ Cancel")
+                        .on_hover_text("Discard the recording (Esc)")
+                        .clicked()
+                    {
+                        action = Some(RecorderAction::Cancel);
+                    }
+                    if ui
+                        .button("⏹ Stop")
+                        .on_hover_text("Stop and transcribe into the prompt")
+                        .clicked()
+                    {
+                        action = Some(RecorderAction::Stop);
+                    }
+                    level_meter(ui, level);
+                });
+            });
+            waveform(ui, &self.recorder.waveform());
+        });
+
+        action
+    }
+}
+
+/// Horizontal VU meter, green to red
+fn level_meter(ui: &mut egui::Ui, level: f32) {
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(METER_WIDTH, 10.0), egui::Sense::hover());
+    let fraction = meter_fraction(level);
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+    let mut filled = rect;
+    filled.set_width(rect.width() * fraction);
+    painter.rect_filled(filled, 2.0, meter_color(fraction));
+    response.on_hover_text("Input level");
+}
+
+/// Bars of the recording's peaks, the whole recording squeezed into the width
+fn waveform(ui: &mut egui::Ui, peaks: &[f32]) {
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), WAVEFORM_HEIGHT),
+        egui::Sense::hover(),
+    );
+    let bars = waveform_bars(peaks, (rect.width() / BAR_WIDTH) as usize);
+    let color = ui.visuals().widgets.active.fg_stroke.color;
+    let painter = ui.painter();
+    for (index, peak) in bars.iter().enumerate() {
+        let x = rect.left() + (index as f32 + 0.5) * BAR_WIDTH;
+        let half_height = (meter_fraction(*peak) * rect.height() / 2.0).max(0.5);
+        painter.line_segment(
+            [
+                egui::pos2(x, rect.center().y - half_height),
+                egui::pos2(x, rect.center().y + half_height),
+            ],
+            egui::Stroke::new(BAR_WIDTH - 1.0, color),
+        );
+    }
+}
+
+/// At most `bars` peaks: the maximum of each group of waveform buckets
+fn waveform_bars(peaks: &[f32], bars: usize) -> Vec<f32> {
+    if bars == 0 || peaks.len() <= bars {
+        return peaks.to_vec();
+    }
+    let group = peaks.len().div_ceil(bars);
+    peaks
+        .chunks(group)
+        .map(|chunk| chunk.iter().copied().fold(0.0, f32::max))
+        .collect()
+}
+
+/// Meter position of a peak level: -60 dB..0 dB on a linear scale
+fn meter_fraction(level: f32) -> f32 {
+    if level <= 0.0 {
+        return 0.0;
+    }
+    ((20.0 * level.log10() + 60.0) / 60.0).clamp(0.0, 1.0)
+}
+
+fn meter_color(fraction: f32) -> egui::Color32 {
+    if fraction > 0.95 {
+        egui::Color32::from_rgb(220, 60, 60)
+    } else if fraction > 0.8 {
+        egui::Color32::from_rgb(220, 180, 60)
+    } else {
+        egui::Color32::from_rgb(60, 180, 80)
+    }
+}
+
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waveform_bars() {
+        assert_eq!(waveform_bars(&[0.1, 0.2], 10), vec![0.1, 0.2]);
+        assert_eq!(waveform_bars(&[0.1, 0.5, 0.2, 0.3, 0.9], 2), vec![0.5, 0.9]);
+        assert_eq!(waveform_bars(&[0.1, 0.5, 0.2, 0.3], 2), vec![0.5, 0.3]);
+    }
+
+    #[test]
+    fn test_meter_fraction() {
+        assert_eq!(meter_fraction(0.0), 0.0);
+        assert_eq!(meter_fraction(1.0), 1.0);
+        assert_eq!(meter_fraction(0.0001), 0.0);
+        assert!((meter_fraction(0.1) - 2.0 / 3.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(7_900)), "0:07");
+        assert_eq!(format_elapsed(Duration::from_secs(125)), "2:05");
+    }
+}
@@ -0,0 +1,236 @@
+//! Runs gia requests in-process through gia-core instead of spawning the `gia` binary.
+
+use anyhow::{Context, Result, bail};
+use gia_core::output::{FooterMetadata, build_footer_metadata, output_text_with_usage};
+use gia_core::{
+    Config, ContentSource, ConversationManager, OutputMode, TextCallback, complete_request,
+    prepare_request,
+};
+use std::sync::Arc;
+
+/// Outcome of a finished request
+#[derive(Debug, Clone)]
+pub struct GiaResponse {
+    pub text: String,
+    pub conversation_id: Option<String>,
+    pub footer: FooterMetadata,
+}
+
+impl GiaResponse {
+    /// Footer shown below the response, like the one of the browser preview:
+    /// model, tokens and conversation id, then the inputs
+    pub fn footer_lines(&self) -> Vec<String> {
+        let mut status = vec![format!(
+            "{}::{}",
+            self.footer.provider_name, self.footer.model_name
+        )];
+        if let Some(usage) = &self.footer.token_usage {
+            status.push(format!("Tokens: {}", usage.format_short()));
+        }
+        if let Some(id) = &self.conversation_id {
+            status.push(format!("Conversation: {id}"));
+        }
+
+        let mut lines = vec![status.join(" · ")];
+        let inputs = self.footer.input_items();
+        if !inputs.is_empty() {
+            lines.push(format!("Inputs: {}", inputs.join(" · ")));
+        }
+        lines
+    }
+}
+
+/// Build the request config from gia command line arguments. The GUI never
+/// reads stdin and shows its own progress, so both are turned off.
+pub fn request_config(args: &[String]) -> Result<Config> {
+    let mut config = Config::from_arg_list(args)?;
+
+    if config.list_conversations.is_some()
+        || config.show_conversation.is_some()
+        || config.list_audio_devices
+        || config.serve.is_some()
+        || config.mcp_serve
+    {
+        bail!("This option is not supported in giagui");
+    }
+
+    config.read_stdin = false;
+    config.spinner = false;
+    Ok(config)
+}
+
+/// Run a request like `gia` does and return the response instead of printing it.
+/// With `on_text` the response text is streamed to it while it is generated.
+/// Browser, clipboard and TTS output still happen as requested in the config.
+pub async fn run_request(config: Config, on_text: Option<TextCallback<'_>>) -> Result<GiaResponse> {
+    run_with_content(config, Vec::new(), on_text).await
+}
+
+/// Run a request with an audio file recorded in the window, added after the
+/// prompt like `--record-audio` does
+pub async fn run_with_recording(config: Config, opus_path: String) -> Result<GiaResponse> {
+    run_with_content(config, vec![ContentSource::AudioRecording(opus_path)], None).await
+}
+
+/// Run a request with content added after the prompt, like a pasted image
+pub async fn run_with_content(
+    mut config: Config,
+    extra_content: Vec<ContentSource>,
+    on_text: Option<TextCallback<'_>>,
+) -> Result<GiaResponse> {
+    let conversation_manager =
+        Arc::new(ConversationManager::new().context("Failed to initialize conversation manager")?);
+
+    // Input gathering reads files, the clipboard and the microphone
+    let manager = Arc::clone(&conversation_manager);
+    let (mut config, mut conversation, api_keys) = tokio::task::spawn_blocking(move || {
+        prepare_request(&mut config, &manager)
+            .map(|(conversation, keys)| (config, conversation, keys))
+    })
+    .await
+    .context("Request preparation panicked")??;
+    config.ordered_content.extend(extra_content);
+
+    if config.ordered_content.is_empty() {
+        bail!("No input content provided. Enter a prompt or use -c/-f/-i for additional input.");
+    }
+
+    let (text, usage) = complete_request(
+        &config,
+        &conversation_manager,
+        &mut conversation,
+        &api_keys,
+        on_text,
+    )
+    .await?;
+
+    let conversation_id = (!config.no_save).then(|| conversation.id.clone());
+    let footer = build_footer_metadata(&config, Some(usage));
+
+    if !matches!(config.output_mode, OutputMode::Stdout) {
+        let text = text.clone();
+        let id = conversation.id.clone();
+        tokio::task::spawn_blocking(move || {
+            output_text_with_usage(&text, &config, Some(usage), &id)
+        })
+        .await
+        .context("Response output panicked")?
+        .context("Failed to output response")?;
+    }
+
+    Ok(GiaResponse {
+        text,
+        conversation_id,
+        footer,
+    })
+}
+
+/// Open a conversation (default: the latest) in the browser (and speak it with TTS)
+pub async fn show_conversation(id: Option<String>, tts_language: Option<String>) -> Result<()> {
+    let mut args = vec![match id {
+        Some(id) => format!("--show-conversation={id}"),
+        None => "--show-conversation".to_string(),
+    }];
+    if let Some(language) = tts_language {
+        args.push(format!("--tts={language}"));
+    }
+
+    let mut config = Config::from_arg_list(&args)?;
+    config.read_stdin = false;
+    tokio::task::spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(gia_core::run_app(config))
+    })
+    .await
+    .context("Showing the conversation panicked")?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_request_config_disables_stdin_and_spinner() {
+        let config =
+            request_config(&args(&["--model", "ollama::llama3.2", "--", "Hello"])).unwrap();
+        assert!(!config.read_stdin);
+        assert!(!config.spinner);
+        assert_eq!(config.model, "ollama::llama3.2");
+        assert_eq!(config.prompt, "Hello");
+    }
+
+    #[test]
+    fn test_request_config_rejects_non_request_modes() {
+        for list in [
+            &["--list-conversations"][..],
+            &["--show-conversation"][..],
+            &["--list-audio-devices"][..],
+            &["serve"][..],
+            &["mcp-serve"][..],
+        ] {
+            let err = request_config(&args(list)).unwrap_err();
+            assert!(
+                err.to_string().contains("not supported in giagui"),
+                "{list:?}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_request_config_reports_invalid_arguments() {
+        assert!(request_config(&args(&["--no-such-option"])).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_run_request_without_content_fails() {
+        let home = tempfile::TempDir::new().unwrap();
+        let old_home = std::env::var_os("HOME");
+        unsafe { std::env::set_var("HOME", home.path()) };
+
+        let config = request_config(&args(&["--model", "ollama::llama3.2"])).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(run_request(config, None));
+
+        match old_home {
+            Some(value) => unsafe { std::env::set_var("HOME", value) },
+            None => unsafe { std::env::remove_var("HOME") },
+        }
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string().contains("No input content provided"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_footer_lines() {
+        let mut config =
+            request_config(&args(&["--model", "gemini-2.5-flash", "--", "Hi"])).unwrap();
+        config
+            .ordered_content
+            .push(gia_core::ContentSource::ClipboardImage);
+        let usage = gia_core::TokenUsage {
+            prompt_tokens: Some(3),
+            completion_tokens: Some(2),
+            total_tokens: Some(5),
+        };
+        let response = GiaResponse {
+            text: "Hello".to_string(),
+            conversation_id: Some("abc".to_string()),
+            footer: build_footer_metadata(&config, Some(usage)),
+        };
+
+        assert_eq!(
+            response.footer_lines(),
+            vec![
+                "gemini::gemini-2.5-flash · Tokens: 3+2=5 · Conversation: abc",
+                "Inputs: Clipboard content"
+            ]
+        );
+    }
+}
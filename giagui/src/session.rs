@@ -0,0 +1,515 @@
+//! A tab: its own prompt form, conversation, model and role. Tabs run requests
+//! independently; prompts sent while a request runs wait in the tab's queue.
+
+use crate::attachments::{self, Attachment};
+use crate::recorder::format_elapsed;
+use crate::runner::{self, GiaResponse};
+use anyhow::Context;
+use egui_commonmark::CommonMarkCache;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const TITLE_LENGTH: usize = 24;
+
+/// Response or error of a finished request
+type RequestResult = Result<GiaResponse, String>;
+
+/// A prompt with the options of its tab at the time it was sent
+pub struct PromptRequest {
+    prompt: String,
+    model: String,
+    task: String,
+    role: String,
+    use_clipboard: bool,
+    browser_output: bool,
+    resume: bool,
+    tts_language: Option<String>,
+    options: Vec<String>,
+    attachments: Vec<Attachment>,
+}
+
+impl PromptRequest {
+    /// gia arguments of the request; `conversation` is the tab's conversation
+    /// once the request starts (a queued prompt resumes the one before it)
+    fn args(&self, conversation: Option<&str>) -> Vec<String> {
+        let mut args = vec![];
+
+        if self.use_clipboard {
+            args.push("-c".to_string());
+        }
+        if self.browser_output {
+            args.push("--browser-output".to_string());
+        }
+        // Without a conversation of its own the tab starts a new one rather
+        // than resuming the latest conversation, which may belong to another tab
+        if self.resume
+            && let Some(id) = conversation
+        {
+            args.push(format!("--resume={}", id));
+        }
+
+        args.push("--model".to_string());
+        args.push(self.model.clone());
+
+        if !self.task.is_empty() {
+            args.push("-t".to_string());
+            args.push(self.task.clone());
+        }
+        if !self.role.is_empty() {
+            args.push("--role".to_string());
+            args.push(self.role.clone());
+        }
+        if let Some(language) = &self.tts_language {
+            args.push(format!("--tts={}", language));
+        }
+
+        args.extend(attachments::attachment_args(&self.attachments));
+        args.extend(self.options.iter().cloned());
+
+        if !self.prompt.is_empty() {
+            args.push("--".to_string());
+            args.push(self.prompt.clone());
+        }
+        args
+    }
+
+    /// First line of the prompt, shortened for the queue and tab titles
+    pub fn preview(&self) -> String {
+        let line = self.prompt.lines().next().unwrap_or("").trim();
+        if line.is_empty() {
+            return "(attachments only)".to_string();
+        }
+        shorten(line, TITLE_LENGTH)
+    }
+}
+
+fn shorten(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+    let short: String = text.chars().take(length - 1).collect();
+    format!("{}…", short.trim_end())
+}
+
+pub struct Session {
+    pub id: u64,
+    pub title: Option<String>,
+    pub prompt: String,
+    pub options: String,
+    pub attachments: Vec<Attachment>,
+    pub use_clipboard: bool,
+    pub browser_output: bool,
+    pub resume: bool,
+    pub tts_enabled: bool,
+    pub selected_conversation: Option<String>, // the tab's conversation, resumed by its prompts
+    pub model: String,
+    pub task: String,
+    pub role: String, // kept for the tab's next prompts
+    pub response: String,
+    pub footer_lines: Vec<String>,
+    pub last_error: Option<String>,
+    pub markdown_cache: CommonMarkCache,
+    pub clear_prompt_on_next_record: bool,
+    pub unread: bool, // a response arrived while another tab was shown
+    queue: VecDeque<PromptRequest>,
+    started: Option<Instant>,
+    next_request_id: u64,
+    executing_request: Arc<Mutex<Option<u64>>>, // id of the request still running
+    running_request: Option<(u64, tokio::task::AbortHandle)>,
+    pending_response: Arc<Mutex<Option<(u64, RequestResult)>>>,
+    streaming_text: Arc<Mutex<String>>,
+    pending_error: Arc<Mutex<Option<String>>>,
+}
+
+impl Session {
+    pub fn new(id: u64, model: String) -> Self {
+        Self {
+            id,
+            title: None,
+            prompt: String::new(),
+            options: String::new(),
+            attachments: Vec::new(),
+            use_clipboard: false,
+            browser_output: false,
+            resume: false,
+            tts_enabled: false,
+            selected_conversation: None,
+            model,
+            task: String::new(),
+            role: String::new(),
+            response: String::new(),
+            footer_lines: Vec::new(),
+            last_error: None,
+            markdown_cache: CommonMarkCache::default(),
+            clear_prompt_on_next_record: false,
+            unread: false,
+            queue: VecDeque::new(),
+            started: None,
+            next_request_id: 0,
+            executing_request: Arc::new(Mutex::new(None)),
+            running_request: None,
+            pending_response: Arc::new(Mutex::new(None)),
+            streaming_text: Arc::new(Mutex::new(String::new())),
+            pending_error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn is_executing(&self) -> bool {
+        self.executing_request.lock().unwrap().is_some()
+    }
+
+    /// Where background work for this tab reports errors
+    pub fn error_sink(&self) -> Arc<Mutex<Option<String>>> {
+        Arc::clone(&self.pending_error)
+    }
+
+    /// Tab label: title, progress of a running request and queued prompts
+    pub fn label(&self) -> String {
+        let mut label = self.title.clone().unwrap_or_else(|| "New chat".to_string());
+        if let Some(started) = self.started
+            && self.is_executing()
+        {
+            label = format!("⏳ {} {}", label, format_elapsed(started.elapsed()));
+        } else if self.unread {
+            label = format!("● {}", label);
+        }
+        if !self.queue.is_empty() {
+            label.push_str(&format!(" +{}", self.queue.len()));
+        }
+        label
+    }
+
+    pub fn elapsed(&self) -> Option<String> {
+        self.started
+            .map(|started| format_elapsed(started.elapsed()))
+    }
+
+    /// Previews of the queued prompts, next one first
+    pub fn queued_prompts(&self) -> Vec<String> {
+        self.queue.iter().map(PromptRequest::preview).collect()
+    }
+
+    pub fn remove_queued(&mut self, index: usize) {
+        self.queue.remove(index);
+    }
+
+    /// Send the form: start it now, or queue it behind the running request.
+    /// The form is cleared like after sending; the role stays for the next prompts.
+    pub fn send(&mut self, runtime: &tokio::runtime::Handle, tts_language: &str) {
+        let request = PromptRequest {
+            prompt: self.prompt.clone(),
+            model: self.model.clone(),
+            task: std::mem::take(&mut self.task),
+            role: self.role.clone(),
+            use_clipboard: self.use_clipboard,
+            browser_output: self.browser_output,
+            resume: self.resume,
+            tts_language: self.tts_enabled.then(|| tts_language.to_string()),
+            options: self
+                .options
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            attachments: std::mem::take(&mut self.attachments),
+        };
+
+        // Uncheck clipboard, clear options, and enable resume after sending;
+        // the next recording replaces the sent prompt
+        self.options.clear();
+        self.use_clipboard = false;
+        self.resume = true;
+        self.clear_prompt_on_next_record = true;
+        if self.title.is_none() {
+            self.title = Some(request.preview());
+        }
+
+        if self.is_executing() {
+            self.queue.push_back(request);
+        } else {
+            self.start(request, runtime);
+        }
+    }
+
+    fn start(&mut self, request: PromptRequest, runtime: &tokio::runtime::Handle) {
+        let args = request.args(self.selected_conversation.as_deref());
+
+        let config = match runner::request_config(&args) {
+            Ok(config) => config,
+            Err(e) => {
+                self.last_error = Some(format!("Invalid options: {:#}", e));
+                return;
+            }
+        };
+
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        *self.executing_request.lock().unwrap() = Some(request_id);
+        self.started = Some(Instant::now());
+        self.last_error = None;
+        self.footer_lines.clear();
+        self.streaming_text.lock().unwrap().clear();
+
+        let executing_request = Arc::clone(&self.executing_request);
+        let pending_response = Arc::clone(&self.pending_response);
+        let streaming_text = Arc::clone(&self.streaming_text);
+        let pasted_image = attachments::pasted_image(&request.attachments);
+
+        let task = runtime.spawn(async move {
+            let mut on_text = |text: &str| streaming_text.lock().unwrap().push_str(text);
+            let result = async {
+                // The pasted image is sent after the prompt, like `-c` sends a clipboard image
+                let extra_content = match pasted_image {
+                    Some(image) => vec![
+                        tokio::task::spawn_blocking(move || {
+                            attachments::pasted_image_content(&image)
+                        })
+                        .await
+                        .context("Image encoding panicked")??,
+                    ],
+                    None => Vec::new(),
+                };
+                runner::run_with_content(config, extra_content, Some(&mut on_text)).await
+            }
+            .await
+            .map_err(|e| format!("Error: {:#}", e));
+
+            // A cancelled request may still finish; only the current one reports
+            let mut executing = executing_request.lock().unwrap();
+            if *executing == Some(request_id) {
+                *executing = None;
+                *pending_response.lock().unwrap() = Some((request_id, result));
+            }
+        });
+        self.running_request = Some((request_id, task.abort_handle()));
+    }
+
+    /// Start the next queued prompt once the tab is idle
+    fn start_next(&mut self, runtime: &tokio::runtime::Handle) {
+        while !self.is_executing()
+            && let Some(request) = self.queue.pop_front()
+        {
+            self.start(request, runtime);
+        }
+    }
+
+    /// Cancel the running request; queued prompts go on
+    pub fn cancel(&mut self, runtime: &tokio::runtime::Handle) {
+        if let Some((_, task)) = self.running_request.take() {
+            task.abort();
+            *self.executing_request.lock().unwrap() = None;
+            self.started = None;
+            self.last_error = Some("Request cancelled".to_string());
+            self.start_next(runtime);
+        }
+    }
+
+    /// Cancel the running request and drop the queue, e.g. when the tab is closed
+    pub fn cancel_all(&mut self) {
+        self.queue.clear();
+        if let Some((_, task)) = self.running_request.take() {
+            task.abort();
+            *self.executing_request.lock().unwrap() = None;
+        }
+    }
+
+    /// Pick up streamed text, errors and finished requests. Returns the
+    /// conversation id of a finished request.
+    pub fn poll(&mut self, runtime: &tokio::runtime::Handle) -> Option<String> {
+        // Show the response text streamed so far
+        if self.is_executing()
+            && let Ok(text) = self.streaming_text.lock()
+            && !text.is_empty()
+            && *text != self.response
+        {
+            self.response = text.clone();
+        }
+
+        if let Some(error) = self.pending_error.lock().unwrap().take() {
+            self.last_error = Some(error);
+        }
+
+        let (request_id, result) = self.pending_response.lock().unwrap().take()?;
+        // Ignore the response of a request cancelled after it finished
+        if self.running_request.as_ref().map(|(id, _)| *id) != Some(request_id) {
+            return None;
+        }
+        self.running_request = None;
+        self.started = None;
+        let mut finished = None;
+        match result {
+            Ok(response) => {
+                self.footer_lines = response.footer_lines();
+                self.last_error = None;
+                self.response = response.text;
+                if let Some(id) = response.conversation_id {
+                    self.selected_conversation = Some(id.clone());
+                    finished = Some(id);
+                }
+            }
+            Err(error) => self.last_error = Some(error),
+        }
+        self.start_next(runtime);
+        finished
+    }
+
+    /// Add a transcript to the prompt, replacing the sent prompt after a send
+    pub fn add_transcript(&mut self, text: &str) {
+        if self.clear_prompt_on_next_record {
+            self.prompt.clear();
+            self.clear_prompt_on_next_record = false; // Reset flag after use
+        } else if !self.prompt.is_empty() {
+            self.prompt.push(' ');
+        }
+        self.prompt.push_str(text);
+    }
+
+    /// Clear the form and start over with a new conversation in this tab
+    pub fn clear(&mut self) {
+        self.prompt.clear();
+        self.options.clear();
+        self.attachments.clear();
+        self.response.clear();
+        self.footer_lines.clear();
+        self.last_error = None;
+        self.use_clipboard = false;
+        self.browser_output = false;
+        self.resume = false;
+        self.selected_conversation = None;
+        self.title = None;
+        self.clear_prompt_on_next_record = false;
+    }
+
+    /// Show a saved conversation in this tab; the next prompt resumes it
+    pub fn open_conversation(&mut self, conversation: &gia_core::Conversation) {
+        self.response = crate::history::chat_markdown(conversation);
+        self.footer_lines = vec![format!(
+            "{} · Conversation: {}",
+            conversation.metadata.model_used, conversation.id
+        )];
+        self.last_error = None;
+        let summary = gia_core::ConversationSummary::from_conversation(conversation);
+        self.title = Some(shorten(
+            summary
+                .title
+                .or(summary.first_user_message)
+                .as_deref()
+                .unwrap_or(&conversation.id),
+            TITLE_LENGTH,
+        ));
+        self.selected_conversation = Some(conversation.id.clone());
+        self.resume = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(prompt: &str) -> PromptRequest {
+        PromptRequest {
+            prompt: prompt.to_string(),
+            model: "gemini-2.5-flash".to_string(),
+            task: String::new(),
+            role: "rust-dev".to_string(),
+            use_clipboard: false,
+            browser_output: false,
+            resume: true,
+            tts_language: None,
+            options: vec!["--no-save".to_string()],
+            attachments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_request_args() {
+        let request = request("Explain lifetimes");
+        assert_eq!(
+            request.args(Some("abc")),
+            vec![
+                "--resume=abc",
+                "--model",
+                "gemini-2.5-flash",
+                "--role",
+                "rust-dev",
+                "--no-save",
+                "--",
+                "Explain lifetimes"
+            ]
+        );
+        assert_eq!(request.args(None)[0], "--model");
+    }
+
+    #[test]
+    fn test_request_preview() {
+        assert_eq!(request("Hi\nmore").preview(), "Hi");
+        assert_eq!(request("").preview(), "(attachments only)");
+        assert_eq!(
+            request("Explain the borrow checker in detail").preview(),
+            "Explain the borrow chec…"
+        );
+    }
+
+    #[test]
+    fn test_send_queues_while_executing() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut session = Session::new(1, "ollama::llama3.2".to_string());
+        session.role = "rust-dev".to_string();
+        session.task = "review".to_string();
+        session.prompt = "First question".to_string();
+        *session.executing_request.lock().unwrap() = Some(0);
+
+        session.send(runtime.handle(), "de-DE");
+        session.prompt = "Second question".to_string();
+        session.send(runtime.handle(), "de-DE");
+
+        assert_eq!(
+            session.queued_prompts(),
+            vec!["First question", "Second question"]
+        );
+        assert_eq!(session.title.as_deref(), Some("First question"));
+        assert_eq!(session.role, "rust-dev");
+        assert!(session.task.is_empty());
+        assert!(session.resume);
+        assert!(session.label().ends_with(" +2"));
+
+        session.remove_queued(0);
+        assert_eq!(session.queued_prompts(), vec!["Second question"]);
+        session.cancel_all();
+        assert!(session.queued_prompts().is_empty());
+    }
+
+    #[test]
+    fn test_poll_ignores_cancelled_response() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let mut session = Session::new(1, "ollama::llama3.2".to_string());
+        let task = runtime.spawn(std::future::pending::<()>());
+        session.running_request = Some((2, task.abort_handle()));
+        *session.executing_request.lock().unwrap() = Some(2);
+
+        *session.pending_response.lock().unwrap() = Some((1, Err("late".to_string())));
+        assert!(session.poll(runtime.handle()).is_none());
+        assert!(session.last_error.is_none());
+        assert!(session.running_request.is_some());
+
+        *session.executing_request.lock().unwrap() = None;
+        *session.pending_response.lock().unwrap() = Some((2, Err("failed".to_string())));
+        session.poll(runtime.handle());
+        assert_eq!(session.last_error.as_deref(), Some("failed"));
+        assert!(session.running_request.is_none());
+    }
+
+    #[test]
+    fn test_add_transcript() {
+        let mut session = Session::new(1, "gemini-2.5-flash".to_string());
+        session.add_transcript("Hello");
+        session.add_transcript("world");
+        assert_eq!(session.prompt, "Hello world");
+
+        session.clear_prompt_on_next_record = true;
+        session.add_transcript("New");
+        assert_eq!(session.prompt, "New");
+    }
+}
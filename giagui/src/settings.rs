@@ -0,0 +1,461 @@
+//! Settings window: defaults for requests, persisted to the `settings` section of ~/.gia/config.
+
+use anyhow::{Context, Result};
+use eframe::egui;
+use gia_core::api_key::{check_api_key, validate_api_key_format};
+use gia_core::config_file::{
+    Settings, THEMES, config_file_has_comments, reload_settings, save_settings,
+};
+use gia_core::constants::GEMINI_MODELS;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Settings as edited in the form; empty fields mean "use the default"
+#[derive(Debug, Default, Clone, PartialEq)]
+struct SettingsForm {
+    default_model: String,
+    api_keys: String, // one key per line
+    audio_device: String,
+    tts_language: String,
+    tts_voice: String,
+    context_limit: String,
+    conversations_dir: String,
+    outputs_dir: String,
+    theme: String,
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn path_text(value: &Option<PathBuf>) -> String {
+    value
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+fn optional(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+impl SettingsForm {
+    fn from_settings(settings: &Settings) -> Self {
+        Self {
+            default_model: text(&settings.default_model),
+            api_keys: settings.api_keys.join("\n"),
+            audio_device: text(&settings.audio_device),
+            tts_language: text(&settings.tts_language),
+            tts_voice: text(&settings.tts_voice),
+            context_limit: settings
+                .context_limit
+                .map(|limit| limit.to_string())
+                .unwrap_or_default(),
+            conversations_dir: path_text(&settings.conversations_dir),
+            outputs_dir: path_text(&settings.outputs_dir),
+            theme: text(&settings.theme),
+        }
+    }
+
+    fn api_keys(&self) -> Vec<String> {
+        self.api_keys
+            .lines()
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn to_settings(&self) -> Result<Settings> {
+        let context_limit = optional(&self.context_limit)
+            .map(|limit| {
+                limit
+                    .parse::<usize>()
+                    .with_context(|| format!("Context limit '{limit}' is not a number"))
+            })
+            .transpose()?;
+
+        Ok(Settings {
+            default_model: optional(&self.default_model),
+            api_keys: self.api_keys(),
+            audio_device: optional(&self.audio_device),
+            tts_language: optional(&self.tts_language),
+            tts_voice: optional(&self.tts_voice),
+            context_limit,
+            conversations_dir: optional(&self.conversations_dir).map(PathBuf::from),
+            outputs_dir: optional(&self.outputs_dir).map(PathBuf::from),
+            theme: optional(&self.theme),
+        })
+    }
+}
+
+/// Start and end of a key, enough to tell keys apart
+fn masked_key(key: &str) -> String {
+    if key.chars().count() <= 10 {
+        return "…".to_string();
+    }
+    let start: String = key.chars().take(6).collect();
+    let end: String = key
+        .chars()
+        .rev()
+        .take(4)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("{start}…{end}")
+}
+
+/// Note for a setting that an environment variable overrides
+fn env_override(variable: &str) -> Option<String> {
+    std::env::var_os(variable).map(|_| format!("⚠ {variable} is set and overrides this"))
+}
+
+/// Apply the theme setting (dark, light or system) to the window
+pub fn apply_theme(ctx: &egui::Context, theme: Option<&str>) {
+    ctx.set_theme(match theme {
+        Some("dark") => egui::ThemePreference::Dark,
+        Some("light") => egui::ThemePreference::Light,
+        _ => egui::ThemePreference::System,
+    });
+}
+
+#[derive(Default)]
+pub struct SettingsWindow {
+    pub open: bool,
+    form: SettingsForm,
+    audio_devices: Vec<String>,
+    voices: Vec<(String, String)>, // (name, language)
+    key_check: Arc<Mutex<Option<Vec<String>>>>,
+    key_results: Vec<String>,
+    checking_keys: bool,
+    drops_comments: bool,
+    error: Option<String>,
+}
+
+impl SettingsWindow {
+    /// Open the window with the saved settings and the devices and voices of this machine
+    pub fn open(&mut self) {
+        self.form = SettingsForm::from_settings(&reload_settings());
+        self.drops_comments = config_file_has_comments();
+        self.audio_devices = gia_core::audio::audio_input_devices().unwrap_or_default();
+        self.voices = gia_core::output::tts_voices().unwrap_or_default();
+        self.key_check = Arc::new(Mutex::new(None));
+        self.key_results.clear();
+        self.checking_keys = false;
+        self.error = None;
+        self.open = true;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.open {
+            self.open = false;
+        } else {
+            self.open();
+        }
+    }
+
+    /// Show the window; returns the settings once they were saved
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        ollama_models: &[String],
+        runtime: &tokio::runtime::Handle,
+    ) -> Option<Settings> {
+        let mut saved = None;
+        let mut open = self.open;
+
+        egui::Window::new("⚙ Settings")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        self.model_row(ui, ollama_models);
+                        self.api_keys_row(ui, runtime);
+                        self.audio_device_row(ui);
+                        self.tts_rows(ui);
+                        self.storage_rows(ui);
+                        self.theme_row(ui);
+                    });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::from_rgb(220, 80, 80), error);
+                }
+                ui.weak("Saved to the settings section of ~/.gia/config");
+                if self.drops_comments {
+                    ui.colored_label(
+                        egui::Color32::from_rgb(220, 160, 60),
+                        "⚠ Saving removes the comments in ~/.gia/config",
+                    );
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save").clicked() {
+                        match self.form.to_settings().and_then(|settings| {
+                            save_settings(&settings)?;
+                            Ok(settings)
+                        }) {
+                            Ok(settings) => {
+                                self.error = None;
+                                self.open = false;
+                                saved = Some(settings);
+                            }
+                            Err(e) => self.error = Some(format!("{:#}", e)),
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.open = false;
+                    }
+                });
+            });
+
+        // Closing with the window's ✖ discards the changes like Cancel
+        self.open &= open;
+        saved
+    }
+
+    fn model_row(&mut self, ui: &mut egui::Ui, ollama_models: &[String]) {
+        ui.label("Default model");
+        ui.vertical(|ui| {
+            egui::ComboBox::from_id_salt("settings_model")
+                .selected_text(if self.form.default_model.is_empty() {
+                    "Default"
+                } else {
+                    &self.form.default_model
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.form.default_model, String::new(), "Default");
+                    ui.label("Gemini Models:");
+                    for model in GEMINI_MODELS {
+                        ui.selectable_value(
+                            &mut self.form.default_model,
+                            model.to_string(),
+                            *model,
+                        );
+                    }
+                    if !ollama_models.is_empty() {
+                        ui.separator();
+                        ui.label("Ollama Models:");
+                        for model in ollama_models {
+                            ui.selectable_value(&mut self.form.default_model, model.clone(), model);
+                        }
+                    }
+                });
+            if let Some(note) = env_override("GIA_DEFAULT_MODEL") {
+                ui.weak(note);
+            }
+        });
+        ui.end_row();
+    }
+
+    fn api_keys_row(&mut self, ui: &mut egui::Ui, runtime: &tokio::runtime::Handle) {
+        ui.label("Gemini API keys")
+            .on_hover_text("One key per line, later keys are used when earlier ones fail");
+        ui.vertical(|ui| {
+            ui.add(
+                egui::TextEdit::multiline(&mut self.form.api_keys)
+                    .password(true)
+                    .desired_rows(2)
+                    .hint_text("One key per line"),
+            );
+
+            let keys = self.form.api_keys();
+            for key in keys.iter().filter(|key| !validate_api_key_format(key)) {
+                ui.colored_label(
+                    egui::Color32::from_rgb(220, 160, 60),
+                    format!("⚠ {} doesn't look like a Gemini API key", masked_key(key)),
+                );
+            }
+            if let Some(note) = env_override("GEMINI_API_KEY") {
+                ui.weak(note);
+            }
+
+            if let Some(results) = self.key_check.lock().unwrap().take() {
+                self.checking_keys = false;
+                self.key_results = results;
+            }
+            ui.horizontal(|ui| {
+                let button = ui.add_enabled(
+                    !keys.is_empty() && !self.checking_keys,
+                    egui::Button::new("🔌 Test API keys"),
+                );
+                if button.clicked() {
+                    self.checking_keys = true;
+                    let key_check = Arc::clone(&self.key_check);
+                    let ctx = ui.ctx().clone();
+                    runtime.spawn_blocking(move || {
+                        let results = keys
+                            .iter()
+                            .map(|key| match check_api_key(key) {
+                                Ok(()) => format!("✔ {}: OK", masked_key(key)),
+                                Err(e) => format!("✖ {}: {:#}", masked_key(key), e),
+                            })
+                            .collect();
+                        *key_check.lock().unwrap() = Some(results);
+                        ctx.request_repaint();
+                    });
+                }
+                if self.checking_keys {
+                    ui.spinner();
+                }
+            });
+            for result in &self.key_results {
+                ui.label(result);
+            }
+        });
+        ui.end_row();
+    }
+
+    fn audio_device_row(&mut self, ui: &mut egui::Ui) {
+        ui.label("Audio device");
+        ui.vertical(|ui| {
+            egui::ComboBox::from_id_salt("settings_audio_device")
+                .selected_text(if self.form.audio_device.is_empty() {
+                    "System default"
+                } else {
+                    &self.form.audio_device
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(
+                        &mut self.form.audio_device,
+                        String::new(),
+                        "System default",
+                    );
+                    for device in &self.audio_devices {
+                        ui.selectable_value(&mut self.form.audio_device, device.clone(), device);
+                    }
+                });
+            if let Some(note) = env_override("GIA_AUDIO_DEVICE") {
+                ui.weak(note);
+            }
+        });
+        ui.end_row();
+    }
+
+    fn tts_rows(&mut self, ui: &mut egui::Ui) {
+        ui.label("TTS language");
+        ui.add(egui::TextEdit::singleline(&mut self.form.tts_language).hint_text("de-DE"));
+        ui.end_row();
+
+        ui.label("TTS voice");
+        egui::ComboBox::from_id_salt("settings_tts_voice")
+            .selected_text(if self.form.tts_voice.is_empty() {
+                "First voice of the language"
+            } else {
+                &self.form.tts_voice
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    &mut self.form.tts_voice,
+                    String::new(),
+                    "First voice of the language",
+                );
+                for (name, language) in &self.voices {
+                    ui.selectable_value(
+                        &mut self.form.tts_voice,
+                        name.clone(),
+                        format!("{name} ({language})"),
+                    );
+                }
+            });
+        ui.end_row();
+    }
+
+    fn storage_rows(&mut self, ui: &mut egui::Ui) {
+        ui.label("Context limit");
+        ui.vertical(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.form.context_limit)
+                    .hint_text(gia_core::constants::DEFAULT_CONTEXT_WINDOW_LIMIT.to_string()),
+            )
+            .on_hover_text("Characters of the conversation sent before it is truncated");
+            if let Some(note) = env_override("CONTEXT_WINDOW_LIMIT") {
+                ui.weak(note);
+            }
+        });
+        ui.end_row();
+
+        ui.label("Conversations folder");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.form.conversations_dir)
+                .hint_text("~/.gia/conversations"),
+        );
+        ui.end_row();
+
+        ui.label("Outputs folder");
+        ui.add(egui::TextEdit::singleline(&mut self.form.outputs_dir).hint_text("~/.gia/outputs"));
+        ui.end_row();
+    }
+
+    fn theme_row(&mut self, ui: &mut egui::Ui) {
+        ui.label("Theme");
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.form.theme, String::new(), "Default");
+            for theme in THEMES {
+                ui.selectable_value(&mut self.form.theme, theme.to_string(), *theme);
+            }
+        });
+        ui.end_row();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_round_trip() {
+        let settings = Settings {
+            default_model: Some("ollama::llama3.2".to_string()),
+            api_keys: vec!["key-one".to_string(), "key-two".to_string()],
+            audio_device: Some("USB Microphone".to_string()),
+            tts_language: Some("en-US".to_string()),
+            tts_voice: None,
+            context_limit: Some(12000),
+            conversations_dir: Some(PathBuf::from("/tmp/conversations")),
+            outputs_dir: None,
+            theme: Some("light".to_string()),
+        };
+        let form = SettingsForm::from_settings(&settings);
+        assert_eq!(form.api_keys, "key-one\nkey-two");
+        assert_eq!(form.to_settings().unwrap(), settings);
+    }
+
+    #[test]
+    fn test_form_empty_fields_are_defaults() {
+        let form = SettingsForm {
+            default_model: "  ".to_string(),
+            api_keys: "\n key-one \n\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            form.to_settings().unwrap(),
+            Settings {
+                api_keys: vec!["key-one".to_string()],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_form_rejects_invalid_context_limit() {
+        let form = SettingsForm {
+            context_limit: "lots".to_string(),
+            ..Default::default()
+        };
+        let err = form.to_settings().unwrap_err();
+        assert_eq!(err.to_string(), "Context limit 'lots' is not a number");
+    }
+
+    #[test]
+    fn test_masked_key() {
+        assert_eq!(masked_key("AIzaSyA1234567890abcd"), "AIzaSy…abcd");
+        assert_eq!(masked_key("short"), "…");
+    }
+}
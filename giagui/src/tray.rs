@@ -0,0 +1,694 @@
+//! Tray mode (`giagui --tray`): the named actions of `~/.gia/config` run in the
+//! background on global hotkeys, like "record → transcribe → paste".
+
+use crate::hotkeys::{self, HotkeyEvent, Hotkeys};
+use crate::recorder::format_elapsed;
+use crate::runner;
+use anyhow::{Context, Result};
+use eframe::egui;
+use gia_core::audio::Recorder;
+use gia_core::config_file::{HotkeyAction, HotkeyInput, HotkeyOutput};
+use gia_core::logging::{log_error, log_info};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// What the worker thread handles
+pub enum TrayEvent {
+    Hotkey(HotkeyEvent),
+    Run(String), // from the window or the tray menu, like a hotkey press
+    CancelRecording,
+    Finished(String, Result<String, String>),
+}
+
+/// State shown in the window and the tray icon
+#[derive(Default)]
+struct TrayStatus {
+    recording: Option<(String, Instant)>,
+    running: Vec<String>,
+    message: Option<String>,
+    error: Option<String>,
+}
+
+impl TrayStatus {
+    fn summary(&self) -> String {
+        if let Some((name, started)) = &self.recording {
+            format!("Recording {name} {}", format_elapsed(started.elapsed()))
+        } else if !self.running.is_empty() {
+            format!("Running {}", self.running.join(", "))
+        } else {
+            "Idle".to_string()
+        }
+    }
+}
+
+/// Short description of an action, e.g. "audio → transkribiere → paste"
+pub fn describe(action: &HotkeyAction) -> String {
+    let mut steps = Vec::new();
+    let inputs: Vec<&str> = action
+        .input
+        .iter()
+        .map(|input| match input {
+            HotkeyInput::Audio => "audio",
+            HotkeyInput::Clipboard => "clipboard",
+        })
+        .collect();
+    if !inputs.is_empty() {
+        steps.push(inputs.join(" + "));
+    }
+    let request = [&action.task, &action.role, &action.prompt]
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(" · ");
+    if !request.is_empty() {
+        steps.push(request);
+    }
+    steps.push(
+        match action.output {
+            HotkeyOutput::Paste => "paste",
+            HotkeyOutput::Clipboard => "clipboard",
+            HotkeyOutput::Notification => "notification",
+            HotkeyOutput::Browser => "browser",
+        }
+        .to_string(),
+    );
+    steps.join(" → ")
+}
+
+/// gia options of an action; a recording is added to the request separately
+fn action_args(action: &HotkeyAction) -> Vec<String> {
+    let mut args = Vec::new();
+    if action.input.contains(&HotkeyInput::Clipboard) {
+        args.push("-c".to_string());
+    }
+    if action.output == HotkeyOutput::Browser {
+        args.push("--browser-output".to_string());
+    }
+    for (option, value) in [
+        ("--model", &action.model),
+        ("-t", &action.task),
+        ("--role", &action.role),
+    ] {
+        if let Some(value) = value {
+            args.push(option.to_string());
+            args.push(value.clone());
+        }
+    }
+    args.extend(action.options.iter().cloned());
+    if let Some(prompt) = &action.prompt {
+        args.push("--".to_string());
+        args.push(prompt.clone());
+    }
+    args
+}
+
+/// Runs the actions: owns the recorder (not `Send`) and the clipboard, which
+/// must stay alive on X11 for pasted text to remain available
+struct Worker {
+    actions: BTreeMap<String, HotkeyAction>,
+    events: Sender<TrayEvent>,
+    status: Arc<Mutex<TrayStatus>>,
+    runtime: tokio::runtime::Handle,
+    ctx: egui::Context,
+    recording: Option<(String, Recorder)>,
+    clipboard: Option<arboard::Clipboard>,
+    #[cfg(target_os = "linux")]
+    icon: Option<ksni::blocking::Handle<icon::TrayIcon>>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<TrayEvent>) {
+        while let Ok(event) = receiver.recv() {
+            match event {
+                TrayEvent::Hotkey(event) => self.hotkey(&event.action, event.pressed),
+                TrayEvent::Run(name) => self.hotkey(&name, true),
+                TrayEvent::CancelRecording => {
+                    if let Some((_, recorder)) = self.recording.take() {
+                        recorder.cancel();
+                        self.update_status(|status| {
+                            status.recording = None;
+                            status.message = Some("Recording cancelled".to_string());
+                        });
+                    }
+                }
+                TrayEvent::Finished(name, result) => self.finished(&name, result),
+            }
+        }
+    }
+
+    fn hotkey(&mut self, name: &str, pressed: bool) {
+        let Some(action) = self.actions.get(name).cloned() else {
+            return;
+        };
+        let recording_this = self
+            .recording
+            .as_ref()
+            .is_some_and(|(recording, _)| recording == name);
+
+        if recording_this && (pressed || action.hold) {
+            self.stop_recording();
+        } else if !pressed {
+            // Release of a hotkey without a recording to stop
+        } else if let Some((other, _)) = &self.recording {
+            let message = format!("Still recording for '{other}'");
+            self.update_status(|status| status.error = Some(message));
+        } else if action.records_audio() {
+            match Recorder::start(None) {
+                Ok(recorder) => {
+                    log_info(&format!("Recording for hotkey '{name}'"));
+                    self.recording = Some((name.to_string(), recorder));
+                    self.update_status(|status| {
+                        status.recording = Some((name.to_string(), Instant::now()));
+                        status.error = None;
+                    });
+                }
+                Err(e) => self.fail(name, format!("Recording failed: {e:#}")),
+            }
+        } else {
+            self.start(name, &action, None);
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        let Some((name, recorder)) = self.recording.take() else {
+            return;
+        };
+        self.update_status(|status| status.recording = None);
+        match recorder.stop() {
+            Ok(wav_path) => {
+                let action = self.actions[&name].clone();
+                self.start(&name, &action, Some(wav_path));
+            }
+            Err(e) => self.fail(&name, format!("Recording failed: {e:#}")),
+        }
+    }
+
+    fn start(&mut self, name: &str, action: &HotkeyAction, wav_path: Option<PathBuf>) {
+        let args = action_args(action);
+        let events = self.events.clone();
+        let name = name.to_string();
+        self.update_status(|status| {
+            status.running.push(name.clone());
+            status.error = None;
+        });
+
+        self.runtime.spawn(async move {
+            let result = async {
+                let config = runner::request_config(&args)?;
+                let response = match wav_path {
+                    Some(wav_path) => {
+                        let opus_path = tokio::task::spawn_blocking(move || {
+                            gia_core::audio::wav_to_opus(&wav_path)
+                        })
+                        .await??;
+                        runner::run_with_recording(config, opus_path).await?
+                    }
+                    None => runner::run_request(config, None).await?,
+                };
+                anyhow::Ok(response.text)
+            }
+            .await
+            .map_err(|e| format!("{e:#}"));
+            let _ = events.send(TrayEvent::Finished(name, result));
+        });
+    }
+
+    fn finished(&mut self, name: &str, result: Result<String, String>) {
+        self.update_status(|status| status.running.retain(|running| running != name));
+        let output = self.actions[name].output;
+        let delivered = result
+            .map_err(anyhow::Error::msg)
+            .and_then(|text| self.deliver(output, text.trim()));
+        match delivered {
+            Ok(message) => {
+                log_info(&format!("Hotkey '{name}': {message}"));
+                self.update_status(|status| status.message = Some(format!("{name}: {message}")));
+            }
+            Err(e) => self.fail(name, format!("{e:#}")),
+        }
+    }
+
+    /// Hand the response over as the action asks; returns what happened
+    fn deliver(&mut self, output: HotkeyOutput, text: &str) -> Result<String> {
+        match output {
+            HotkeyOutput::Paste | HotkeyOutput::Clipboard => {
+                if self.clipboard.is_none() {
+                    self.clipboard =
+                        Some(arboard::Clipboard::new().context("Failed to open the clipboard")?);
+                }
+                if let Some(clipboard) = &mut self.clipboard {
+                    clipboard
+                        .set_text(text)
+                        .context("Failed to write the response to the clipboard")?;
+                }
+                if output == HotkeyOutput::Clipboard {
+                    return Ok("copied to clipboard".to_string());
+                }
+                // Simulated keys only reach X11 windows, not native Wayland ones
+                if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+                    notify("GIA", "Response copied to clipboard, paste it with Ctrl+V");
+                    return Ok("copied to clipboard".to_string());
+                }
+                paste()?;
+                Ok("pasted".to_string())
+            }
+            HotkeyOutput::Notification => {
+                notify("GIA", text);
+                Ok("shown as notification".to_string())
+            }
+            HotkeyOutput::Browser => Ok("opened in browser".to_string()),
+        }
+    }
+
+    fn fail(&mut self, name: &str, error: String) {
+        log_error(&format!("Hotkey '{name}': {error}"));
+        notify(&format!("GIA: {name}"), &error);
+        self.update_status(|status| status.error = Some(format!("{name}: {error}")));
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut TrayStatus)) {
+        let summary = {
+            let mut status = self.status.lock().unwrap();
+            update(&mut status);
+            status.summary()
+        };
+        self.ctx.request_repaint();
+        #[cfg(target_os = "linux")]
+        if let Some(icon) = &self.icon {
+            let recording = self.recording.is_some();
+            icon.update(|icon| {
+                icon.status = summary;
+                icon.recording = recording;
+            });
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = summary;
+    }
+}
+
+/// Press the paste shortcut in the focused window
+fn paste() -> Result<()> {
+    use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+    #[cfg(target_os = "macos")]
+    let modifier = Key::Meta;
+    #[cfg(not(target_os = "macos"))]
+    let modifier = Key::Control;
+
+    let mut enigo = Enigo::new(&Settings::default()).context("Failed to simulate paste")?;
+    enigo
+        .key(modifier, Direction::Press)
+        .context("Failed to simulate paste")?;
+    let result = enigo.key(Key::Unicode('v'), Direction::Click);
+    enigo
+        .key(modifier, Direction::Release)
+        .context("Failed to simulate paste")?;
+    result.context("Failed to simulate paste")
+}
+
+fn notify(summary: &str, body: &str) {
+    if let Err(e) = notify_rust::Notification::new()
+        .summary(summary)
+        .body(body)
+        .show()
+    {
+        eprintln!("Warning: Failed to show notification: {}", e);
+    }
+}
+
+/// Small window listing the actions; hidden to the tray icon when closed
+pub struct TrayApp {
+    actions: BTreeMap<String, HotkeyAction>,
+    events: Sender<TrayEvent>,
+    status: Arc<Mutex<TrayStatus>>,
+    backend: Result<&'static str, String>,
+    quit: Arc<AtomicBool>,
+    has_icon: bool,
+    _hotkeys: Option<Hotkeys>,
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl TrayApp {
+    pub fn new(ctx: &egui::Context, actions: BTreeMap<String, HotkeyAction>) -> Self {
+        let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+        let (events, receiver) = mpsc::channel();
+        let status = Arc::new(Mutex::new(TrayStatus::default()));
+        let quit = Arc::new(AtomicBool::new(false));
+
+        let sender = events.clone();
+        let hotkeys = hotkeys::register(
+            &actions,
+            runtime.handle(),
+            Arc::new(move |event| {
+                let _ = sender.send(TrayEvent::Hotkey(event));
+            }),
+        );
+        let (hotkeys, backend) = match hotkeys {
+            Ok(hotkeys) => {
+                let backend = hotkeys.backend;
+                (Some(hotkeys), Ok(backend))
+            }
+            Err(e) => {
+                eprintln!("Warning: Global hotkeys not available: {:#}", e);
+                (None, Err(format!("{:#}", e)))
+            }
+        };
+
+        #[cfg(target_os = "linux")]
+        let icon = icon::spawn(&actions, events.clone(), ctx.clone(), Arc::clone(&quit));
+        #[cfg(target_os = "linux")]
+        let has_icon = icon.is_some();
+        #[cfg(not(target_os = "linux"))]
+        let has_icon = false;
+
+        // The recorder is not Send, so the worker is created on its thread
+        let (worker_actions, worker_events, worker_status) =
+            (actions.clone(), events.clone(), Arc::clone(&status));
+        let (handle, worker_ctx) = (runtime.handle().clone(), ctx.clone());
+        thread::spawn(move || {
+            Worker {
+                actions: worker_actions,
+                events: worker_events,
+                status: worker_status,
+                runtime: handle,
+                ctx: worker_ctx,
+                recording: None,
+                clipboard: None,
+                #[cfg(target_os = "linux")]
+                icon,
+            }
+            .run(receiver)
+        });
+
+        Self {
+            actions,
+            events,
+            status,
+            backend,
+            quit,
+            has_icon,
+            _hotkeys: hotkeys,
+            _runtime: runtime,
+        }
+    }
+}
+
+impl eframe::App for TrayApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Closing the window keeps the hotkeys running in the tray
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.has_icon
+            && !self.quit.load(Ordering::Relaxed)
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("GIA hotkeys");
+            match &self.backend {
+                Ok(backend) => ui.weak(format!("Global hotkeys: {backend}")),
+                Err(e) => ui.colored_label(egui::Color32::RED, format!("No global hotkeys: {e}")),
+            };
+            ui.separator();
+
+            egui::Grid::new("hotkeys").striped(true).show(ui, |ui| {
+                for (name, action) in &self.actions {
+                    if ui.button("▶").on_hover_text("Run now").clicked() {
+                        let _ = self.events.send(TrayEvent::Run(name.clone()));
+                    }
+                    ui.strong(name);
+                    ui.monospace(&action.keys);
+                    ui.label(describe(action));
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+
+            let status = self.status.lock().unwrap();
+            ui.horizontal(|ui| {
+                if status.recording.is_some() {
+                    ui.colored_label(egui::Color32::from_rgb(220, 60, 60), "⏺");
+                    ui.label(status.summary());
+                    if ui.button("✖ Cancel").clicked() {
+                        let _ = self.events.send(TrayEvent::CancelRecording);
+                    }
+                } else {
+                    if !status.running.is_empty() {
+                        ui.spinner();
+                    }
+                    ui.label(status.summary());
+                }
+            });
+            if let Some(message) = &status.message {
+                ui.weak(message);
+            }
+            if let Some(error) = &status.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            if status.recording.is_some() || !status.running.is_empty() {
+                ctx.request_repaint_after(Duration::from_millis(250));
+            }
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod icon {
+    use super::{TrayEvent, describe};
+    use eframe::egui;
+    use gia_core::config_file::HotkeyAction;
+    use ksni::blocking::TrayMethods;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::Sender;
+
+    /// StatusNotifierItem with the actions in its menu
+    pub struct TrayIcon {
+        actions: Vec<(String, String)>, // name, menu label
+        events: Sender<TrayEvent>,
+        ctx: egui::Context,
+        quit: Arc<AtomicBool>,
+        pixmap: Vec<ksni::Icon>,
+        pub status: String,
+        pub recording: bool,
+    }
+
+    impl TrayIcon {
+        fn show_window(&self) {
+            self.ctx
+                .send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            self.ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+    }
+
+    impl ksni::Tray for TrayIcon {
+        fn id(&self) -> String {
+            "giagui".to_string()
+        }
+
+        fn title(&self) -> String {
+            "GIA".to_string()
+        }
+
+        fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+            self.pixmap.clone()
+        }
+
+        fn tool_tip(&self) -> ksni::ToolTip {
+            ksni::ToolTip {
+                title: "GIA".to_string(),
+                description: self.status.clone(),
+                ..Default::default()
+            }
+        }
+
+        fn activate(&mut self, _x: i32, _y: i32) {
+            self.show_window();
+        }
+
+        fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+            use ksni::menu::StandardItem;
+
+            let mut items: Vec<ksni::MenuItem<Self>> = self
+                .actions
+                .iter()
+                .map(|(name, label)| {
+                    let name = name.clone();
+                    StandardItem {
+                        label: label.clone(),
+                        activate: Box::new(move |icon: &mut Self| {
+                            let _ = icon.events.send(TrayEvent::Run(name.clone()));
+                        }),
+                        ..Default::default()
+                    }
+                    .into()
+                })
+                .collect();
+            items.push(ksni::MenuItem::Separator);
+            items.push(
+                StandardItem {
+                    label: "Cancel recording".to_string(),
+                    enabled: self.recording,
+                    activate: Box::new(|icon: &mut Self| {
+                        let _ = icon.events.send(TrayEvent::CancelRecording);
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            );
+            items.push(
+                StandardItem {
+                    label: "Show window".to_string(),
+                    activate: Box::new(|icon: &mut Self| icon.show_window()),
+                    ..Default::default()
+                }
+                .into(),
+            );
+            items.push(
+                StandardItem {
+                    label: "Quit".to_string(),
+                    activate: Box::new(|icon: &mut Self| {
+                        icon.quit.store(true, Ordering::Relaxed);
+                        icon.ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            );
+            items
+        }
+    }
+
+    /// Show the tray icon; None when the desktop has no tray
+    pub fn spawn(
+        actions: &BTreeMap<String, HotkeyAction>,
+        events: Sender<TrayEvent>,
+        ctx: egui::Context,
+        quit: Arc<AtomicBool>,
+    ) -> Option<ksni::blocking::Handle<TrayIcon>> {
+        let icon = TrayIcon {
+            actions: actions
+                .iter()
+                .map(|(name, action)| {
+                    (
+                        name.clone(),
+                        format!("{name} ({}): {}", action.keys, describe(action)),
+                    )
+                })
+                .collect(),
+            events,
+            ctx,
+            quit,
+            pixmap: vec![pixmap()],
+            status: "Idle".to_string(),
+            recording: false,
+        };
+        match icon.spawn() {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                eprintln!("Warning: No tray icon ({}), keep the window open", e);
+                None
+            }
+        }
+    }
+
+    /// The app icon in ARGB32, network byte order
+    fn pixmap() -> ksni::Icon {
+        let image = image::load_from_memory(include_bytes!("../../icons/gia.png"))
+            .expect("Failed to load icon")
+            .resize(64, 64, image::imageops::FilterType::Triangle)
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+        let data = image
+            .pixels()
+            .flat_map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                [a, r, g, b]
+            })
+            .collect();
+        ksni::Icon {
+            width: width as i32,
+            height: height as i32,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcribe() -> HotkeyAction {
+        HotkeyAction {
+            keys: "Alt+U".to_string(),
+            input: vec![HotkeyInput::Audio],
+            task: Some("transkribiere".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_action_args() {
+        assert_eq!(action_args(&transcribe()), vec!["-t", "transkribiere"]);
+
+        let translate = HotkeyAction {
+            keys: "Ctrl+Alt+T".to_string(),
+            input: vec![HotkeyInput::Clipboard],
+            model: Some("gemini-2.5-pro".to_string()),
+            role: Some("translator".to_string()),
+            options: vec!["--no-save".to_string()],
+            prompt: Some("translate to english".to_string()),
+            output: HotkeyOutput::Browser,
+            ..Default::default()
+        };
+        assert_eq!(
+            action_args(&translate),
+            vec![
+                "-c",
+                "--browser-output",
+                "--model",
+                "gemini-2.5-pro",
+                "--role",
+                "translator",
+                "--no-save",
+                "--",
+                "translate to english"
+            ]
+        );
+        assert!(runner::request_config(&action_args(&translate)).is_ok());
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&transcribe()), "audio → transkribiere → paste");
+        let action = HotkeyAction {
+            keys: "Alt+T".to_string(),
+            input: vec![HotkeyInput::Audio, HotkeyInput::Clipboard],
+            prompt: Some("translate".to_string()),
+            output: HotkeyOutput::Notification,
+            ..Default::default()
+        };
+        assert_eq!(
+            describe(&action),
+            "audio + clipboard → translate → notification"
+        );
+    }
+
+    #[test]
+    fn test_status_summary() {
+        let mut status = TrayStatus::default();
+        assert_eq!(status.summary(), "Idle");
+        status.running = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(status.summary(), "Running a, b");
+        status.recording = Some(("transcribe".to_string(), Instant::now()));
+        assert_eq!(status.summary(), "Recording transcribe 0:00");
+    }
+}
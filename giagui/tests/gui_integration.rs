@@ -77,25 +77,16 @@ fn test_short_spinner_flag() {
 
 #[test]
 fn test_gia_dependency_check() {
-    let config = TestConfig::new();
-
-    // If we don't have gia available, GUI should handle it gracefully
-    if !config.has_gia_in_path() && !config.gia_binary.exists() {
-        eprintln!("Note: gia not available for dependency test");
-        return;
-    }
-
-    // Test that gia is accessible
-    let output = config
-        .gia_command()
+    // Requests run in-process through gia-core, so giagui must not need gia in PATH
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_giagui"))
         .arg("--version")
+        .env("PATH", "")
         .output()
-        .expect("Failed to check gia version");
+        .expect("Failed to execute giagui without PATH");
 
-    if output.status.success() {
-        let stdout = str::from_utf8(&output.stdout).unwrap();
-        assert!(stdout.contains("gia"));
-    }
+    assert!(output.status.success());
+    let stdout = str::from_utf8(&output.stdout).unwrap();
+    assert!(stdout.contains("giagui"));
 }
 
 #[test]